@@ -0,0 +1,29 @@
+//! Stands in for the 1C starter in the end-to-end tests of `tests/`: appends
+//! its arguments as a JSON array to the file named by `FAKE_STARTER_LOG` and
+//! exits with `FAKE_STARTER_EXIT`, 0 by default.
+
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    if let Some(log) = env::var_os("FAKE_STARTER_LOG") {
+        let line = serde_json::to_string(&args).expect("arguments serialize");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log)
+            .expect("log file opens");
+        writeln!(file, "{line}").expect("log file is writable");
+    }
+    let code = env::var("FAKE_STARTER_EXIT")
+        .ok()
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+    process::exit(code);
+}
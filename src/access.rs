@@ -0,0 +1,179 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use std::error::Error;
+
+use crate::config::RasConfig;
+use crate::designer::Credentials;
+use crate::ras::{self, Access, Cluster, Infobase, Ras};
+use crate::{popup, theme};
+
+/// Rows of the panel: the two locks, then the text fields.
+const LABELS: [&str; 6] = [
+    "Scheduled jobs",
+    "Sessions",
+    "Message",
+    "From",
+    "To",
+    "Permission code",
+];
+
+/// First row holding text rather than a lock.
+const FIRST_TEXT: usize = 2;
+
+pub enum AccessAction {
+    None,
+    Close,
+    /// The locks were changed on the cluster
+    Saved(Access),
+}
+
+/// Locks of the selected server base, shown with Alt+I: scheduled jobs,
+/// sessions with the message and time window of the lock, and the
+/// permission code letting users in anyway. Saved with `rac infobase update`.
+#[derive(Debug)]
+pub struct AccessPanel {
+    pub path: String,
+    ras: Ras,
+    cluster: Cluster,
+    infobase: Infobase,
+    credentials: Credentials,
+    jobs_denied: bool,
+    sessions_denied: bool,
+    /// Message, from, to and permission code
+    inputs: [Input; 4],
+    focus: usize,
+    error: Option<String>,
+}
+
+impl AccessPanel {
+    /// Reads the locks of server base `path` through the ras of its cluster,
+    /// as the infobase administrator of `credentials`.
+    pub fn open(
+        path: &str,
+        credentials: Credentials,
+        config: &RasConfig,
+    ) -> Result<AccessPanel, Box<dyn Error>> {
+        let (ras, cluster, infobase) = ras::server_infobase(path, config)?;
+        let access = ras.access(&cluster, &infobase, &credentials)?;
+        Ok(AccessPanel {
+            path: path.trim().to_string(),
+            ras,
+            cluster,
+            infobase,
+            credentials,
+            jobs_denied: access.jobs_denied,
+            sessions_denied: access.sessions_denied,
+            inputs: [
+                access.denied_message.into(),
+                access.denied_from.into(),
+                access.denied_to.into(),
+                access.permission_code.into(),
+            ],
+            focus: 0,
+            error: None,
+        })
+    }
+
+    /// The locks as edited.
+    pub fn access(&self) -> Access {
+        let text = |index: usize| self.inputs[index].value().trim().to_string();
+        Access {
+            jobs_denied: self.jobs_denied,
+            sessions_denied: self.sessions_denied,
+            denied_message: text(0),
+            denied_from: text(1),
+            denied_to: text(2),
+            permission_code: text(3),
+        }
+    }
+
+    /// Saves the locks and reads them back, as the cluster normalizes the dates.
+    fn save(&self) -> Result<Access, Box<dyn Error>> {
+        self.ras.set_access(
+            &self.cluster,
+            &self.infobase,
+            &self.access(),
+            &self.credentials,
+        )?;
+        self.ras
+            .access(&self.cluster, &self.infobase, &self.credentials)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> AccessAction {
+        let Event::Key(key_event) = event else {
+            return AccessAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return AccessAction::None;
+        }
+        match key_event.code {
+            KeyCode::Esc => return AccessAction::Close,
+            KeyCode::Enter => match self.save() {
+                Ok(access) => return AccessAction::Saved(access),
+                Err(e) => self.error = Some(e.to_string()),
+            },
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % LABELS.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus = (self.focus + LABELS.len() - 1) % LABELS.len()
+            }
+            KeyCode::Char(' ') if self.focus == 0 => self.jobs_denied = !self.jobs_denied,
+            KeyCode::Char(' ') if self.focus == 1 => self.sessions_denied = !self.sessions_denied,
+            _ if self.focus >= FIRST_TEXT => {
+                self.inputs[self.focus - FIRST_TEXT].handle_event(event);
+            }
+            _ => {}
+        }
+        AccessAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), LABELS.len() as u16 + 4);
+        let label_width = LABELS.iter().map(|label| label.len()).max().unwrap_or(0) + 2;
+        let lock = |denied: bool| if denied { "[x] denied" } else { "[ ] allowed" };
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, label) in LABELS.iter().enumerate() {
+            let value = match index {
+                0 => lock(self.jobs_denied),
+                1 => lock(self.sessions_denied),
+                _ => self.inputs[index - FIRST_TEXT].value(),
+            };
+            let label = format!("{:<label_width$}", format!("{label}:"));
+            let line = Line::from(vec![label.into(), value.into()]);
+            lines.push(if index == self.focus {
+                line.bold()
+            } else {
+                line
+            });
+        }
+        lines.push(Line::default());
+        lines.push(match &self.error {
+            Some(error) => Span::styled(error.as_str(), theme::error()).into(),
+            None => Span::styled(
+                "Space: toggle, Enter: save, Esc: cancel, dates as 2026-10-16T09:00:00",
+                theme::hint(),
+            )
+            .into(),
+        });
+
+        let block = Block::bordered().title(format!("Access: {}", self.path));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+
+        if self.focus >= FIRST_TEXT {
+            let cursor = self.inputs[self.focus - FIRST_TEXT].visual_cursor();
+            frame.set_cursor_position((
+                inner.x + (label_width + cursor) as u16,
+                inner.y + self.focus as u16,
+            ));
+        }
+    }
+}
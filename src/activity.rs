@@ -0,0 +1,153 @@
+use chrono::Local;
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+
+use crate::journal::{self, Launch};
+use crate::{keys, locale, popup, theme};
+
+/// Launches of the log listed in the recent view.
+const RECENT_LAUNCHES: usize = 200;
+
+pub enum ActivityAction {
+    None,
+    Close,
+    /// Put the base into the input of the picker
+    Select(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Recent,
+    Bases,
+}
+
+/// The launch log shown with Ctrl+J: the latest launches, or how often and
+/// how recently each base was launched.
+#[derive(Debug)]
+pub struct ActivityPanel {
+    /// Latest first
+    recent: Vec<Launch>,
+    /// Bases with their summary, most launched first
+    bases: Vec<(String, String)>,
+    view: View,
+    state: ListState,
+}
+
+impl ActivityPanel {
+    pub fn new(launches: Vec<Launch>) -> ActivityPanel {
+        let now = Local::now().timestamp();
+        let stats = journal::stats(&launches);
+        let bases = journal::most_used(&stats)
+            .into_iter()
+            .map(|(path, stats)| (path.clone(), stats.summary(now)))
+            .collect();
+        let recent = launches.into_iter().rev().take(RECENT_LAUNCHES).collect();
+        ActivityPanel {
+            recent,
+            bases,
+            view: View::Recent,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn selected_path(&self) -> Option<String> {
+        let index = self.state.selected()?;
+        match self.view {
+            View::Recent => self.recent.get(index).map(|launch| launch.path.clone()),
+            View::Bases => self.bases.get(index).map(|(path, _)| path.clone()),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ActivityAction {
+        let Event::Key(key_event) = event else {
+            return ActivityAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return ActivityAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => return ActivityAction::Close,
+            KeyCode::Enter => {
+                if let Some(path) = self.selected_path() {
+                    return ActivityAction::Select(path);
+                }
+            }
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.view = match self.view {
+                    View::Recent => View::Bases,
+                    View::Bases => View::Recent,
+                };
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+        ActivityAction::None
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let (title, items): (&str, Vec<Line>) = match self.view {
+            View::Recent => (
+                "Recent launches",
+                self.recent
+                    .iter()
+                    .map(|launch| {
+                        let time = locale::timestamp(launch.at);
+                        let mut line = Line::from(vec![
+                            Span::styled(format!("{time}  {}  ", launch.user), theme::hint()),
+                            Span::raw(format!("{}  {}", launch.path, launch.mode.label())),
+                        ]);
+                        if let Some(failure) = &launch.failure {
+                            line.push_span(Span::styled(
+                                format!("  failed: {failure}"),
+                                theme::error(),
+                            ));
+                        }
+                        line
+                    })
+                    .collect(),
+            ),
+            View::Bases => (
+                "Launches by base",
+                self.bases
+                    .iter()
+                    .map(|(path, summary)| {
+                        Line::from(vec![
+                            Span::raw(path.as_str()),
+                            Span::styled(format!("  {summary}"), theme::hint()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        };
+        let items = if items.is_empty() {
+            vec![Line::styled(
+                format!("No launches in {} yet", journal::file().display()),
+                theme::hint(),
+            )]
+        } else {
+            items
+        };
+        let height = (items.len() as u16).clamp(1, frame.area().height.saturating_sub(8)) + 4;
+        let area = popup::area(frame.area(), height);
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+        let list = List::new(items).highlight_style(theme::selected());
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        let hint = Span::styled(
+            "Enter: pick base, Tab: switch view, Esc: close",
+            theme::hint(),
+        );
+        frame.render_widget(Paragraph::new(Line::from(hint)), hint_area);
+    }
+}
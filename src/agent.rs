@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::{parse_base_path, production_guard_word};
+
+/// Time the designer gets to open the base and start listening.
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub struct AgentOptions {
+    pub credentials: Credentials,
+    pub port: u16,
+    /// Address the agent listens on, `127.0.0.1` keeps it to this machine
+    pub listen: String,
+    /// Directory the agent commands read and write files in
+    pub base_dir: Option<PathBuf>,
+    /// Open an ssh session to the agent once it listens
+    pub connect: bool,
+}
+
+/// Starts the designer of `path` in agent mode (`/AgentMode`), which takes
+/// commands over SSH. With `connect` an ssh session is opened to it and the
+/// designer is stopped when the session ends; otherwise it runs until it
+/// is stopped with Ctrl+C. A production base asks to type its name first.
+pub fn run(path: &str, options: AgentOptions, bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    if bases.is_production(path) {
+        confirm_production(path)?;
+    }
+    let mut batch = Batch::new(&parsed, &options.credentials)?;
+    batch
+        .arg("/AgentMode")
+        .arg("/AgentPort")
+        .arg(options.port.to_string())
+        .arg("/AgentListenAddress")
+        .arg(&options.listen)
+        .arg("/AgentSSHHostKeyAuto");
+    if let Some(dir) = &options.base_dir {
+        batch.arg("/AgentBaseDir").arg(dir);
+    }
+
+    // the agent is reached locally when it listens on every address
+    let host = match options.listen.as_str() {
+        "0.0.0.0" | "::" => "127.0.0.1",
+        listen => listen,
+    };
+    let address = (host, options.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("Can't resolve {host}"))?;
+
+    let mut designer = batch.spawn()?;
+    eprintln!("Starting the designer agent of {}...", parsed.target());
+    let started = Instant::now();
+    while !listening(address) {
+        if let Some(e) = designer.exited() {
+            return Err(format!("Designer agent failed: {e}").into());
+        }
+        if started.elapsed() >= START_TIMEOUT {
+            designer.stop();
+            return Err(format!(
+                "Designer agent did not listen on {host}:{} within {} s",
+                options.port,
+                START_TIMEOUT.as_secs()
+            )
+            .into());
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    let destination = match &options.credentials.user {
+        Some(user) => format!("{user}@{host}"),
+        None => host.to_string(),
+    };
+    if !options.connect {
+        println!(
+            "Designer agent listening on {host}:{}, connect with: ssh -p {} {destination}",
+            options.port, options.port
+        );
+        designer
+            .wait()
+            .map_err(|e| format!("Designer agent failed: {e}"))?;
+        return Ok(());
+    }
+    let status = Command::new("ssh")
+        .args(["-p", &options.port.to_string(), &destination])
+        .status();
+    designer.stop();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("ssh exited with {status}").into()),
+        Err(e) => Err(format!("Could not start ssh: {e}").into()),
+    }
+}
+
+/// The agent opens the designer of the base to anyone reaching its port.
+fn confirm_production(path: &str) -> Result<(), Box<dyn Error>> {
+    let word = production_guard_word(path);
+    print!(
+        "'{}' is a production base, starting its DESIGNER agent. Type '{word}' to continue: ",
+        path.trim()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != word {
+        return Err("Agent cancelled".into());
+    }
+    Ok(())
+}
+
+fn listening(address: SocketAddr) -> bool {
+    TcpStream::connect_timeout(&address, Duration::from_millis(500)).is_ok()
+}
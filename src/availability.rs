@@ -0,0 +1,78 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bases::Bases;
+use crate::check;
+use crate::config::CheckConfig;
+use crate::events::AppEvent;
+
+/// Whether a listed base answered its last probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Up,
+    /// Unreachable, with what failed
+    Down(String),
+}
+
+/// Reachability of the bases in sight in the history pane, probed in the
+/// background with the quick checks of `precheck` and probed again once
+/// their result is older than the interval of `[availability]`.
+#[derive(Debug, Default)]
+pub struct Availability {
+    probed: HashMap<String, (Instant, Status)>,
+    /// Bases whose probe is still running
+    probing: HashSet<String>,
+}
+
+impl Availability {
+    /// Result of the last probe of `path`, `None` before the first one.
+    pub fn status(&self, path: &str) -> Option<&Status> {
+        self.probed.get(path).map(|(_, status)| status)
+    }
+
+    /// Probes those of `paths` never probed or probed more than `interval`
+    /// ago, each in a thread of its own posting `AppEvent::Availability`.
+    pub fn probe(
+        &mut self,
+        paths: impl IntoIterator<Item = String>,
+        interval: Duration,
+        bases: &Bases,
+        config: &CheckConfig,
+        sender: &Sender<AppEvent>,
+    ) {
+        let due: Vec<String> = paths
+            .into_iter()
+            .filter(|path| !path.trim().is_empty() && !self.probing.contains(path))
+            .filter(|path| {
+                self.probed
+                    .get(path)
+                    .is_none_or(|(at, _)| at.elapsed() >= interval)
+            })
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+        let shared = Arc::new((bases.clone(), config.clone()));
+        for path in due {
+            self.probing.insert(path.clone());
+            let shared = Arc::clone(&shared);
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let (bases, config) = &*shared;
+                let status = match check::precheck(&path, bases, config) {
+                    Ok(()) => Status::Up,
+                    Err(e) => Status::Down(e),
+                };
+                let _ = sender.send(AppEvent::Availability { path, status });
+            });
+        }
+    }
+
+    pub fn finish(&mut self, path: String, status: Status) {
+        self.probing.remove(&path);
+        self.probed.insert(path, (Instant::now(), status));
+    }
+}
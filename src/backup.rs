@@ -0,0 +1,270 @@
+use chrono::{Datelike, Days, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::bases::Bases;
+use crate::dump::{self, DumpOptions, Operation};
+use crate::maintenance::Window;
+use crate::upload::{self, Destination};
+use crate::{normalize_input, platform};
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Time of a dump in its file name, `erp-20261016-090000.dt`.
+const STAMP: &str = "%Y%m%d-%H%M%S";
+
+/// A backup plan, `[[backup]]` in the config: where the dumps of a base go
+/// and which of them are kept.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Plan {
+    /// Name given to `backup run`, and the start of the dump file names
+    pub name: String,
+    /// Connection string or alias of the base
+    pub base: String,
+    pub dir: PathBuf,
+    /// Newest dumps kept
+    #[serde(default)]
+    pub keep_last: Option<usize>,
+    /// Weeks back for which the newest dump of each week is kept
+    #[serde(default)]
+    pub keep_weekly: Option<u32>,
+    /// Where every dump is copied, `[[backup.upload]]`
+    #[serde(default)]
+    pub upload: Vec<Destination>,
+    /// Time of day the plan may run in, `backup run --force` runs it anyway
+    #[serde(default)]
+    pub window: Option<Window>,
+}
+
+impl Plan {
+    /// The dumps of the plan in its directory, newest first.
+    pub fn dumps(&self) -> Result<Vec<(PathBuf, NaiveDateTime)>, Box<dyn Error>> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Could not read {}: {e}", self.dir.display()))?;
+        let prefix = format!("{}-", self.name);
+        let mut dumps: Vec<(PathBuf, NaiveDateTime)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let stamp = name.strip_prefix(&prefix)?.strip_suffix(".dt")?;
+                let time = NaiveDateTime::parse_from_str(stamp, STAMP).ok()?;
+                Some((entry.path(), time))
+            })
+            .collect();
+        dumps.sort_by_key(|(_, time)| Reverse(*time));
+        Ok(dumps)
+    }
+
+    /// The dumps the retention settings let go, oldest last. Without
+    /// settings every dump is kept.
+    fn expired(&self) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        if self.keep_last.is_none() && self.keep_weekly.is_none() {
+            return Ok(Vec::new());
+        }
+        let dumps = self.dumps()?;
+        let mut kept: HashSet<usize> = (0..self.keep_last.unwrap_or(0).min(dumps.len())).collect();
+        if let Some(weeks) = self.keep_weekly {
+            let since = Local::now()
+                .naive_local()
+                .checked_sub_days(Days::new(u64::from(weeks) * 7))
+                .unwrap_or_default();
+            let mut weeks_seen = HashSet::new();
+            for (index, (_, time)) in dumps.iter().enumerate() {
+                let week = time.iso_week();
+                if *time >= since && weeks_seen.insert((week.year(), week.week())) {
+                    kept.insert(index);
+                }
+            }
+        }
+        Ok(dumps
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !kept.contains(index))
+            .map(|(_, (path, _))| path)
+            .collect())
+    }
+}
+
+/// Finds the `[[backup]]` plan named `name`.
+pub fn find<'a>(plans: &'a [Plan], name: &str) -> Result<&'a Plan, Box<dyn Error>> {
+    plans
+        .iter()
+        .find(|plan| plan.name == name)
+        .ok_or_else(|| format!("No [[backup]] plan named '{name}' in the config").into())
+}
+
+/// Fails when `plan` has a window and it is not open now.
+pub fn check_window(plan: &Plan) -> Result<(), Box<dyn Error>> {
+    match &plan.window {
+        Some(window) if !window.contains(Utc::now()) => Err(format!(
+            "Plan {} only runs within {}, pass --force to run it now",
+            plan.name,
+            window.label()
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Dumps the base of `plan` into its directory, copies the dump to the
+/// destinations of the plan, then deletes the dumps its retention settings
+/// no longer keep. A failed copy keeps every dump. Returns the new dump.
+pub fn run(plan: &Plan, bases: &Bases, options: DumpOptions) -> Result<PathBuf, Box<dyn Error>> {
+    fs::create_dir_all(&plan.dir)
+        .map_err(|e| format!("Could not create {}: {e}", plan.dir.display()))?;
+    let out = plan
+        .dir
+        .join(format!("{}-{}.dt", plan.name, Local::now().format(STAMP)));
+    let path = bases.expand_alias(&normalize_input(&plan.base));
+    dump::run(&path, Operation::Dump(out.clone()), bases, options)?;
+    upload_dump(plan, &out)?;
+    prune(plan, false)?;
+    Ok(out)
+}
+
+/// Copies the newest dump of `plan` to its destinations again, `backup
+/// upload` after a run whose copy failed.
+pub fn upload_latest(plan: &Plan) -> Result<(), Box<dyn Error>> {
+    let (latest, _) = plan
+        .dumps()?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No dumps of {} in {}", plan.name, plan.dir.display()))?;
+    if plan.upload.is_empty() {
+        return Err(format!(
+            "The plan {} has no [[backup.upload]] destinations",
+            plan.name
+        )
+        .into());
+    }
+    upload_dump(plan, &latest)
+}
+
+/// Copies `dump` and its manifest to every destination of `plan`.
+fn upload_dump(plan: &Plan, dump: &Path) -> Result<(), Box<dyn Error>> {
+    let manifest = manifest_file(dump);
+    for destination in &plan.upload {
+        upload::upload(dump, destination)?;
+        if manifest.is_file() {
+            upload::upload(&manifest, destination)?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the dumps of `plan` its retention settings no longer keep, with
+/// their manifests; with `dry_run` only lists them.
+pub fn prune(plan: &Plan, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let expired = plan.expired()?;
+    if expired.is_empty() {
+        println!("No dumps of {} to delete", plan.name);
+    }
+    for dump in expired {
+        if dry_run {
+            println!("Would delete {}", dump.display());
+            continue;
+        }
+        fs::remove_file(&dump).map_err(|e| format!("Could not delete {}: {e}", dump.display()))?;
+        let _ = fs::remove_file(manifest_file(&dump));
+        println!("Deleted {}", dump.display());
+    }
+    Ok(())
+}
+
+/// What is known about a .dt dump, kept next to it as `<file>.manifest.toml`
+/// so that a damaged or swapped file can be told before it is restored.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Manifest {
+    /// Connection string of the dumped base
+    pub base: String,
+    /// When the dump finished, RFC 3339
+    pub created: String,
+    /// Version of the platform that made the dump
+    pub platform: String,
+    pub size: u64,
+    /// SHA-256 of the file, lowercase hex
+    pub sha256: String,
+}
+
+fn manifest_file(dump: &Path) -> PathBuf {
+    let mut name = dump.as_os_str().to_owned();
+    name.push(".manifest.toml");
+    PathBuf::from(name)
+}
+
+/// Hashes the dump of `base` just made and writes its manifest.
+pub fn record(dump: &Path, base: &str) -> Result<Manifest, Box<dyn Error>> {
+    let (size, sha256) = hash(dump)?;
+    let manifest = Manifest {
+        base: base.trim().to_string(),
+        created: Local::now().to_rfc3339(),
+        platform: platform::newest()
+            .map(|platform| platform.version_string())
+            .unwrap_or_default(),
+        size,
+        sha256,
+    };
+    let file = manifest_file(dump);
+    fs::write(&file, toml::to_string(&manifest)?)
+        .map_err(|e| format!("Could not write {}: {e}", file.display()))?;
+    Ok(manifest)
+}
+
+/// Checks `dump` against its manifest, `rbaserun backup verify`.
+pub fn verify(dump: &Path) -> Result<(), Box<dyn Error>> {
+    let file = manifest_file(dump);
+    let content = fs::read_to_string(&file)
+        .map_err(|e| format!("Could not read the manifest {}: {e}", file.display()))?;
+    let manifest: Manifest = toml::from_str(&content)
+        .map_err(|e| format!("Invalid manifest {}: {e}", file.display()))?;
+    let (size, sha256) = hash(dump)?;
+    if size != manifest.size || sha256 != manifest.sha256 {
+        return Err(format!(
+            "{} does not match its manifest: {size} bytes with SHA-256 {sha256}, expected {} bytes with {}",
+            dump.display(),
+            manifest.size,
+            manifest.sha256
+        )
+        .into());
+    }
+    println!(
+        "{} is intact: dump of {} made {} with platform {}",
+        dump.display(),
+        manifest.base,
+        manifest.created,
+        manifest.platform
+    );
+    Ok(())
+}
+
+/// Size and SHA-256 of `file`.
+fn hash(file: &Path) -> Result<(u64, String), Box<dyn Error>> {
+    let mut input =
+        File::open(file).map_err(|e| format!("Could not open {}: {e}", file.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut size = 0;
+    loop {
+        let read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Could not read {}: {e}", file.display()).into()),
+        };
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, hex(&hasher.finalize())))
+}
+
+/// Lowercase hex of `bytes`.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,446 @@
+use ratatui::style::Color;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::browser::BrowserConfig;
+use crate::conditions::When;
+use crate::config::{CheckOverride, RemoteConfig};
+use crate::hooks::Hooks;
+use crate::maintenance::Window;
+use crate::requires::Requirements;
+use crate::tunnel::TunnelConfig;
+use crate::{paths, remote, store};
+
+/// Name of the bases file in the data directory and the machine config directory
+pub const FILE_NAME: &str = "rbaserun_bases.toml";
+
+pub fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// Version of the bases file format written by this build.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Last revision handed out, see `Bases::revision`.
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Forward migrations, the one at index `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [fn(&mut Table); SCHEMA_VERSION as usize] = [
+    // version 0 is the unversioned format, it only lacks the version key
+    |_| {},
+];
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Bases {
+    /// Regexes marking connection strings as production in addition to the `prod` tag
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    prod_patterns: Vec<String>,
+    #[serde(default, rename = "base")]
+    entries: Vec<BaseEntry>,
+    /// Jump host the bases were loaded from, saves go back there
+    #[serde(skip)]
+    remote: Option<RemoteConfig>,
+    /// Paths of the bases from the machine-wide file, never saved
+    #[serde(skip)]
+    managed: Vec<String>,
+    #[serde(skip)]
+    managed_patterns: Vec<String>,
+    /// Personal entries hidden by a managed base of the same path, saved as they were
+    #[serde(skip)]
+    overridden: Vec<BaseEntry>,
+    /// Positions in `entries` by trimmed path, built on the first `find` after
+    /// a change so that looking up every listed base stays cheap
+    #[serde(skip)]
+    index: OnceLock<HashMap<String, usize>>,
+    #[serde(skip)]
+    revision: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BaseEntry {
+    pub path: String,
+    /// Display name shown next to the connection string, e.g. `Accounting/erp`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Short name launching the base from the shell, e.g. `rbaserun prod`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tunnel: Option<TunnelConfig>,
+    /// Host (optionally `host:port`) to connect to with mstsc instead of the 1C client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rdp: Option<String>,
+    /// Infobase user passed with `/N`, the password is kept in the OS keyring
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Short marker shown in front of the entry in the list, e.g. `🔴 prod`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub badge: Option<String>,
+    /// Any color name or `#rrggbb` value understood by the terminal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Folder grouping the base in the history pane, e.g. `Clients`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// Pinned bases stay at the top of the history and are exported to
+    /// integrations such as Windows Terminal
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Open this web base in the browser by default, set from the last launch
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prefer_browser: bool,
+    /// Never open a second session of this base, e.g. with a per-user session
+    /// limit: a running client is brought to the front instead
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_instance: bool,
+    /// Browser, profile and publication path this web base opens with,
+    /// over `[browser]` of rbaserun.toml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserConfig>,
+    /// Configuration repository the base is bound to, e.g. `tcp://repo-srv/erp`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Local git repository or worktree with the configuration sources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<PathBuf>,
+    /// Named shell commands for `rbaserun run`, e.g. `update = "vrunner update-dev --src src"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub actions: BTreeMap<String, String>,
+    /// 1C switches appended to every launch, e.g. `["/L", "en", "/DisableStartupDialogs"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub switches: Vec<String>,
+    #[serde(flatten)]
+    pub process: ProcessEnv,
+    /// Commands run around a launch of this base, after the ones of `[hooks]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+    /// Times of day when launches warn that the base is being worked on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<Window>,
+    /// What has to be in place before a launch, checked after the hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires: Option<Requirements>,
+    /// Connection check settings overriding the `[check]` table of rbaserun.toml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check: Option<CheckOverride>,
+    /// `.ico` file shown for the base by shortcuts, Windows Terminal profiles
+    /// and the launcher feed instead of the icon of rbaserun
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<PathBuf>,
+    /// Machines the base is listed on, for bases files shared between them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<When>,
+    /// `ID=` of the base in `ibases.v8i`, kept so exports and imports round-trip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// Where the client of a base runs, set up before it is started.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProcessEnv {
+    /// Working directory of the started client, e.g. where relative `/Out` logs go
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    /// Variables added to the environment of the client, e.g. `ONEC_LOG_CFG` or `HTTPS_PROXY`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl BaseEntry {
+    pub fn color(&self) -> Option<Color> {
+        self.color
+            .as_deref()
+            .and_then(|color| Color::from_str(color).ok())
+    }
+}
+
+impl Bases {
+    /// Loads the local bases file, or the shared one from `remote` when set.
+    pub fn load(remote: Option<&RemoteConfig>) -> Result<Bases, Box<dyn Error>> {
+        let (content, location) = match remote {
+            Some(remote) => (remote::read(remote)?, remote.location()),
+            None => match fs::read_to_string(file()) {
+                Ok(content) => (content, file().display().to_string()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Bases::default()),
+                Err(e) => return Err(e.into()),
+            },
+        };
+        let (mut bases, version) = Bases::parse(&content, &location)?;
+        bases.remote = remote.cloned();
+        if version < SCHEMA_VERSION && !content.trim().is_empty() {
+            let backup = format!("{location}.v{version}.bak");
+            match remote {
+                Some(remote) => remote::write(
+                    &RemoteConfig {
+                        path: format!("{}.v{version}.bak", remote.path),
+                        ..remote.clone()
+                    },
+                    &content,
+                )?,
+                None => fs::write(&backup, &content)?,
+            }
+            bases.save()?;
+            eprintln!(
+                "Migrated {location} to schema version {SCHEMA_VERSION}, the old file is kept as {backup}"
+            );
+        }
+        bases.overlay_machine()?;
+        Ok(bases)
+    }
+
+    /// Adds the bases and prod patterns of the machine-wide bases file. Its
+    /// bases win over personal entries of the same path.
+    fn overlay_machine(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(path) = paths::machine_dir().map(|dir| dir.join(FILE_NAME)) else {
+            return Ok(());
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("Could not read {}: {e}", path.display()).into()),
+        };
+        let (machine, _) = Bases::parse(&content, &path.display().to_string())?;
+        for entry in machine.entries {
+            let path = entry.path.trim().to_string();
+            if let Some(index) = self.entries.iter().position(|own| own.path.trim() == path) {
+                self.overridden.push(self.entries.remove(index));
+            }
+            self.managed.push(path);
+            self.entries.push(entry);
+        }
+        self.changed();
+        for pattern in machine.prod_patterns {
+            if !self.prod_patterns.contains(&pattern) {
+                self.prod_patterns.push(pattern.clone());
+                self.managed_patterns.push(pattern);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads another bases file, e.g. a conflicting copy left by a sync tool.
+    pub fn read(path: &Path) -> Result<Bases, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        Ok(Bases::parse(&content, &path.display().to_string())?.0)
+    }
+
+    /// Parses and migrates a bases file, returning the schema version it had.
+    fn parse(content: &str, location: &str) -> Result<(Bases, u32), Box<dyn Error>> {
+        let mut table: Table =
+            toml::from_str(content).map_err(|e| format!("Invalid {location}: {e}"))?;
+        let version = match table.remove("version") {
+            None => 0,
+            Some(Value::Integer(version)) => u32::try_from(version)
+                .map_err(|_| format!("Invalid {location}: unknown version {version}"))?,
+            Some(_) => return Err(format!("Invalid {location}: version must be a number").into()),
+        };
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "{location} has schema version {version}, this rbaserun only reads up to \
+                 {SCHEMA_VERSION}, please update it"
+            )
+            .into());
+        }
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(&mut table);
+        }
+
+        let mut bases: Bases = table
+            .try_into()
+            .map_err(|e| format!("Invalid {location}: {e}"))?;
+        bases.changed();
+        for pattern in &bases.prod_patterns {
+            Regex::new(pattern).map_err(|e| format!("Invalid prod pattern '{pattern}': {e}"))?;
+        }
+        Ok((bases, version))
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let own = Bases {
+            prod_patterns: self
+                .prod_patterns
+                .iter()
+                .filter(|pattern| !self.managed_patterns.contains(pattern))
+                .cloned()
+                .collect(),
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| !self.is_managed(&entry.path))
+                .chain(&self.overridden)
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        let content = format!(
+            "version = {SCHEMA_VERSION}\n{}",
+            toml::to_string_pretty(&own)?
+        );
+        match &self.remote {
+            Some(remote) => remote::write(remote, &content),
+            None => {
+                paths::prepare(&file())?;
+                store::backup(&file());
+                Ok(fs::write(file(), content)?)
+            }
+        }
+    }
+
+    pub fn entries(&self) -> &[BaseEntry] {
+        &self.entries
+    }
+
+    /// Changes with every change of the entries, so the picker can tell
+    /// whether its search index is still current.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn changed(&mut self) {
+        self.index = OnceLock::new();
+        self.revision = REVISION.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// Whether `path` comes from the machine-wide bases file.
+    pub fn is_managed(&self, path: &str) -> bool {
+        self.managed.iter().any(|managed| managed == path.trim())
+    }
+
+    /// The bases of the machine-wide bases file.
+    pub fn managed(&self) -> impl Iterator<Item = &BaseEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.is_managed(&entry.path))
+    }
+
+    pub fn prod_patterns(&self) -> &[String] {
+        &self.prod_patterns
+    }
+
+    pub fn add_prod_pattern(&mut self, pattern: String) {
+        self.prod_patterns.push(pattern);
+    }
+
+    pub fn find(&self, path: &str) -> Option<&BaseEntry> {
+        let index = self.index.get_or_init(|| {
+            let mut index = HashMap::with_capacity(self.entries.len());
+            for (position, entry) in self.entries.iter().enumerate() {
+                index
+                    .entry(entry.path.trim().to_string())
+                    .or_insert(position);
+            }
+            index
+        });
+        index
+            .get(path.trim())
+            .map(|&position| &self.entries[position])
+    }
+
+    /// The base with `alias`, ignoring case.
+    pub fn find_alias(&self, alias: &str) -> Option<&BaseEntry> {
+        let alias = alias.trim();
+        self.entries.iter().find(|entry| {
+            entry
+                .alias
+                .as_deref()
+                .is_some_and(|own| own.eq_ignore_ascii_case(alias))
+        })
+    }
+
+    /// The connection string `input` is an alias of, `input` itself otherwise.
+    pub fn expand_alias(&self, input: &str) -> String {
+        match self.find_alias(input) {
+            Some(entry) => entry.path.clone(),
+            None => input.to_string(),
+        }
+    }
+
+    /// Fails when `alias` could be read as a connection string or already
+    /// belongs to a base other than `path`.
+    pub fn check_alias(&self, path: &str, alias: &str) -> Result<(), Box<dyn Error>> {
+        if alias
+            .chars()
+            .any(|c| c.is_whitespace() || "\"';=/\\".contains(c))
+        {
+            return Err("Alias can't contain spaces, quotes, ';', '=' or slashes".into());
+        }
+        match self.find_alias(alias) {
+            Some(other) if other.path.trim() != path.trim() => {
+                Err(format!("Alias '{alias}' is already used by {}", other.path).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the `when` of the base leaves it out on this machine.
+    pub fn is_elsewhere(&self, path: &str) -> bool {
+        self.find(path)
+            .and_then(|entry| entry.when.as_ref())
+            .is_some_and(|when| !when.holds())
+    }
+
+    pub fn is_single_instance(&self, path: &str) -> bool {
+        self.find(path).is_some_and(|entry| entry.single_instance)
+    }
+
+    pub fn is_production(&self, path: &str) -> bool {
+        let tagged = self.find(path).is_some_and(|entry| {
+            entry
+                .tags
+                .iter()
+                .any(|tag| tag.eq_ignore_ascii_case("prod"))
+        });
+        tagged
+            || self
+                .prod_patterns
+                .iter()
+                .filter_map(|pattern| Regex::new(pattern).ok())
+                .any(|pattern| pattern.is_match(path.trim()))
+    }
+
+    /// Drops the settings of `path`, returning them.
+    pub fn remove(&mut self, path: &str) -> Option<BaseEntry> {
+        let path = path.trim();
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.path.trim() == path)?;
+        self.changed();
+        Some(self.entries.remove(index))
+    }
+
+    /// The settings of `path`, added when it has none. The caller may change
+    /// the path of the entry too.
+    pub fn entry_mut(&mut self, path: &str) -> &mut BaseEntry {
+        self.changed();
+        let path = path.trim();
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.path.trim() == path)
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(BaseEntry {
+                    path: path.to_string(),
+                    ..Default::default()
+                });
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[index]
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+/// Browser `ws=` bases open in for the web client, `[browser]` in
+/// rbaserun.toml, overridden by `browser` of a base and by `--browser`. The
+/// default browser of the system opens them without a `program`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BrowserConfig {
+    /// Browser executable, e.g. `C:\Program Files\Google\Chrome\Application\chrome.exe`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<PathBuf>,
+    /// Profile of the browser, e.g. a Chrome profile with the 1C extension:
+    /// the profile directory of Chromium browsers, the profile name of Firefox
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Appended to the `ws=` URL, e.g. `ru_RU/` to open a localized publication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_path: Option<String>,
+}
+
+impl BrowserConfig {
+    /// These settings with the ones `over` sets taking precedence.
+    pub fn merge(&self, over: &BrowserConfig) -> BrowserConfig {
+        BrowserConfig {
+            program: over.program.clone().or_else(|| self.program.clone()),
+            profile: over.profile.clone().or_else(|| self.profile.clone()),
+            url_path: over.url_path.clone().or_else(|| self.url_path.clone()),
+        }
+    }
+
+    /// `url` with `url_path` appended, e.g. `http://web/demo/ru_RU/`.
+    pub fn url(&self, url: &str) -> String {
+        match self
+            .url_path
+            .as_deref()
+            .map(|path| path.trim_start_matches('/'))
+        {
+            Some(path) if !path.is_empty() => format!("{}/{path}", url.trim_end_matches('/')),
+            _ => url.to_string(),
+        }
+    }
+
+    /// The browser and its arguments opening `url`, `None` to leave it to
+    /// the default browser.
+    pub fn command_line(&self, url: &str) -> Option<(PathBuf, Vec<String>)> {
+        let program = self.program.clone()?;
+        let mut args = Vec::new();
+        if let Some(profile) = &self.profile {
+            if is_firefox(&program) {
+                args.extend(["-P".to_string(), profile.clone()]);
+            } else {
+                args.push(format!("--profile-directory={profile}"));
+            }
+        }
+        args.push(self.url(url));
+        Some((program, args))
+    }
+}
+
+fn is_firefox(program: &Path) -> bool {
+    program
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().to_lowercase().contains("firefox"))
+}
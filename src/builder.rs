@@ -0,0 +1,334 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use std::path::Path;
+
+use crate::editor::split_switches;
+use crate::{PathKind, parse_base_path, popup, theme};
+
+pub enum BuilderAction {
+    None,
+    Cancel,
+    /// The connection string is valid, with the user and switches typed for it
+    Done(Built),
+}
+
+/// What the builder assembled.
+#[derive(Debug)]
+pub struct Built {
+    pub path: String,
+    pub user: Option<String>,
+    pub switches: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Server,
+    File,
+    Web,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Server => "server",
+            Kind::File => "file",
+            Kind::Web => "web",
+        }
+    }
+
+    fn next(self, forward: bool) -> Kind {
+        match (self, forward) {
+            (Kind::Server, true) | (Kind::Web, false) => Kind::File,
+            (Kind::File, true) | (Kind::Server, false) => Kind::Web,
+            (Kind::Web, true) | (Kind::File, false) => Kind::Server,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldId {
+    Kind,
+    Host,
+    Ref,
+    FilePath,
+    Url,
+    User,
+    Switches,
+}
+
+impl FieldId {
+    fn label(self) -> &'static str {
+        match self {
+            FieldId::Kind => "Type",
+            FieldId::Host => "Host",
+            FieldId::Ref => "Ref",
+            FieldId::FilePath => "Path",
+            FieldId::Url => "URL",
+            FieldId::User => "User",
+            FieldId::Switches => "Switches",
+        }
+    }
+}
+
+/// Whether a field holds something the connection can be made of.
+enum Check {
+    Ok,
+    /// Usable, but likely not what was meant
+    Warning(String),
+    Error(String),
+}
+
+/// Popup assembling a connection string from its parts for those who don't
+/// remember the `Srvr="…";Ref="…";` syntax, checking each part as it is typed.
+#[derive(Debug)]
+pub struct ConnectionBuilder {
+    kind: Kind,
+    host: Input,
+    ref_name: Input,
+    file: Input,
+    url: Input,
+    user: Input,
+    switches: Input,
+    focus: usize,
+    error: Option<String>,
+}
+
+impl ConnectionBuilder {
+    /// Starts from `path` when it parses, e.g. the connection in the input.
+    pub fn new(path: &str) -> ConnectionBuilder {
+        let mut builder = ConnectionBuilder {
+            kind: Kind::Server,
+            host: Input::default(),
+            ref_name: Input::default(),
+            file: Input::default(),
+            url: Input::default(),
+            user: Input::default(),
+            switches: Input::default(),
+            focus: 0,
+            error: None,
+        };
+        match parse_base_path(path) {
+            Ok(PathKind::Server { host, ref_name }) => {
+                builder.host = host.into();
+                builder.ref_name = ref_name.into();
+            }
+            Ok(PathKind::File { path }) => {
+                builder.kind = Kind::File;
+                builder.file = path.into();
+            }
+            Ok(PathKind::Web { url }) => {
+                builder.kind = Kind::Web;
+                builder.url = url.into();
+            }
+            Err(_) => {}
+        }
+        builder
+    }
+
+    /// The fields of the current type, in the order they are shown.
+    fn fields(&self) -> Vec<FieldId> {
+        let mut fields = vec![FieldId::Kind];
+        fields.extend(match self.kind {
+            Kind::Server => &[FieldId::Host, FieldId::Ref][..],
+            Kind::File => &[FieldId::FilePath],
+            Kind::Web => &[FieldId::Url],
+        });
+        fields.extend([FieldId::User, FieldId::Switches]);
+        fields
+    }
+
+    fn input(&self, field: FieldId) -> Option<&Input> {
+        match field {
+            FieldId::Kind => None,
+            FieldId::Host => Some(&self.host),
+            FieldId::Ref => Some(&self.ref_name),
+            FieldId::FilePath => Some(&self.file),
+            FieldId::Url => Some(&self.url),
+            FieldId::User => Some(&self.user),
+            FieldId::Switches => Some(&self.switches),
+        }
+    }
+
+    fn input_mut(&mut self, field: FieldId) -> Option<&mut Input> {
+        match field {
+            FieldId::Kind => None,
+            FieldId::Host => Some(&mut self.host),
+            FieldId::Ref => Some(&mut self.ref_name),
+            FieldId::FilePath => Some(&mut self.file),
+            FieldId::Url => Some(&mut self.url),
+            FieldId::User => Some(&mut self.user),
+            FieldId::Switches => Some(&mut self.switches),
+        }
+    }
+
+    fn value(&self, field: FieldId) -> &str {
+        self.input(field).map_or("", |input| input.value().trim())
+    }
+
+    fn check(&self, field: FieldId) -> Check {
+        let value = self.value(field);
+        match field {
+            FieldId::Kind | FieldId::User => Check::Ok,
+            FieldId::Host | FieldId::Ref if value.is_empty() => Check::Error("required".into()),
+            FieldId::Host if value.contains(char::is_whitespace) => {
+                Check::Error("no spaces, e.g. srv or srv1:1541,srv2:1541".into())
+            }
+            FieldId::Ref if value.contains(|c: char| c.is_whitespace() || "\\/;".contains(c)) => {
+                Check::Error("the infobase name on the cluster, no spaces or slashes".into())
+            }
+            FieldId::Host | FieldId::Ref => Check::Ok,
+            FieldId::FilePath if value.is_empty() => Check::Error("required".into()),
+            FieldId::FilePath if !Path::new(value).join("1Cv8.1CD").is_file() => {
+                Check::Warning("no 1Cv8.1CD there on this machine".into())
+            }
+            FieldId::FilePath => Check::Ok,
+            FieldId::Url if value.is_empty() => Check::Error("required".into()),
+            FieldId::Url if !(value.starts_with("http://") || value.starts_with("https://")) => {
+                Check::Error("starts with http:// or https://".into())
+            }
+            FieldId::Url => Check::Ok,
+            FieldId::Switches => match split_switches(value)
+                .into_iter()
+                .find(|switch| !switch.starts_with('/'))
+            {
+                Some(_) if !value.is_empty() => {
+                    Check::Warning("1C switches start with /, e.g. /L en".into())
+                }
+                _ => Check::Ok,
+            },
+        }
+    }
+
+    /// The connection string of the fields, once they are all valid.
+    fn connection(&self) -> Result<String, String> {
+        if let Some(field) = self
+            .fields()
+            .into_iter()
+            .find(|field| matches!(self.check(*field), Check::Error(_)))
+        {
+            return Err(format!("Fill in {} first", field.label()));
+        }
+        let path = match self.kind {
+            Kind::Server => PathKind::Server {
+                host: self.value(FieldId::Host).to_string(),
+                ref_name: self.value(FieldId::Ref).to_string(),
+            },
+            Kind::File => PathKind::File {
+                path: self.value(FieldId::FilePath).to_string(),
+            },
+            Kind::Web => PathKind::Web {
+                url: self.value(FieldId::Url).to_string(),
+            },
+        };
+        let connection = path.connection_string();
+        // the string has to read back as the same base
+        parse_base_path(&connection).map_err(|e| e.to_string())?;
+        Ok(connection)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> BuilderAction {
+        let Event::Key(key_event) = event else {
+            return BuilderAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return BuilderAction::None;
+        }
+        let fields = self.fields();
+        let field = fields[self.focus];
+        match key_event.code {
+            KeyCode::Esc => return BuilderAction::Cancel,
+            KeyCode::Enter => match self.connection() {
+                Ok(path) => {
+                    let user = self.value(FieldId::User);
+                    return BuilderAction::Done(Built {
+                        path,
+                        user: (!user.is_empty()).then(|| user.to_string()),
+                        switches: split_switches(self.value(FieldId::Switches)),
+                    });
+                }
+                Err(e) => self.error = Some(e),
+            },
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus = (self.focus + fields.len() - 1) % fields.len()
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if field == FieldId::Kind => {
+                self.kind = self.kind.next(key_event.code != KeyCode::Left);
+                self.error = None;
+            }
+            _ => {
+                if let Some(input) = self.input_mut(field) {
+                    input.handle_event(event);
+                    self.error = None;
+                }
+            }
+        }
+        BuilderAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let fields = self.fields();
+        let area = popup::area(frame.area(), fields.len() as u16 + 6);
+
+        let label_width = "Switches: ".len();
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let label = format!("{:<label_width$}", format!("{}:", field.label()));
+            let value = match field {
+                FieldId::Kind => format!("< {} >", self.kind.label()),
+                field => self.value(*field).to_string(),
+            };
+            let mut line = Line::from(vec![label.into(), value.into()]);
+            match self.check(*field) {
+                Check::Ok => {}
+                Check::Warning(text) => {
+                    line.push_span(Span::styled(format!("  {text}"), theme::warning()))
+                }
+                Check::Error(text) => {
+                    line.push_span(Span::styled(format!("  {text}"), theme::error()))
+                }
+            }
+            lines.push(if index == self.focus {
+                line.bold()
+            } else {
+                line
+            });
+        }
+        lines.push(Line::default());
+        lines.push(match self.connection() {
+            Ok(path) => Line::from(vec![
+                "Result:   ".into(),
+                Span::styled(path, theme::success()),
+            ]),
+            Err(_) => Line::styled("Result:   incomplete", theme::hint()),
+        });
+        lines.push(match &self.error {
+            Some(error) => Span::styled(error.clone(), theme::error()).into(),
+            None => Span::styled(
+                "Enter: use, Esc: cancel, Tab: next field, Left/Right: change the type",
+                theme::hint(),
+            )
+            .into(),
+        });
+
+        let block = Block::bordered().title("New connection");
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+
+        if let Some(input) = self.input(fields[self.focus]) {
+            frame.set_cursor_position((
+                inner.x + (label_width + input.visual_cursor()) as u16,
+                inner.y + self.focus as u16,
+            ));
+        }
+    }
+}
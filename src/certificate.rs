@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme};
+
+use std::error::Error;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Leaf certificate presented by an https publication.
+#[derive(Debug)]
+pub struct Certificate {
+    pub not_after: DateTime<Utc>,
+    pub issuer: String,
+}
+
+impl Certificate {
+    pub fn days_left(&self) -> i64 {
+        (self.not_after - Utc::now()).num_days()
+    }
+}
+
+/// Reads the certificate of `host:port` without validating it, so expired and
+/// self-signed certificates can be reported too.
+pub fn fetch(host: &str, port: u16, timeout: Duration) -> Result<Certificate, Box<dyn Error>> {
+    let provider = Arc::new(ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(InspectOnly(provider)))
+        .with_no_client_auth();
+
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or(format!("Could not resolve {host}"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut connection =
+        ClientConnection::new(Arc::new(config), ServerName::try_from(host.to_string())?)?;
+    while connection.is_handshaking() {
+        connection.complete_io(&mut stream)?;
+    }
+    let der = connection
+        .peer_certificates()
+        .and_then(|certificates| certificates.first())
+        .ok_or("Server sent no certificate")?;
+
+    let (_, certificate) = x509_parser::parse_x509_certificate(der)?;
+    let not_after = DateTime::from_timestamp(certificate.validity().not_after.timestamp(), 0)
+        .ok_or("Invalid certificate expiry")?;
+    let issuer = certificate
+        .issuer()
+        .iter_common_name()
+        .next()
+        .and_then(|name| name.as_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| certificate.issuer().to_string());
+    Ok(Certificate { not_after, issuer })
+}
+
+/// Accepts any certificate but still checks handshake signatures.
+#[derive(Debug)]
+struct InspectOnly(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for InspectOnly {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -0,0 +1,160 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+};
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::{history, keys, paths, platform, popup, theme};
+
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// The version whose notes were last shown, so that they show once.
+const FILE_NAME: &str = "rbaserun_version";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// The sections of the changelog added since the version that ran last,
+/// `None` when the version did not change. Remembers this version either
+/// way. A first run shows nothing, unless a history tells of an update from
+/// a version that did not remember itself yet; that one sees the latest section.
+pub fn since_last_run() -> Option<String> {
+    let last = fs::read_to_string(file()).ok();
+    if last.as_deref().map(str::trim) == Some(VERSION) {
+        return None;
+    }
+    let _ = paths::prepare(&file()).and_then(|()| fs::write(file(), VERSION));
+    let sections = sections();
+    let notes: Vec<&str> = match last.as_deref().and_then(parse_version) {
+        Some(last) => sections
+            .iter()
+            .filter(|(version, _)| *version > last)
+            .map(|(_, text)| *text)
+            .collect(),
+        None if history::file().exists() => {
+            sections.iter().take(1).map(|(_, text)| *text).collect()
+        }
+        None => Vec::new(),
+    };
+    (!notes.is_empty()).then(|| notes.join("\n\n"))
+}
+
+/// `## <version>` sections of the changelog with their version, newest first
+/// as they are written.
+fn sections() -> Vec<(Vec<u32>, &'static str)> {
+    CHANGELOG
+        .split("\n## ")
+        .skip(1)
+        .filter_map(|section| {
+            let heading = section.lines().next()?;
+            Some((parse_version(heading)?, section.trim()))
+        })
+        .collect()
+}
+
+fn parse_version(text: &str) -> Option<Vec<u32>> {
+    text.trim()
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Writes the whole changelog to the temp directory and opens it with the
+/// default application.
+fn open_full() -> Result<(), Box<dyn Error>> {
+    let path = env::temp_dir().join(format!("rbaserun-{VERSION}-changelog.md"));
+    fs::write(&path, CHANGELOG)?;
+    Command::new(platform::browser_opener())
+        .arg(&path)
+        .spawn()
+        .map_err(|e| format!("Could not open {}: {e}", path.display()))?;
+    Ok(())
+}
+
+pub enum WhatsNewAction {
+    None,
+    Close,
+}
+
+/// Popup with the notes of the versions since the last run, shown once
+/// when the picker opens after an update.
+#[derive(Debug)]
+pub struct WhatsNewPanel {
+    notes: String,
+    scroll: u16,
+    notice: Option<String>,
+}
+
+impl WhatsNewPanel {
+    pub fn new(notes: String) -> WhatsNewPanel {
+        WhatsNewPanel {
+            notes,
+            scroll: 0,
+            notice: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> WhatsNewAction {
+        let Event::Key(key_event) = event else {
+            return WhatsNewAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return WhatsNewAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc | KeyCode::Enter => return WhatsNewAction::Close,
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('o') => {
+                self.notice = Some(match open_full() {
+                    Ok(()) => "Opened the release notes".to_string(),
+                    Err(e) => e.to_string(),
+                });
+            }
+            _ => {}
+        }
+        WhatsNewAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let lines: Vec<Line> = self
+            .notes
+            .lines()
+            .map(|line| match parse_version(line) {
+                Some(_) => Line::from(line).bold(),
+                None => Line::from(line),
+            })
+            .collect();
+        let height = (lines.len() as u16 + 4).min(frame.area().height.saturating_sub(2));
+        let area = popup::area(frame.area(), height);
+        let footer = match &self.notice {
+            Some(notice) => Span::styled(notice.as_str(), theme::hint()),
+            None => Span::styled(
+                "o: full release notes, Up/Down: scroll, Esc: close",
+                theme::hint(),
+            ),
+        };
+        let block = Block::bordered()
+            .title(format!("What's new in rbaserun {VERSION}"))
+            .title_bottom(footer);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(block),
+            area,
+        );
+    }
+}
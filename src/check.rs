@@ -0,0 +1,467 @@
+#[cfg(feature = "http")]
+use ureq::http::Uri;
+#[cfg(feature = "http")]
+use ureq::{Agent, ResponseExt};
+
+use std::error::Error;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::bases::Bases;
+use crate::config::{CheckConfig, RasConfig};
+use crate::ras::{self, Database};
+use crate::tunnel::split_host_port;
+use crate::{PathKind, commands, credentials, history, parse_base_path};
+#[cfg(feature = "http")]
+use crate::{certificate, locale};
+
+/// Certificates expiring sooner than this fail the check.
+#[cfg(feature = "http")]
+const CERTIFICATE_WARN_DAYS: i64 = 14;
+
+/// Outcome of one probe of a connection check.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Probe {
+    fn new(name: impl Into<String>, ok: bool, detail: impl Into<String>) -> Probe {
+        Probe {
+            name: name.into(),
+            ok,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Checks whether the base behind `path` looks reachable.
+fn check(path: &PathKind, config: &CheckConfig) -> Vec<Probe> {
+    match path {
+        PathKind::Server { host, .. } => check_server(host, config, true),
+        PathKind::File { path } => vec![check_file(path)],
+        PathKind::Web { url } => check_web(url, config),
+    }
+}
+
+/// Probes the cluster ports of `host`, the agent only with `agent` since
+/// clients connect without it.
+fn check_server(host: &str, config: &CheckConfig, agent: bool) -> Vec<Probe> {
+    let (host, port) = split_host_port(host);
+    // clusters on non-default ports usually shift all of them by the same offset
+    let offset = i32::from(port.unwrap_or(config.manager_port)) - i32::from(config.manager_port);
+    let shift = |port: u16| u16::try_from(i32::from(port) + offset).unwrap_or(port);
+
+    let mut probes = Vec::new();
+    if agent {
+        probes.push(check_component(
+            &host,
+            Component::Agent,
+            shift(config.agent_port),
+            shift(config.agent_port),
+            config.timeout(),
+        ));
+    }
+    probes.push(check_component(
+        &host,
+        Component::Manager,
+        shift(config.manager_port),
+        shift(config.manager_port),
+        config.timeout(),
+    ));
+    probes.push(match config.process_ports.range() {
+        Ok(range) => check_component(
+            &host,
+            Component::Processes,
+            shift(*range.start()),
+            shift(*range.end()),
+            config.timeout(),
+        ),
+        Err(e) => Probe::new(Component::Processes.name(), false, e),
+    });
+    probes
+}
+
+/// Probes the database server of the server base `path`, asking ras where
+/// it is. `cluster_up` tells whether the cluster answered the other probes.
+fn check_database(
+    path: &str,
+    bases: &Bases,
+    ras: &RasConfig,
+    config: &CheckConfig,
+    cluster_up: bool,
+) -> Probe {
+    let credentials = credentials::stored(path, bases);
+    let database = match ras::database(path, &credentials, ras) {
+        Ok(database) => database,
+        Err(e) => return Probe::new("dbms", false, format!("unknown, ras: {e}")),
+    };
+    let Some((host, port)) = database_address(&database, path) else {
+        return Probe::new(
+            "dbms",
+            false,
+            format!("no default port for {} {}", database.dbms, database.server),
+        );
+    };
+    let name = format!("dbms {} {host}:{port}", database.dbms);
+    if connect(&host, port, config.timeout()) {
+        Probe::new(name, true, "open")
+    } else if cluster_up {
+        Probe::new(
+            name,
+            false,
+            "no answer, cluster up but DBMS down, sessions fail to open the infobase",
+        )
+    } else {
+        Probe::new(name, false, "no answer")
+    }
+}
+
+/// Host and port of the database server as 1C takes it: `host`, `host,1434`
+/// or `host\instance` for SQL Server, `host port=5433` for PostgreSQL.
+/// `localhost` is the cluster host of `path`, where the server connects from.
+fn database_address(database: &Database, path: &str) -> Option<(String, u16)> {
+    let default = match database.dbms.as_str() {
+        "MSSQLServer" => 1433,
+        "PostgreSQL" => 5432,
+        "IBMDB2" => 50000,
+        "OracleDatabase" => 1521,
+        _ => return None,
+    };
+    let server = database.server.trim();
+    let server = server.strip_prefix("tcp:").unwrap_or(server);
+    let (host, port) = if let Some((host, port)) = server.split_once(',') {
+        (host, port.trim().parse().ok())
+    } else if let Some((host, options)) = server.split_once(char::is_whitespace) {
+        let port = options
+            .split_whitespace()
+            .find_map(|option| option.strip_prefix("port="))
+            .and_then(|port| port.parse().ok());
+        (host, port)
+    } else {
+        (server, None)
+    };
+    // named SQL Server instances listen on the port the instance is configured to
+    let host = host.split('\\').next().unwrap_or(host).trim();
+    let (host, host_port) = split_host_port(host);
+    let host = match host.to_lowercase().as_str() {
+        "localhost" | "127.0.0.1" | "." | "(local)" => ras::cluster_host(path)?,
+        _ => host,
+    };
+    Some((host, port.or(host_port).unwrap_or(default)))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Component {
+    Agent,
+    Manager,
+    Processes,
+}
+
+impl Component {
+    fn name(self) -> &'static str {
+        match self {
+            Component::Agent => "agent",
+            Component::Manager => "manager",
+            Component::Processes => "working processes",
+        }
+    }
+
+    /// What an unreachable component means for the user.
+    fn diagnosis(self) -> &'static str {
+        match self {
+            Component::Agent => {
+                "ragent is down or the port is filtered, cluster administration fails"
+            }
+            Component::Manager => "rmngr is down or the port is filtered, clients cannot connect",
+            Component::Processes => "no rphost answers, sessions fail right after connecting",
+        }
+    }
+}
+
+fn check_component(
+    host: &str,
+    component: Component,
+    from: u16,
+    to: u16,
+    timeout: Duration,
+) -> Probe {
+    let ports = if from == to {
+        format!("{host}:{from}")
+    } else {
+        format!("{host}:{from}-{to}")
+    };
+    let name = format!("{} {ports}", component.name());
+    match check_ports(host, from, to, timeout) {
+        0 => Probe::new(name, false, format!("no answer, {}", component.diagnosis())),
+        _ if from == to => Probe::new(name, true, "open"),
+        open => Probe::new(name, true, format!("{open} of {} open", to - from + 1)),
+    }
+}
+
+/// Probes all ports of a range in parallel and returns how many of them accept
+/// connections.
+fn check_ports(host: &str, from: u16, to: u16, timeout: Duration) -> usize {
+    thread::scope(|scope| {
+        let probes: Vec<_> = (from..=to)
+            .map(|port| scope.spawn(move || connect(host, port, timeout)))
+            .collect();
+        probes
+            .into_iter()
+            .filter_map(|probe| probe.join().ok())
+            .filter(|open| *open)
+            .count()
+    })
+}
+
+fn connect(host: &str, port: u16, timeout: Duration) -> bool {
+    let Ok(addrs) = (host, port).to_socket_addrs() else {
+        return false;
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok())
+}
+
+fn check_file(path: &str) -> Probe {
+    let dir = Path::new(path);
+    if !dir.is_dir() {
+        Probe::new("file", false, format!("{path} not found"))
+    } else if !dir.join("1Cv8.1CD").is_file() {
+        Probe::new("file", false, "no 1Cv8.1CD in the directory")
+    } else {
+        Probe::new("file", true, "1Cv8.1CD found")
+    }
+}
+
+#[cfg(feature = "http")]
+fn http_agent(config: &CheckConfig) -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(config.timeout()))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+#[cfg(feature = "http")]
+fn check_web(url: &str, config: &CheckConfig) -> Vec<Probe> {
+    let agent = http_agent(config);
+    // inspected first so that expired certificates show up next to the failed request
+    let mut probes: Vec<Probe> = check_certificate(url, config.timeout())
+        .into_iter()
+        .collect();
+    let mut response = match agent.get(url).call() {
+        Ok(response) => response,
+        Err(e) => {
+            probes.push(Probe::new("http", false, e.to_string()));
+            return probes;
+        }
+    };
+
+    let status = response.status();
+    let reason = status.canonical_reason().unwrap_or_default();
+    let status = status.as_u16();
+    probes.push(Probe::new(
+        "http",
+        config.http_status.contains(&status),
+        format!("status {status} {reason}").trim_end().to_string(),
+    ));
+    let final_url = response.get_uri().to_string();
+    if final_url.trim_end_matches('/') != url.trim_end_matches('/') {
+        probes.push(Probe::new("redirect", true, format!("to {final_url}")));
+    }
+
+    let body = response.body_mut().read_to_string().unwrap_or_default();
+    probes.push(match Page::classify(&body) {
+        Page::WebClient => Probe::new("page", true, "1C web client"),
+        Page::ServerError(server) => Probe::new("page", false, format!("{server} error page")),
+        Page::Unknown => Probe::new("page", false, "does not look like a 1C web client"),
+    });
+    if let Some(expected) = &config.http_contains
+        && !body.contains(expected.as_str())
+    {
+        probes.push(Probe::new("content", false, format!("no \"{expected}\"")));
+    }
+    probes
+}
+
+#[cfg(feature = "http")]
+/// Reports expiry and issuer of https publications, `None` for plain http.
+fn check_certificate(url: &str, timeout: Duration) -> Option<Probe> {
+    let uri: Uri = url.parse().ok()?;
+    if uri.scheme_str() != Some("https") {
+        return None;
+    }
+    let host = uri.host()?;
+    let certificate = match certificate::fetch(host, uri.port_u16().unwrap_or(443), timeout) {
+        Ok(certificate) => certificate,
+        Err(e) => return Some(Probe::new("certificate", false, e.to_string())),
+    };
+    let days = certificate.days_left();
+    let expiry = if days < 0 {
+        format!("expired on {}", locale::date(&certificate.not_after))
+    } else {
+        format!(
+            "expires {} (in {days} days)",
+            locale::date(&certificate.not_after)
+        )
+    };
+    Some(Probe::new(
+        "certificate",
+        days >= CERTIFICATE_WARN_DAYS,
+        format!("{expiry}, issued by {}", certificate.issuer),
+    ))
+}
+
+#[cfg(feature = "http")]
+enum Page {
+    WebClient,
+    ServerError(&'static str),
+    Unknown,
+}
+
+#[cfg(feature = "http")]
+impl Page {
+    fn classify(body: &str) -> Page {
+        const WEB_CLIENT: [&str; 4] = [
+            "e1cib",
+            "1C:Enterprise",
+            "1С:Предприятие",
+            "mod_main_loader",
+        ];
+        const SERVERS: [(&str, &str); 3] = [
+            ("Internet Information Services", "IIS"),
+            ("Microsoft-IIS", "IIS"),
+            ("Apache", "Apache"),
+        ];
+        if WEB_CLIENT.iter().any(|marker| body.contains(marker)) {
+            return Page::WebClient;
+        }
+        match SERVERS.iter().find(|(marker, _)| body.contains(marker)) {
+            Some((_, server)) => Page::ServerError(server),
+            None => Page::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+/// A HEAD request to a web base, failing on missing publications and server
+/// errors only: redirects to a login page are fine before a launch.
+fn head_web(url: &str, config: &CheckConfig) -> Probe {
+    match http_agent(config).head(url).call() {
+        Ok(response) => {
+            let status = response.status();
+            let reason = status.canonical_reason().unwrap_or_default();
+            let ok = !status.is_server_error() && status.as_u16() != 404;
+            let detail = format!("status {} {reason}", status.as_u16());
+            Probe::new("http", ok, detail.trim_end())
+        }
+        Err(e) => Probe::new("http", false, e.to_string()),
+    }
+}
+
+/// Without the `http` feature web bases can only be launched, not checked.
+#[cfg(not(feature = "http"))]
+fn check_web(_url: &str, _config: &CheckConfig) -> Vec<Probe> {
+    vec![Probe::new(
+        "http",
+        false,
+        "rbaserun was built without the http feature",
+    )]
+}
+
+/// Skipped without the `http` feature, leaving it to the client to fail.
+#[cfg(not(feature = "http"))]
+fn head_web(_url: &str, _config: &CheckConfig) -> Probe {
+    Probe::new("http", true, "not checked, built without the http feature")
+}
+
+/// Quick check run before a launch when `precheck` is on, so an unreachable
+/// base fails with a clear error instead of 1C failing after it started.
+/// Checks the ports clients need on a server (any of the servers of a
+/// cluster list will do), the database file of a file base and a HEAD
+/// request to a web base.
+pub fn precheck(path: &str, bases: &Bases, config: &CheckConfig) -> Result<(), String> {
+    let parsed = parse_base_path(path).map_err(|e| format!("Parsing error: {e}"))?;
+    let config = config.with(bases.find(path).and_then(|entry| entry.check.as_ref()));
+    let failed: Vec<Probe> = match &parsed {
+        PathKind::Server { host, .. } => {
+            let mut failed = Vec::new();
+            for host in host.split(',').map(str::trim) {
+                let probes = check_server(host, &config, false);
+                if probes.iter().all(|probe| probe.ok) {
+                    return Ok(());
+                }
+                failed.extend(probes.into_iter().filter(|probe| !probe.ok));
+            }
+            failed
+        }
+        PathKind::File { path } => vec![check_file(path)],
+        PathKind::Web { url } => vec![head_web(url, &config)],
+    };
+    let failed: Vec<String> = failed
+        .into_iter()
+        .filter(|probe| !probe.ok)
+        .map(|probe| format!("{}: {}", probe.name, probe.detail))
+        .collect();
+    if failed.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{} is not reachable ({}), see `rbaserun check` for details",
+        path.trim(),
+        failed.join("; ")
+    ))
+}
+
+/// Parses `path` and checks it with the settings of its base entry applied.
+pub fn check_path(
+    path: &str,
+    bases: &Bases,
+    config: &CheckConfig,
+    ras: &RasConfig,
+) -> Result<Vec<Probe>, String> {
+    let parsed = parse_base_path(path).map_err(|e| format!("Parsing error: {e}"))?;
+    let config = config.with(bases.find(path).and_then(|entry| entry.check.as_ref()));
+    let mut probes = check(&parsed, &config);
+    if config.dbms && matches!(parsed, PathKind::Server { .. }) {
+        let cluster_up = probes.iter().all(|probe| probe.ok);
+        probes.push(check_database(path, bases, ras, &config, cluster_up));
+    }
+    Ok(probes)
+}
+
+/// Checks `paths` (all known bases when empty) and prints one line per probe.
+pub fn run(
+    paths: Vec<String>,
+    bases: &Bases,
+    config: &CheckConfig,
+    ras: &RasConfig,
+) -> Result<(), Box<dyn Error>> {
+    let paths = if paths.is_empty() {
+        commands::known_paths(&history::load(), bases)
+    } else {
+        paths
+    };
+    let mut failed = 0;
+    for path in &paths {
+        println!("{path}");
+        let probes = check_path(path, bases, config, ras)
+            .unwrap_or_else(|e| vec![Probe::new("parse", false, e)]);
+        if probes.iter().any(|probe| !probe.ok) {
+            failed += 1;
+        }
+        for probe in probes {
+            let status = if probe.ok { "ok  " } else { "FAIL" };
+            println!("  {status} {}: {}", probe.name, probe.detail);
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} bases failed the check", paths.len()).into());
+    }
+    Ok(())
+}
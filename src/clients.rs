@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::events::AppEvent;
+use crate::{parse_base_path, platform};
+
+/// How often the process list is read again while the picker is open.
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 1C clients running on this machine, by the base they opened, read from
+/// the command lines of the `1cv8` and `1cv8c` processes. Clients started
+/// elsewhere than rbaserun count too.
+#[derive(Debug, Default)]
+pub struct LocalClients {
+    /// Process ids by the lowercased target of the base, see `key`
+    clients: HashMap<String, Vec<u32>>,
+    scanned: Option<Instant>,
+    scanning: bool,
+}
+
+impl LocalClients {
+    /// Processes of the clients that opened `path`.
+    pub fn of(&self, path: &str) -> &[u32] {
+        key(path)
+            .and_then(|key| self.clients.get(&key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Reads the process list in a thread posting `AppEvent::LocalClients`,
+    /// unless it was read less than `SCAN_INTERVAL` ago.
+    pub fn scan(&mut self, sender: &Sender<AppEvent>) {
+        if self.scanning || self.scanned.is_some_and(|at| at.elapsed() < SCAN_INTERVAL) {
+            return;
+        }
+        self.scanning = true;
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let clients = platform::client_processes()
+                .into_iter()
+                .filter_map(|(pid, command_line)| Some((pid, opened_base(&command_line)?)))
+                .collect();
+            let _ = sender.send(AppEvent::LocalClients(clients));
+        });
+    }
+
+    pub fn finish(&mut self, clients: Vec<(u32, String)>) {
+        self.scanning = false;
+        self.scanned = Some(Instant::now());
+        self.clients.clear();
+        for (pid, target) in clients {
+            self.clients.entry(target).or_default().push(pid);
+        }
+    }
+}
+
+/// Processes of the clients that opened `path`, reading the process list
+/// right away, for launches from the command line.
+pub fn running(path: &str) -> Vec<u32> {
+    let Some(key) = key(path) else {
+        return Vec::new();
+    };
+    platform::client_processes()
+        .into_iter()
+        .filter(|(_, command_line)| opened_base(command_line).as_ref() == Some(&key))
+        .map(|(pid, _)| pid)
+        .collect()
+}
+
+/// Brings the client `pid` of the single-instance base `path` to the front,
+/// explaining why nothing was launched when that fails.
+pub fn focus_single(path: &str, pid: u32) -> Result<(), String> {
+    platform::focus_window(pid).map_err(|e| {
+        format!(
+            "{} allows a single session, already open in process {pid}: {e}",
+            path.trim()
+        )
+    })
+}
+
+/// Text shown next to a base with clients running, e.g. `2 local sessions`.
+pub fn label(count: usize) -> String {
+    if count == 1 {
+        "1 local session".to_string()
+    } else {
+        format!("{count} local sessions")
+    }
+}
+
+/// The base a client command line opened as a key of `LocalClients`: the value
+/// after `/S`, `/F` or `/WS`.
+fn opened_base(command_line: &str) -> Option<String> {
+    let args = split(command_line);
+    let position = args.iter().position(|arg| {
+        ["/S", "/F", "/WS"]
+            .iter()
+            .any(|key| arg.eq_ignore_ascii_case(key))
+    })?;
+    let target = args.get(position + 1)?;
+    Some(normalize(target))
+}
+
+fn key(path: &str) -> Option<String> {
+    parse_base_path(path)
+        .ok()
+        .map(|path| normalize(&path.target()))
+}
+
+/// Targets compare without case and trailing separators, the way 1C does.
+fn normalize(target: &str) -> String {
+    target.trim().trim_end_matches(['\\', '/']).to_lowercase()
+}
+
+/// Arguments of a command line, with the double quotes of the Windows one.
+fn split(command_line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command_line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
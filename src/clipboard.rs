@@ -0,0 +1,28 @@
+#[cfg(feature = "clipboard")]
+use arboard::Clipboard;
+
+use std::error::Error;
+
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> Result<(), Box<dyn Error>> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| format!("Clipboard error: {e}").into())
+}
+
+#[cfg(feature = "clipboard")]
+pub fn paste() -> Result<String, Box<dyn Error>> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| format!("Clipboard error: {e}").into())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> Result<(), Box<dyn Error>> {
+    Err("Clipboard error: rbaserun was built without the clipboard feature".into())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn paste() -> Result<String, Box<dyn Error>> {
+    Err("Clipboard error: rbaserun was built without the clipboard feature".into())
+}
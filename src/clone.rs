@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::fs::{self, File, TryLockError};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::bases::Bases;
+use crate::space::{self, DATABASE_FILE, LOCK_FILE};
+use crate::{PathKind, parse_base_path};
+use crate::{history, locale, theme};
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Copies the database file of a file base into `dest` and registers the copy
+/// as a new base tagged `copy`.
+pub fn run(source: &str, dest: &Path, bases: &mut Bases) -> Result<(), Box<dyn Error>> {
+    let source_dir = source_dir(source)?;
+    let source_file = source_dir.join(DATABASE_FILE);
+    let mut input = File::open(&source_file)
+        .map_err(|e| format!("Could not open {}: {e}", source_file.display()))?;
+    match input.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Err("The base is in use, close all its sessions first".into());
+        }
+        Err(TryLockError::Error(e)) => return Err(e.into()),
+    }
+    if source_dir.join(LOCK_FILE).exists() {
+        eprintln!("Warning: {LOCK_FILE} exists, the copy may contain unfinished transactions");
+    }
+
+    fs::create_dir_all(dest)?;
+    let dest_file = dest.join(DATABASE_FILE);
+    if dest_file.exists() {
+        return Err(format!("{} already exists", dest_file.display()).into());
+    }
+    let size = input.metadata()?.len();
+    space::check(dest, size)?;
+
+    if let Err(e) = copy_with_progress(&mut input, &dest_file, size) {
+        let _ = fs::remove_file(&dest_file);
+        return Err(format!("Copy failed: {e}").into());
+    }
+
+    let dest = fs::canonicalize(dest)?;
+    let path = format!("File=\"{}\";", dest.display());
+    let entry = bases.entry_mut(&path);
+    if !entry.tags.iter().any(|tag| tag == "copy") {
+        entry.tags.push("copy".into());
+    }
+    bases.save()?;
+    history::remember(path.clone())?;
+    println!("Registered {path}");
+    Ok(())
+}
+
+/// Directory of a file base given as a connection string or a plain path.
+pub fn source_dir(source: &str) -> Result<PathBuf, Box<dyn Error>> {
+    match parse_base_path(source) {
+        Ok(PathKind::File { path }) => Ok(PathBuf::from(path)),
+        Ok(path) => Err(format!(
+            "Only file bases can be copied, this is a {} base",
+            path.kind_name()
+        )
+        .into()),
+        Err(_) if Path::new(source).is_dir() => Ok(PathBuf::from(source)),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn copy_with_progress(input: &mut File, dest: &Path, size: u64) -> io::Result<()> {
+    let mut output = File::create(dest)?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut copied = 0u64;
+    let mut stderr = io::stderr();
+    // without motion the progress is only reported once the copy is done
+    let redraw = !theme::reduced_motion();
+    if !redraw {
+        writeln!(stderr, "Copying {}...", locale::size(size))?;
+    }
+    loop {
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buffer[..read])?;
+        copied += read as u64;
+        if !redraw {
+            continue;
+        }
+        let percent = (copied * 100).checked_div(size).unwrap_or(100);
+        write!(
+            stderr,
+            "\rCopying {percent:>3}% ({} of {})",
+            locale::size(copied),
+            locale::size(size)
+        )?;
+        stderr.flush()?;
+    }
+    if redraw {
+        writeln!(stderr)?;
+    } else {
+        writeln!(stderr, "Copied {}", locale::size(copied))?;
+    }
+    output.sync_all()
+}
@@ -0,0 +1,253 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+
+use std::error::Error;
+
+use crate::config::RasConfig;
+use crate::fuzzy;
+use crate::popup;
+use crate::ras::{Listed, Ras};
+use crate::theme;
+
+pub enum BrowserAction {
+    None,
+    Select(String),
+    /// Add the listed infobases of the shown cluster to the history
+    Import(Vec<Listed>),
+    Close,
+}
+
+/// Infobases of the registered clusters and of the host of the current base,
+/// shown with Ctrl+B and filtered by typing, so a base can be picked without
+/// knowing its ref name. Tab switches between the clusters.
+#[derive(Debug)]
+pub struct ClusterBrowser {
+    targets: Vec<Ras>,
+    current: usize,
+    entries: Vec<Listed>,
+    /// Name of the cluster shown next to the bases when there are several
+    several: bool,
+    filter: String,
+    state: ListState,
+    /// Result of the last listing or import, shown instead of the hint
+    notice: Option<(String, bool)>,
+}
+
+impl ClusterBrowser {
+    /// Lists the infobases of the ras of `host`, or of the first registered
+    /// cluster when there is no host.
+    pub fn open(host: Option<&str>, config: &RasConfig) -> Result<ClusterBrowser, Box<dyn Error>> {
+        let targets = Ras::all(host, config);
+        if targets.is_empty() {
+            return Err("Type a host, select a server base or register a cluster to browse".into());
+        }
+        let current = host
+            .and_then(|host| {
+                targets
+                    .iter()
+                    .position(|ras| ras.host.eq_ignore_ascii_case(host))
+            })
+            .unwrap_or(0);
+        let mut browser = ClusterBrowser {
+            targets,
+            current,
+            entries: Vec::new(),
+            several: false,
+            filter: String::new(),
+            state: ListState::default().with_selected(Some(0)),
+            notice: None,
+        };
+        browser.load();
+        Ok(browser)
+    }
+
+    /// The ras of the cluster shown.
+    pub fn ras(&self) -> &Ras {
+        &self.targets[self.current]
+    }
+
+    pub fn notify(&mut self, notice: String, error: bool) {
+        self.notice = Some((notice, error));
+    }
+
+    fn load(&mut self) {
+        self.notice = None;
+        self.state.select(Some(0));
+        match self.ras().list() {
+            Ok(entries) => {
+                if entries.is_empty() {
+                    self.notify(format!("No infobases found on {}", self.ras().host), true);
+                }
+                self.several = entries
+                    .iter()
+                    .any(|entry| entry.cluster != entries[0].cluster);
+                self.entries = entries;
+            }
+            Err(e) => {
+                self.entries.clear();
+                self.notify(e.to_string(), true);
+            }
+        }
+    }
+
+    fn switch(&mut self, forward: bool) {
+        let count = self.targets.len();
+        if count < 2 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.load();
+    }
+
+    /// Entries matching the filter, best first, with the matched characters
+    /// of their names.
+    fn matching(&self) -> Vec<(&Listed, Vec<usize>)> {
+        let mut matching: Vec<(&Listed, fuzzy::Match)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry, fuzzy::find(&self.filter, &entry.name)?)))
+            .collect();
+        matching.sort_by_key(|(_, found)| -found.score);
+        matching
+            .into_iter()
+            .map(|(entry, found)| (entry, found.positions))
+            .collect()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> BrowserAction {
+        let Event::Key(key_event) = event else {
+            return BrowserAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return BrowserAction::None;
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc => return BrowserAction::Close,
+            KeyCode::Tab => self.switch(true),
+            KeyCode::BackTab => self.switch(false),
+            KeyCode::Char('a') if ctrl => {
+                let matching: Vec<Listed> = self
+                    .matching()
+                    .into_iter()
+                    .map(|(entry, _)| entry.clone())
+                    .collect();
+                if !matching.is_empty() {
+                    return BrowserAction::Import(matching);
+                }
+            }
+            KeyCode::Enter => {
+                let matching = self.matching();
+                let index = self.state.selected().unwrap_or(0);
+                if let Some((entry, _)) = matching.get(index) {
+                    return BrowserAction::Select(entry.path.clone());
+                }
+            }
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.state.select(Some(0));
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+        BrowserAction::None
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let matching = self.matching();
+        let tabs = self.targets.len() > 1;
+        let height = (matching.len() as u16).clamp(1, frame.area().height.saturating_sub(10))
+            + 5
+            + tabs as u16;
+        let area = popup::area(frame.area(), height);
+        let items: Vec<Line> = if matching.is_empty() {
+            vec![Line::styled("No infobase matches", theme::hint())]
+        } else {
+            matching
+                .iter()
+                .map(|(entry, positions)| {
+                    let mut line =
+                        Line::from(fuzzy::highlight(&entry.name, positions, Style::new()));
+                    if !entry.descr.is_empty() {
+                        line.push_span(Span::styled(format!("  {}", entry.descr), theme::hint()));
+                    }
+                    if self.several {
+                        line.push_span(Span::styled(
+                            format!("  [{}]", entry.cluster),
+                            theme::hint(),
+                        ));
+                    }
+                    line
+                })
+                .collect()
+        };
+
+        let block = Block::bordered().title(format!("Infobases on {}", self.ras().name));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let [tabs_area, filter_area, list_area, hint_area] = Layout::vertical([
+            Constraint::Length(tabs as u16),
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        if tabs {
+            let names: Vec<Span> = self
+                .targets
+                .iter()
+                .enumerate()
+                .flat_map(|(i, ras)| {
+                    let style = if i == self.current {
+                        theme::selected()
+                    } else {
+                        theme::hint()
+                    };
+                    [
+                        Span::styled(format!(" {} ", ras.name), style),
+                        Span::raw(" "),
+                    ]
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(Line::from(names)), tabs_area);
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("Filter: ", theme::hint()),
+                Span::raw(self.filter.as_str()),
+            ])),
+            filter_area,
+        );
+        let list = List::new(items).highlight_style(theme::selected());
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        let hint = match &self.notice {
+            Some((notice, true)) => Line::styled(notice.as_str(), theme::error()),
+            Some((notice, false)) => Line::from(notice.as_str()),
+            None if tabs => Line::styled(
+                "Enter: use, Ctrl+A: import the listed, Tab: next cluster, Esc: close",
+                theme::hint(),
+            ),
+            None => Line::styled(
+                "Enter: put into the input, Ctrl+A: import the listed, Esc: close",
+                theme::hint(),
+            ),
+        };
+        frame.render_widget(Paragraph::new(hint), hint_area);
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::events::AppEvent;
+use crate::{parse_base_path, paths, platform};
+
+const FILE_NAME: &str = "rbaserun_columns.json";
+
+/// Seconds a column command may run for one base, unless `timeout_secs` says otherwise.
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Commands run at the same time while the columns are refreshed.
+const WORKERS: usize = 4;
+
+fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// A value shown next to every base of the history, printed by a shell
+/// command run with the base in `RBASERUN_*` variables, e.g. the release
+/// deployed to it. `[[columns]]` in rbaserun.toml.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Column {
+    pub name: String,
+    pub command: String,
+    /// Seconds the command may run for one base before it is killed
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// The first line a column command printed for a base.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Value {
+    pub text: String,
+    /// Unix timestamp of the run, seconds
+    pub at: i64,
+}
+
+/// The column values of the bases, by path and column name, kept in
+/// rbaserun_columns.json so that the commands only run when asked to.
+#[derive(Debug, Default)]
+pub struct ColumnValues {
+    values: BTreeMap<String, BTreeMap<String, Value>>,
+    /// Bases whose commands still run
+    pending: usize,
+    /// Failures of the running refresh
+    failures: Vec<String>,
+}
+
+impl ColumnValues {
+    pub fn load() -> ColumnValues {
+        let values = fs::read_to_string(file())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        ColumnValues {
+            values,
+            ..Default::default()
+        }
+    }
+
+    fn save(&self) {
+        if let Ok(content) = serde_json::to_string_pretty(&self.values) {
+            let _ = paths::prepare(&file()).and_then(|()| fs::write(file(), content));
+        }
+    }
+
+    /// The value of the column `name` of `path`, `None` before it was run.
+    pub fn get(&self, path: &str, name: &str) -> Option<&Value> {
+        self.values.get(path.trim())?.get(name)
+    }
+
+    pub fn is_refreshing(&self) -> bool {
+        self.pending > 0
+    }
+
+    /// Runs `columns` for `paths` in the background, a few bases at a time,
+    /// posting `AppEvent::ColumnValues` for each base.
+    pub fn refresh(&mut self, columns: &[Column], paths: Vec<String>, sender: &Sender<AppEvent>) {
+        let paths: Vec<String> = paths
+            .into_iter()
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+        if self.is_refreshing() || columns.is_empty() || paths.is_empty() {
+            return;
+        }
+        self.pending = paths.len();
+        self.failures.clear();
+        let queue = Arc::new(Mutex::new(paths));
+        let columns = Arc::new(columns.to_vec());
+        for _ in 0..WORKERS {
+            let queue = Arc::clone(&queue);
+            let columns = Arc::clone(&columns);
+            let sender = sender.clone();
+            thread::spawn(move || {
+                loop {
+                    let Some(path) = queue.lock().ok().and_then(|mut queue| queue.pop()) else {
+                        return;
+                    };
+                    let values = run(&columns, &path);
+                    if sender
+                        .send(AppEvent::ColumnValues { path, values })
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Stores the values of one base. Once every base is done the values are
+    /// saved and the failures returned, the first one with a count of the others.
+    pub fn finish(
+        &mut self,
+        path: String,
+        values: Vec<(String, Result<String, String>)>,
+    ) -> Option<Result<(), String>> {
+        let at = Local::now().timestamp();
+        for (name, value) in values {
+            match value {
+                Ok(text) => {
+                    self.values
+                        .entry(path.clone())
+                        .or_default()
+                        .insert(name, Value { text, at });
+                }
+                Err(e) => self.failures.push(format!("Column {name} of {path}: {e}")),
+            }
+        }
+        self.pending = self.pending.saturating_sub(1);
+        if self.is_refreshing() {
+            return None;
+        }
+        self.save();
+        let failures = mem::take(&mut self.failures);
+        Some(match failures.as_slice() {
+            [] => Ok(()),
+            [failure] => Err(failure.clone()),
+            [failure, others @ ..] => Err(format!("{failure}, and {} more", others.len())),
+        })
+    }
+}
+
+/// Runs every column for `path`, keeping the first line of what each printed.
+fn run(columns: &[Column], path: &str) -> Vec<(String, Result<String, String>)> {
+    let env = match parse_base_path(path) {
+        Ok(parsed) => vec![
+            ("RBASERUN_PATH".to_string(), path.to_string()),
+            ("RBASERUN_KIND".to_string(), parsed.kind_name().to_string()),
+            ("RBASERUN_TARGET".to_string(), parsed.target()),
+            ("RBASERUN_NAME".to_string(), parsed.name()),
+        ],
+        Err(e) => {
+            return columns
+                .iter()
+                .map(|column| (column.name.clone(), Err(e.to_string())))
+                .collect();
+        }
+    };
+    columns
+        .iter()
+        .map(|column| {
+            let timeout = Duration::from_secs(column.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+            let value = platform::run_captured(&column.command, &env, timeout).map(|stdout| {
+                stdout
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .unwrap_or_default()
+                    .to_string()
+            });
+            (column.name.clone(), value)
+        })
+        .collect()
+}
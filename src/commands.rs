@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+use std::env;
+use std::error::Error;
+
+use crate::bases::Bases;
+use crate::{check_alias, history, integrate, lockdown, normalize_input, parse_base_path};
+
+#[derive(Serialize)]
+pub struct ListedBase<'a> {
+    pub path: &'a str,
+    pub kind: Option<&'static str>,
+    pub target: Option<String>,
+    pub badge: Option<&'a str>,
+    pub tags: &'a [String],
+    pub production: bool,
+}
+
+/// History entries first, followed by bases that only exist in the bases
+/// file, without the ones whose `when` leaves them out on this machine.
+pub fn known_paths(history: &[String], bases: &Bases) -> Vec<String> {
+    let mut paths = history.to_vec();
+    for entry in bases.entries() {
+        if !paths.iter().any(|path| path.trim() == entry.path.trim()) {
+            paths.push(entry.path.clone());
+        }
+    }
+    paths.retain(|path| !bases.is_elsewhere(path));
+    paths
+}
+
+/// Saves `path` to the history with `name` as its alias.
+pub fn add(name: &str, path: &str, bases: &mut Bases) -> Result<(), Box<dyn Error>> {
+    let path = normalize_input(path);
+    parse_base_path(&path).map_err(|e| format!("Parsing error: {e}"))?;
+    if bases.is_managed(&path) {
+        return Err(format!("{path} is managed by the machine configuration").into());
+    }
+    check_alias(bases, &path, name)?;
+    bases.entry_mut(&path).alias = Some(name.to_string());
+    bases.save()?;
+    history::remember(path.clone())?;
+    println!("Added {name}: {path}");
+    Ok(())
+}
+
+/// Removes the base with alias or connection string `name` from the history
+/// and drops its settings from the bases file.
+pub fn remove(name: &str, bases: &mut Bases) -> Result<(), Box<dyn Error>> {
+    let path = normalize_input(&bases.expand_alias(name));
+    let mut history = history::load();
+    let listed = history.iter().any(|other| other.trim() == path);
+    if !listed && bases.find(&path).is_none() {
+        return Err(format!("No base '{name}' in the history or the bases file").into());
+    }
+    if bases.is_managed(&path) {
+        return Err(format!("{path} is managed by the machine configuration").into());
+    }
+    if bases.is_production(&path) {
+        lockdown::check_removal(&path)?;
+    }
+    history.retain(|other| other.trim() != path);
+    history::save(&history)?;
+    if bases.remove(&path).is_some() {
+        bases.save()?;
+    }
+    println!("Removed {path}");
+    Ok(())
+}
+
+pub fn list(history: &[String], bases: &Bases, json: bool) -> Result<(), Box<dyn Error>> {
+    let paths = known_paths(history, bases);
+
+    if !json {
+        for path in &paths {
+            println!("{path}");
+        }
+        return Ok(());
+    }
+
+    println!("{}", serde_json::to_string_pretty(&listed(&paths, bases))?);
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct FeedItem {
+    title: String,
+    subtitle: String,
+    icon: String,
+    command: String,
+    arguments: Vec<String>,
+}
+
+/// Items for OS launcher plugins (PowerToys Run, Flow Launcher, Keypirinha).
+pub fn launcher_feed(history: &[String], bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let exe = env::current_exe()?.display().to_string();
+    let feed: Vec<FeedItem> = known_paths(history, bases)
+        .into_iter()
+        .map(|path| FeedItem {
+            title: integrate::display_name(&path, bases),
+            subtitle: match parse_base_path(&path) {
+                Ok(parsed) => format!("{}: {}", parsed.kind_name(), parsed.target()),
+                Err(_) => path.clone(),
+            },
+            icon: integrate::icon(&path, bases, &exe),
+            command: integrate::launch_command_line(&exe, &path, &["--quiet"]),
+            arguments: vec!["launch".into(), path, "--quiet".into()],
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&feed)?);
+    Ok(())
+}
+
+pub fn listed<'a>(paths: &'a [String], bases: &'a Bases) -> Vec<ListedBase<'a>> {
+    paths
+        .iter()
+        .map(|path| {
+            let entry = bases.find(path);
+            let (kind, target) = match parse_base_path(path) {
+                Ok(path) => (Some(path.kind_name()), Some(path.target())),
+                Err(_) => (None, None),
+            };
+            ListedBase {
+                path,
+                kind,
+                target,
+                badge: entry.and_then(|entry| entry.badge.as_deref()),
+                tags: entry.map_or(&[], |entry| entry.tags.as_slice()),
+                production: bases.is_production(path),
+            }
+        })
+        .collect()
+}
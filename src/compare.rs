@@ -0,0 +1,62 @@
+use chrono::Local;
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::designer::{Batch, Credentials};
+use crate::parse_base_path;
+
+/// Compares the main configuration of `path` with a .cf file using the
+/// designer and prints the text report.
+pub fn run(
+    path: &str,
+    cf: &Path,
+    report: Option<PathBuf>,
+    full: bool,
+    credentials: &Credentials,
+) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    if !cf.is_file() {
+        return Err(format!("{} not found", cf.display()).into());
+    }
+    let keep_report = report.is_some();
+    let report = report.unwrap_or_else(|| {
+        env::temp_dir().join(format!(
+            "rbaserun-compare-{}-{}.txt",
+            parsed.name(),
+            Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+
+    eprintln!("Comparing {} with {}...", parsed.target(), cf.display());
+    let mut batch = Batch::new(&parsed, credentials)?;
+    batch
+        .arg("/CompareCfg")
+        .arg("-FirstConfigurationType")
+        .arg("MainConfiguration")
+        .arg("-SecondConfigurationType")
+        .arg("File")
+        .arg("-SecondFile")
+        .arg(cf)
+        .arg("-ReportType")
+        .arg(if full { "Full" } else { "Brief" })
+        .arg("-ReportFormat")
+        .arg("txt")
+        .arg("-ReportFile")
+        .arg(&report);
+    batch.run().map_err(|e| format!("Comparison failed: {e}"))?;
+
+    let bytes = fs::read(&report)?;
+    print!(
+        "{}",
+        String::from_utf8_lossy(&bytes).trim_start_matches('\u{feff}')
+    );
+    if keep_report {
+        eprintln!("Report saved to {}", report.display());
+    } else {
+        let _ = fs::remove_file(&report);
+    }
+    Ok(())
+}
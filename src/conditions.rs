@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::{Mutex, OnceLock};
+
+use crate::platform;
+
+/// Machines a base is listed on, `when` of a base in a shared bases file,
+/// e.g. `when = { hosts = ["ACC-*"], networks = ["10.8.0.0/16"] }`. A base
+/// with both is listed where both hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct When {
+    /// Patterns of the machine name with `*` and `?`, case-insensitive
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// Subnets such as `192.168.10.0/24`; one holds while this machine has
+    /// an address in it, e.g. on the office LAN or with the VPN connected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<String>,
+}
+
+impl When {
+    /// Whether the base is listed on this machine. The answers are kept
+    /// for the rest of the run, as the picker asks on every key.
+    pub fn holds(&self) -> bool {
+        let host = self.hosts.is_empty()
+            || self
+                .hosts
+                .iter()
+                .any(|pattern| wildcard(&pattern.to_lowercase(), host_name()));
+        host && (self.networks.is_empty() || self.networks.iter().any(|network| on(network)))
+    }
+}
+
+fn host_name() -> &'static str {
+    static NAME: OnceLock<String> = OnceLock::new();
+    NAME.get_or_init(|| platform::host_name().to_lowercase())
+}
+
+/// Whether this machine has an address in `network`, a CIDR subnet.
+fn on(network: &str) -> bool {
+    static SEEN: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+    let mut seen = SEEN
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    *seen
+        .entry(network.to_string())
+        .or_insert_with(|| local_address_in(network).unwrap_or(false))
+}
+
+/// Asks the routing table which local address reaches `network` and checks
+/// that it lies in `network`. Connecting a UDP socket sends nothing.
+fn local_address_in(network: &str) -> Option<bool> {
+    let (address, bits) = network.trim().split_once('/')?;
+    let address: IpAddr = address.parse().ok()?;
+    let bits: u32 = bits.parse().ok()?;
+    let (bind, width) = match address {
+        IpAddr::V4(_) => ("0.0.0.0:0", 32),
+        IpAddr::V6(_) => ("[::]:0", 128),
+    };
+    if bits > width {
+        return None;
+    }
+    let full = u128::MAX >> (128 - width);
+    let mask = full & !full.checked_shr(bits).unwrap_or(0);
+    let number = |ip: IpAddr| match ip {
+        IpAddr::V4(ip) => u128::from(u32::from(ip)),
+        IpAddr::V6(ip) => u128::from(ip),
+    };
+    // a host inside the subnet, the network address itself may not route
+    let probe = match address {
+        IpAddr::V4(ip) => IpAddr::from((u32::from(ip) | 1).to_be_bytes()),
+        IpAddr::V6(ip) => IpAddr::from((u128::from(ip) | 1).to_be_bytes()),
+    };
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect((probe, 9)).ok()?;
+    let local = socket.local_addr().ok()?.ip();
+    Some(number(local) & mask == number(address) & mask)
+}
+
+/// Matches `text` against `pattern` with `*` for any run of characters and
+/// `?` for one.
+fn wildcard(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,590 @@
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backup::Plan;
+use crate::browser::BrowserConfig;
+use crate::columns::Column;
+use crate::hooks::Hooks;
+use crate::keys::{Action, Keys};
+use crate::locale::Locale;
+use crate::lockdown::Lockdown;
+use crate::notify::Notify;
+use crate::theme::Preset;
+use crate::{LaunchMode, PathKind, history, parse_base_path, paths, platform, templates};
+
+const CONFIG_FILE: &str = "rbaserun.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Starter (`1cestart`) the bases are launched with
+    pub starter: PathBuf,
+    /// Platform version launched directly instead of through the starter
+    pub platform: Option<String>,
+    /// Mode used on Enter and by `launch` without `--designer`
+    pub default_mode: LaunchMode,
+    /// Launch history, in the data directory by default; relative paths start
+    /// at the working directory
+    pub history_file: PathBuf,
+    /// Directory of the `.cf` and `.dt` templates `create` offers
+    pub templates: PathBuf,
+    /// Show the launch mode picker on every Enter instead of using the Ctrl+D toggle
+    pub ask_mode: bool,
+    /// How many errors of the current session are kept in the errors popup
+    pub error_history: usize,
+    /// Backups kept of the history and of the bases file, each taken before
+    /// the first change of a run, 0 keeps none
+    pub store_backups: usize,
+    /// Pick bases in plain numbered lines for screen readers, like `--linear`
+    pub linear: bool,
+    /// Preselect the mode a base is usually launched in at this time of the
+    /// week instead of the mode of its last launch
+    pub smart_mode: bool,
+    /// Stronger colors and underlined matches, following the system when unset
+    pub high_contrast: Option<bool>,
+    /// No progress lines redrawn in place, following the system when unset
+    pub reduced_motion: Option<bool>,
+    pub theme: ThemeConfig,
+    /// Capture the mouse: clicks and the wheel in the history, the designer
+    /// toggle and dragging the border of the detail pane
+    pub mouse: bool,
+    /// Count the 1C clients running on this machine next to their bases
+    pub local_sessions: bool,
+    /// Show what changed once after an update
+    pub whats_new: bool,
+    /// Suggest actions never used in the status line
+    pub tips: bool,
+    /// Open on the favorites, the pinned bases, when there are any
+    pub favorites_on_start: bool,
+    /// Record launch latencies, failures and backup runs to local files for
+    /// `stats --internal` and `/metrics`
+    pub metrics: bool,
+    /// Save passwords in the OS keyring; off on headless machines without one
+    pub keyring: bool,
+    /// Language of dates, times and sizes, following the environment when unset
+    pub locale: Option<Locale>,
+    /// Command opening the sources repository of a base with Ctrl+G
+    pub editor: String,
+    /// Browser web bases are opened in instead of the default one
+    pub browser: BrowserConfig,
+    /// What Esc does while the input is not empty
+    pub esc: EscAction,
+    /// Quit with `q` while an entry of the history list is selected
+    pub quit_on_q: bool,
+    /// Keys of the picker by action, replacing the default keys of the actions listed
+    pub keymap: BTreeMap<Action, Keys>,
+    /// Reopen the picker where a killed session left off
+    pub restore_session: bool,
+    /// Ask before launching a base again within this many seconds, 0 never asks
+    pub duplicate_window_secs: u64,
+    /// Order of the history: latest launches first, or the most launched bases
+    pub sort: SortOrder,
+    /// List the bases registered in the 1C launcher below the history
+    pub ibases: bool,
+    /// `ibases.v8i` to read instead of the one of the current user
+    pub ibases_file: Option<PathBuf>,
+    pub layout: LayoutConfig,
+    pub terminal: TerminalConfig,
+    pub check: CheckConfig,
+    pub availability: AvailabilityConfig,
+    pub release: ReleaseConfig,
+    /// Commands run before and after every launch, see `hooks`
+    pub hooks: Hooks,
+    /// Rhai script deciding the badge, visibility and order of the listed
+    /// bases, see `decorate`
+    pub decorate: Option<PathBuf>,
+    /// Values printed by shell commands, shown next to every base, see `columns`
+    pub columns: Vec<Column>,
+    /// Dumps of bases made with `backup run`, `[[backup]]`
+    #[serde(rename = "backup")]
+    pub backups: Vec<Plan>,
+    /// Where the results of `backup run` are reported, `[notify]`
+    pub notify: Notify,
+    /// Check that a base is reachable before launching it, with the `[check]` settings
+    pub precheck: bool,
+    /// Shared bases file on a jump host, read and written over SSH
+    pub remote: Option<RemoteConfig>,
+    /// Cluster administration server, for Ctrl+B and the license check of sets
+    pub ras: RasConfig,
+    /// Client licenses checked before the bases of a launch set are launched
+    pub licenses: Option<LicenseConfig>,
+    /// Only taken from the machine config, user configs can't loosen it
+    pub lockdown: Lockdown,
+}
+
+/// Colors of the picker, `[theme]`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Built-in colors, dark or high contrast following `high_contrast` when unset
+    pub preset: Option<Preset>,
+    /// Styles replacing those of the preset by part, e.g. `error = "bold red"`
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Height of the detail pane below the history list, 0 hides it
+    pub detail_height: u16,
+    /// Terminals narrower than this hide the detail pane and shorten columns
+    pub narrow_width: u16,
+    /// Terminals at least this wide show favorites, history and details side
+    /// by side, 0 never does
+    pub wide_width: u16,
+    /// Draw below the prompt instead of switching to the alternate screen
+    pub inline: bool,
+    pub inline_height: u16,
+}
+
+/// What the terminal supports, `[terminal]`, detected from the environment
+/// when unset, see `tui::Capabilities`.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Colors; without them the picker keeps bold, reversed and underlined text
+    pub color: Option<bool>,
+    /// Switching to the alternate screen; without it the picker draws inline
+    pub alternate_screen: Option<bool>,
+    /// UTF-8 output; without it borders and markers are drawn in ASCII
+    pub unicode: Option<bool>,
+    /// OSC 8 links, e.g. the URL of a web base in the detail pane opened with Ctrl+click
+    pub hyperlinks: Option<bool>,
+}
+
+/// Status markers of the bases in the history, `[availability]`, probed in
+/// the background like before a launch with `precheck`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct AvailabilityConfig {
+    pub enabled: bool,
+    /// Seconds after which a base in sight is probed again
+    pub interval_secs: u64,
+}
+
+impl AvailabilityConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+}
+
+/// Name and version of the configuration of the selected base in the detail
+/// pane, `[release]`. Off by default, reading them opens a session on the base.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ReleaseConfig {
+    pub enabled: bool,
+    /// Seconds after which the release of a base is read again
+    pub max_age_secs: u64,
+}
+
+impl ReleaseConfig {
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age_secs)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteConfig {
+    /// SSH destination, e.g. `me@bastion`
+    pub host: String,
+    /// Path of the bases file on the host
+    pub path: String,
+    /// Extra ssh options such as `["-p", "2222"]`
+    #[serde(default)]
+    pub ssh_args: Vec<String>,
+}
+
+impl RemoteConfig {
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.host, self.path)
+    }
+}
+
+/// Clusters are queried with `rac` through the administration server (ras)
+/// on the host of a server base.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RasConfig {
+    /// `rac` executable, looked up in PATH by default
+    pub rac: Option<PathBuf>,
+    /// Port of ras on the cluster host
+    pub port: u16,
+    /// Clusters registered for Ctrl+B, `[[ras.cluster]]`
+    #[serde(rename = "cluster")]
+    pub clusters: Vec<ClusterConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClusterConfig {
+    /// Name shown in the cluster browser and given to `cluster import`
+    pub name: String,
+    pub host: String,
+    /// Port of ras on the host, the `[ras]` port by default
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Cluster administrator, the password is kept in the OS keyring
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Directory of the cluster registry as reachable from here, e.g.
+    /// `\\srv1\srvinfo`, for the event logs of the infobases
+    #[serde(default)]
+    pub srvinfo: Option<PathBuf>,
+    /// Directory of the technological log of the host as reachable from here
+    #[serde(default)]
+    pub tech_log: Option<PathBuf>,
+}
+
+/// Sessions are counted on the cluster of each server base.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LicenseConfig {
+    /// Client licenses available to the sessions of a cluster
+    pub available: u32,
+    #[serde(default)]
+    pub on_shortage: ShortageAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortageAction {
+    /// Print a warning and launch anyway
+    #[default]
+    Warn,
+    /// Wait until enough sessions have ended
+    Pause,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Recent,
+    /// Most launches in the launch log first, pinned entries still on top
+    Frequent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EscAction {
+    /// Clear the input first, quit on the next Esc
+    Clear,
+    Quit,
+}
+
+/// Connection check settings, individual bases can override them in their
+/// `[base.check]` table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Timeout of a single probe
+    pub timeout_ms: u64,
+    /// Cluster agent (ragent) port of server bases
+    pub agent_port: u16,
+    /// Cluster manager (rmngr) port, the one clients connect to
+    pub manager_port: u16,
+    /// Working process (rphost) ports, a number or a `"1560-1591"` range
+    pub process_ports: PortSpec,
+    /// Status codes accepted from web bases
+    pub http_status: Vec<u16>,
+    /// Text the web base response has to contain
+    pub http_contains: Option<String>,
+    /// Probe the database server of server bases too, found through ras
+    pub dbms: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CheckOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manager_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_ports: Option<PortSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<Vec<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_contains: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dbms: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Port(u16),
+    Range(String),
+}
+
+impl PortSpec {
+    pub fn range(&self) -> Result<RangeInclusive<u16>, String> {
+        match self {
+            PortSpec::Port(port) => Ok(*port..=*port),
+            PortSpec::Range(range) => {
+                let parse = |port: &str| port.trim().parse::<u16>().ok();
+                match range.split_once('-') {
+                    Some((from, to)) => match (parse(from), parse(to)) {
+                        (Some(from), Some(to)) if from <= to => Ok(from..=to),
+                        _ => Err(format!("Invalid port range: {range}")),
+                    },
+                    None => parse(range)
+                        .map(|port| port..=port)
+                        .ok_or(format!("Invalid port: {range}")),
+                }
+            }
+        }
+    }
+}
+
+impl CheckConfig {
+    /// Settings for one base with its overrides applied.
+    pub fn with(&self, base: Option<&CheckOverride>) -> CheckConfig {
+        let Some(base) = base else {
+            return self.clone();
+        };
+        CheckConfig {
+            timeout_ms: base.timeout_ms.unwrap_or(self.timeout_ms),
+            agent_port: base.agent_port.unwrap_or(self.agent_port),
+            manager_port: base.manager_port.unwrap_or(self.manager_port),
+            process_ports: base
+                .process_ports
+                .clone()
+                .unwrap_or_else(|| self.process_ports.clone()),
+            http_status: base
+                .http_status
+                .clone()
+                .unwrap_or_else(|| self.http_status.clone()),
+            http_contains: base
+                .http_contains
+                .clone()
+                .or_else(|| self.http_contains.clone()),
+            dbms: base.dbms.unwrap_or(self.dbms),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+impl Default for RasConfig {
+    fn default() -> RasConfig {
+        RasConfig {
+            rac: None,
+            port: 1545,
+            clusters: Vec::new(),
+        }
+    }
+}
+
+impl Default for CheckConfig {
+    fn default() -> CheckConfig {
+        CheckConfig {
+            timeout_ms: 2000,
+            agent_port: 1540,
+            manager_port: 1541,
+            process_ports: PortSpec::Range("1560-1591".into()),
+            http_status: vec![200],
+            http_contains: None,
+            dbms: false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            starter: platform::default_starter(),
+            platform: None,
+            default_mode: LaunchMode::Enterprise,
+            history_file: history::default_file(),
+            templates: templates::default_dir(),
+            ask_mode: false,
+            error_history: 50,
+            store_backups: 10,
+            linear: false,
+            smart_mode: false,
+            high_contrast: None,
+            reduced_motion: None,
+            theme: ThemeConfig::default(),
+            mouse: true,
+            local_sessions: true,
+            whats_new: true,
+            favorites_on_start: false,
+            tips: true,
+            metrics: false,
+            keyring: true,
+            locale: None,
+            editor: "code".into(),
+            browser: BrowserConfig::default(),
+            esc: EscAction::Clear,
+            quit_on_q: true,
+            keymap: BTreeMap::new(),
+            restore_session: true,
+            duplicate_window_secs: 10,
+            sort: SortOrder::Recent,
+            ibases: true,
+            ibases_file: None,
+            layout: LayoutConfig::default(),
+            terminal: TerminalConfig::default(),
+            check: CheckConfig::default(),
+            availability: AvailabilityConfig::default(),
+            release: ReleaseConfig::default(),
+            hooks: Hooks::default(),
+            decorate: None,
+            columns: Vec::new(),
+            backups: Vec::new(),
+            notify: Notify::default(),
+            precheck: false,
+            remote: None,
+            ras: RasConfig::default(),
+            licenses: None,
+            lockdown: Lockdown::default(),
+        }
+    }
+}
+
+impl Default for AvailabilityConfig {
+    fn default() -> AvailabilityConfig {
+        AvailabilityConfig {
+            enabled: true,
+            interval_secs: 60,
+        }
+    }
+}
+
+impl Default for ReleaseConfig {
+    fn default() -> ReleaseConfig {
+        ReleaseConfig {
+            enabled: false,
+            max_age_secs: 3600,
+        }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> LayoutConfig {
+        LayoutConfig {
+            detail_height: 6,
+            narrow_width: 80,
+            wide_width: 160,
+            inline: false,
+            inline_height: 20,
+        }
+    }
+}
+
+/// Merges `layer` into `table`, nested tables key by key and everything else
+/// replaced.
+fn overlay(table: &mut Table, layer: Table) {
+    for (key, value) in layer {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(own)), Value::Table(layer)) => overlay(own, layer),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Loads `path`, or the first config file found in the working directory,
+    /// next to the executable or in the user config directory, layered over
+    /// the machine-wide config when there is one.
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+        let files = Config::files(path)?;
+        let machine = Config::machine_file();
+        if files.is_empty() {
+            return Ok(Config::default());
+        }
+
+        let mut table = Table::new();
+        for file in &files {
+            let content = fs::read_to_string(file)
+                .map_err(|e| format!("Could not read {}: {e}", file.display()))?;
+            let mut layer: Table =
+                toml::from_str(&content).map_err(|e| format!("Invalid {}: {e}", file.display()))?;
+            if machine.as_ref() != Some(file) {
+                layer.remove("lockdown");
+            }
+            overlay(&mut table, layer);
+        }
+        let location = files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let config: Config = table
+            .try_into()
+            .map_err(|e| format!("Invalid {location}: {e}"))?;
+        config
+            .check
+            .process_ports
+            .range()
+            .map_err(|e| format!("Invalid {location}: {e}"))?;
+        Ok(config)
+    }
+
+    /// The files `load` reads for `path`, the machine-wide config first.
+    pub fn files(path: Option<&Path>) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let user = match path {
+            Some(path) if !path.is_file() => {
+                return Err(format!("Config file {} not found", path.display()).into());
+            }
+            Some(path) => Some(path.to_path_buf()),
+            None => Config::candidates().into_iter().find(|path| path.is_file()),
+        };
+        Ok(Config::machine_file().into_iter().chain(user).collect())
+    }
+
+    fn machine_file() -> Option<PathBuf> {
+        paths::machine_dir()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .filter(|path| path.is_file())
+    }
+
+    fn candidates() -> Vec<PathBuf> {
+        let mut candidates = vec![PathBuf::from(CONFIG_FILE)];
+        if let Some(dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            candidates.push(dir.join(CONFIG_FILE));
+        }
+        if let Some(dir) = paths::config_dir() {
+            candidates.push(dir.join("config.toml"));
+        }
+        candidates
+    }
+
+    /// `default_mode` for `path`, Enterprise for bases that cannot be opened
+    /// in a browser.
+    pub fn default_mode_for(&self, path: &str) -> LaunchMode {
+        match self.default_mode {
+            LaunchMode::WebBrowser
+                if !matches!(parse_base_path(path), Ok(PathKind::Web { .. })) =>
+            {
+                LaunchMode::Enterprise
+            }
+            mode => mode,
+        }
+    }
+
+    /// Mode of a launch from the command line.
+    pub fn cli_mode(&self, path: &str, designer: bool) -> LaunchMode {
+        if designer {
+            LaunchMode::Designer
+        } else {
+            self.default_mode_for(path)
+        }
+    }
+}
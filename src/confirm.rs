@@ -0,0 +1,167 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::{LaunchMode, keys, popup, theme};
+
+pub enum ConfirmAction {
+    None,
+    Confirmed,
+    Cancel,
+    /// Bring the client already running the base to the front instead
+    Focus(u32),
+}
+
+/// What a `Confirm` guards against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guard {
+    /// Launching a production base in Designer mode, confirmed by typing its name
+    Production,
+    /// Launching a base again right after it was launched, confirmed with `y`
+    Duplicate,
+    /// Removing the user settings of a base, confirmed by typing its name
+    ResetSettings,
+}
+
+/// Popup that only lets a launch through once `expected` has been typed in,
+/// or `y` pressed when there is nothing to type.
+#[derive(Debug)]
+pub struct Confirm {
+    pub path: String,
+    pub mode: LaunchMode,
+    pub guard: Guard,
+    prompt: String,
+    expected: Option<String>,
+    /// Process of a client with the base open, offered with `f`
+    focus: Option<u32>,
+    input: Input,
+    error: Option<String>,
+}
+
+impl Confirm {
+    pub fn new(path: String, prompt: String, expected: String) -> Confirm {
+        Confirm {
+            path,
+            mode: LaunchMode::Designer,
+            guard: Guard::Production,
+            prompt,
+            expected: Some(expected),
+            focus: None,
+            input: Input::default(),
+            error: None,
+        }
+    }
+
+    /// Asks before removing the user settings of `path`.
+    pub fn reset_settings(path: String, prompt: String, expected: String) -> Confirm {
+        Confirm {
+            guard: Guard::ResetSettings,
+            ..Confirm::new(path, prompt, expected)
+        }
+    }
+
+    /// Asks whether to launch `path` again. Enter does not confirm, so a
+    /// double-pressed Enter can't get through.
+    /// `focus` is the process of a client of this machine that has the base
+    /// open already.
+    pub fn duplicate(
+        path: String,
+        mode: LaunchMode,
+        prompt: String,
+        focus: Option<u32>,
+    ) -> Confirm {
+        Confirm {
+            path,
+            mode,
+            guard: Guard::Duplicate,
+            prompt,
+            expected: None,
+            focus,
+            input: Input::default(),
+            error: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ConfirmAction {
+        let Event::Key(key_event) = event else {
+            return ConfirmAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return ConfirmAction::None;
+        }
+        let Some(expected) = &self.expected else {
+            return match keys::binding(key_event.code) {
+                KeyCode::Char('y') => ConfirmAction::Confirmed,
+                KeyCode::Char('f') if let Some(pid) = self.focus => ConfirmAction::Focus(pid),
+                KeyCode::Char('n') | KeyCode::Esc => ConfirmAction::Cancel,
+                _ => ConfirmAction::None,
+            };
+        };
+        match key_event.code {
+            KeyCode::Esc => return ConfirmAction::Cancel,
+            KeyCode::Enter => {
+                if self.input.value().trim() == expected {
+                    return ConfirmAction::Confirmed;
+                }
+                self.error = Some(format!("Type '{expected}' exactly to continue"));
+            }
+            _ => {
+                self.input.handle_event(event);
+            }
+        }
+        ConfirmAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let Some(expected) = &self.expected else {
+            let area = popup::area(frame.area(), 4);
+            let hint = if self.focus.is_some() {
+                "y: launch again, f: bring to front, n: cancel"
+            } else {
+                "y: launch again, n: cancel"
+            };
+            let lines: Vec<Line> = vec![
+                self.prompt.clone().into(),
+                Span::styled(hint, theme::hint()).into(),
+            ];
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title("Confirm").style(theme::warning())),
+                area,
+            );
+            return;
+        };
+        let area = popup::area(frame.area(), 6);
+
+        let lines: Vec<Line> = vec![
+            self.prompt.clone().into(),
+            format!("Type '{expected}' to continue:").into(),
+            self.input.value().to_string().bold().into(),
+            match &self.error {
+                Some(error) => Span::styled(error.clone(), theme::error()).into(),
+                None => Span::styled("Enter: confirm, Esc: cancel", theme::hint()).into(),
+            },
+        ];
+
+        let block = Block::bordered().title("Confirm").style(theme::error());
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(block),
+            area,
+        );
+
+        let x = self.input.visual_cursor() as u16;
+        frame.set_cursor_position((inner.x + x, inner.y + 2));
+    }
+}
@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::designer::Credentials;
+use crate::{PathKind, normalize_input};
+
+/// Keys of the connection strings of `ibases.v8i` and the 1C connection
+/// dialog, in the spelling the platform writes them.
+const KEYS: [&str; 15] = [
+    "File", "Srvr", "Ref", "ws", "wsn", "wsp", "wspauto", "wspsrv", "wspuser", "wsppwd", "Usr",
+    "Pwd", "LicDstr", "prmod", "Zn",
+];
+
+/// Keys whose values are passwords, never kept in the history.
+const PASSWORD_KEYS: [&str; 2] = ["Pwd", "wsppwd"];
+
+/// The parameters of a connection string such as `Srvr="srv";Ref="erp";`.
+/// Keys are matched regardless of case, values are kept as written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionString {
+    /// Directory of a file base, `File`
+    pub file: Option<String>,
+    /// Cluster of a server base, `Srvr`
+    pub server: Option<String>,
+    /// Name of the infobase on the cluster, `Ref`
+    pub reference: Option<String>,
+    /// Url of a web base, `ws`
+    pub url: Option<String>,
+    /// Infobase user, `Usr`
+    pub user: Option<String>,
+    /// Password of the infobase user, `Pwd`
+    pub password: Option<String>,
+    /// Whether the client may get a license from the server, `LicDstr`
+    pub license_distribution: Option<String>,
+    /// Every parameter in order, known keys in their standard spelling
+    pub params: Vec<(String, String)>,
+}
+
+impl ConnectionString {
+    /// Splits `input` into `key=value` pairs separated by `;`. Values may be
+    /// quoted, with a quote inside written twice: `File="D:\a ""b""";`.
+    pub fn parse(input: &str) -> Result<ConnectionString, Box<dyn Error>> {
+        let mut connection = ConnectionString::default();
+        let mut chars = input.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace() || *c == ';').is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+            let key = read_key(&mut chars)?;
+            let value = read_value(&mut chars, &key)?;
+            connection.set(&key, value);
+        }
+        if connection.params.is_empty() {
+            return Err("Empty connection string".into());
+        }
+        Ok(connection)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        let key = KEYS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(key))
+            .map_or(key, |known| *known);
+        let field = match key {
+            "File" => Some(&mut self.file),
+            "Srvr" => Some(&mut self.server),
+            "Ref" => Some(&mut self.reference),
+            "ws" => Some(&mut self.url),
+            "Usr" => Some(&mut self.user),
+            "Pwd" => Some(&mut self.password),
+            "LicDstr" => Some(&mut self.license_distribution),
+            _ => None,
+        };
+        if let Some(field) = field {
+            *field = Some(value.clone());
+        }
+        self.params.push((key.to_string(), value));
+    }
+
+    /// The infobase user of `Usr` with the password of `Pwd`, passed to the
+    /// client as `/N` and `/P` since the base itself is started without them.
+    pub fn credentials(&self) -> Option<Credentials> {
+        let user = self.user.clone().filter(|user| !user.is_empty())?;
+        Some(Credentials {
+            user: Some(user),
+            password: self.password.clone(),
+        })
+    }
+
+    /// The base the parameters point at: a web base when `ws` is given, then
+    /// a file base, then a server base.
+    pub fn path(&self) -> Result<PathKind, Box<dyn Error>> {
+        let present = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+        if let Some(url) = present(&self.url) {
+            return Ok(PathKind::Web { url });
+        }
+        if let Some(path) = present(&self.file) {
+            return Ok(PathKind::File { path });
+        }
+        match (present(&self.server), present(&self.reference)) {
+            (Some(host), Some(ref_name)) => Ok(PathKind::Server { host, ref_name }),
+            (Some(_), None) => Err("Srvr= needs Ref= with the name of the infobase".into()),
+            _ => {
+                Err("expected File=\"<path>\";, Srvr=\"host\";Ref=\"ref\"; or ws=\"<url>\";".into())
+            }
+        }
+    }
+}
+
+fn read_key(chars: &mut Peekable<Chars>) -> Result<String, Box<dyn Error>> {
+    let mut key = String::new();
+    loop {
+        match chars.next() {
+            Some('=') => break,
+            Some(';') | None => {
+                return Err(format!("expected key=value, found '{}'", key.trim()).into());
+            }
+            Some(c) => key.push(c),
+        }
+    }
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("expected a key before '='".into());
+    }
+    Ok(key.to_string())
+}
+
+fn read_value(chars: &mut Peekable<Chars>, key: &str) -> Result<String, Box<dyn Error>> {
+    while chars.next_if(|c| *c == ' ').is_some() {}
+    let mut value = String::new();
+    if chars.next_if_eq(&'"').is_none() {
+        while let Some(c) = chars.next_if(|c| *c != ';') {
+            value.push(c);
+        }
+        return Ok(value.trim().to_string());
+    }
+    loop {
+        match chars.next() {
+            Some('"') if chars.next_if_eq(&'"').is_some() => value.push('"'),
+            Some('"') => break,
+            Some(c) => value.push(c),
+            None => return Err(format!("unterminated quote in the value of {key}").into()),
+        }
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.next() {
+        Some(';') | None => Ok(value),
+        Some(c) => Err(format!("expected ';' after the value of {key}, found '{c}'").into()),
+    }
+}
+
+/// `path` without the passwords of its connection string, as it is kept in
+/// the history. Paths without any are returned as they are.
+pub fn without_password(path: &str) -> String {
+    let Ok(connection) = ConnectionString::parse(&normalize_input(path)) else {
+        return path.to_string();
+    };
+    let is_password = |key: &str| PASSWORD_KEYS.contains(&key);
+    if !connection.params.iter().any(|(key, _)| is_password(key)) {
+        return path.to_string();
+    }
+    connection
+        .params
+        .iter()
+        .filter(|(key, _)| !is_password(key))
+        .map(|(key, value)| format!("{key}={};", quote(value)))
+        .collect()
+}
+
+/// `value` quoted for a connection string, quotes inside written twice.
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_quoted_values() {
+        let connection = ConnectionString::parse(r#"Srvr="srv:1541"; Ref="erp;test";"#).unwrap();
+        assert_eq!(connection.server.as_deref(), Some("srv:1541"));
+        assert_eq!(connection.reference.as_deref(), Some("erp;test"));
+    }
+
+    #[test]
+    fn reads_doubled_quotes_inside_values() {
+        let connection = ConnectionString::parse(r#"File="D:\a ""b""";"#).unwrap();
+        assert_eq!(connection.file.as_deref(), Some(r#"D:\a "b""#));
+    }
+
+    #[test]
+    fn reads_unquoted_values() {
+        let connection = ConnectionString::parse("Srvr=srv ; Ref=erp").unwrap();
+        assert_eq!(connection.server.as_deref(), Some("srv"));
+        assert_eq!(connection.reference.as_deref(), Some("erp"));
+    }
+
+    #[test]
+    fn matches_keys_regardless_of_case() {
+        let connection = ConnectionString::parse(r#"SRVR="srv";ref="erp";usr="ivan";"#).unwrap();
+        assert_eq!(connection.server.as_deref(), Some("srv"));
+        assert_eq!(connection.reference.as_deref(), Some("erp"));
+        assert_eq!(connection.user.as_deref(), Some("ivan"));
+        assert_eq!(connection.params[0].0, "Srvr");
+    }
+
+    #[test]
+    fn rejects_broken_strings() {
+        assert!(ConnectionString::parse("").is_err());
+        assert!(ConnectionString::parse(r#"File="D:\a"#).is_err());
+        assert!(ConnectionString::parse(r#"File="D:\a"x;"#).is_err());
+        assert!(ConnectionString::parse("Srvr;Ref=erp").is_err());
+    }
+
+    #[test]
+    fn takes_the_credentials() {
+        let connection =
+            ConnectionString::parse(r#"Srvr="srv";Ref="erp";Usr="ivan";Pwd="s;cret";"#).unwrap();
+        let credentials = connection.credentials().unwrap();
+        assert_eq!(credentials.user.as_deref(), Some("ivan"));
+        assert_eq!(credentials.password.as_deref(), Some("s;cret"));
+        let anonymous = ConnectionString::parse(r#"Srvr="srv";Ref="erp";Usr="";"#).unwrap();
+        assert!(anonymous.credentials().is_none());
+    }
+
+    #[test]
+    fn strips_the_passwords() {
+        assert_eq!(
+            without_password(r#"Srvr="srv";Ref="erp";Usr="ivan";pwd="secret";"#),
+            r#"Srvr="srv";Ref="erp";Usr="ivan";"#
+        );
+        assert_eq!(
+            without_password(r#"Srvr="srv";Ref="erp";"#),
+            r#"Srvr="srv";Ref="erp";"#
+        );
+        assert_eq!(without_password("srv;erp"), "srv;erp");
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::Local;
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::history::{self, HistoryEntry};
+use crate::space::LOCK_FILE;
+use crate::{PathKind, clients, parse_base_path, production_guard_word};
+
+pub struct RepairOptions {
+    pub credentials: Credentials,
+    /// Only check the base, `-TestOnly`
+    pub test_only: bool,
+    pub yes: bool,
+}
+
+/// Why the last session of `path` looks like it ended abnormally, e.g. `the
+/// client exited with 0xC0000005`: the outcome of its last client waited
+/// for, or a lock file left in a file base. `open_here` tells whether a
+/// client of this machine has the base open, the lock file is its then.
+pub fn abnormal_end(path: &str, entry: Option<&HistoryEntry>, open_here: bool) -> Option<String> {
+    if let Some(entry) = entry.filter(|entry| entry.failed_recently(Local::now().timestamp())) {
+        match entry.exit_code {
+            Some(code) if code < 0 => {
+                return Some(format!("the client exited with {:#010X}", code as u32));
+            }
+            Some(code) if code != 0 => return Some(format!("the client exited with {code}")),
+            None if entry
+                .failure
+                .as_deref()
+                .is_some_and(|failure| failure.starts_with("was killed")) =>
+            {
+                return Some("the client was killed".to_string());
+            }
+            _ => {}
+        }
+    }
+    if !open_here
+        && let Ok(PathKind::File { path }) = parse_base_path(path)
+        && Path::new(&path).join(LOCK_FILE).exists()
+    {
+        return Some(format!(
+            "{LOCK_FILE} was left behind, unless the base is open on another machine"
+        ));
+    }
+    None
+}
+
+/// Checks and repairs the file base `path` in the designer
+/// (`/IBCheckAndRepair`), after typing the name of the base unless only
+/// checking it. A repair clears the abnormal end of the last session.
+pub fn repair(path: &str, options: RepairOptions, bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    if !matches!(parsed, PathKind::File { .. }) {
+        return Err(format!(
+            "Only file bases can be checked and repaired, this is a {} base",
+            parsed.kind_name()
+        )
+        .into());
+    }
+    if !clients::running(path).is_empty() {
+        return Err("The base is open on this machine, close its sessions first".into());
+    }
+    if !options.test_only && (!options.yes || bases.is_production(path)) {
+        let word = production_guard_word(path);
+        print!("The base will be repaired in place. Type '{word}' to continue: ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if answer.trim() != word {
+            return Err("Cancelled".into());
+        }
+    }
+
+    let mut batch = Batch::new(&parsed, &options.credentials)?;
+    batch
+        .arg("/IBCheckAndRepair")
+        .arg("-ReIndex")
+        .arg("-LogIntegrity")
+        .arg("-RecalcTotals")
+        .arg("-IBCompression");
+    let label = if options.test_only {
+        batch.arg("-TestOnly");
+        format!("Checking {}...", parsed.target())
+    } else {
+        format!("Checking and repairing {}...", parsed.target())
+    };
+    let started = Instant::now();
+    batch
+        .run_with_progress(&label)
+        .map_err(|e| format!("Designer failed: {e}"))?;
+    let seconds = started.elapsed().as_secs();
+    let done = if options.test_only {
+        "Checked"
+    } else {
+        history::record_outcome(path, Some(0), None)?;
+        "Checked and repaired"
+    };
+    println!(
+        "{done} {} in {}:{:02}",
+        parsed.target(),
+        seconds / 60,
+        seconds % 60
+    );
+    Ok(())
+}
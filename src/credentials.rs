@@ -0,0 +1,45 @@
+use std::error::Error;
+
+use crate::bases::Bases;
+use crate::designer::Credentials;
+use crate::secrets;
+
+/// Credentials saved for `path`: the user in the bases file, the password in
+/// the OS keyring.
+pub fn stored(path: &str, bases: &Bases) -> Credentials {
+    let Some(user) = bases.find(path).and_then(|entry| entry.user.clone()) else {
+        return Credentials::default();
+    };
+    let password = secrets::get(&account(path)).ok();
+    Credentials {
+        user: Some(user),
+        password,
+    }
+}
+
+/// Explicitly given credentials, falling back to the ones saved for `path`.
+pub fn resolve(explicit: &Credentials, path: &str, bases: &Bases) -> Credentials {
+    if explicit.user.is_some() {
+        explicit.clone()
+    } else {
+        stored(path, bases)
+    }
+}
+
+/// Saves the credentials for `path`, forgetting them when there is no user.
+pub fn remember(
+    path: &str,
+    credentials: &Credentials,
+    bases: &mut Bases,
+) -> Result<(), Box<dyn Error>> {
+    match (&credentials.user, &credentials.password) {
+        (Some(_), Some(password)) => secrets::set(&account(path), password)?,
+        _ => secrets::delete(&account(path)),
+    }
+    bases.entry_mut(path).user = credentials.user.clone();
+    bases.save()
+}
+
+fn account(path: &str) -> String {
+    format!("base {}", path.trim())
+}
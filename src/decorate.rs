@@ -0,0 +1,144 @@
+#[cfg(feature = "scripting")]
+use chrono::{Datelike, Local, Timelike};
+#[cfg(feature = "scripting")]
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "scripting")]
+use std::fs;
+use std::path::Path;
+
+/// Operations a script may run for one entry, so that a loop in it can't
+/// hang the picker.
+#[cfg(feature = "scripting")]
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What the script of `decorate` in rbaserun.toml said about a listed base.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Decoration {
+    /// Shown instead of the badge saved with the base
+    pub badge: Option<String>,
+    /// Left out of the history pane
+    pub hidden: bool,
+    /// Entries with a lower key are listed first, 0 by default
+    pub sort: i64,
+}
+
+/// A listed base as the script sees it in `entry`.
+#[derive(Debug, Default)]
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+pub struct Fields<'a> {
+    pub path: &'a str,
+    pub kind: Option<&'static str>,
+    pub target: Option<String>,
+    pub name: Option<String>,
+    pub alias: Option<&'a str>,
+    pub badge: Option<&'a str>,
+    pub folder: Option<&'a str>,
+    pub tags: &'a [String],
+    pub production: bool,
+    pub pinned: bool,
+    /// Launches in the launch log, 0 unless `sort = "frequent"` read it
+    pub launches: usize,
+}
+
+/// The Rhai script deciding the badge, visibility and order of each listed
+/// base, run for every entry when the list is built. It sees the base in
+/// `entry` and the local time in `hour` and `weekday` (1 for Monday), and
+/// returns a map with any of `badge`, `hidden` and `sort`, or nothing.
+pub struct Decorator {
+    #[cfg(feature = "scripting")]
+    engine: Engine,
+    #[cfg(feature = "scripting")]
+    ast: AST,
+}
+
+impl fmt::Debug for Decorator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Decorator")
+    }
+}
+
+impl Decorator {
+    #[cfg(feature = "scripting")]
+    pub fn load(path: &Path) -> Result<Decorator, Box<dyn Error>> {
+        let script = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(&script)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(Decorator { engine, ast })
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn load(path: &Path) -> Result<Decorator, Box<dyn Error>> {
+        Err(format!(
+            "{}: rbaserun was built without the scripting feature",
+            path.display()
+        )
+        .into())
+    }
+
+    #[cfg(feature = "scripting")]
+    pub fn decorate(&self, fields: &Fields) -> Result<Decoration, String> {
+        let now = Local::now();
+        let mut scope = Scope::new();
+        scope.push_constant("entry", entry(fields));
+        scope.push_constant("hour", now.hour() as i64);
+        scope.push_constant("weekday", now.weekday().number_from_monday() as i64);
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| format!("Decorate script on {}: {e}", fields.path.trim()))?;
+        if result.is_unit() {
+            return Ok(Decoration::default());
+        }
+        let Some(map) = result.try_cast::<Map>() else {
+            return Err(format!(
+                "Decorate script on {}: expected a map like #{{ hidden: true }}",
+                fields.path.trim()
+            ));
+        };
+        Ok(Decoration {
+            badge: map
+                .get("badge")
+                .and_then(|badge| badge.clone().into_string().ok()),
+            hidden: map
+                .get("hidden")
+                .and_then(|hidden| hidden.as_bool().ok())
+                .unwrap_or(false),
+            sort: map
+                .get("sort")
+                .and_then(|sort| sort.as_int().ok())
+                .unwrap_or(0),
+        })
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn decorate(&self, _fields: &Fields) -> Result<Decoration, String> {
+        // `load` never returns a decorator in this build
+        Ok(Decoration::default())
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn entry(fields: &Fields) -> Map {
+    let text = |value: Option<&str>| value.map_or(Dynamic::UNIT, |value| value.into());
+    let mut map = Map::new();
+    map.insert("path".into(), fields.path.trim().into());
+    map.insert("kind".into(), text(fields.kind));
+    map.insert("target".into(), text(fields.target.as_deref()));
+    map.insert("name".into(), text(fields.name.as_deref()));
+    map.insert("alias".into(), text(fields.alias));
+    map.insert("badge".into(), text(fields.badge));
+    map.insert("folder".into(), text(fields.folder));
+    let tags: Array = fields.tags.iter().map(|tag| tag.as_str().into()).collect();
+    map.insert("tags".into(), tags.into());
+    map.insert("production".into(), fields.production.into());
+    map.insert("pinned".into(), fields.pinned.into());
+    map.insert("launches".into(), (fields.launches as i64).into());
+    map
+}
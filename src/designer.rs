@@ -0,0 +1,190 @@
+use chrono::Local;
+
+use std::env;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{LaunchMode, PathKind};
+use crate::{lockdown, platform};
+
+/// Credentials of the infobase user running designer batch operations.
+#[derive(Debug, Default, Clone)]
+pub struct Credentials {
+    pub user: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Finds `1cv8` of the newest installed platform. Batch operations need it
+/// instead of the starter because the starter returns before the work is done.
+pub fn platform_binary() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(platform::newest()?.binary(LaunchMode::Designer))
+}
+
+/// A designer started in batch mode for `path`.
+pub struct Batch {
+    command: Command,
+    log: PathBuf,
+}
+
+impl Batch {
+    pub fn new(path: &PathKind, credentials: &Credentials) -> Result<Batch, Box<dyn Error>> {
+        Batch::start(LaunchMode::Designer, path, credentials)
+    }
+
+    /// The thick client in Enterprise mode for `path`, for running external
+    /// data processors with `/Execute`.
+    pub fn enterprise(path: &PathKind, credentials: &Credentials) -> Result<Batch, Box<dyn Error>> {
+        Batch::start(LaunchMode::Enterprise, path, credentials)
+    }
+
+    fn start(
+        mode: LaunchMode,
+        path: &PathKind,
+        credentials: &Credentials,
+    ) -> Result<Batch, Box<dyn Error>> {
+        lockdown::check_mode(mode)?;
+        let mut command = Command::new(platform_binary()?);
+        command.arg(if mode == LaunchMode::Designer {
+            "DESIGNER"
+        } else {
+            "ENTERPRISE"
+        });
+        match path {
+            PathKind::Server { host, ref_name } => {
+                command.args(["/S", &format!("{host}\\{ref_name}")])
+            }
+            PathKind::File { path } => command.args(["/F", path]),
+            PathKind::Web { .. } => {
+                return Err("Designer batch operations need a server or file base".into());
+            }
+        };
+        if let Some(user) = &credentials.user {
+            command.args(["/N", user]);
+        }
+        if let Some(password) = &credentials.password {
+            command.args(["/P", password]);
+        }
+        Ok(Batch::logged(command, &path.name()))
+    }
+
+    /// `CREATEINFOBASE` of the base with connection string `path`.
+    pub fn create(path: &str) -> Result<Batch, Box<dyn Error>> {
+        let mut command = Command::new(platform_binary()?);
+        command.arg("CREATEINFOBASE").arg(path);
+        Ok(Batch::logged(command, "create"))
+    }
+
+    fn logged(mut command: Command, name: &str) -> Batch {
+        let log = env::temp_dir().join(format!(
+            "rbaserun-{name}-{}.log",
+            Local::now().format("%Y%m%d-%H%M%S%3f")
+        ));
+        command.arg("/Out").arg(&log).arg("/DisableStartupDialogs");
+        Batch { command, log }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Batch {
+        self.command.arg(arg);
+        self
+    }
+
+    /// Runs the designer and waits for it, its log becomes the error on failure.
+    pub fn run(mut self) -> Result<String, Box<dyn Error>> {
+        let status = self.command.status()?;
+        self.finish(status)
+    }
+
+    /// Like `run`, counting the time spent on stderr while waiting: a dump
+    /// or a restore of a large base takes many minutes and the designer
+    /// shows nothing in batch mode.
+    pub fn run_with_progress(mut self, label: &str) -> Result<String, Box<dyn Error>> {
+        let mut child = self.command.spawn()?;
+        let started = Instant::now();
+        let terminal = io::stderr().is_terminal();
+        if !terminal {
+            eprintln!("{label}");
+        }
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if terminal {
+                let seconds = started.elapsed().as_secs();
+                eprint!("\r{label} {}:{:02}", seconds / 60, seconds % 60);
+                let _ = io::stderr().flush();
+            }
+            thread::sleep(Duration::from_millis(500));
+        };
+        if terminal {
+            eprintln!();
+        }
+        self.finish(status)
+    }
+
+    /// Starts the designer without waiting for it, for the agent mode that
+    /// runs until it is stopped.
+    pub fn spawn(mut self) -> Result<Running, Box<dyn Error>> {
+        let child = self.command.spawn()?;
+        Ok(Running { child, batch: self })
+    }
+
+    fn finish(self, status: ExitStatus) -> Result<String, Box<dyn Error>> {
+        let log = read_log(&self.log);
+        let _ = fs::remove_file(&self.log);
+        if !status.success() {
+            return Err(if log.is_empty() {
+                format!("Designer exited with {status}").into()
+            } else {
+                log.into()
+            });
+        }
+        Ok(log)
+    }
+}
+
+/// A designer started with `Batch::spawn`.
+pub struct Running {
+    child: Child,
+    batch: Batch,
+}
+
+impl Running {
+    /// Why the designer exited, `None` while it runs.
+    pub fn exited(&mut self) -> Option<String> {
+        let status = self.child.try_wait().ok()??;
+        let log = read_log(&self.batch.log);
+        Some(if log.is_empty() {
+            format!("Designer exited with {status}")
+        } else {
+            log
+        })
+    }
+
+    pub fn wait(mut self) -> Result<String, Box<dyn Error>> {
+        let status = self.child.wait()?;
+        self.batch.finish(status)
+    }
+
+    /// Kills the designer, removing its log.
+    pub fn stop(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.batch.log);
+    }
+}
+
+/// Designer logs are written in UTF-8 with a BOM or in the ANSI code page
+/// depending on the platform version, non UTF-8 bytes are replaced.
+fn read_log(path: &Path) -> String {
+    let bytes = fs::read(path).unwrap_or_default();
+    String::from_utf8_lossy(&bytes)
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .to_string()
+}
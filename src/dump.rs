@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::backup;
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::space;
+use crate::{parse_base_path, production_guard_word};
+
+/// A designer batch command of `rbaserun designer-dump`.
+pub enum Operation {
+    /// `/DumpIB` into a .dt file
+    Dump(PathBuf),
+    /// `/RestoreIB` from a .dt file, replacing all data
+    Restore(PathBuf),
+    /// `/LoadCfg` of a .cf file, with `/UpdateDBCfg` when `update` is set
+    LoadCf { file: PathBuf, update: bool },
+}
+
+pub struct DumpOptions {
+    pub credentials: Credentials,
+    /// Permission code (`/UC`) letting the designer in while sessions are denied
+    pub unlock_code: Option<String>,
+    pub yes: bool,
+}
+
+/// Runs `operation` on the base `path` and waits for the designer to finish.
+pub fn run(
+    path: &str,
+    operation: Operation,
+    bases: &Bases,
+    options: DumpOptions,
+) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    let target = parsed.target();
+
+    let (label, done) = match &operation {
+        Operation::Dump(out) => {
+            space::check_dump(&parsed, out)?;
+            (
+                format!("Dumping {target} to {}...", out.display()),
+                format!("Dumped {target} to {}", out.display()),
+            )
+        }
+        Operation::Restore(file) => {
+            replace_with(path, "All data", file, bases, options.yes)?;
+            space::check_restore(&parsed, file)?;
+            (
+                format!("Restoring {} into {target}...", file.display()),
+                format!("Restored {} into {target}", file.display()),
+            )
+        }
+        Operation::LoadCf { file, .. } => {
+            replace_with(path, "The configuration", file, bases, options.yes)?;
+            (
+                format!("Loading {} into {target}...", file.display()),
+                format!("Loaded {} into {target}", file.display()),
+            )
+        }
+    };
+
+    let mut batch = Batch::new(&parsed, &options.credentials)?;
+    match &operation {
+        Operation::Dump(out) => batch.arg("/DumpIB").arg(out),
+        Operation::Restore(dt) => batch.arg("/RestoreIB").arg(dt),
+        Operation::LoadCf { file, update } => {
+            batch.arg("/LoadCfg").arg(file);
+            if *update {
+                batch.arg("/UpdateDBCfg");
+            }
+            &mut batch
+        }
+    };
+    if let Some(code) = &options.unlock_code {
+        batch.arg("/UC").arg(code);
+    }
+
+    let started = Instant::now();
+    batch
+        .run_with_progress(&label)
+        .map_err(|e| format!("Designer failed: {e}"))?;
+    let seconds = started.elapsed().as_secs();
+    println!("{done} in {}:{:02}", seconds / 60, seconds % 60);
+    if let Operation::Dump(out) = &operation {
+        match backup::record(out, path) {
+            Ok(manifest) => println!("SHA-256 {}", manifest.sha256),
+            Err(e) => eprintln!("Warning: the dump has no manifest: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `file` exists and asks to type the name of the base before
+/// `what` of it is replaced, production bases even with `--yes`.
+fn replace_with(
+    path: &str,
+    what: &str,
+    file: &Path,
+    bases: &Bases,
+    yes: bool,
+) -> Result<(), Box<dyn Error>> {
+    if !file.is_file() {
+        return Err(format!("{} not found", file.display()).into());
+    }
+    if yes && !bases.is_production(path) {
+        return Ok(());
+    }
+    let word = production_guard_word(path);
+    print!(
+        "{what} of '{}' will be replaced with {}. Type '{word}' to continue: ",
+        path.trim(),
+        file.display()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != word {
+        return Err("Cancelled".into());
+    }
+    Ok(())
+}
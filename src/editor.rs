@@ -0,0 +1,313 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::mem;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::bases::BaseEntry;
+use crate::keys;
+use crate::popup;
+use crate::script::shell_quote;
+use crate::theme;
+
+pub enum EditorAction {
+    None,
+    Save,
+    Cancel,
+}
+
+pub enum DiscardAction {
+    None,
+    Discard,
+    Keep,
+}
+
+#[derive(Debug)]
+struct Field {
+    label: &'static str,
+    input: Input,
+}
+
+#[derive(Debug)]
+pub struct EntryEditor {
+    pub path: String,
+    pub error: Option<String>,
+    fields: Vec<Field>,
+    /// Field values the editor was opened with
+    initial: Vec<String>,
+    focus: usize,
+}
+
+impl EntryEditor {
+    pub fn new(path: String, entry: Option<&BaseEntry>) -> EntryEditor {
+        let value = |get: fn(&BaseEntry) -> &Option<String>| {
+            entry
+                .and_then(|entry| get(entry).clone())
+                .unwrap_or_default()
+        };
+        let fields = vec![
+            Field {
+                label: "Alias",
+                input: value(|entry| &entry.alias).into(),
+            },
+            Field {
+                label: "Badge",
+                input: value(|entry| &entry.badge).into(),
+            },
+            Field {
+                label: "Color",
+                input: value(|entry| &entry.color).into(),
+            },
+            Field {
+                label: "Tags",
+                input: entry
+                    .map(|entry| entry.tags.join(", "))
+                    .unwrap_or_default()
+                    .into(),
+            },
+            Field {
+                label: "Switches",
+                input: entry
+                    .map(|entry| {
+                        let quoted: Vec<String> =
+                            entry.switches.iter().map(|arg| shell_quote(arg)).collect();
+                        quoted.join(" ")
+                    })
+                    .unwrap_or_default()
+                    .into(),
+            },
+            Field {
+                label: "Directory",
+                input: entry
+                    .and_then(|entry| entry.process.working_dir.as_ref())
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default()
+                    .into(),
+            },
+            Field {
+                label: "Env",
+                input: entry
+                    .map(|entry| {
+                        let quoted: Vec<String> = entry
+                            .process
+                            .env
+                            .iter()
+                            .map(|(name, value)| shell_quote(&format!("{name}={value}")))
+                            .collect();
+                        quoted.join(" ")
+                    })
+                    .unwrap_or_default()
+                    .into(),
+            },
+        ];
+        let initial = fields
+            .iter()
+            .map(|field| field.input.value().to_string())
+            .collect();
+        EntryEditor {
+            path,
+            error: None,
+            fields,
+            initial,
+            focus: 0,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EditorAction {
+        let Event::Key(key_event) = event else {
+            return EditorAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return EditorAction::None;
+        }
+        match key_event.code {
+            KeyCode::Esc => return EditorAction::Cancel,
+            KeyCode::Enter => return EditorAction::Save,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % self.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus = (self.focus + self.fields.len() - 1) % self.fields.len()
+            }
+            _ => {
+                self.fields[self.focus].input.handle_event(event);
+            }
+        }
+        EditorAction::None
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.fields
+            .iter()
+            .zip(&self.initial)
+            .any(|(field, initial)| field.input.value() != initial)
+    }
+
+    pub fn handle_discard_event(&self, event: &Event) -> DiscardAction {
+        let Event::Key(key_event) = event else {
+            return DiscardAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return DiscardAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Char('y') => DiscardAction::Discard,
+            KeyCode::Char('n') | KeyCode::Esc => DiscardAction::Keep,
+            _ => DiscardAction::None,
+        }
+    }
+
+    pub fn apply(&self, entry: &mut BaseEntry) -> Result<(), Box<dyn Error>> {
+        let color = self.value("Color");
+        if let Some(color) = &color {
+            Color::from_str(color).map_err(|_| format!("Unknown color: {color}"))?;
+        }
+        let env = self
+            .value("Env")
+            .map(|env| parse_env(&env))
+            .transpose()?
+            .unwrap_or_default();
+        entry.alias = self.alias();
+        entry.badge = self.value("Badge");
+        entry.color = color;
+        entry.tags = self
+            .value("Tags")
+            .map(|tags| {
+                tags.split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        entry.switches = self
+            .value("Switches")
+            .map(|switches| split_switches(&switches))
+            .unwrap_or_default();
+        entry.process.working_dir = self.value("Directory").map(PathBuf::from);
+        entry.process.env = env;
+        Ok(())
+    }
+
+    pub fn alias(&self) -> Option<String> {
+        self.value("Alias")
+    }
+
+    fn value(&self, label: &str) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|field| field.label == label)
+            .map(|field| field.input.value().trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), self.fields.len() as u16 + 4);
+
+        let label_width = self
+            .fields
+            .iter()
+            .map(|field| field.label.len())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, field) in self.fields.iter().enumerate() {
+            let label = format!("{:<label_width$}", format!("{}:", field.label));
+            let line = Line::from(vec![label.into(), field.input.value().into()]);
+            lines.push(if index == self.focus {
+                line.bold()
+            } else {
+                line
+            });
+        }
+        lines.push(Line::default());
+        match &self.error {
+            Some(error) => lines.push(Span::styled(error.clone(), theme::error()).into()),
+            None => lines.push(
+                Span::styled("Enter: save, Esc: cancel, Tab: next field", theme::hint()).into(),
+            ),
+        }
+
+        let block = Block::bordered().title(format!("Edit: {}", self.path));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+
+        let cursor = self.fields[self.focus].input.visual_cursor();
+        frame.set_cursor_position((
+            inner.x + (label_width + cursor) as u16,
+            inner.y + self.focus as u16,
+        ));
+    }
+
+    pub fn render_discard_prompt(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), 4);
+        let lines: Vec<Line> = vec![
+            "Discard unsaved changes?".into(),
+            Span::styled("y: discard, n / Esc: keep editing", theme::hint()).into(),
+        ];
+        let block = Block::bordered()
+            .title("Unsaved changes")
+            .style(theme::warning());
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+/// Variables typed as `NAME=value` words, quoted like the switches.
+fn parse_env(text: &str) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    split_switches(text)
+        .into_iter()
+        .map(|word| match word.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.to_string()))
+            }
+            _ => Err(format!("Expected NAME=value in Env, got '{word}'").into()),
+        })
+        .collect()
+}
+
+/// Splits `text` at whitespace outside double quotes, e.g. `/UC "my code"`,
+/// the reverse of joining switches quoted with `shell_quote`.
+pub fn split_switches(text: &str) -> Vec<String> {
+    let mut switches = Vec::new();
+    let mut current = String::new();
+    let mut started = false;
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // a quote escaped by `shell_quote`, backslashes of paths stay
+            '\\' if chars.peek() == Some(&'"') => {
+                current.extend(chars.next());
+                started = true;
+            }
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    switches.push(mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        switches.push(current);
+    }
+    switches
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    Frame,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+};
+
+use std::collections::VecDeque;
+
+use crate::{popup, theme};
+
+#[derive(Debug)]
+pub struct ErrorEntry {
+    pub time: DateTime<Local>,
+    pub text: String,
+}
+
+/// Errors raised during the current session, newest first.
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    entries: VecDeque<ErrorEntry>,
+    capacity: usize,
+    scroll: u16,
+    max_scroll: u16,
+    notice: Option<String>,
+}
+
+impl ErrorLog {
+    pub fn new(capacity: usize) -> ErrorLog {
+        ErrorLog {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, text: String) {
+        self.entries.push_front(ErrorEntry {
+            time: Local::now(),
+            text,
+        });
+        self.entries.truncate(self.capacity.max(1));
+    }
+
+    pub fn latest(&self) -> Option<&ErrorEntry> {
+        self.entries.front()
+    }
+
+    pub fn open(&mut self) {
+        self.scroll = 0;
+        self.notice = None;
+    }
+
+    pub fn set_notice(&mut self, notice: String) {
+        self.notice = Some(notice);
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = (self.scroll + lines).min(self.max_scroll);
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), frame.area().height.saturating_sub(4));
+        let inner_width = area.width.saturating_sub(2).max(1) as usize;
+        let inner_height = area.height.saturating_sub(2);
+
+        let lines: Vec<Line> = if self.entries.is_empty() {
+            vec![Span::styled("No errors in this session", theme::hint()).into()]
+        } else {
+            self.entries
+                .iter()
+                .map(|entry| {
+                    Line::from(vec![
+                        Span::styled(
+                            format!("[{}] ", entry.time.format("%H:%M:%S")),
+                            theme::hint(),
+                        ),
+                        Span::styled(entry.text.clone(), theme::error()),
+                    ])
+                })
+                .collect()
+        };
+
+        let wrapped_height: usize = lines
+            .iter()
+            .map(|line| line.width().div_ceil(inner_width).max(1))
+            .sum();
+        self.max_scroll = (wrapped_height as u16).saturating_sub(inner_height);
+        self.scroll = self.scroll.min(self.max_scroll);
+
+        let hint = self
+            .notice
+            .clone()
+            .unwrap_or_else(|| "Up/Down/PgUp/PgDn: scroll, c: copy latest, Esc: close".into());
+        let block = Block::bordered()
+            .title(format!("Errors ({})", self.entries.len()))
+            .title_bottom(hint);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(block),
+            area,
+        );
+    }
+}
@@ -0,0 +1,505 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use chrono::{Days, Local};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use crate::bases::Bases;
+use crate::config::{ClusterConfig, RasConfig};
+use crate::designer::{Batch, Credentials};
+use crate::ras::{self, cluster_host};
+use crate::{PathKind, keys, locale, parse_base_path, popup, production_guard_word, theme};
+
+/// Records or lines read from the end of a log.
+const TAIL: usize = 500;
+
+/// How bad a line of a log is, for its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A log the viewer shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// The event log of the infobase, `1Cv8Log`
+    Events,
+    /// The technological log of the cluster host, `tech_log` of its `[[ras.cluster]]`
+    Tech,
+}
+
+/// Directory of the event log of `path`: `1Cv8Log` next to a file base, or
+/// in the registry of the cluster for a server base when its `srvinfo` is
+/// configured and reachable from here.
+fn event_log_dir(path: &str, config: &RasConfig) -> Result<PathBuf, Box<dyn Error>> {
+    match parse_base_path(path)? {
+        PathKind::File { path } => Ok(Path::new(&path).join("1Cv8Log")),
+        PathKind::Server { .. } => {
+            let host = cluster_host(path).unwrap_or_default();
+            let srvinfo = registered(&host, config)
+                .and_then(|cluster| cluster.srvinfo.as_ref())
+                .ok_or_else(|| {
+                    format!("Set srvinfo of the [[ras.cluster]] of {host} to read its event logs")
+                })?;
+            let (_, cluster, infobase) = ras::server_infobase(path, config)?;
+            Ok(srvinfo
+                .join(format!("reg_{}", cluster.port))
+                .join(infobase.id)
+                .join("1Cv8Log"))
+        }
+        PathKind::Web { .. } => Err("Web bases have no event log reachable from here".into()),
+    }
+}
+
+fn registered<'a>(host: &str, config: &'a RasConfig) -> Option<&'a ClusterConfig> {
+    config
+        .clusters
+        .iter()
+        .find(|cluster| cluster.host.eq_ignore_ascii_case(host))
+}
+
+/// The last records of the newest `.lgp` file of the event log in `dir`, as
+/// `date importance user event: comment` lines, oldest first.
+fn read_events(dir: &Path) -> Result<Vec<(String, Level)>, Box<dyn Error>> {
+    let newest = newest_file(dir, "lgp")?;
+    let Some(newest) = newest else {
+        if dir.join("1Cv8.lgd").exists() {
+            return Err(format!(
+                "The event log in {} is in the SQLite format (1Cv8.lgd), only the sequential one can be read here",
+                dir.display()
+            )
+            .into());
+        }
+        return Err(format!("No event log files in {}", dir.display()).into());
+    };
+    let (users, events) = dictionary(&dir.join("1Cv8.lgf"));
+    let content =
+        fs::read(&newest).map_err(|e| format!("Could not read {}: {e}", newest.display()))?;
+    let content = String::from_utf8_lossy(&content);
+    let records = records(&content);
+    let skip = records.len().saturating_sub(TAIL);
+    Ok(records[skip..]
+        .iter()
+        .filter_map(|record| {
+            let fields = fields(record);
+            let field = |index: usize| fields.get(index).map(String::as_str).unwrap_or("");
+            let name = |names: &HashMap<String, String>, index| {
+                names.get(field(index)).cloned().unwrap_or_default()
+            };
+            let (level, mark) = match field(8) {
+                "E" => (Level::Error, "E"),
+                "W" => (Level::Warning, "W"),
+                "N" => (Level::Info, "N"),
+                _ => (Level::Info, "I"),
+            };
+            let mut line = format!(
+                "{} {mark} {} {}",
+                date(field(0))?,
+                name(&users, 3),
+                name(&events, 7)
+            );
+            if !field(9).is_empty() {
+                line.push_str(&format!(": {}", field(9).replace(['\r', '\n'], " ")));
+            }
+            Some((line, level))
+        })
+        .collect())
+}
+
+/// Names of the users and events of the `1Cv8.lgf` dictionary, by the
+/// numbers the records refer to them with.
+fn dictionary(file: &Path) -> (HashMap<String, String>, HashMap<String, String>) {
+    let mut users = HashMap::new();
+    let mut events = HashMap::new();
+    let Ok(content) = fs::read(file) else {
+        return (users, events);
+    };
+    for record in records(&String::from_utf8_lossy(&content)) {
+        let fields = fields(record);
+        match fields.first().map(String::as_str) {
+            // {1,uuid,"name",number}
+            Some("1") if fields.len() >= 4 => {
+                users.insert(fields[3].clone(), fields[2].clone());
+            }
+            // {4,"_$Session$_.Start",number}
+            Some("4") if fields.len() >= 3 => {
+                let name = fields[1].replace("_$", "").replace("$_", "");
+                events.insert(fields[2].clone(), name);
+            }
+            _ => {}
+        }
+    }
+    (users, events)
+}
+
+/// The `{...}` records of a log file at the top level.
+fn records(content: &str) -> Vec<&str> {
+    let mut records = Vec::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, c) in content.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '{' if !quoted => {
+                if depth == 0 {
+                    start = index;
+                }
+                depth += 1;
+            }
+            '}' if !quoted && depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    records.push(&content[start..=index]);
+                }
+            }
+            _ => {}
+        }
+    }
+    records
+}
+
+/// Top-level fields of a record, strings without their quotes.
+fn fields(record: &str) -> Vec<String> {
+    let inner = record
+        .trim()
+        .strip_prefix('{')
+        .and_then(|inner| inner.strip_suffix('}'))
+        .unwrap_or(record);
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    for c in inner.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '{' if !quoted => depth += 1,
+            '}' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                fields.push(unquote(current.trim()));
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    fields.push(unquote(current.trim()));
+    fields
+}
+
+fn unquote(field: &str) -> String {
+    match field
+        .strip_prefix('"')
+        .and_then(|field| field.strip_suffix('"'))
+    {
+        Some(text) => text.replace("\"\"", "\""),
+        None => field.to_string(),
+    }
+}
+
+/// `20261016093000` as `2026-10-16 09:30:00`.
+fn date(stamp: &str) -> Option<String> {
+    if stamp.len() != 14 || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{} {}:{}:{}",
+        &stamp[0..4],
+        &stamp[4..6],
+        &stamp[6..8],
+        &stamp[8..10],
+        &stamp[10..12],
+        &stamp[12..14]
+    ))
+}
+
+/// The last lines of the newest `.log` file of the technological log in
+/// `dir`, which keeps a directory per process.
+fn read_tech(dir: &Path) -> Result<Vec<(String, Level)>, Box<dyn Error>> {
+    let mut newest = newest_file(dir, "log")?;
+    for entry in fs::read_dir(dir)?.flatten() {
+        if entry.path().is_dir()
+            && let Some(file) = newest_file(&entry.path(), "log")?
+            && newest
+                .as_ref()
+                .is_none_or(|newest| modified(&file) > modified(newest))
+        {
+            newest = Some(file);
+        }
+    }
+    let newest =
+        newest.ok_or_else(|| format!("No technological log files in {}", dir.display()))?;
+    let content =
+        fs::read(&newest).map_err(|e| format!("Could not read {}: {e}", newest.display()))?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let skip = lines.len().saturating_sub(TAIL);
+    Ok(lines[skip..]
+        .iter()
+        .map(|line| {
+            let line = line.trim_start_matches('\u{feff}');
+            let level = if line.contains(",EXCP,") {
+                Level::Error
+            } else if line.contains(",ATTN,") {
+                Level::Warning
+            } else {
+                Level::Info
+            };
+            (line.to_string(), level)
+        })
+        .collect())
+}
+
+/// The most recently changed file with extension `extension` in `dir`.
+fn newest_file(dir: &Path, extension: &str) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Could not open {}: {e}", dir.display()))?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|own| own.eq_ignore_ascii_case(extension))
+        })
+        .max_by_key(|path| modified(path)))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Bytes and count of the files of the event log in `dir`.
+fn log_size(dir: &Path) -> (u64, usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .fold((0, 0), |(bytes, files), meta| {
+            (bytes + meta.len(), files + 1)
+        })
+}
+
+pub struct ReduceOptions {
+    pub credentials: Credentials,
+    /// Days of records to keep, the older ones are removed
+    pub keep_days: Option<u32>,
+    /// File the removed records are saved to
+    pub save_as: Option<PathBuf>,
+    pub yes: bool,
+}
+
+/// Prints the size of the event log of file base `path`. With `keep_days`
+/// the records older than that are removed by the designer
+/// (`/ReduceEventLogSize`), saved to `save_as` first when it is given, after
+/// typing the name of the base.
+pub fn run(path: &str, options: ReduceOptions, bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    let PathKind::File { path: dir } = &parsed else {
+        return Err(format!("{} is not a file base", path.trim()).into());
+    };
+    let log = Path::new(dir).join("1Cv8Log");
+    if !log.is_dir() {
+        return Err(format!("{} has no event log", path.trim()).into());
+    }
+    let (bytes, files) = log_size(&log);
+    println!(
+        "Event log of {}: {} in {files} files",
+        parsed.target(),
+        locale::size(bytes)
+    );
+    let Some(keep_days) = options.keep_days else {
+        return Ok(());
+    };
+
+    let before = Local::now()
+        .date_naive()
+        .checked_sub_days(Days::new(keep_days.into()))
+        .ok_or("--keep-days is too large")?
+        .format("%Y-%m-%d")
+        .to_string();
+    if !options.yes || bases.is_production(path) {
+        let word = production_guard_word(path);
+        let saved = match &options.save_as {
+            Some(file) => format!("saved to {}", file.display()),
+            None => "not kept".to_string(),
+        };
+        print!("Records before {before} will be removed ({saved}). Type '{word}' to continue: ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if answer.trim() != word {
+            return Err("Cancelled".into());
+        }
+    }
+
+    let mut batch = Batch::new(&parsed, &options.credentials)?;
+    batch.arg("/ReduceEventLogSize").arg(&before);
+    if let Some(file) = &options.save_as {
+        batch.arg("-saveAs").arg(file);
+    }
+    let started = Instant::now();
+    batch
+        .run_with_progress(&format!("Reducing the event log of {}...", parsed.target()))
+        .map_err(|e| format!("Designer failed: {e}"))?;
+    let (after, _) = log_size(&log);
+    let seconds = started.elapsed().as_secs();
+    println!(
+        "Reduced the event log from {} to {} in {}:{:02}",
+        locale::size(bytes),
+        locale::size(after),
+        seconds / 60,
+        seconds % 60
+    );
+    Ok(())
+}
+
+pub enum LogAction {
+    None,
+    Close,
+}
+
+/// The end of the event log of the selected base, shown with Alt+L so a
+/// failed launch can be looked into without remoting into the server. Tab
+/// switches to the technological log of the cluster host when it has one.
+#[derive(Debug)]
+pub struct LogPanel {
+    path: String,
+    source: Source,
+    /// Event log directory, or why it is not known
+    events: Result<PathBuf, String>,
+    tech: Option<PathBuf>,
+    lines: Result<Vec<(String, Level)>, String>,
+    /// Bytes of the event log files, once read
+    size: Option<u64>,
+    /// Lines scrolled up from the end
+    scroll: usize,
+}
+
+impl LogPanel {
+    pub fn open(path: &str, config: &RasConfig) -> Result<LogPanel, Box<dyn Error>> {
+        let events = event_log_dir(path, config).map_err(|e| e.to_string());
+        let tech = cluster_host(path).and_then(|host| registered(&host, config)?.tech_log.clone());
+        if let (Err(e), None) = (&events, &tech) {
+            return Err(e.clone().into());
+        }
+        let mut panel = LogPanel {
+            path: path.trim().to_string(),
+            source: if events.is_ok() {
+                Source::Events
+            } else {
+                Source::Tech
+            },
+            events,
+            tech,
+            lines: Ok(Vec::new()),
+            size: None,
+            scroll: 0,
+        };
+        panel.load();
+        Ok(panel)
+    }
+
+    fn load(&mut self) {
+        self.scroll = 0;
+        if let Ok(dir) = &self.events {
+            self.size = Some(log_size(dir).0);
+        }
+        self.lines = match (self.source, &self.events, &self.tech) {
+            (Source::Events, Ok(dir), _) => read_events(dir),
+            (Source::Events, Err(e), _) => Err(e.clone().into()),
+            (Source::Tech, _, Some(dir)) => read_tech(dir),
+            (Source::Tech, _, None) => Err("No tech_log configured for this cluster".into()),
+        }
+        .map_err(|e| e.to_string());
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> LogAction {
+        let Event::Key(key_event) = event else {
+            return LogAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return LogAction::None;
+        }
+        let count = self.lines.as_ref().map_or(0, Vec::len);
+        let page = 10;
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => return LogAction::Close,
+            KeyCode::Char('l') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                return LogAction::Close;
+            }
+            KeyCode::Up => self.scroll = (self.scroll + 1).min(count),
+            KeyCode::Down => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.scroll = (self.scroll + page).min(count),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(page),
+            KeyCode::Home => self.scroll = count,
+            KeyCode::End => self.scroll = 0,
+            KeyCode::Char('r') => self.load(),
+            KeyCode::Tab if self.tech.is_some() => {
+                self.source = match self.source {
+                    Source::Events => Source::Tech,
+                    Source::Tech => Source::Events,
+                };
+                self.load();
+            }
+            _ => {}
+        }
+        LogAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), frame.area().height.saturating_sub(4));
+        let height = area.height.saturating_sub(3) as usize;
+        let mut lines: Vec<Line> = match &self.lines {
+            Ok(log) if log.is_empty() => vec![Line::styled("The log is empty", theme::hint())],
+            Ok(log) => {
+                let end = log.len() - self.scroll.min(log.len());
+                log[end.saturating_sub(height)..end]
+                    .iter()
+                    .map(|(line, level)| match level {
+                        Level::Error => Line::styled(line.as_str(), theme::error()),
+                        Level::Warning => Line::styled(line.as_str(), theme::warning()),
+                        Level::Info => Line::from(line.as_str()),
+                    })
+                    .collect()
+            }
+            Err(e) => vec![Line::styled(e.as_str(), theme::error())],
+        };
+        while lines.len() < height {
+            lines.insert(0, Line::default());
+        }
+        let hint = if self.tech.is_some() {
+            "Up/Down, PgUp/PgDn: scroll, Tab: event/technological log, r: reload, Esc: close"
+        } else {
+            "Up/Down, PgUp/PgDn: scroll, r: reload, Esc: close"
+        };
+        lines.push(Span::styled(hint, theme::hint()).into());
+        let title = match self.source {
+            Source::Events => match self.size {
+                Some(size) => format!("Event log: {}, {}", self.path, locale::size(size)),
+                None => format!("Event log: {}", self.path),
+            },
+            Source::Tech => format!("Technological log: {}", self.path),
+        };
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
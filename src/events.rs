@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvError, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use ratatui::crossterm::event::{self, Event};
+
+use crate::availability::Status;
+use crate::check::Probe;
+use crate::history::HistoryEntry;
+use crate::ibases::RegisteredBase;
+use crate::journal;
+use crate::release::Release;
+use crate::watch::Outcome;
+
+/// How often the picker gets a `Tick` to poll the clients it waits for and
+/// the files it shows.
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Everything the picker reacts to, in the order it happened: terminal input
+/// and the results of work done in the background.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// Keys, mouse and paste of the terminal
+    Key(Event),
+    Tick,
+    /// A connection check started with `App::start_check` finished
+    CheckResult {
+        path: String,
+        probes: Result<Vec<Probe>, String>,
+    },
+    /// A base in sight of the history pane was probed, see `Availability`
+    Availability {
+        path: String,
+        status: Status,
+    },
+    /// Running 1C clients of this machine by process id and the base they
+    /// opened, see `LocalClients`
+    LocalClients(Vec<(u32, String)>),
+    /// The configuration of a base was read over COM, see `Releases`
+    Release {
+        path: String,
+        release: Result<Release, String>,
+    },
+    /// The `[[columns]]` commands ran for a base, see `ColumnValues`
+    ColumnValues {
+        path: String,
+        values: Vec<(String, Result<String, String>)>,
+    },
+    /// A client launched with `--wait` exited
+    LaunchResult(Result<Outcome, String>),
+    /// The history, the bases file, the config or `ibases.v8i` was written by
+    /// another process
+    FilesChanged,
+    Loaded(Loaded),
+}
+
+/// Lists read in the background when the picker opens, so that it shows up
+/// before a large history or a slow `ibases.v8i` has been read.
+#[derive(Debug)]
+pub enum Loaded {
+    /// The history, listed as soon as it is read
+    History(Vec<HistoryEntry>),
+    /// The bases of the 1C launcher and the launch statistics, read after the
+    /// history
+    Launcher {
+        bases: Vec<RegisteredBase>,
+        stats: BTreeMap<String, journal::Stats>,
+    },
+}
+
+/// The channel the picker reads its events from. Background work gets a
+/// `sender` to post its result.
+pub struct Events {
+    sender: Sender<AppEvent>,
+    receiver: Receiver<AppEvent>,
+}
+
+impl Events {
+    /// Starts reading the terminal and watching `files` for changes. The
+    /// reader stops once the events are dropped.
+    pub fn start(files: Vec<PathBuf>) -> Events {
+        let (sender, receiver) = mpsc::channel();
+        let input = sender.clone();
+        thread::spawn(move || read_terminal(input, files));
+        Events { sender, receiver }
+    }
+
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.sender.clone()
+    }
+
+    /// Blocks until the next event.
+    pub fn next(&self) -> Result<AppEvent, RecvError> {
+        self.receiver.recv()
+    }
+}
+
+fn read_terminal(sender: Sender<AppEvent>, files: Vec<PathBuf>) {
+    let mut modified: Vec<_> = files.iter().map(|file| modified_at(file)).collect();
+    let mut last_tick = Instant::now();
+    loop {
+        let timeout = TICK_INTERVAL.saturating_sub(last_tick.elapsed());
+        let event = match event::poll(timeout) {
+            Ok(true) => match event::read() {
+                Ok(event) => Some(AppEvent::Key(event)),
+                Err(_) => return,
+            },
+            Ok(false) => None,
+            Err(_) => return,
+        };
+        if let Some(event) = event
+            && sender.send(event).is_err()
+        {
+            return;
+        }
+        if last_tick.elapsed() < TICK_INTERVAL {
+            continue;
+        }
+        last_tick = Instant::now();
+        let now: Vec<_> = files.iter().map(|file| modified_at(file)).collect();
+        if now != modified {
+            modified = now;
+            if sender.send(AppEvent::FilesChanged).is_err() {
+                return;
+            }
+        }
+        if sender.send(AppEvent::Tick).is_err() {
+            return;
+        }
+    }
+}
+
+fn modified_at(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|meta| meta.modified()).ok()
+}
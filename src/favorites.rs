@@ -0,0 +1,122 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState},
+};
+
+use crate::{keys, popup, theme};
+
+/// Keys launching the favorites, in the order they are listed.
+const HOTKEYS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+pub enum FavoritesAction {
+    None,
+    Close,
+    Launch(String),
+}
+
+/// A pinned base as listed in the favorites.
+#[derive(Debug)]
+pub struct Favorite {
+    pub path: String,
+    /// Name or alias of the base, its connection string without one
+    pub label: String,
+    /// Server and infobase, the file path or the URL
+    pub target: String,
+}
+
+/// Popup with only the pinned bases, each launched with a single key, for
+/// those who only ever use a handful of bases.
+#[derive(Debug)]
+pub struct FavoritesPanel {
+    favorites: Vec<Favorite>,
+    state: ListState,
+}
+
+impl FavoritesPanel {
+    pub fn new(mut favorites: Vec<Favorite>) -> FavoritesPanel {
+        favorites.truncate(HOTKEYS.len());
+        FavoritesPanel {
+            favorites,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> FavoritesAction {
+        let Event::Key(key_event) = event else {
+            return FavoritesAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return FavoritesAction::None;
+        }
+        let modified = key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let index = match keys::binding(key_event.code) {
+            KeyCode::Esc => return FavoritesAction::Close,
+            KeyCode::Up => {
+                self.state.select_previous();
+                return FavoritesAction::None;
+            }
+            KeyCode::Down => {
+                self.state.select_next();
+                return FavoritesAction::None;
+            }
+            KeyCode::Enter => self.state.selected(),
+            KeyCode::Char(c) if !modified => HOTKEYS.find(c.to_ascii_lowercase()),
+            _ => None,
+        };
+        match index.and_then(|index| self.favorites.get(index)) {
+            Some(favorite) => FavoritesAction::Launch(favorite.path.clone()),
+            None => FavoritesAction::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), self.favorites.len() as u16 + 2);
+        let list = List::new(items(&self.favorites, true))
+            .block(Block::bordered().title("Favorites: press a key to launch, Esc to close"))
+            .highlight_style(theme::selected());
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
+
+/// The favorites as a pane of the wide layout, with the keys they get in
+/// the popup opened by `key`.
+pub fn render_pane(frame: &mut Frame, area: Rect, favorites: &[Favorite], key: &str) {
+    if area.width == 0 {
+        return;
+    }
+    let favorites = &favorites[..favorites.len().min(HOTKEYS.len())];
+    let list = List::new(items(favorites, false))
+        .block(Block::bordered().title(format!("Favorites ({key})")));
+    frame.render_widget(list, area);
+}
+
+/// A line per favorite with its key and label, and its target when `targets`.
+fn items(favorites: &[Favorite], targets: bool) -> Vec<ListItem<'_>> {
+    let width = favorites
+        .iter()
+        .map(|favorite| favorite.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    favorites
+        .iter()
+        .zip(HOTKEYS.chars())
+        .map(|(favorite, key)| {
+            let mut spans = vec![
+                Span::styled(format!("{key} "), theme::alias()),
+                Span::raw(favorite.label.as_str()),
+            ];
+            if targets {
+                let pad = width - favorite.label.chars().count() + 2;
+                spans.push(Span::raw(" ".repeat(pad)));
+                spans.push(Span::styled(favorite.target.as_str(), theme::hint()));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect()
+}
@@ -0,0 +1,111 @@
+use ratatui::{style::Style, text::Span};
+
+use crate::theme;
+
+const MATCH: i64 = 16;
+const CONSECUTIVE: i64 = 8;
+const BOUNDARY: i64 = 8;
+const GAP: i64 = 1;
+
+/// The query characters found in order within a text, like fzf matches them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    /// Char indices of the matched characters in the text
+    pub positions: Vec<usize>,
+}
+
+/// Matches `query` against `text` ignoring case, `None` when some query
+/// character is missing. An empty query matches everything with no score.
+pub fn find(query: &str, text: &str) -> Option<Match> {
+    let query: Vec<char> = query.trim().chars().map(lower).collect();
+    if query.is_empty() {
+        return Some(Match::default());
+    }
+    // the first occurrence of the whole subsequence, found before copying
+    // the text since most texts don't match...
+    let mut next = 0;
+    let mut end = 0;
+    for (index, c) in text.chars().map(lower).enumerate() {
+        if c == query[next] {
+            next += 1;
+            if next == query.len() {
+                end = index;
+                break;
+            }
+        }
+    }
+    if next < query.len() {
+        return None;
+    }
+    let text: Vec<char> = text.chars().collect();
+    let lowered: Vec<char> = text.iter().copied().map(lower).collect();
+    // ...narrowed down to the shortest window ending there
+    let mut positions = vec![0; query.len()];
+    let mut remaining = query.len();
+    for index in (0..=end).rev() {
+        if lowered[index] == query[remaining - 1] {
+            remaining -= 1;
+            positions[remaining] = index;
+            if remaining == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut score = 0;
+    for (number, &position) in positions.iter().enumerate() {
+        score += MATCH;
+        if is_boundary(&text, position) {
+            score += BOUNDARY;
+        }
+        if number > 0 {
+            let gap = position - positions[number - 1] - 1;
+            if gap == 0 {
+                score += CONSECUTIVE;
+            } else {
+                score -= GAP * gap as i64;
+            }
+        }
+    }
+    Some(Match { score, positions })
+}
+
+/// `text` split into spans in `style` with the matched characters highlighted.
+pub fn highlight(text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut highlighted = false;
+    for (index, (offset, _)) in text.char_indices().enumerate() {
+        let matched = positions.contains(&index);
+        if matched != highlighted {
+            spans.push(styled(&text[start..offset], highlighted, style));
+            start = offset;
+            highlighted = matched;
+        }
+    }
+    spans.push(styled(&text[start..], highlighted, style));
+    spans.retain(|span| !span.content.is_empty());
+    spans
+}
+
+fn styled(text: &str, highlighted: bool, style: Style) -> Span<'static> {
+    let style = if highlighted {
+        theme::matched(style)
+    } else {
+        style
+    };
+    Span::styled(text.to_string(), style)
+}
+
+pub fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Start of the text, of a word or of a part of a connection string.
+fn is_boundary(text: &[char], index: usize) -> bool {
+    let Some(&previous) = index.checked_sub(1).and_then(|index| text.get(index)) else {
+        return true;
+    };
+    !previous.is_alphanumeric() || (previous.is_lowercase() && text[index].is_uppercase())
+}
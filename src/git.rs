@@ -0,0 +1,25 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Current branch of the repository or worktree at `repo`, or the short commit
+/// hash when HEAD is detached.
+pub fn branch(repo: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir(repo)?.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(reference.trim_start_matches("refs/heads/").to_string()),
+        None => Some(format!("detached at {}", head.get(..8).unwrap_or(head))),
+    }
+}
+
+/// `.git` is a directory in a regular checkout and a `gitdir: <path>` file in
+/// a worktree.
+fn git_dir(repo: &Path) -> Option<PathBuf> {
+    let dot_git = repo.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let dir = Path::new(content.trim().strip_prefix("gitdir:")?.trim());
+    Some(repo.join(dir))
+}
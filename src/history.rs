@@ -0,0 +1,477 @@
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::connection::without_password;
+use crate::{Client, LaunchMode, fuzzy, paths, store};
+
+pub const FILE_NAME: &str = "rbaserun_history.json";
+/// The plain text history of earlier versions, one connection string per line
+pub const LEGACY_FILE_NAME: &str = "rbaserun_history.txt";
+
+/// Launches of an entry kept for guessing its usual mode per time of day.
+const RECENT_LAUNCHES: usize = 30;
+/// Launches of a time slot needed before its usual mode is guessed.
+const USUAL_MIN_LAUNCHES: usize = 3;
+
+/// How long a failed launch lists its base among the recently failed ones.
+const FAILED_RECENTLY_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Version of the history file format written by this build.
+const SCHEMA_VERSION: u32 = 1;
+
+static HISTORY_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Paths this process has read from the history file or saved, see `save`.
+static LISTED: Mutex<BTreeSet<Arc<str>>> = Mutex::new(BTreeSet::new());
+
+#[derive(Debug, Default, Deserialize)]
+struct HistoryFile {
+    version: u32,
+    entries: Vec<HistoryEntry>,
+}
+
+/// `HistoryFile` as written, borrowing the entries.
+#[derive(Serialize)]
+struct HistoryFileRef<'a> {
+    version: u32,
+    entries: &'a [HistoryEntry],
+}
+
+/// A launched base, most recent first in the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    /// Shared with the list of the picker instead of copied, see `App::set_history`
+    pub path: Arc<str>,
+    /// Name the base was picked by, e.g. its title in the 1C launcher
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Unix timestamp of the last launch, seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_launched: Option<i64>,
+    #[serde(default)]
+    pub launch_count: u32,
+    /// Mode of the last launch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<LaunchMode>,
+    /// Client picked for the last launch, none when the starter chose
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<Client>,
+    /// Latest launches, most recent last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent: Vec<RecentLaunch>,
+    /// Exit code of the client of the last launch waited for with `--wait`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// How the last launch failed: the launch error, or the client waited
+    /// for exiting with an error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+    /// Unix timestamp of that failure, seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecentLaunch {
+    /// Unix timestamp, seconds
+    pub at: i64,
+    pub mode: LaunchMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<Client>,
+}
+
+/// Part of the week launches are told apart by for `HistoryEntry::usual`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Monday to Friday, 9:00 to 18:00
+    WorkHours,
+    OffHours,
+}
+
+impl Slot {
+    pub fn at<Tz: TimeZone>(time: &DateTime<Tz>) -> Slot {
+        let weekend = matches!(time.weekday(), Weekday::Sat | Weekday::Sun);
+        if !weekend && (9..18).contains(&time.hour()) {
+            Slot::WorkHours
+        } else {
+            Slot::OffHours
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Slot::WorkHours => "weekdays 9-18",
+            Slot::OffHours => "evenings and weekends",
+        }
+    }
+}
+
+/// The Ctrl+D toggle and client recalled for a launch in `mode`.
+fn recalled(mode: LaunchMode, client: Option<Client>) -> (bool, Option<Client>) {
+    let client = match mode {
+        LaunchMode::Thick => client.or(Some(Client::Thick)),
+        LaunchMode::WebBrowser => client.or(Some(Client::Web)),
+        LaunchMode::Enterprise | LaunchMode::Designer => client,
+    };
+    (mode == LaunchMode::Designer, client)
+}
+
+impl HistoryEntry {
+    /// The Ctrl+D toggle and the client of the last launch, recalled when the
+    /// entry is highlighted. `None` for bases never launched.
+    pub fn remembered(&self) -> Option<(bool, Option<Client>)> {
+        Some(recalled(self.mode?, self.client))
+    }
+
+    /// The last launch failed within the last week, see `failure`.
+    pub fn failed_recently(&self, now: i64) -> bool {
+        self.failure.is_some()
+            && self
+                .failed_at
+                .is_some_and(|failed_at| now - failed_at < FAILED_RECENTLY_SECONDS)
+    }
+
+    /// Designer or not as the base is usually launched in the slot of `now`,
+    /// with the client of the latest such launch. `None` unless at least three
+    /// quarters of the recent launches in the slot agree and that differs
+    /// from the last launch, which `remembered` recalls anyway.
+    pub fn usual(&self, now: &DateTime<Local>) -> Option<(bool, Option<Client>, Slot)> {
+        let slot = Slot::at(now);
+        let launches: Vec<&RecentLaunch> = self
+            .recent
+            .iter()
+            .filter(|launch| {
+                Local
+                    .timestamp_opt(launch.at, 0)
+                    .single()
+                    .is_some_and(|time| Slot::at(&time) == slot)
+            })
+            .collect();
+        if launches.len() < USUAL_MIN_LAUNCHES {
+            return None;
+        }
+        let designer_launches = launches
+            .iter()
+            .filter(|launch| launch.mode == LaunchMode::Designer)
+            .count();
+        let designer = if designer_launches * 4 >= launches.len() * 3 {
+            true
+        } else if designer_launches * 4 <= launches.len() {
+            false
+        } else {
+            return None;
+        };
+        if self.remembered()?.0 == designer {
+            return None;
+        }
+        let latest = launches
+            .iter()
+            .rev()
+            .find(|launch| (launch.mode == LaunchMode::Designer) == designer)?;
+        let (_, client) = recalled(latest.mode, latest.client);
+        Some((designer, client, slot))
+    }
+}
+
+/// Uses `file` instead of the default history file, called once at startup.
+pub fn set_file(file: PathBuf) {
+    let _ = HISTORY_FILE.set(file);
+}
+
+/// The history in the per-user data directory.
+pub fn default_file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// The history file in use.
+pub fn file() -> &'static Path {
+    HISTORY_FILE.get_or_init(default_file)
+}
+
+/// Converts the plain text history next to the history file, once: the old
+/// file is kept with a `.bak` extension.
+pub fn import_legacy() -> Result<(), Box<dyn Error>> {
+    let legacy = file().with_file_name(LEGACY_FILE_NAME);
+    if file().exists() || !legacy.is_file() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(&legacy)
+        .map_err(|e| format!("Could not read {}: {e}", legacy.display()))?;
+    let entries = parse_lines(&content);
+    let _lock = lock()?;
+    write(&entries)?;
+    let backup = legacy.with_extension("txt.bak");
+    fs::rename(&legacy, &backup)?;
+    eprintln!(
+        "Imported {} entries from {} into {}, the old file is kept as {}",
+        entries.len(),
+        legacy.display(),
+        file().display(),
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Connection strings of the history, most recent first.
+pub fn load() -> Vec<String> {
+    load_entries()
+        .into_iter()
+        .map(|entry| entry.path.to_string())
+        .collect()
+}
+
+/// The history with its metadata, empty when the file is missing or unreadable.
+pub fn load_entries() -> Vec<HistoryEntry> {
+    let entries = read().unwrap_or_default();
+    let mut listed = LISTED.lock().unwrap_or_else(|e| e.into_inner());
+    listed.extend(entries.iter().map(|entry| entry.path.clone()));
+    entries
+}
+
+fn read() -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+    let content = match fs::read_to_string(file()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    parse(&content, &file().display().to_string())
+}
+
+/// Parses a history file read from `location`, JSON or plain text.
+fn parse(content: &str, location: &str) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+    // a history_file still in the plain text format is read as such and
+    // rewritten as JSON on the next save
+    if !content.trim_start().starts_with('{') {
+        return Ok(parse_lines(content));
+    }
+    let history: HistoryFile =
+        serde_json::from_str(content).map_err(|e| format!("Invalid {location}: {e}"))?;
+    if history.version > SCHEMA_VERSION {
+        return Err(format!(
+            "{location} has schema version {}, this rbaserun only reads up to \
+             {SCHEMA_VERSION}, please update it",
+            history.version
+        )
+        .into());
+    }
+    Ok(history.entries)
+}
+
+/// The entries a save starts from, an error when the file can't be read so
+/// that it is not overwritten.
+fn read_for_update() -> Result<Vec<HistoryEntry>, io::Error> {
+    read().map_err(|e| io::Error::other(format!("{e}, not overwriting it")))
+}
+
+fn parse_lines(content: &str) -> Vec<HistoryEntry> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| HistoryEntry {
+            path: line.into(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// `file()` with `suffix` appended to its name, e.g. `rbaserun_history.json.lock`.
+fn sibling(suffix: &str) -> PathBuf {
+    let mut name = OsString::from(file().as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Waits for the other rbaserun instances to finish their changes of the
+/// history, which is theirs again once the returned file is dropped.
+fn lock() -> Result<File, io::Error> {
+    paths::prepare(file())?;
+    let lock = sibling(".lock");
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock)
+        .map_err(|e| io::Error::other(format!("Could not open {}: {e}", lock.display())))?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Replaces the history file through a temporary one, so that readers never
+/// see it half written. Called with the lock held.
+fn write(entries: &[HistoryEntry]) -> Result<(), Box<dyn Error>> {
+    let history = HistoryFileRef {
+        version: SCHEMA_VERSION,
+        entries,
+    };
+    let temp = sibling(".tmp");
+    store::backup(file());
+    fs::write(&temp, serde_json::to_string_pretty(&history)? + "\n")?;
+    fs::rename(&temp, file())?;
+    Ok(())
+}
+
+/// Applies `change` to the entries of the history file as it is now and
+/// writes them back unless it returns false. Instances saving at the same
+/// time take turns, each starting from what the previous one wrote.
+fn update(change: impl FnOnce(&mut Vec<HistoryEntry>) -> bool) -> Result<(), io::Error> {
+    let _lock = lock()?;
+    let mut entries = read_for_update()?;
+    if !change(&mut entries) {
+        return Ok(());
+    }
+    write(&entries).map_err(|e| io::Error::other(e.to_string()))
+}
+
+/// Replaces the history with `content`, a backup read from `location`. It is
+/// parsed first so that a damaged backup is refused, and written like any
+/// other change so that running instances never see it half written.
+pub fn restore_from(content: &str, location: &str) -> Result<(), Box<dyn Error>> {
+    let entries = parse(content, location)?;
+    let _lock = lock()?;
+    write(&entries)
+}
+
+/// Saves the history in the order of `paths`, keeping the metadata of the
+/// entries that stay. Entries another instance added since this one read the
+/// history stay as well, at their place in the file. An unreadable history
+/// file is left alone.
+pub fn save<P: AsRef<str>>(paths: &[P]) -> Result<(), io::Error> {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| without_password(path.as_ref()))
+        .collect();
+    let mut listed = LISTED.lock().unwrap_or_else(|e| e.into_inner());
+    update(|current| {
+        let mut unlisted = Vec::new();
+        let mut known = HashMap::new();
+        for (index, entry) in current.drain(..).enumerate() {
+            if listed.contains(&entry.path)
+                || paths.iter().any(|path| path.as_ref() == &*entry.path)
+            {
+                known.insert(entry.path.clone(), entry);
+            } else {
+                unlisted.push((index, entry));
+            }
+        }
+        current.extend(paths.iter().map(|path| {
+            known.remove(path.as_ref()).unwrap_or_else(|| HistoryEntry {
+                path: path.as_ref().into(),
+                ..Default::default()
+            })
+        }));
+        for (index, entry) in unlisted {
+            current.insert(index.min(current.len()), entry);
+        }
+        true
+    })?;
+    listed.extend(paths.iter().map(|path| Arc::from(path.as_ref())));
+    Ok(())
+}
+
+/// Moves `path` to the top of `history`, adding it when it is new.
+pub fn touch<P: AsRef<str>>(history: &mut Vec<P>, path: P) {
+    if let Some(index) = history.iter().position(|x| x.as_ref() == path.as_ref()) {
+        history.remove(index);
+    }
+    history.insert(0, path);
+}
+
+/// Adds `path` to the top of the history without recording a launch, e.g.
+/// for a freshly cloned base.
+pub fn remember(path: String) -> Result<(), io::Error> {
+    let mut history = load();
+    touch(&mut history, path);
+    save(&history)
+}
+
+/// Moves `path` to the top of the history and records the launch. Like
+/// every path of the history, it is kept without its password.
+pub fn record_launch(
+    path: &str,
+    mode: LaunchMode,
+    client: Option<Client>,
+    name: Option<String>,
+) -> Result<(), io::Error> {
+    let path = without_password(path);
+    let path = path.as_str();
+    update(|entries| {
+        let mut entry = match entries.iter().position(|entry| &*entry.path == path) {
+            Some(index) => entries.remove(index),
+            None => HistoryEntry {
+                path: path.into(),
+                ..Default::default()
+            },
+        };
+        let now = Local::now().timestamp();
+        entry.name = entry.name.take().or(name);
+        entry.last_launched = Some(now);
+        entry.launch_count += 1;
+        entry.mode = Some(mode);
+        entry.client = client;
+        entry.exit_code = None;
+        entry.failure = None;
+        entry.failed_at = None;
+        entry.recent.push(RecentLaunch {
+            at: now,
+            mode,
+            client,
+        });
+        if entry.recent.len() > RECENT_LAUNCHES {
+            entry.recent.drain(..entry.recent.len() - RECENT_LAUNCHES);
+        }
+        entries.insert(0, entry);
+        true
+    })
+}
+
+/// Records how the client of the last launch of `path` exited.
+pub fn record_outcome(
+    path: &str,
+    code: Option<i32>,
+    failure: Option<String>,
+) -> Result<(), io::Error> {
+    let path = without_password(path);
+    update(|entries| {
+        let Some(entry) = entries.iter_mut().find(|entry| &*entry.path == path) else {
+            return false;
+        };
+        entry.exit_code = code;
+        entry.failed_at = failure.as_ref().map(|_| Local::now().timestamp());
+        entry.failure = failure;
+        true
+    })
+}
+
+/// Records that launching `path`, a base of the history, failed with `error`.
+pub fn record_failure(path: &str, error: &str) -> Result<(), io::Error> {
+    let path = without_password(path);
+    update(|entries| {
+        let Some(entry) = entries.iter_mut().find(|entry| &*entry.path == path) else {
+            return false;
+        };
+        entry.failure = Some(error.to_string());
+        entry.failed_at = Some(Local::now().timestamp());
+        true
+    })
+}
+
+/// Indices of the entries fuzzy matching `query`, best first; all of them in
+/// order when it is empty.
+pub fn filter(history: &[String], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = history
+        .iter()
+        .enumerate()
+        .filter_map(|(index, path)| Some((fuzzy::find(query, path)?.score, index)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| Reverse(score));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use std::time::Duration;
+
+use crate::platform;
+
+/// Seconds a hook may run before it is killed, unless `timeout_secs` says otherwise.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Shell commands run around a launch, `[hooks]` in rbaserun.toml for every
+/// base and `hooks` of a base for that one, e.g. starting a VPN, mounting a
+/// share or writing to an audit log. The global ones run first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run before the pre-check and the launch, which fails when one of them does
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Run once the client was started, failures are only reported
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+    /// Seconds each command may run before it is killed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// The hooks of a launch: the global ones, then the ones of the base.
+pub struct LaunchHooks<'a> {
+    global: &'a Hooks,
+    base: Option<&'a Hooks>,
+    /// `RBASERUN_*` variables describing the launch
+    env: Vec<(String, String)>,
+}
+
+impl<'a> LaunchHooks<'a> {
+    /// `env` are the launch parameters without the `RBASERUN_` prefix, e.g.
+    /// `("PATH", …)`, `("MODE", …)`.
+    pub fn new(global: &'a Hooks, base: Option<&'a Hooks>, env: &[(&str, String)]) -> Self {
+        LaunchHooks {
+            global,
+            base,
+            env: env
+                .iter()
+                .map(|(name, value)| (format!("RBASERUN_{name}"), value.clone()))
+                .collect(),
+        }
+    }
+
+    /// Runs the `before` hooks in order, stopping at the first failure.
+    pub fn before(&self) -> Result<usize, String> {
+        let commands = self.commands(|hooks| &hooks.before);
+        for command in &commands {
+            self.run(command, &[])?;
+        }
+        Ok(commands.len())
+    }
+
+    /// Runs the `after` hooks with `RBASERUN_PID` of the started client,
+    /// returning the failures.
+    pub fn after(&self, pid: u32) -> Vec<String> {
+        let pid = [("RBASERUN_PID".to_string(), pid.to_string())];
+        self.commands(|hooks| &hooks.after)
+            .iter()
+            .filter_map(|command| self.run(command, &pid).err())
+            .collect()
+    }
+
+    fn commands(&self, list: impl Fn(&Hooks) -> &Vec<String>) -> Vec<String> {
+        list(self.global)
+            .iter()
+            .chain(self.base.map_or(&[][..], |base| list(base)))
+            .filter(|command| !command.trim().is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// The timeout of the base, then the global one.
+    fn timeout(&self) -> Duration {
+        let secs = self
+            .base
+            .and_then(|base| base.timeout_secs)
+            .or(self.global.timeout_secs)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        Duration::from_secs(secs)
+    }
+
+    fn run(&self, command: &str, extra: &[(String, String)]) -> Result<(), String> {
+        let env: Vec<(String, String)> = self.env.iter().chain(extra).cloned().collect();
+        platform::run_captured(command, &env, self.timeout())
+            .map(|_| ())
+            .map_err(|e| format!("Hook {e}"))
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::path::{Path, PathBuf};
+
+use crate::bases::Bases;
+use crate::{commands, history, parse_base_path, platform};
+
+/// A base registered in the standard 1C launcher.
+#[derive(Debug, Clone)]
+pub struct RegisteredBase {
+    pub name: String,
+    /// Folder in the launcher tree, `/` for the root
+    pub folder: String,
+    /// The `Connect=` string, e.g. `Srvr="srv";Ref="erp";`
+    pub connect: String,
+    /// The `ID=` of the base, a UUID
+    pub id: Option<String>,
+    /// Pushed by the machine-wide bases file rather than registered in 1C
+    pub managed: bool,
+}
+
+impl RegisteredBase {
+    /// Name with its launcher folders, e.g. `Accounting/Test/erp`.
+    pub fn title(&self) -> String {
+        let folder = self.folder.trim_matches('/');
+        if folder.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{folder}/{}", self.name)
+        }
+    }
+}
+
+/// Location of `ibases.v8i` used by the 1C launcher of the current user.
+pub fn default_file() -> Option<PathBuf> {
+    platform::ibases_file()
+}
+
+/// Bases registered in the launcher, empty when the file is missing or unreadable.
+pub fn load(file: Option<PathBuf>) -> Vec<RegisteredBase> {
+    file.or_else(default_file)
+        .and_then(|file| fs::read(file).ok())
+        .map(|bytes| parse(&String::from_utf8_lossy(&bytes)))
+        .unwrap_or_default()
+}
+
+/// Parses the INI-like `ibases.v8i` format. Sections without a `Connect=`
+/// line are launcher folders and are skipped.
+pub fn parse(content: &str) -> Vec<RegisteredBase> {
+    let mut bases = Vec::new();
+    let mut current: Option<RegisteredBase> = None;
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            bases.extend(current.take().filter(|base| !base.connect.is_empty()));
+            current = Some(RegisteredBase {
+                name: name.to_string(),
+                folder: "/".to_string(),
+                connect: String::new(),
+                id: None,
+                managed: false,
+            });
+            continue;
+        }
+        let (Some(base), Some((key, value))) = (current.as_mut(), line.split_once('=')) else {
+            continue;
+        };
+        match key.trim() {
+            "Connect" => base.connect = value.trim().to_string(),
+            "Folder" => base.folder = value.trim().to_string(),
+            "ID" => base.id = Some(value.trim().to_string()).filter(|id| !id.is_empty()),
+            _ => {}
+        }
+    }
+    bases.extend(current.filter(|base| !base.connect.is_empty()));
+    bases
+}
+
+/// Writes the known bases to `file` in the `ibases.v8i` format of the 1C
+/// launcher, in their folders and with their names. Bases get an `ID=` kept
+/// in the bases file, so exporting again or importing the file back keeps it.
+pub fn export(file: &Path, bases: &mut Bases) -> Result<(), Box<dyn Error>> {
+    let mut sections = Vec::new();
+    let mut folders = BTreeSet::new();
+    let mut assigned = false;
+    for path in commands::known_paths(&history::load(), bases) {
+        let Ok(parsed) = parse_base_path(&path) else {
+            eprintln!("Skipping unsupported connection string: {path}");
+            continue;
+        };
+        let entry = bases.find(&path);
+        let name = entry
+            .and_then(|entry| entry.name.clone().or(entry.alias.clone()))
+            .unwrap_or_else(|| parsed.name());
+        let folder = entry
+            .and_then(|entry| entry.folder.as_deref())
+            .map(|folder| folder.trim_matches('/'))
+            .filter(|folder| !folder.is_empty())
+            .map(str::to_string);
+        let id = match entry.and_then(|entry| entry.id.clone()) {
+            Some(id) => id,
+            None if bases.is_managed(&path) => new_id(),
+            None => {
+                let id = new_id();
+                bases.entry_mut(&path).id = Some(id.clone());
+                assigned = true;
+                id
+            }
+        };
+        if let Some(folder) = &folder {
+            let mut parent = String::new();
+            for part in folder.split('/') {
+                folders.insert((parent.clone(), part.to_string()));
+                parent = format!("{parent}/{part}");
+            }
+        }
+        sections.push((name, parsed.connection_string(), id, folder));
+    }
+    if sections.is_empty() {
+        return Err("No bases to export".into());
+    }
+
+    let mut content = String::from("\u{feff}");
+    let mut order = 0;
+    for (parent, name) in &folders {
+        order += 1;
+        let id = folder_id(&format!("{parent}/{name}"));
+        content.push_str(&format!(
+            "[{name}]\r\nID={id}\r\nOrderInList={order}\r\nFolder={}\r\nOrderInTree={order}\r\nExternal=0\r\n",
+            if parent.is_empty() { "/" } else { parent }
+        ));
+    }
+    for (name, connect, id, folder) in &sections {
+        order += 1;
+        let folder = folder
+            .as_ref()
+            .map_or("/".to_string(), |folder| format!("/{folder}"));
+        content.push_str(&format!(
+            "[{name}]\r\nConnect={connect}\r\nID={id}\r\nOrderInList={order}\r\nFolder={folder}\r\nOrderInTree={order}\r\nExternal=0\r\nClientConnectionSpeed=Normal\r\nApp=Auto\r\nWA=1\r\nVersion=8.3\r\n"
+        ));
+    }
+    fs::write(file, content).map_err(|e| format!("Could not write {}: {e}", file.display()))?;
+    if assigned {
+        bases.save()?;
+    }
+    println!(
+        "Exported {} bases in {} folders to {}",
+        sections.len(),
+        folders.len(),
+        file.display()
+    );
+    Ok(())
+}
+
+/// A random UUID for a new base of the launcher list.
+fn new_id() -> String {
+    let state = RandomState::new();
+    let mut bytes = [0u8; 16];
+    for (salt, half) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(salt);
+        half.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    format_id(bytes)
+}
+
+/// The same UUID for a folder on every export, derived from its path.
+fn folder_id(path: &str) -> String {
+    let mut bytes = [0u8; 16];
+    for (seed, half) in bytes.chunks_mut(8).enumerate() {
+        // FNV-1a, seeded per half
+        let mut hash = 0xcbf2_9ce4_8422_2325u64 ^ seed as u64;
+        for byte in path.bytes() {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+        half.copy_from_slice(&hash.to_le_bytes());
+    }
+    format_id(bytes)
+}
+
+/// Formats 16 bytes as a version 4 UUID.
+fn format_id(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
@@ -0,0 +1,244 @@
+use clap::ValueEnum;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::bases::Bases;
+use crate::{history, ibases, normalize_input, parse_base_path};
+
+/// Fields holding the connection string, the display name, the folder and the
+/// user in the lists of other launchers, compared ignoring case.
+const CONNECTION_FIELDS: [&str; 6] = [
+    "connect",
+    "connectionstring",
+    "connection",
+    "conn",
+    "ibpath",
+    "path",
+];
+const NAME_FIELDS: [&str; 4] = ["name", "title", "caption", "description"];
+const FOLDER_FIELDS: [&str; 2] = ["folder", "group"];
+const USER_FIELDS: [&str; 4] = ["user", "usr", "username", "login"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// `ibases.v8i` of the 1C launcher and lists exported in the same format
+    V8i,
+    /// XML lists such as the ones of StartManager or AllBases
+    Xml,
+}
+
+/// A base read from another launcher's list.
+#[derive(Debug)]
+struct Imported {
+    connect: String,
+    name: Option<String>,
+    folder: String,
+    user: Option<String>,
+    /// `ID=` of a base of an `ibases.v8i`
+    id: Option<String>,
+}
+
+impl Imported {
+    /// Launcher folders of the base, grouping it in the history pane.
+    fn folder(&self) -> Option<String> {
+        let folder = self.folder.trim_matches(['/', '\\']).replace('\\', "/");
+        (!folder.is_empty()).then_some(folder)
+    }
+}
+
+/// Adds the bases listed in `file` to the history, keeping their names,
+/// folders and users in the bases file.
+pub fn run(
+    file: &Path,
+    format: Option<ImportFormat>,
+    bases: &mut Bases,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = fs::read(file).map_err(|e| format!("Could not read {}: {e}", file.display()))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let content = content.trim_start_matches('\u{feff}');
+    let format = match format {
+        Some(format) => format,
+        None => detect(file, content).ok_or("Unknown list format, pass --format")?,
+    };
+    let imported = match format {
+        ImportFormat::V8i => ibases::parse(content)
+            .into_iter()
+            .map(|base| Imported {
+                connect: base.connect,
+                name: Some(base.name),
+                folder: base.folder,
+                user: None,
+                id: base.id,
+            })
+            .collect(),
+        ImportFormat::Xml => parse_xml(content)?,
+    };
+    if imported.is_empty() {
+        return Err(format!("No bases found in {}", file.display()).into());
+    }
+
+    let target = |path: &str| parse_base_path(path).ok().map(|path| path.target());
+    let mut history = history::load();
+    let mut known: Vec<_> = history.iter().map(|path| target(path)).collect();
+    let (mut added, mut skipped) = (0, 0);
+    for base in &imported {
+        let path = normalize_input(&base.connect);
+        let Some(base_target) = target(&path) else {
+            eprintln!("Skipping unsupported connection string: {path}");
+            skipped += 1;
+            continue;
+        };
+        if !known.contains(&Some(base_target.clone())) {
+            history.push(path.clone());
+            known.push(Some(base_target));
+            added += 1;
+        }
+        let (name, folder, user) = (base.name.clone(), base.folder(), base.user.clone());
+        if name.is_some() || folder.is_some() || user.is_some() || base.id.is_some() {
+            let entry = bases.entry_mut(&path);
+            entry.name = entry.name.take().or(name);
+            entry.folder = entry.folder.take().or(folder);
+            entry.user = entry.user.take().or(user);
+            entry.id = entry.id.take().or(base.id.clone());
+        }
+    }
+    history::save(&history)?;
+    bases.save()?;
+
+    println!(
+        "Imported {} bases from {}: {added} new, {skipped} skipped",
+        imported.len() - skipped,
+        file.display()
+    );
+    Ok(())
+}
+
+fn detect(file: &Path, content: &str) -> Option<ImportFormat> {
+    let extension = file.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "v8i" => Some(ImportFormat::V8i),
+        "xml" => Some(ImportFormat::Xml),
+        _ if content.trim_start().starts_with('<') => Some(ImportFormat::Xml),
+        _ if content.contains("Connect=") => Some(ImportFormat::V8i),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    /// Attributes and the text of leaf children, keyed by lowercase name
+    fields: BTreeMap<String, String>,
+    name: String,
+    text: String,
+}
+
+impl Element {
+    fn field(&self, names: &[&str]) -> Option<String> {
+        names
+            .iter()
+            .filter_map(|name| self.fields.get(*name))
+            .map(|value| value.trim().to_string())
+            .find(|value| !value.is_empty())
+    }
+
+    fn connection(&self) -> Option<String> {
+        CONNECTION_FIELDS
+            .iter()
+            .filter_map(|name| self.fields.get(*name))
+            .find(|value| parse_base_path(value).is_ok())
+            .cloned()
+    }
+}
+
+/// Reads bases from XML lists, whether their fields are attributes or child
+/// elements. Named elements without a connection string around a base are
+/// taken as its folders.
+fn parse_xml(content: &str) -> Result<Vec<Imported>, Box<dyn Error>> {
+    let mut reader = Reader::from_str(content);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut imported = Vec::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Invalid XML at byte {}: {e}", reader.buffer_position()))?;
+        match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let mut element = Element {
+                    name: String::from_utf8_lossy(start.local_name().as_ref()).to_lowercase(),
+                    ..Default::default()
+                };
+                for attribute in start.attributes().flatten() {
+                    let key =
+                        String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_lowercase();
+                    element
+                        .fields
+                        .insert(key, attribute.unescape_value()?.into_owned());
+                }
+                stack.push(element);
+                if matches!(event, Event::Empty(_)) {
+                    close(&mut stack, &mut imported);
+                }
+            }
+            Event::End(_) => close(&mut stack, &mut imported),
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text.decode()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&data.decode()?);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(element) = stack.last_mut() {
+                    match reference.resolve_char_ref()? {
+                        Some(char) => element.text.push(char),
+                        None => element.text.push_str(
+                            resolve_predefined_entity(&reference.decode()?).unwrap_or_default(),
+                        ),
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(imported)
+}
+
+/// Pops the innermost element, turning it into a base when it has a
+/// connection string or into a field of its parent when it is a leaf.
+fn close(stack: &mut Vec<Element>, imported: &mut Vec<Imported>) {
+    let Some(element) = stack.pop() else {
+        return;
+    };
+    if let Some(connect) = element.connection() {
+        let mut folders: Vec<String> = stack
+            .iter()
+            .filter(|parent| parent.connection().is_none())
+            .filter_map(|parent| parent.field(&NAME_FIELDS))
+            .collect();
+        folders.extend(element.field(&FOLDER_FIELDS));
+        imported.push(Imported {
+            connect,
+            name: element.field(&NAME_FIELDS),
+            folder: folders.join("/"),
+            user: element.field(&USER_FIELDS),
+            id: None,
+        });
+    } else if element.fields.is_empty()
+        && let Some(parent) = stack.last_mut()
+    {
+        parent
+            .fields
+            .entry(element.name)
+            .or_insert_with(|| element.text.trim().to_string());
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::json;
+
+use std::env;
+use std::error::Error;
+use std::path::Path;
+
+use crate::bases::Bases;
+use crate::{commands, parse_base_path, platform};
+
+const POWERSHELL_MODULE: &str = r#"# Generated by `rbaserun integrate powershell`, regenerate after moving rbaserun.
+$RBaseExe = '{exe}'
+
+function Get-RBase {
+    [CmdletBinding()]
+    param(
+        [Parameter(Position = 0)]
+        [string]$Filter = '*'
+    )
+    & $RBaseExe list --json | ConvertFrom-Json | Where-Object { $_.path -like $Filter }
+}
+
+function Start-RBase {
+    [CmdletBinding()]
+    param(
+        [Parameter(Mandatory, Position = 0, ValueFromPipelineByPropertyName)]
+        [string]$Path,
+        [switch]$Designer
+    )
+    process {
+        $arguments = @($Path)
+        if ($Designer) { $arguments += '--designer' }
+        & $RBaseExe @arguments
+    }
+}
+
+Export-ModuleMember -Function Get-RBase, Start-RBase
+"#;
+
+/// Prints a PowerShell module wrapping the JSON output of `rbaserun list`.
+pub fn powershell() -> Result<(), Box<dyn Error>> {
+    let exe = env::current_exe()?;
+    let exe = exe.display().to_string().replace('\'', "''");
+    print!("{}", POWERSHELL_MODULE.replace("{exe}", &exe));
+    Ok(())
+}
+
+/// Prints a Windows Terminal settings fragment with a profile and a new-tab
+/// action for every pinned base.
+pub fn windows_terminal(bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let exe = env::current_exe()?.display().to_string();
+    let pinned: Vec<_> = bases
+        .entries()
+        .iter()
+        .filter(|entry| entry.pinned)
+        .collect();
+    if pinned.is_empty() {
+        return Err("No pinned bases, set `pinned = true` for some bases in the bases file".into());
+    }
+
+    let mut profiles = Vec::new();
+    let mut actions = Vec::new();
+    for entry in pinned {
+        let name = format!("1C: {}", display_name(&entry.path, bases));
+        let commandline = launch_command_line(&exe, &entry.path, &[]);
+        let mut profile = json!({
+            "name": name,
+            "commandline": commandline,
+            "closeOnExit": "graceful",
+            "hidden": false,
+        });
+        if let Some(icon) = &entry.icon {
+            profile["icon"] = json!(icon);
+        }
+        profiles.push(profile);
+        actions.push(json!({
+            "name": format!("Launch {name}"),
+            "command": { "action": "newTab", "profile": name },
+        }));
+    }
+
+    let fragment = json!({ "profiles": profiles, "actions": actions });
+    println!("{}", serde_json::to_string_pretty(&fragment)?);
+    Ok(())
+}
+
+/// Writes a shortcut launching each pinned base, or each listed one with
+/// `all`, into `dir`, with the `icon` of the base when it has one.
+pub fn shortcuts(
+    dir: &Path,
+    all: bool,
+    history: &[String],
+    bases: &Bases,
+) -> Result<(), Box<dyn Error>> {
+    let exe = env::current_exe()?;
+    let paths: Vec<String> = commands::known_paths(history, bases)
+        .into_iter()
+        .filter(|path| all || bases.find(path).is_some_and(|entry| entry.pinned))
+        .collect();
+    if paths.is_empty() {
+        return Err("No pinned bases, pin some with `p` in the picker or pass --all".into());
+    }
+    for path in &paths {
+        let icon = bases.find(path).and_then(|entry| entry.icon.as_deref());
+        let args = [
+            "launch".to_string(),
+            path.trim().to_string(),
+            "--quiet".to_string(),
+        ];
+        let file = platform::shortcut(dir, &display_name(path, bases), &exe, &args, icon)?;
+        println!("{}", file.display());
+    }
+    Ok(())
+}
+
+/// Icon of `path` for launcher feeds: the `icon` of the base, otherwise
+/// rbaserun itself.
+pub fn icon(path: &str, bases: &Bases, exe: &str) -> String {
+    bases
+        .find(path)
+        .and_then(|entry| entry.icon.as_ref())
+        .map_or_else(|| exe.to_string(), |icon| icon.display().to_string())
+}
+
+/// Short name of a base for menus: its badge followed by the ref, file or
+/// publication name.
+pub fn display_name(path: &str, bases: &Bases) -> String {
+    let name = match parse_base_path(path) {
+        Ok(path) => path.name(),
+        Err(_) => path.to_string(),
+    };
+    match bases.find(path).and_then(|entry| entry.badge.as_deref()) {
+        Some(badge) => format!("{badge} {name}"),
+        None => name,
+    }
+}
+
+/// Windows command line running `rbaserun launch <path>`, quoted the way
+/// `CommandLineToArgvW` expects.
+pub fn launch_command_line(exe: &str, path: &str, extra: &[&str]) -> String {
+    let mut commandline = format!("\"{exe}\" launch \"{}\"", path.replace('"', "\\\""));
+    for arg in extra {
+        commandline.push(' ');
+        commandline.push_str(arg);
+    }
+    commandline
+}
@@ -0,0 +1,142 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::{LaunchMode, locale, lockdown, paths};
+
+pub const FILE_NAME: &str = "rbaserun_launches.jsonl";
+
+pub fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// One launch attempt, appended to the launch log and never rewritten, unlike
+/// the history which only keeps the latest launches of each base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Launch {
+    /// Unix timestamp, seconds
+    pub at: i64,
+    /// OS user who launched the base
+    pub user: String,
+    pub path: String,
+    pub mode: LaunchMode,
+    /// Why the launch failed, `None` when the client was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+/// Launches of one base summed up from the log.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    /// Launches that started the client
+    pub launches: usize,
+    pub failures: usize,
+    /// Unix timestamp of the latest attempt, seconds
+    pub last: i64,
+}
+
+impl Stats {
+    /// E.g. `launched 42 times, last used 2 days ago`.
+    pub fn summary(&self, now: i64) -> String {
+        let count = match self.launches {
+            0 => "never launched".to_string(),
+            1 => "launched once".to_string(),
+            count => format!("launched {count} times"),
+        };
+        let mut summary = format!("{count}, last used {}", locale::ago(now, self.last));
+        if self.failures > 0 {
+            summary.push_str(&format!(", {} failed", self.failures));
+        }
+        summary
+    }
+}
+
+/// Appends a launch of `path` to the log; the log must never break a launch.
+pub fn record(path: &str, mode: LaunchMode, failure: Option<String>) {
+    let launch = Launch {
+        at: Local::now().timestamp(),
+        user: lockdown::user_name(),
+        path: path.trim().to_string(),
+        mode,
+        failure,
+    };
+    let _ = append(&launch);
+}
+
+fn append(launch: &Launch) -> Result<(), Box<dyn Error>> {
+    paths::prepare(&file())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(file())?;
+    writeln!(file, "{}", serde_json::to_string(launch)?)?;
+    Ok(())
+}
+
+/// Every launch in the log, oldest first; empty when there is no log yet.
+pub fn load() -> Vec<Launch> {
+    let Ok(file) = File::open(file()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        // lines torn by concurrent writers are skipped
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Seconds since `path` was last started when that was at most `window`
+/// seconds ago, for the guard against launching a base twice by accident.
+pub fn launched_within(path: &str, window: u64) -> Option<i64> {
+    if window == 0 {
+        return None;
+    }
+    let now = Local::now().timestamp();
+    let path = path.trim();
+    load()
+        .iter()
+        .rev()
+        .take_while(|launch| now - launch.at <= window as i64)
+        .find(|launch| launch.path == path && launch.failure.is_none())
+        .map(|launch| now - launch.at)
+}
+
+/// Launches of every base in `launches`, by connection string.
+pub fn stats(launches: &[Launch]) -> BTreeMap<String, Stats> {
+    let mut stats: BTreeMap<String, Stats> = BTreeMap::new();
+    for launch in launches {
+        let base = stats.entry(launch.path.clone()).or_default();
+        match launch.failure {
+            Some(_) => base.failures += 1,
+            None => base.launches += 1,
+        }
+        base.last = base.last.max(launch.at);
+    }
+    stats
+}
+
+/// The bases of `stats`, most launched first, ties broken by the latest launch.
+pub fn most_used(stats: &BTreeMap<String, Stats>) -> Vec<(&String, &Stats)> {
+    let mut bases: Vec<_> = stats.iter().collect();
+    bases.sort_by_key(|(_, stats)| Reverse((stats.launches, stats.last)));
+    bases
+}
+
+/// Prints how often and how recently each base was launched, most used first.
+pub fn report() -> Result<(), Box<dyn Error>> {
+    let launches = load();
+    if launches.is_empty() {
+        println!("No launches recorded in {}", file().display());
+        return Ok(());
+    }
+    let now = Local::now().timestamp();
+    let stats = stats(&launches);
+    for (path, stats) in most_used(&stats) {
+        println!("{path}\t{}", stats.summary(now));
+    }
+    Ok(())
+}
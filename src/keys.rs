@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Keys of the Russian ЙЦУКЕН layout and the QWERTY keys at the same place.
+const RUSSIAN_LAYOUT: [(char, char); 33] = [
+    ('й', 'q'),
+    ('ц', 'w'),
+    ('у', 'e'),
+    ('к', 'r'),
+    ('е', 't'),
+    ('н', 'y'),
+    ('г', 'u'),
+    ('ш', 'i'),
+    ('щ', 'o'),
+    ('з', 'p'),
+    ('х', '['),
+    ('ъ', ']'),
+    ('ф', 'a'),
+    ('ы', 's'),
+    ('в', 'd'),
+    ('а', 'f'),
+    ('п', 'g'),
+    ('р', 'h'),
+    ('о', 'j'),
+    ('л', 'k'),
+    ('д', 'l'),
+    ('ж', ';'),
+    ('э', '\''),
+    ('я', 'z'),
+    ('ч', 'x'),
+    ('с', 'c'),
+    ('м', 'v'),
+    ('и', 'b'),
+    ('т', 'n'),
+    ('ь', 'm'),
+    ('б', ','),
+    ('ю', '.'),
+    ('ё', '`'),
+];
+
+/// Maps a key typed with the Russian layout active to the key bindings are
+/// defined with, so shortcuts keep working without switching the layout.
+pub fn binding(code: KeyCode) -> KeyCode {
+    let KeyCode::Char(c) = code else {
+        return code;
+    };
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    match RUSSIAN_LAYOUT.iter().find(|(russian, _)| *russian == lower) {
+        Some((_, latin)) => KeyCode::Char(*latin),
+        None => KeyCode::Char(c),
+    }
+}
+
+/// What a key does in the picker, named in the `[keymap]` section of the
+/// config like `toggle_designer = "alt+d"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Launch,
+    /// Clear the input, or quit when it is empty
+    Quit,
+    Up,
+    Down,
+    NextMatch,
+    PreviousMatch,
+    ToggleDesigner,
+    Credentials,
+    NextPlatform,
+    NextClient,
+    EditEntry,
+    Share,
+    Rdp,
+    Sources,
+    BrowseCluster,
+    Licenses,
+    Check,
+    ShowFailed,
+    Errors,
+    RecordMacro,
+    Sets,
+    /// The launch log with per-base statistics
+    Activity,
+    /// Form assembling a connection string from its parts
+    BuildConnection,
+    /// Bring a client already running the base to the front
+    FocusClient,
+    /// Run a `rbaserun-<name>` plugin with the selected base
+    Plugins,
+    /// Run the `[[columns]]` commands for the bases of the history again
+    RefreshColumns,
+    /// Launch the base with `/ClearCache`
+    ClearCache,
+    /// Remove the user settings the clients keep of the base on this machine
+    ResetSettings,
+    /// Scheduled jobs and session locks of a server base, over rac
+    InfobaseAccess,
+    /// The end of the event log of the base
+    EventLog,
+    /// Only the pinned bases, each launched with a single key
+    Favorites,
+    Paste,
+    // the selected history entry
+    Delete,
+    EditPath,
+    Pin,
+    Copy,
+    MoveToFolder,
+    Retry,
+    MoveUp,
+    MoveDown,
+}
+
+impl Action {
+    /// The actions left in a kiosk build.
+    pub fn in_kiosk(self) -> bool {
+        matches!(
+            self,
+            Action::Launch
+                | Action::Quit
+                | Action::Up
+                | Action::Down
+                | Action::NextMatch
+                | Action::PreviousMatch
+                | Action::Errors
+                | Action::Favorites
+        )
+    }
+}
+
+/// Keys of an action in the config, one or a list: `"ctrl+d"`, `["down", "j"]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    fn specs(&self) -> &[String] {
+        match self {
+            Keys::One(spec) => std::slice::from_ref(spec),
+            Keys::Many(specs) => specs,
+        }
+    }
+}
+
+const DEFAULT_KEYMAP: [(Action, &str); 41] = [
+    (Action::Launch, "enter"),
+    (Action::Quit, "esc"),
+    (Action::Up, "up"),
+    (Action::Down, "down"),
+    (Action::NextMatch, "tab"),
+    (Action::PreviousMatch, "shift+tab"),
+    (Action::ToggleDesigner, "ctrl+d"),
+    (Action::Credentials, "ctrl+n"),
+    (Action::NextPlatform, "ctrl+p"),
+    (Action::NextClient, "ctrl+o"),
+    (Action::EditEntry, "ctrl+e"),
+    (Action::Share, "ctrl+s"),
+    (Action::Rdp, "ctrl+r"),
+    (Action::Sources, "ctrl+g"),
+    (Action::BrowseCluster, "ctrl+b"),
+    (Action::Licenses, "ctrl+q"),
+    (Action::Check, "ctrl+t"),
+    (Action::ShowFailed, "ctrl+f"),
+    (Action::Errors, "ctrl+l"),
+    (Action::RecordMacro, "ctrl+x"),
+    (Action::Sets, "ctrl+y"),
+    (Action::Activity, "ctrl+j"),
+    (Action::BuildConnection, "ctrl+k"),
+    (Action::FocusClient, "ctrl+w"),
+    (Action::Plugins, "ctrl+u"),
+    (Action::RefreshColumns, "ctrl+a"),
+    (Action::ClearCache, "alt+c"),
+    (Action::ResetSettings, "alt+r"),
+    (Action::InfobaseAccess, "alt+i"),
+    (Action::EventLog, "alt+l"),
+    (Action::Favorites, "alt+k"),
+    (Action::Paste, "ctrl+v"),
+    (Action::Paste, "shift+insert"),
+    (Action::Delete, "delete"),
+    (Action::EditPath, "e"),
+    (Action::Pin, "p"),
+    (Action::Copy, "c"),
+    (Action::MoveToFolder, "f"),
+    (Action::Retry, "r"),
+    (Action::MoveUp, "shift+up"),
+    (Action::MoveDown, "shift+down"),
+];
+
+/// A key with its modifiers, as written in the keymap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Reads `ctrl+d`, `alt+shift+up`, `f5`, `j` and the like.
+    pub fn parse(spec: &str) -> Result<Key, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        // "ctrl++" binds the plus key
+        if spec.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let name = parts.pop().filter(|name| !name.is_empty());
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier '{modifier}' in key '{spec}'")),
+            };
+        }
+        let name = name.ok_or_else(|| format!("No key in '{spec}'"))?;
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+            _ => match name.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key '{name}' in '{spec}'")),
+                },
+            },
+        };
+        Ok(Key::from_event(code, modifiers))
+    }
+
+    /// Shift is part of the character or of BackTab, matched without it.
+    fn from_event(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match binding(code) {
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Key { code, modifiers }
+    }
+
+    /// Plain characters type into the input unless an entry is selected.
+    fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+
+    /// How hints show the key, e.g. `Ctrl+D`.
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name);
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(c) if self.is_text() => label.push(c),
+            KeyCode::Char(c) => label.push(c.to_ascii_uppercase()),
+            KeyCode::F(n) => label.push_str(&format!("F{n}")),
+            KeyCode::BackTab => label.push_str("Shift+Tab"),
+            code => label.push_str(&format!("{code:?}")),
+        }
+        label
+    }
+}
+
+/// The keys of the picker, the defaults with the `[keymap]` of the config
+/// applied: an action listed there gets exactly the keys listed, and they no
+/// longer do what they did by default.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::new(&BTreeMap::new()).expect("default keymap")
+    }
+}
+
+impl Keymap {
+    pub fn new(overrides: &BTreeMap<Action, Keys>) -> Result<Keymap, Box<dyn Error>> {
+        let mut configured = Vec::new();
+        for (action, keys) in overrides {
+            for spec in keys.specs() {
+                let key = Key::parse(spec).map_err(|e| format!("[keymap] {e}"))?;
+                configured.push((key, *action));
+            }
+        }
+        let mut bindings: Vec<(Key, Action)> = DEFAULT_KEYMAP
+            .iter()
+            .filter(|(action, _)| !overrides.contains_key(action))
+            .map(|(action, spec)| (Key::parse(spec).expect("default key"), *action))
+            .filter(|(key, _)| !configured.iter().any(|(own, _)| own == key))
+            .collect();
+        bindings.extend(configured);
+        Ok(Keymap { bindings })
+    }
+
+    /// The action of `event`. Keys without Ctrl or Alt only act while a
+    /// history entry is `selected`, otherwise they are typed.
+    pub fn action(&self, event: &KeyEvent, selected: bool) -> Option<Action> {
+        let key = Key::from_event(event.code, event.modifiers);
+        if key.is_text() && !selected {
+            return None;
+        }
+        self.bindings
+            .iter()
+            .find(|(own, _)| *own == key)
+            .map(|(_, action)| *action)
+    }
+
+    /// The first key of `action` for hints, e.g. `Ctrl+D`; `-` when unbound.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(_, own)| *own == action)
+            .map_or_else(|| "-".to_string(), |(key, _)| key.label())
+    }
+}
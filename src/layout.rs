@@ -0,0 +1,108 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+
+use crate::config::LayoutConfig;
+
+/// Width of the favorites pane of the wide layout, borders included.
+const FAVORITES_WIDTH: u16 = 32;
+
+/// Share of the width the detail pane takes in the wide layout.
+const DETAILS_PERCENT: u16 = 35;
+
+/// Characters a custom column value or a base name keeps in the narrow
+/// layout before it is cut.
+const NARROW_TEXT: usize = 16;
+
+/// How much room the picker has, from the width of the terminal and the
+/// breakpoints of `[layout]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Below `narrow_width`: no detail pane, shortened columns
+    Narrow,
+    #[default]
+    Normal,
+    /// From `wide_width` on: favorites, history and details side by side
+    Wide,
+}
+
+impl Breakpoint {
+    pub fn of(width: u16, config: &LayoutConfig) -> Breakpoint {
+        if width < config.narrow_width {
+            Breakpoint::Narrow
+        } else if config.wide_width > 0 && width >= config.wide_width {
+            Breakpoint::Wide
+        } else {
+            Breakpoint::Normal
+        }
+    }
+
+    /// `text` as a column of the history shows it, cut to fit the narrow layout.
+    pub fn shorten(self, text: &str) -> String {
+        if self != Breakpoint::Narrow || text.chars().count() <= NARROW_TEXT {
+            return text.to_string();
+        }
+        let mut short: String = text.chars().take(NARROW_TEXT - 1).collect();
+        short.push('…');
+        short
+    }
+}
+
+/// Where the panes of the picker go; the ones not shown get an empty area.
+#[derive(Debug, Default)]
+pub struct Panes {
+    pub input: Rect,
+    pub config: Rect,
+    pub running: Rect,
+    pub favorites: Rect,
+    pub history: Rect,
+    pub details: Rect,
+}
+
+/// Lays out the picker in `area`: `running` clients above the history,
+/// the detail pane `detail_height` lines high below it, or beside it with
+/// the pinned bases on the other side when the layout is wide and
+/// `favorites` are there to show. A height of 0 hides the details in both.
+pub fn panes(
+    area: Rect,
+    breakpoint: Breakpoint,
+    running: u16,
+    detail_height: u16,
+    favorites: bool,
+) -> Panes {
+    // the wide layout gives the details a column of their own
+    let below = match breakpoint {
+        Breakpoint::Normal => detail_height,
+        Breakpoint::Narrow | Breakpoint::Wide => 0,
+    };
+    let [input, config, running, history, details] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(2),
+        Constraint::Length(if running > 0 { running.min(3) + 2 } else { 0 }),
+        Constraint::Min(3),
+        Constraint::Length(below),
+    ])
+    .areas(area);
+    let mut panes = Panes {
+        input,
+        config,
+        running,
+        history,
+        details,
+        ..Panes::default()
+    };
+    if breakpoint == Breakpoint::Wide {
+        let [favorites, history, details] = Layout::horizontal([
+            Constraint::Length(if favorites { FAVORITES_WIDTH } else { 0 }),
+            Constraint::Min(20),
+            Constraint::Percentage(if detail_height > 0 {
+                DETAILS_PERCENT
+            } else {
+                0
+            }),
+        ])
+        .areas(panes.history);
+        panes.favorites = favorites;
+        panes.history = history;
+        panes.details = details;
+    }
+    panes
+}
@@ -0,0 +1,216 @@
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use crate::bases::Bases;
+use crate::config::{LicenseConfig, RasConfig};
+use crate::ras::{self, Ras};
+
+/// Seconds between two session counts while waiting for a free license.
+const POLL_SECS: u64 = 30;
+
+/// Applications taking a client license, background jobs and consoles don't.
+const LICENSED_APPS: [&str; 4] = ["1CV8", "1CV8C", "WebClient", "Designer"];
+
+/// Client licenses of one license key handed out by the clusters of a host.
+#[derive(Debug, Clone)]
+pub struct KeyUsage {
+    pub series: String,
+    /// `soft` for a software key, `hasp` for a hardware one
+    pub kind: String,
+    pub used: u32,
+    /// Hard limit of the key, the users it licenses
+    pub limit: u32,
+}
+
+/// License usage on the clusters administered by the ras of a host.
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub host: String,
+    pub keys: Vec<KeyUsage>,
+    /// Sessions licensed by a key on the client computer
+    pub client_side: u32,
+    /// Sessions taking no client license, such as background jobs
+    pub unlicensed: u32,
+    /// Soft limit from `[licenses] available`, when configured
+    pub available: Option<u32>,
+}
+
+impl Usage {
+    pub fn used(&self) -> u32 {
+        self.keys.iter().map(|key| key.used).sum()
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.keys.iter().map(|key| key.limit).sum()
+    }
+
+    /// Lines of the summary, shared by `rbaserun licenses` and the Ctrl+Q
+    /// panel, with whether they show a limit that is reached.
+    pub fn lines(&self) -> Vec<(String, bool)> {
+        let (used, limit) = (self.used(), self.limit());
+        let mut lines = vec![(
+            format!(
+                "Server licenses: {used} of {limit} in use, {} free",
+                limit.saturating_sub(used)
+            ),
+            used >= limit,
+        )];
+        if let Some(available) = self.available {
+            lines.push((
+                format!(
+                    "Configured limit: {used} of {available} in use, {} free",
+                    available.saturating_sub(used)
+                ),
+                used >= available,
+            ));
+        }
+        for key in &self.keys {
+            lines.push((
+                format!(
+                    "  {} ({}): {} of {}",
+                    key.series, key.kind, key.used, key.limit
+                ),
+                key.used >= key.limit,
+            ));
+        }
+        lines.push((format!("Client licenses: {}", self.client_side), false));
+        lines.push((
+            format!("Sessions without a license: {}", self.unlicensed),
+            false,
+        ));
+        lines
+    }
+}
+
+/// Counts the licenses of the sessions on the clusters of `host`.
+pub fn usage(
+    host: &str,
+    config: Option<&LicenseConfig>,
+    ras: &RasConfig,
+) -> Result<Usage, Box<dyn Error>> {
+    let ras = Ras::new(host, ras);
+    let mut usage = Usage {
+        host: host.to_string(),
+        keys: Vec::new(),
+        client_side: 0,
+        unlicensed: 0,
+        available: config.map(|config| config.available),
+    };
+    for cluster in ras.clusters()? {
+        let licenses = ras.session_licenses(&cluster)?;
+        let sessions = ras.session_apps(&cluster)?.len() as u32;
+        usage.unlicensed += sessions.saturating_sub(licenses.len() as u32);
+        for license in licenses {
+            if !license.by_server {
+                usage.client_side += 1;
+                continue;
+            }
+            match usage
+                .keys
+                .iter_mut()
+                .find(|key| key.series == license.series)
+            {
+                Some(key) => {
+                    key.used += 1;
+                    key.limit = key.limit.max(license.max_users);
+                }
+                None => usage.keys.push(KeyUsage {
+                    series: license.series,
+                    kind: license.kind,
+                    used: 1,
+                    limit: license.max_users,
+                }),
+            }
+        }
+    }
+    Ok(usage)
+}
+
+/// Prints the license usage on the clusters of `input`, a registered
+/// cluster, a host name or the path or alias of a server base.
+pub fn report(
+    input: &str,
+    bases: &Bases,
+    config: Option<&LicenseConfig>,
+    ras: &RasConfig,
+) -> Result<(), Box<dyn Error>> {
+    let host = ras::resolve(input, bases, ras)?.host;
+    for (line, _) in usage(&host, config, ras)?.lines() {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Licensed sessions on the clusters administered by the ras of `host`.
+pub fn sessions(host: &str, config: &RasConfig) -> Result<u32, Box<dyn Error>> {
+    let ras = Ras::new(host, config);
+    let mut count = 0;
+    for cluster in ras.clusters()? {
+        count += ras
+            .session_apps(&cluster)?
+            .iter()
+            .filter(|app| LICENSED_APPS.contains(&app.as_str()))
+            .count();
+    }
+    Ok(count as u32)
+}
+
+/// Counts the sessions on the clusters of `paths` and warns about those the
+/// launches of set `name` would run out of licenses on. Returns these hosts.
+pub fn check_set<'a>(
+    name: &str,
+    paths: impl IntoIterator<Item = &'a str>,
+    bases: &Bases,
+    config: &LicenseConfig,
+    ras: &RasConfig,
+) -> Vec<String> {
+    let mut launches: Vec<(String, u32)> = Vec::new();
+    for host in paths
+        .into_iter()
+        .filter_map(|path| ras::cluster_host(&bases.expand_alias(path)))
+    {
+        match launches.iter_mut().find(|(own, _)| *own == host) {
+            Some((_, count)) => *count += 1,
+            None => launches.push((host, 1)),
+        }
+    }
+
+    let mut short = Vec::new();
+    for (host, count) in launches {
+        match sessions(&host, ras) {
+            Ok(in_use) if in_use + count > config.available => {
+                eprintln!(
+                    "Warning: '{name}' launches {count} sessions on {host}, {} of {} licenses are free",
+                    config.available.saturating_sub(in_use),
+                    config.available
+                );
+                short.push(host);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: could not count the sessions on {host}: {e}"),
+        }
+    }
+    short
+}
+
+/// Waits until a license is free on `host`. Gives up waiting when the
+/// sessions can't be counted.
+pub fn wait(host: &str, config: &LicenseConfig, ras: &RasConfig) {
+    loop {
+        match sessions(host, ras) {
+            Ok(in_use) if in_use < config.available => return,
+            Ok(in_use) => {
+                eprintln!(
+                    "{in_use} of {} licenses in use on {host}, checking again in {POLL_SECS} s",
+                    config.available
+                );
+                thread::sleep(Duration::from_secs(POLL_SECS));
+            }
+            Err(e) => {
+                eprintln!("Warning: could not count the sessions on {host}: {e}");
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::cmp::Reverse;
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::bases::Bases;
+use crate::config::Config;
+use crate::designer::Credentials;
+use crate::watch::Launched;
+use crate::{
+    Client, KIOSK, LaunchMode, Launcher, fuzzy, history, ibases, launch_from_cli, parse_base_path,
+};
+
+/// Matches read out after a filter, the others are only counted.
+const MAX_READ: usize = 10;
+
+const HELP: &str = "Type part of a base and press Enter to filter. Type the number of a match \
+to launch it, d and the number to launch it in designer mode. Enter on an empty line repeats \
+the matches, q quits.";
+
+/// A base the picker offers, from the history or the 1C launcher.
+struct Listed {
+    path: String,
+    alias: Option<String>,
+    name: Option<String>,
+}
+
+/// Options of a launch that the picker does not change.
+pub struct LinearOptions<'a> {
+    pub config: &'a Config,
+    pub launcher: &'a Launcher,
+    pub client: Option<Client>,
+    pub credentials: Credentials,
+    pub print_selection: bool,
+}
+
+/// The picker for screen readers: nothing is redrawn, every prompt and
+/// announcement is a plain line, and matches are picked by number.
+pub fn run(bases: &Bases, options: LinearOptions) -> Result<Launched, Box<dyn Error>> {
+    // with --print-selection stdout carries only the chosen base
+    let mut out: Box<dyn Write> = if options.print_selection {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    };
+    let mut listed = listed(bases, options.config);
+    // indices into `listed` of the last filter's matches, best first
+    let mut matches: Vec<usize> = (0..listed.len()).collect();
+    writeln!(out, "rbaserun, {} bases. {HELP}", listed.len())?;
+
+    let stdin = io::stdin();
+    let mut line = String::new();
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(Launched::default());
+        }
+        let input = line.trim();
+        let (designer, number) = match input.strip_prefix(['d', 'D']) {
+            Some(rest) if rest.trim().parse::<usize>().is_ok() => (true, rest.trim()),
+            _ => (false, input),
+        };
+        if input.is_empty() {
+            announce(&mut out, &listed, &matches, bases)?;
+        } else if input.eq_ignore_ascii_case("q") {
+            return Ok(Launched::default());
+        } else if input == "?" {
+            writeln!(out, "{HELP}")?;
+        } else if let Ok(number) = number.parse::<usize>() {
+            let Some(entry) = number
+                .checked_sub(1)
+                .and_then(|index| matches.get(index))
+                .map(|&index| &listed[index])
+            else {
+                writeln!(
+                    out,
+                    "No match number {number}, there are {}.",
+                    matches.len()
+                )?;
+                continue;
+            };
+            if designer && KIOSK {
+                writeln!(out, "Designer mode is not available in this kiosk build.")?;
+                continue;
+            }
+            let path = entry.path.clone();
+            if options.print_selection {
+                println!("{path}");
+                return Ok(Launched::default());
+            }
+            let mode = match options.client {
+                Some(client) if !designer => client.mode(),
+                _ => options.config.cli_mode(&path, designer),
+            };
+            writeln!(out, "Launching {} in {} mode.", spoken(entry), mode.label())?;
+            let name = entry.name.clone();
+            match launch(&path, mode, name, bases, &options) {
+                Ok(launched) => {
+                    if !options.launcher.dry_run {
+                        writeln!(out, "Launched.")?;
+                    }
+                    return Ok(launched);
+                }
+                Err(e) => writeln!(out, "Error: {e}")?,
+            }
+        } else {
+            matches = filter(&listed, input);
+            if matches.is_empty() && parse_base_path(input).is_ok() {
+                writeln!(out, "No matches, the typed connection string is number 1.")?;
+                listed.push(Listed {
+                    path: input.to_string(),
+                    alias: None,
+                    name: None,
+                });
+                matches = vec![listed.len() - 1];
+                continue;
+            }
+            announce(&mut out, &listed, &matches, bases)?;
+        }
+    }
+}
+
+/// Prints the bases as numbered lines and launches the one named by the
+/// line read from stdin: its number, a line as printed (e.g. picked with fzf),
+/// an alias or a connection string. Piped input is read first, so that the
+/// list is only printed when it brings no selection.
+pub fn pick(
+    bases: &Bases,
+    options: LinearOptions,
+    designer: bool,
+) -> Result<Launched, Box<dyn Error>> {
+    let listed = listed(bases, options.config);
+    let terminal = io::stdin().is_terminal();
+    let mut line = String::new();
+    if !terminal {
+        io::stdin().lock().read_line(&mut line)?;
+    }
+    if line.trim().is_empty() {
+        // with --print-selection stdout carries only the chosen base
+        let mut out: Box<dyn Write> = if options.print_selection && terminal {
+            Box::new(io::stderr())
+        } else {
+            Box::new(io::stdout())
+        };
+        let mut text = String::new();
+        for (number, base) in listed.iter().enumerate() {
+            text.push_str(&format!("{}) {}", number + 1, base.path));
+            match (&base.alias, &base.name) {
+                (Some(alias), Some(name)) => text.push_str(&format!("  {alias}: {name}\n")),
+                (Some(label), None) | (None, Some(label)) => text.push_str(&format!("  {label}\n")),
+                (None, None) => text.push('\n'),
+            }
+        }
+        // a picker such as head may stop reading early
+        match out.write_all(text.as_bytes()).and_then(|()| out.flush()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+        if !terminal {
+            return Ok(Launched::default());
+        }
+        eprint!("Launch [1-{}, Enter to cancel]: ", listed.len());
+        io::stderr().flush()?;
+        io::stdin().lock().read_line(&mut line)?;
+        if line.trim().is_empty() {
+            return Ok(Launched::default());
+        }
+    }
+
+    let (path, name) = selected(line.trim(), &listed, bases)?;
+    if options.print_selection {
+        println!("{path}");
+        return Ok(Launched::default());
+    }
+    let mode = match options.client {
+        Some(client) if !designer => client.mode(),
+        _ => options.config.cli_mode(&path, designer),
+    };
+    launch(&path, mode, name, bases, &options)
+}
+
+/// The base named by a line of `pick` and its name.
+fn selected(
+    line: &str,
+    listed: &[Listed],
+    bases: &Bases,
+) -> Result<(String, Option<String>), Box<dyn Error>> {
+    let number = line
+        .split([')', '.', ' ', '\t'])
+        .next()
+        .and_then(|number| number.parse::<usize>().ok());
+    if let Some(number) = number {
+        return match number.checked_sub(1).and_then(|index| listed.get(index)) {
+            Some(base) => Ok((base.path.clone(), base.name.clone())),
+            None => Err(format!("No base number {number}, there are {}", listed.len()).into()),
+        };
+    }
+    let path = bases.expand_alias(line);
+    if let Some(base) = listed.iter().find(|base| base.path == path) {
+        return Ok((base.path.clone(), base.name.clone()));
+    }
+    parse_base_path(&path).map_err(|e| format!("'{line}' is neither a number nor a base: {e}"))?;
+    Ok((path, None))
+}
+
+fn launch(
+    path: &str,
+    mode: LaunchMode,
+    name: Option<String>,
+    bases: &Bases,
+    options: &LinearOptions,
+) -> Result<Launched, Box<dyn Error>> {
+    let launched = launch_from_cli(
+        path.to_string(),
+        mode,
+        bases,
+        options.launcher,
+        &options.credentials,
+    )?;
+    if !options.launcher.dry_run {
+        history::record_launch(path, mode, options.client, name)?;
+    }
+    Ok(launched)
+}
+
+/// The history followed by the bases of the 1C launcher it lacks.
+fn listed(bases: &Bases, config: &Config) -> Vec<Listed> {
+    let mut listed: Vec<Listed> = history::load()
+        .into_iter()
+        .map(|path| {
+            let entry = bases.find(&path);
+            Listed {
+                alias: entry.and_then(|entry| entry.alias.clone()),
+                name: entry.and_then(|entry| entry.name.clone()),
+                path,
+            }
+        })
+        .collect();
+    if config.ibases {
+        let target = |path: &str| parse_base_path(path).ok().map(|path| path.target());
+        let known: Vec<_> = listed.iter().map(|base| target(&base.path)).collect();
+        listed.extend(
+            ibases::load(config.ibases_file.clone())
+                .into_iter()
+                .filter(|base| !known.contains(&target(&base.connect)))
+                .map(|base| Listed {
+                    name: Some(base.title()),
+                    path: base.connect,
+                    alias: None,
+                }),
+        );
+    }
+    listed
+}
+
+/// Indices of the bases fuzzy matching `query` by connection string, alias
+/// or name, best first.
+fn filter(listed: &[Listed], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = listed
+        .iter()
+        .enumerate()
+        .filter_map(|(index, base)| {
+            let score = [Some(&base.path), base.alias.as_ref(), base.name.as_ref()]
+                .into_iter()
+                .flatten()
+                .filter_map(|text| fuzzy::find(query, text))
+                .map(|matched| matched.score)
+                .max()?;
+            Some((score, index))
+        })
+        .collect();
+    scored.sort_by_key(|&(score, _)| Reverse(score));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Reads out the number of matches and the first of them, one per line.
+fn announce(
+    out: &mut dyn Write,
+    listed: &[Listed],
+    matches: &[usize],
+    bases: &Bases,
+) -> io::Result<()> {
+    match matches.len() {
+        0 => return writeln!(out, "No matches."),
+        1 => writeln!(out, "1 match.")?,
+        count => writeln!(out, "{count} matches.")?,
+    }
+    for (number, base) in matches
+        .iter()
+        .take(MAX_READ)
+        .map(|&index| &listed[index])
+        .enumerate()
+    {
+        let production = if bases.is_production(&base.path) {
+            ", production"
+        } else {
+            ""
+        };
+        writeln!(out, "{}. {}{production}", number + 1, spoken(base))?;
+    }
+    if matches.len() > MAX_READ {
+        writeln!(
+            out,
+            "And {} more, type more of the name to narrow them down.",
+            matches.len() - MAX_READ
+        )?;
+    }
+    Ok(())
+}
+
+/// A base as a sentence: its name or alias first, then the connection string.
+fn spoken(base: &Listed) -> String {
+    let mut parts = Vec::new();
+    parts.extend(base.name.clone());
+    parts.extend(base.alias.as_ref().map(|alias| format!("alias {alias}")));
+    parts.push(base.path.clone());
+    parts.join(", ")
+}
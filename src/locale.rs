@@ -0,0 +1,154 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::Deserialize;
+
+use std::env;
+use std::sync::OnceLock;
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Language numbers, dates and times are shown in, `locale` in the config.
+/// The rest of the interface is in English either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Ru,
+}
+
+impl Locale {
+    /// The locale of `LC_ALL`, `LC_TIME` or `LANG`, English unless it is Russian.
+    fn from_env() -> Locale {
+        let name = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        if name.to_lowercase().starts_with("ru") {
+            Locale::Ru
+        } else {
+            Locale::En
+        }
+    }
+}
+
+/// Uses `locale`, or the one of the environment when unset. Called once at
+/// startup.
+pub fn set(locale: Option<Locale>) {
+    let _ = LOCALE.set(locale.unwrap_or_else(Locale::from_env));
+}
+
+fn get() -> Locale {
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+/// E.g. `2026-03-01 09:15`, `01.03.2026 09:15` in Russian.
+pub fn date_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match get() {
+        Locale::En => time.format("%Y-%m-%d %H:%M").to_string(),
+        Locale::Ru => time.format("%d.%m.%Y %H:%M").to_string(),
+    }
+}
+
+/// E.g. `2026-03-01`, `01.03.2026` in Russian.
+#[cfg(feature = "http")]
+pub fn date<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match get() {
+        Locale::En => time.format("%Y-%m-%d").to_string(),
+        Locale::Ru => time.format("%d.%m.%Y").to_string(),
+    }
+}
+
+/// A unix timestamp in seconds as the local date and time, empty when it is
+/// out of range.
+pub fn timestamp(seconds: i64) -> String {
+    Local
+        .timestamp_opt(seconds, 0)
+        .single()
+        .map(|time| date_time(&time))
+        .unwrap_or_default()
+}
+
+/// How long ago `at` was, e.g. `5 minutes ago`, `2 ч назад` in Russian.
+pub fn ago(now: i64, at: i64) -> String {
+    let seconds = (now - at).max(0);
+    let (count, unit) = match seconds {
+        0..60 => {
+            return match get() {
+                Locale::En => "just now".to_string(),
+                Locale::Ru => "только что".to_string(),
+            };
+        }
+        60..3600 => (seconds / 60, Unit::Minute),
+        3600..86400 => (seconds / 3600, Unit::Hour),
+        86400..2592000 => (seconds / 86400, Unit::Day),
+        2592000..31536000 => (seconds / 2592000, Unit::Month),
+        _ => (seconds / 31536000, Unit::Year),
+    };
+    match get() {
+        Locale::En => match count {
+            1 => format!("1 {} ago", unit.english()),
+            count => format!("{count} {}s ago", unit.english()),
+        },
+        Locale::Ru => format!("{count} {} назад", unit.russian()),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+impl Unit {
+    fn english(self) -> &'static str {
+        match self {
+            Unit::Minute => "minute",
+            Unit::Hour => "hour",
+            Unit::Day => "day",
+            Unit::Month => "month",
+            Unit::Year => "year",
+        }
+    }
+
+    /// Abbreviations that need no plural forms.
+    fn russian(self) -> &'static str {
+        match self {
+            Unit::Minute => "мин",
+            Unit::Hour => "ч",
+            Unit::Day => "дн.",
+            Unit::Month => "мес.",
+            Unit::Year => "г.",
+        }
+    }
+}
+
+/// E.g. `1.5 MB`, `1,5 МБ` in Russian.
+pub fn size(bytes: u64) -> String {
+    const UNITS: [(&str, &str); 4] = [("KB", "КБ"), ("MB", "МБ"), ("GB", "ГБ"), ("TB", "ТБ")];
+    if bytes < 1024 {
+        return match get() {
+            Locale::En => format!("{bytes} B"),
+            Locale::Ru => format!("{bytes} Б"),
+        };
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let (english, russian) = UNITS[unit];
+    match get() {
+        Locale::En => format!("{value:.1} {english}"),
+        Locale::Ru => format!("{} {russian}", format!("{value:.1}").replace('.', ",")),
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::{LaunchMode, paths, platform};
+
+const AUDIT_FILE: &str = "rbaserun_audit.jsonl";
+
+static LOCKDOWN: OnceLock<Lockdown> = OnceLock::new();
+static GROUPS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Restrictions pushed by IT, only read from the `[lockdown]` table of the
+/// machine config and from `RBASERUN_*` environment variables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Lockdown {
+    /// Refuse designer launches and designer batch runs
+    pub disable_designer: bool,
+    /// Refuse removing production bases or their `prod` tag
+    pub protect_prod: bool,
+    /// Record every launch attempt to `audit_file`
+    pub audit: bool,
+    pub audit_file: PathBuf,
+    /// Actions limited to some OS users and `@groups`, e.g.
+    /// `refresh = ["@1c-admins", "alice"]`. Actions without a rule are open
+    /// to everyone.
+    pub permissions: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditEvent<'a> {
+    time: String,
+    user: String,
+    path: &'a str,
+    mode: LaunchMode,
+    error: Option<String>,
+}
+
+impl Default for Lockdown {
+    fn default() -> Lockdown {
+        Lockdown {
+            disable_designer: false,
+            protect_prod: false,
+            audit: false,
+            audit_file: paths::data_file(AUDIT_FILE),
+            permissions: BTreeMap::new(),
+        }
+    }
+}
+
+/// Applies `lockdown` tightened by the environment, called once at startup.
+pub fn set(mut lockdown: Lockdown) {
+    lockdown.disable_designer |= env_flag("RBASERUN_DISABLE_DESIGNER");
+    lockdown.protect_prod |= env_flag("RBASERUN_PROTECT_PROD");
+    lockdown.audit |= env_flag("RBASERUN_AUDIT");
+    if let Some(file) = env::var_os("RBASERUN_AUDIT_FILE") {
+        lockdown.audit_file = PathBuf::from(file);
+    }
+    let _ = LOCKDOWN.set(lockdown);
+}
+
+pub fn get() -> &'static Lockdown {
+    LOCKDOWN.get_or_init(Lockdown::default)
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+pub fn check_mode(mode: LaunchMode) -> Result<(), Box<dyn Error>> {
+    if mode != LaunchMode::Designer {
+        return Ok(());
+    }
+    if get().disable_designer {
+        return Err("Designer mode is disabled by the machine configuration".into());
+    }
+    check_action("designer")
+}
+
+/// Fails when the rule for `action`, a command like `repository update` or
+/// `designer`, does not list the current user or one of their groups. The
+/// most specific rule applies, `repository update` before `repository`.
+pub fn check_action(action: &str) -> Result<(), Box<dyn Error>> {
+    let rule = get()
+        .permissions
+        .iter()
+        .filter(|(rule, _)| {
+            action == rule.as_str()
+                || action
+                    .strip_prefix(rule.as_str())
+                    .is_some_and(|rest| rest.starts_with(' '))
+        })
+        .max_by_key(|(rule, _)| rule.len());
+    let Some((_, allowed)) = rule else {
+        return Ok(());
+    };
+    let user = user_name();
+    let permitted = allowed.iter().any(|who| match who.strip_prefix('@') {
+        Some(group) => groups().iter().any(|own| same_name(own, group)),
+        None => same_name(&user, who),
+    });
+    if permitted {
+        return Ok(());
+    }
+    Err(format!(
+        "Not permitted: {action} is limited to {} by the machine configuration, {user} is not among them",
+        allowed.join(", ")
+    )
+    .into())
+}
+
+pub fn user_name() -> String {
+    env::var("USERNAME")
+        .or_else(|_| env::var("USER"))
+        .ok()
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            let output = Command::new("whoami").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Groups of the current OS user, looked up once.
+fn groups() -> &'static [String] {
+    GROUPS.get_or_init(platform::user_groups)
+}
+
+/// Windows users and groups may be named with or without their domain, e.g.
+/// `BUILTIN\Administrators` or `Administrators`.
+fn same_name(own: &str, wanted: &str) -> bool {
+    own.eq_ignore_ascii_case(wanted)
+        || own
+            .rsplit_once('\\')
+            .is_some_and(|(_, name)| name.eq_ignore_ascii_case(wanted))
+}
+
+/// Fails when `path`, a production base, may not lose its protection.
+pub fn check_removal(path: &str) -> Result<(), Box<dyn Error>> {
+    if get().protect_prod {
+        return Err(format!(
+            "{} is a production base protected by the machine configuration",
+            path.trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Appends a launch to the audit file when auditing is on. Attempts are
+/// recorded before launching, so a launch that cannot be audited is refused,
+/// and failures once more with their `error`.
+pub fn audit(path: &str, mode: LaunchMode, error: Option<String>) -> Result<(), Box<dyn Error>> {
+    let lockdown = get();
+    if !lockdown.audit {
+        return Ok(());
+    }
+    let event = AuditEvent {
+        time: Local::now().to_rfc3339(),
+        user: user_name(),
+        path: path.trim(),
+        mode,
+        error,
+    };
+    paths::prepare(&lockdown.audit_file)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&lockdown.audit_file)
+        .map_err(|e| {
+            format!(
+                "Could not open the audit file {}: {e}",
+                lockdown.audit_file.display()
+            )
+        })?;
+    writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    Ok(())
+}
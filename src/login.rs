@@ -0,0 +1,124 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use crate::designer::Credentials;
+use crate::popup;
+use crate::theme;
+
+pub enum LoginAction {
+    None,
+    Apply,
+    Cancel,
+}
+
+const LABEL_WIDTH: usize = 10;
+
+/// Popup asking for the infobase user and password passed with `/N` and `/P`.
+#[derive(Debug)]
+pub struct LoginPanel {
+    pub path: String,
+    /// Save the credentials for the base instead of using them once
+    pub remember: bool,
+    user: Input,
+    password: Input,
+    focus: usize,
+}
+
+impl LoginPanel {
+    pub fn new(path: String, credentials: Credentials, remember: bool) -> LoginPanel {
+        LoginPanel {
+            path,
+            remember,
+            user: credentials.user.unwrap_or_default().into(),
+            password: credentials.password.unwrap_or_default().into(),
+            focus: 0,
+        }
+    }
+
+    pub fn credentials(&self) -> Credentials {
+        let user = self.user.value().trim();
+        let password = self.password.value();
+        Credentials {
+            user: (!user.is_empty()).then(|| user.to_string()),
+            password: (!password.is_empty()).then(|| password.to_string()),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> LoginAction {
+        let Event::Key(key_event) = event else {
+            return LoginAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return LoginAction::None;
+        }
+        match key_event.code {
+            KeyCode::Esc => return LoginAction::Cancel,
+            KeyCode::Enter => return LoginAction::Apply,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % 3,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + 2) % 3,
+            KeyCode::Char(' ') if self.focus == 2 => self.remember = !self.remember,
+            _ => {
+                match self.focus {
+                    0 => self.user.handle_event(event),
+                    1 => self.password.handle_event(event),
+                    _ => None,
+                };
+            }
+        }
+        LoginAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), 7);
+
+        let masked = "*".repeat(self.password.value().chars().count());
+        let remember = if self.remember { "[x]" } else { "[ ]" };
+        let fields = [
+            ("User:", self.user.value().to_string()),
+            ("Password:", masked),
+            ("Remember:", remember.to_string()),
+        ];
+        let mut lines: Vec<Line> = fields
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, value))| {
+                let line = Line::from(format!("{label:<LABEL_WIDTH$}{value}"));
+                if index == self.focus {
+                    line.bold()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(
+            Span::styled(
+                "Enter: use, Esc: cancel, Tab: next field, Space: toggle remember",
+                theme::hint(),
+            )
+            .into(),
+        );
+
+        let block = Block::bordered().title(format!("Credentials: {}", self.path));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+
+        let cursor = match self.focus {
+            0 => self.user.visual_cursor(),
+            1 => self.password.visual_cursor(),
+            _ => 1,
+        };
+        frame.set_cursor_position((
+            inner.x + (LABEL_WIDTH + cursor) as u16,
+            inner.y + self.focus as u16,
+        ));
+    }
+}
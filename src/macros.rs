@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use crate::bases::Bases;
+use crate::config::{Config, ShortageAction};
+use crate::designer::Credentials;
+use crate::{LaunchMode, Launcher, history, launch_from_cli, licenses, paths, ras};
+
+pub const FILE_NAME: &str = "rbaserun_macros.toml";
+
+/// Function keys macros are bound to, F1 to F12.
+const KEYS: u8 = 12;
+
+pub fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Macros {
+    #[serde(default, rename = "macro")]
+    pub macros: Vec<Macro>,
+}
+
+/// A launch set: bases recorded in the picker or added with `set add`,
+/// launched in order.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Macro {
+    pub name: String,
+    /// Function key replaying the macro in the picker, e.g. `F5`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Seconds waited between two launches, e.g. to let the cluster settle
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub stagger: u64,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Step {
+    pub path: String,
+    pub mode: LaunchMode,
+    /// Seconds waited before this launch instead of the `stagger` of the set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+}
+
+fn is_zero(seconds: &u64) -> bool {
+    *seconds == 0
+}
+
+impl Macro {
+    /// Seconds waited before step `index`, the first one starts right away
+    /// unless it has a delay of its own.
+    pub fn delay(&self, index: usize) -> u64 {
+        let step = &self.steps[index];
+        match step.delay {
+            Some(delay) => delay,
+            None if index == 0 => 0,
+            None => self.stagger,
+        }
+    }
+}
+
+impl Macros {
+    pub fn load() -> Result<Macros, Box<dyn Error>> {
+        let content = match fs::read_to_string(file()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Macros::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {e}", file().display()).into())
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        paths::prepare(&file())?;
+        fs::write(file(), toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.name == name)
+    }
+
+    fn find_mut(&mut self, name: &str) -> Result<&mut Macro, Box<dyn Error>> {
+        self.macros
+            .iter_mut()
+            .find(|m| m.name == name)
+            .ok_or_else(|| format!("No launch set named '{name}'").into())
+    }
+
+    /// The macro bound to function key `n`.
+    pub fn by_key(&self, n: u8) -> Option<&Macro> {
+        let key = format!("F{n}");
+        self.macros.iter().find(|m| {
+            m.key
+                .as_deref()
+                .is_some_and(|own| own.eq_ignore_ascii_case(&key))
+        })
+    }
+
+    /// Saves `steps` as macro `name`, replacing the steps of one of the same
+    /// name, and binds a new one to the first free function key. Returns the key.
+    pub fn record(&mut self, name: &str, steps: Vec<Step>) -> Option<String> {
+        if let Some(existing) = self.macros.iter_mut().find(|m| m.name == name) {
+            existing.steps = steps;
+            return existing.key.clone();
+        }
+        let key = (1..=KEYS)
+            .find(|&n| self.by_key(n).is_none())
+            .map(|n| format!("F{n}"));
+        self.macros.push(Macro {
+            name: name.to_string(),
+            key: key.clone(),
+            stagger: 0,
+            steps,
+        });
+        key
+    }
+
+    /// Appends `step` to set `name`, creating the set when it is new.
+    pub fn add_step(&mut self, name: &str, step: Step) {
+        match self.macros.iter_mut().find(|m| m.name == name) {
+            Some(existing) => existing.steps.push(step),
+            None => {
+                self.record(name, vec![step]);
+            }
+        }
+    }
+
+    /// Removes the steps launching `path` from set `name`, or the whole set
+    /// when `path` is `None`.
+    pub fn remove(&mut self, name: &str, path: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let Some(path) = path else {
+            self.find_mut(name)?;
+            self.macros.retain(|m| m.name != name);
+            return Ok(());
+        };
+        let set = self.find_mut(name)?;
+        let count = set.steps.len();
+        set.steps.retain(|step| step.path.trim() != path.trim());
+        if set.steps.len() == count {
+            return Err(format!("{} is not in launch set '{name}'", path.trim()).into());
+        }
+        Ok(())
+    }
+
+    pub fn set_stagger(&mut self, name: &str, seconds: u64) -> Result<(), Box<dyn Error>> {
+        self.find_mut(name)?.stagger = seconds;
+        Ok(())
+    }
+}
+
+/// Prints every set with its key and the bases it launches.
+pub fn list() -> Result<(), Box<dyn Error>> {
+    let macros = Macros::load()?;
+    for set in &macros.macros {
+        let mut header = set.name.clone();
+        if let Some(key) = &set.key {
+            header.push_str(&format!(" ({key})"));
+        }
+        if set.stagger > 0 {
+            header.push_str(&format!(", {} s apart", set.stagger));
+        }
+        println!("{header}");
+        for (index, step) in set.steps.iter().enumerate() {
+            let delay = match step.delay {
+                Some(_) => format!("\tafter {} s", set.delay(index)),
+                None => String::new(),
+            };
+            println!("\t{}\t{}{delay}", step.path.trim(), step.mode.label());
+        }
+    }
+    Ok(())
+}
+
+/// Launches the steps of macro `name` one after another, waiting the delays
+/// in between. A failed step does not stop the others, the error lists how
+/// many failed. With `[licenses]` configured, clusters short of client
+/// licenses are warned about or waited for.
+pub fn run(
+    name: &str,
+    bases: &Bases,
+    launcher: &Launcher,
+    credentials: &Credentials,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let macros = Macros::load()?;
+    let recorded = macros
+        .find(name)
+        .ok_or_else(|| format!("No macro named '{name}', record one in the picker with Ctrl+X"))?;
+    let short = match &config.licenses {
+        Some(licenses) => licenses::check_set(
+            name,
+            recorded.steps.iter().map(|step| step.path.as_str()),
+            bases,
+            licenses,
+            &config.ras,
+        ),
+        None => Vec::new(),
+    };
+    let mut waiting = Vec::new();
+    let mut failed = 0;
+    for (index, step) in recorded.steps.iter().enumerate() {
+        let delay = recorded.delay(index);
+        if delay > 0 && !launcher.dry_run {
+            eprintln!("Waiting {delay} s before launching {}", step.path.trim());
+            thread::sleep(Duration::from_secs(delay));
+        }
+        if let Some(licenses) = &config.licenses
+            && licenses.on_shortage == ShortageAction::Pause
+            && !launcher.dry_run
+            && let Some(host) = ras::cluster_host(&bases.expand_alias(&step.path))
+            && short.contains(&host)
+        {
+            licenses::wait(&host, licenses, &config.ras);
+        }
+        match launch_from_cli(step.path.clone(), step.mode, bases, launcher, credentials) {
+            Ok(launched) => {
+                if !launched.is_detached() {
+                    waiting.push(launched);
+                }
+                if !launcher.dry_run {
+                    history::record_launch(&step.path, step.mode, None, None)?;
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}: {e}", step.path.trim());
+                failed += 1;
+            }
+        }
+    }
+    for launched in waiting {
+        if let Some(message) = launched.waiting() {
+            println!("{message}");
+        }
+        if let Err(e) = launched.wait() {
+            eprintln!("Error: {e}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} launches failed", recorded.steps.len()).into());
+    }
+    Ok(())
+}
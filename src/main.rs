@@ -1,298 +1,5198 @@
-use clap::Parser;
+mod access;
+mod activity;
+mod agent;
+mod availability;
+mod backup;
+mod bases;
+mod browser;
+mod builder;
+#[cfg(feature = "http")]
+mod certificate;
+mod changelog;
+mod check;
+mod clients;
+mod clipboard;
+mod clone;
+mod cluster;
+mod columns;
+mod commands;
+mod compare;
+mod conditions;
+mod config;
+mod confirm;
+mod connection;
+mod crash;
+mod credentials;
+mod decorate;
+mod designer;
+mod dump;
+mod editor;
+mod errors;
+mod eventlog;
+mod events;
+mod favorites;
+mod fuzzy;
+mod git;
+mod history;
+mod hooks;
+mod ibases;
+mod import;
+mod integrate;
+mod journal;
+mod keys;
+mod layout;
+mod licenses;
+mod linear;
+mod locale;
+mod lockdown;
+mod login;
+mod macros;
+mod maintenance;
+mod metrics;
+mod notify;
+mod organize;
+mod paths;
+mod picker;
+mod placeholders;
+mod platform;
+mod plugins;
+mod popup;
+mod ras;
+mod rdp;
+mod refresh;
+mod release;
+mod remote;
+mod report;
+mod repository;
+mod requires;
+mod runner;
+mod script;
+mod search;
+mod secrets;
+mod serve;
+mod session;
+mod sets;
+mod settings;
+mod share;
+mod snapshot;
+mod space;
+mod store;
+mod suggest;
+mod templates;
+mod theme;
+mod tips;
+mod toast;
+mod tui;
+mod tunnel;
+mod upload;
+mod usage;
+mod variables;
+mod watch;
+
+use chrono::Local;
+use clap::{
+    ArgGroup, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
 
 use ratatui::{
-    DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
-    layout::{Constraint, Layout, Rect},
+    Frame,
+    crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    layout::{Margin, Position, Rect},
     style::{Style, Stylize},
-    widgets::{Block, List, ListState, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
 };
 
-use tui_input::{Input, backend::crossterm::EventHandler};
+use tui_input::{Input, InputRequest, backend::crossterm::EventHandler};
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
-use std::path::Path;
-use std::process::Command;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Stdio};
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use std::io::{self, IsTerminal, Write};
+
+use access::{AccessAction, AccessPanel};
+use activity::{ActivityAction, ActivityPanel};
+use agent::AgentOptions;
+use availability::{Availability, Status};
+use bases::{Bases, ProcessEnv};
+use browser::BrowserConfig;
+use builder::{BuilderAction, Built, ConnectionBuilder};
+use changelog::{WhatsNewAction, WhatsNewPanel};
+use check::Probe;
+use clients::LocalClients;
+use cluster::{BrowserAction, ClusterBrowser};
+use columns::ColumnValues;
+use config::{CheckConfig, Config, EscAction, SortOrder};
+use confirm::{Confirm, ConfirmAction, Guard};
+use connection::{ConnectionString, quote};
+use crash::RepairOptions;
+use decorate::{Decoration, Decorator, Fields};
+use designer::Credentials;
+use dump::{DumpOptions, Operation};
+use editor::{DiscardAction, EditorAction, EntryEditor};
+use errors::ErrorLog;
+use eventlog::{LogAction, LogPanel, ReduceOptions};
+use events::{AppEvent, Events, Loaded};
+use favorites::{Favorite, FavoritesAction, FavoritesPanel};
+use history::{HistoryEntry, Slot};
+use hooks::{Hooks, LaunchHooks};
+use ibases::RegisteredBase;
+use import::ImportFormat;
+use keys::{Action, Keymap};
+use layout::Breakpoint;
+use linear::LinearOptions;
+use login::{LoginAction, LoginPanel};
+use macros::{Macros, Step};
+use metrics::Metrics;
+use picker::{ModePicker, PickerAction};
+use platform::Platform;
+use plugins::{Plugin, PluginsAction, PluginsPanel};
+use refresh::RefreshOptions;
+use release::Releases;
+use report::{Report, ReportAction, ReportPanel};
+use script::ScriptFormat;
+use search::SearchIndex;
+use session::Session;
+use sets::{SetsAction, SetsPanel};
+use share::{ShareAction, SharePanel};
+use snapshot::Snapshot;
+use store::Resolution;
+use tips::Tips;
+use tui::{Capabilities, Tui, TuiOptions};
+use tunnel::Tunnel;
+use usage::{UsageAction, UsagePanel};
+use variables::{VariablesAction, VariablesPanel};
+use watch::{Launched, Outcome, Watched};
 
-use std::fs::File;
-use std::io::{self, BufRead, Write};
+/// Built with the `kiosk` feature: bases can only be listed and launched in
+/// Enterprise mode, with no editing, no config overrides and nothing started
+/// but the 1C client.
+const KIOSK: bool = cfg!(feature = "kiosk");
+
+/// Longest pause between the two clicks of a double click in the history pane
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Rows the history pane scrolls by per step of the mouse wheel
+const SCROLL_LINES: u16 = 3;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     path: Option<String>,
 
+    /// Extra 1C switches appended to the launch, e.g. `-- /L en /UC 1234`
+    #[arg(last = true, value_name = "SWITCHES", requires = "path")]
+    switches: Vec<String>,
+
     /// Launch in designer mode
     #[arg(short, long)]
     designer: bool,
-}
 
-#[derive(Debug)]
-enum PathKind {
-    Server { host: String, ref_name: String },
-    File { path: String },
-    Web { url: String },
-}
+    /// Value of a `{NAME}` placeholder of the connection string, e.g. `--var env=test`
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = placeholders::parse_var)]
+    vars: Vec<(String, String)>,
 
-fn parse_base_path(input_path: &str) -> Result<PathKind, Box<dyn Error>> {
-    let s = input_path.trim();
+    /// Config file to read instead of the first rbaserun.toml found
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
 
-    if s.contains("ws=") {
-        return parse_base_web_form(&s);
-    }
+    /// Launch with the client of this installed platform version (e.g. 8.3.24)
+    /// instead of the starter
+    #[arg(long, global = true, value_name = "VERSION")]
+    platform: Option<String>,
 
-    let s = s.to_lowercase();
+    /// Start this client directly instead of going through the starter
+    #[arg(long, value_enum, conflicts_with = "designer")]
+    client: Option<Client>,
 
-    if s.contains("file=") {
-        parse_base_file_form(&s)
-    } else if s.contains("srvr=") && s.contains("ref=") {
-        parse_base_server_form(&s)
-    } else if s.contains(";") {
-        parse_base_simple_form(&s)
-    } else {
-        Err(format!("Could not parse provided path: {input_path}").into())
-    }
+    /// Connect to the base's RDP target instead of launching 1C
+    #[arg(long, conflicts_with = "designer")]
+    rdp: bool,
+
+    /// Open web bases in this browser executable instead of the one configured
+    #[arg(long, value_name = "PROGRAM", conflicts_with_all = ["designer", "client", "rdp"])]
+    browser: Option<PathBuf>,
+
+    /// Render the picker inline below the prompt instead of on the alternate screen
+    #[arg(long)]
+    inline: bool,
+
+    /// Pick bases in plain numbered lines instead of the full-screen picker,
+    /// for screen readers
+    #[arg(long, conflicts_with = "path")]
+    linear: bool,
+
+    /// Print the bases as numbered lines and launch the one whose number, line,
+    /// alias or connection string is read from stdin, e.g. picked with fzf
+    #[arg(long, conflicts_with_all = ["path", "linear", "filter", "inline"])]
+    pick: bool,
+
+    /// Print the chosen connection string to stdout instead of launching it
+    #[arg(long, conflicts_with = "path")]
+    print_selection: bool,
+
+    /// Print the command a launch would run instead of running it
+    #[arg(long, conflicts_with_all = ["print_selection", "rdp"])]
+    dry_run: bool,
+
+    /// Wait for the launched client to exit and report how it failed
+    #[arg(long, conflicts_with_all = ["print_selection", "dry_run", "rdp"])]
+    wait: bool,
+
+    /// Print what was launched (base, mode, command, PID, time and checks)
+    /// for pasting into a ticket; the picker shows it in a popup instead
+    #[arg(long, global = true)]
+    report: bool,
+
+    /// Launch the bases of a macro recorded in the picker with Ctrl+X
+    #[arg(
+        long = "macro",
+        value_name = "NAME",
+        conflicts_with_all = ["path", "linear", "pick", "print_selection", "filter"]
+    )]
+    macro_name: Option<String>,
+
+    /// Open the picker with the history filtered by TEXT and the first match selected
+    #[arg(long, value_name = "TEXT", conflicts_with = "path")]
+    filter: Option<String>,
+
+    /// With --filter, launch right away when exactly one entry matches
+    #[arg(long, requires = "filter")]
+    auto: bool,
+
+    #[command(flatten)]
+    credentials: InfobaseUser,
 }
 
-fn parse_base_simple_form(input: &str) -> Result<PathKind, Box<dyn Error>> {
-    let captures = Regex::new(r"(.+)[;/\\](.+)")
-        .unwrap()
-        .captures(input)
-        .ok_or("expected pattern: host[;/\\]ref")?;
-    Ok(PathKind::Server {
-        host: captures[1].to_string(),
-        ref_name: captures[2].to_string(),
-    })
+#[derive(Subcommand)]
+enum Commands {
+    /// Print the history and the bases from the bases file
+    List {
+        /// Print JSON instead of one connection string per line
+        #[arg(long)]
+        json: bool,
+
+        /// Print JSON items (title, subtitle, icon, command) for OS launcher plugins
+        #[arg(long, conflicts_with = "json")]
+        launcher_feed: bool,
+    },
+
+    /// Save a base to the history under a name, e.g. `add erp 'Srvr="srv";Ref="erp";'`
+    Add {
+        /// Alias the base is launched by, e.g. `rbaserun launch erp`
+        name: String,
+        /// Connection string of the base
+        path: String,
+    },
+
+    /// Name and tag the bases of the history one by one, proposing names
+    Organize {
+        /// Also go through the bases that have a name already
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Remove a base from the history and the bases file
+    #[command(visible_alias = "remove")]
+    Rm {
+        /// Alias or connection string of the base
+        name: String,
+    },
+
+    /// Launch a base right away without starting the picker
+    Launch {
+        path: String,
+
+        /// Extra 1C switches appended to the launch, e.g. `-- /L en /UC 1234`
+        #[arg(last = true, value_name = "SWITCHES")]
+        switches: Vec<String>,
+
+        /// Launch in designer mode
+        #[arg(short, long)]
+        designer: bool,
+
+        /// Value of a `{NAME}` placeholder of the connection string, e.g. `--var env=test`
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = placeholders::parse_var)]
+        vars: Vec<(String, String)>,
+
+        /// Start this client directly instead of going through the starter
+        #[arg(long, value_enum, conflicts_with = "designer")]
+        client: Option<Client>,
+
+        /// Open the web base in this browser executable instead of the one configured
+        #[arg(long, value_name = "PROGRAM", conflicts_with_all = ["designer", "client"])]
+        browser: Option<PathBuf>,
+
+        /// Print nothing and report failures only through the exit code
+        #[arg(short, long)]
+        quiet: bool,
+
+        /// Do not add the base to the history
+        #[arg(long)]
+        no_history: bool,
+
+        /// Print the command the launch would run instead of running it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Wait for the client to exit, failing with its output unless it exits with 0
+        #[arg(long, conflicts_with = "dry_run")]
+        wait: bool,
+
+        /// Launch a temporary copy of the file base, deleted once its client exits
+        #[arg(long, conflicts_with = "dry_run")]
+        snapshot: bool,
+
+        /// Show a desktop notification with the result
+        #[arg(long)]
+        toast: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Serve list/parse/launch operations for other programs
+    #[command(group(ArgGroup::new("transport").required(true).args(["stdio", "http"])))]
+    Serve {
+        /// Read newline-delimited JSON-RPC 2.0 requests from stdin and answer on stdout
+        #[arg(long)]
+        stdio: bool,
+
+        /// Listen for HTTP requests on a local address, e.g. 127.0.0.1:8737
+        #[arg(long, value_name = "ADDR")]
+        http: Option<SocketAddr>,
+
+        /// Bearer token required by POST /launch, a random one is printed when omitted
+        #[arg(long, env = "RBASERUN_TOKEN")]
+        token: Option<String>,
+    },
+
+    /// Generate integration files for other tools
+    Integrate {
+        #[command(subcommand)]
+        target: IntegrateTarget,
+    },
+
+    /// Check whether bases are reachable, all known bases when no path is given
+    Check { paths: Vec<String> },
+
+    /// List the infobases of the clusters on a host through its ras
+    Browse {
+        /// Registered cluster, host name, or the path or alias of a server base
+        host: String,
+    },
+
+    /// Show the client licenses used on the clusters of a host
+    Licenses {
+        /// Registered cluster, host name, or the path or alias of a server base
+        host: String,
+    },
+
+    /// Create a file base, or a server infobase and its database through ras, and register it
+    #[command(group(ArgGroup::new("location").required(true).args(["server", "file"])))]
+    Create {
+        /// Registered cluster, host name, or the path or alias of a server base
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Directory of a new file base
+        #[arg(long, value_name = "DIR")]
+        file: Option<PathBuf>,
+
+        /// Template to start from, a name listed by `templates` or a .cf/.dt file
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Name of the infobase on the cluster
+        #[arg(long, required_unless_present = "file", conflicts_with = "file")]
+        name: Option<String>,
+
+        /// Description, also the name shown in the picker
+        #[arg(long)]
+        descr: Option<String>,
+
+        #[arg(
+            long,
+            value_enum,
+            required_unless_present = "file",
+            conflicts_with = "file"
+        )]
+        dbms: Option<ras::Dbms>,
+
+        /// Database server, the cluster host by default
+        #[arg(long, conflicts_with = "file")]
+        db_server: Option<String>,
+
+        /// Database name, the infobase name by default
+        #[arg(long, conflicts_with = "file")]
+        db_name: Option<String>,
+
+        /// User of the database server
+        #[arg(long, conflicts_with = "file")]
+        db_user: Option<String>,
+
+        #[arg(long, env = "RBASERUN_DB_PASSWORD", hide_env_values = true)]
+        db_password: Option<String>,
+
+        #[arg(long, default_value = "ru")]
+        locale: String,
+
+        /// Security level of the connection to the cluster, 0 to 3
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+        security_level: u8,
+
+        /// Deny scheduled jobs in the new infobase
+        #[arg(long, conflicts_with = "file")]
+        deny_scheduled_jobs: bool,
+    },
+
+    /// List the .cf and .dt templates `create` offers
+    Templates,
+
+    /// Deregister a server infobase from its cluster after typing its name
+    Drop {
+        /// Alias or connection string of the server base
+        path: String,
+
+        /// Drop the database of the infobase as well
+        #[arg(long)]
+        drop_database: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Copy a file base to a new directory and register the copy
+    Clone {
+        /// Connection string or directory of the file base
+        source: String,
+        dest: PathBuf,
+    },
+
+    /// Dump one base and restore the dump into another, e.g. production into test
+    Refresh {
+        source: String,
+        target: String,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+
+        /// Permission code (/UC) for bases with denied sessions
+        #[arg(long, value_name = "CODE")]
+        unlock_code: Option<String>,
+
+        /// Keep the .dt dump at this path
+        #[arg(long, value_name = "PATH")]
+        keep_dump: Option<PathBuf>,
+
+        /// Skip the confirmation, except for production targets
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Dump a base to a .dt file, restore it from one or load a .cf into it
+    #[command(group(ArgGroup::new("operation").required(true).args(["out", "restore", "load_cf"])))]
+    DesignerDump {
+        path: String,
+
+        /// Dump the base into this .dt file
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+
+        /// Replace all data of the base with this .dt file
+        #[arg(long, value_name = "PATH")]
+        restore: Option<PathBuf>,
+
+        /// Load this .cf file as the configuration of the base
+        #[arg(long, value_name = "PATH")]
+        load_cf: Option<PathBuf>,
+
+        /// Update the database configuration after --load-cf
+        #[arg(long, requires = "load_cf")]
+        update_db: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+
+        /// Permission code (/UC) for bases with denied sessions
+        #[arg(long, value_name = "CODE")]
+        unlock_code: Option<String>,
+
+        /// Skip the confirmation, except for production bases
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show the size of the event log of a file base, or reduce it
+    EventLog {
+        path: String,
+
+        /// Remove the records older than this many days
+        #[arg(long, value_name = "DAYS")]
+        keep_days: Option<u32>,
+
+        /// Save the removed records to this file
+        #[arg(long, value_name = "PATH", requires = "keep_days")]
+        save_as: Option<PathBuf>,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+
+        /// Skip the confirmation, except for production bases
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Check and repair a file base in the designer (/IBCheckAndRepair)
+    Repair {
+        path: String,
+
+        /// Only check the base, repairing nothing
+        #[arg(long)]
+        test_only: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+
+        /// Skip the confirmation, except for production bases
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Start the designer of a base in agent mode, taking commands over SSH
+    Agent {
+        path: String,
+
+        /// Port the agent listens on
+        #[arg(long, default_value_t = 1543)]
+        port: u16,
+
+        /// Address the agent listens on
+        #[arg(long, default_value = "127.0.0.1", value_name = "ADDRESS")]
+        listen: String,
+
+        /// Directory the agent reads and writes files in
+        #[arg(long, value_name = "DIR")]
+        base_dir: Option<PathBuf>,
+
+        /// Open an ssh session to the agent and stop it when the session ends
+        #[arg(long)]
+        connect: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Compare the configuration of a base with a .cf file in the designer
+    Compare {
+        path: String,
+        cf: PathBuf,
+
+        /// Keep the text report at this path
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+
+        /// Full report instead of the list of changed objects
+        #[arg(long)]
+        full: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Dumps of the `[[backup]]` plans and checks of .dt dumps
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// Configuration repository operations in designer batch mode
+    Repo {
+        #[command(subcommand)]
+        action: RepoAction,
+    },
+
+    /// Maintenance of the bases file and its backups
+    Store {
+        #[command(subcommand)]
+        action: StoreAction,
+    },
+
+    /// Run a saved action of a base or any command with the base exported as
+    /// environment variables (RBASERUN_*, RUNNER_IBCONNECTION for vanessa-runner)
+    Run {
+        path: String,
+
+        /// Action name from the base's `[base.actions]` table, or a command after `--`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// List the installed 1C platform versions
+    Platforms,
+
+    /// Print the connection string of a base as a QR code
+    Share { path: String },
+
+    /// Write a script reproducing the launch of a base for machines without rbaserun
+    ExportScript {
+        path: String,
+
+        /// Launch in designer mode
+        #[arg(short, long)]
+        designer: bool,
+
+        #[arg(long, value_enum, default_value = "cmd")]
+        format: ScriptFormat,
+
+        /// Write the script to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Add the bases from another launcher's list to the history
+    Import {
+        /// `ibases.v8i` or an XML list, e.g. exported from StartManager or AllBases
+        file: PathBuf,
+
+        /// Format of the list, detected from the extension and contents by default
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+
+        /// Read the file as `ibases.v8i`, like `--format v8i`
+        #[arg(long, conflicts_with = "format")]
+        v8i: bool,
+    },
+
+    /// Write the known bases to a list for the 1C launcher
+    Export {
+        /// `ibases.v8i` to write, with the folders and names of the bases
+        #[arg(long, value_name = "FILE")]
+        v8i: PathBuf,
+    },
+
+    /// Show how often and how recently each base was launched
+    Stats {
+        /// Launch latencies and failure rates from the local metrics file instead
+        #[arg(long)]
+        internal: bool,
+    },
+
+    /// Manage launch sets, the macros recorded in the picker with Ctrl+X
+    Set {
+        #[command(subcommand)]
+        action: SetAction,
+    },
+
+    /// Manage the clusters registered with [[ras.cluster]] in the config
+    Cluster {
+        #[command(subcommand)]
+        action: ClusterAction,
+    },
 }
 
-fn parse_base_server_form(input: &str) -> Result<PathKind, Box<dyn Error>> {
-    let captures = Regex::new(r#""(.+)".+"(.+)""#)
-        .unwrap()
-        .captures(input)
-        .ok_or("expected pattern: Srvr=\"host\";Ref=\"ref\";")?;
-    Ok(PathKind::Server {
-        host: captures[1].to_string(),
-        ref_name: captures[2].to_string(),
-    })
+#[derive(Subcommand)]
+enum ClusterAction {
+    /// Print the registered clusters
+    List,
+
+    /// Save the password of the cluster administrator in the OS keyring
+    Login { name: String },
+
+    /// Add every infobase of a cluster to the history, in a folder named after it
+    Import {
+        /// Registered cluster, host name, or the path or alias of a server base
+        name: String,
+    },
 }
 
-fn parse_base_file_form(input: &str) -> Result<PathKind, Box<dyn Error>> {
-    let captures = Regex::new(r#""(.+)""#)
-        .unwrap()
-        .captures(&input)
-        .ok_or("expected pattern: File=\"<path>\";")?;
-    Ok(PathKind::File {
-        path: captures[1].to_string(),
-    })
+#[derive(Subcommand)]
+enum SetAction {
+    /// Print the launch sets and their bases
+    List,
+
+    /// Launch the bases of a set in order, like --macro
+    Launch {
+        name: String,
+
+        /// Print the commands the launches would run instead of running them
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
+
+    /// Add a base to a set, creating the set when it is new
+    Add {
+        name: String,
+        path: String,
+
+        /// Mode of the launch, the default mode of the base otherwise
+        #[arg(long, value_enum)]
+        mode: Option<LaunchMode>,
+
+        /// Seconds to wait before this launch instead of the stagger of the set
+        #[arg(long, value_name = "SECONDS")]
+        delay: Option<u64>,
+    },
+
+    /// Remove a base from a set, or the whole set without a base
+    Rm { name: String, path: Option<String> },
+
+    /// Wait SECONDS between the launches of a set
+    Stagger { name: String, seconds: u64 },
 }
 
-fn parse_base_web_form(input: &str) -> Result<PathKind, Box<dyn Error>> {
-    let captures = Regex::new(r#""(.+)""#)
-        .unwrap()
-        .captures(input)
-        .ok_or("expected pattern: ws=\"<url>\";")?;
-    Ok(PathKind::Web {
-        url: captures[1].to_string(),
-    })
+#[derive(Subcommand)]
+enum BackupAction {
+    /// Dump the base of a plan, then delete the dumps it no longer keeps
+    Run {
+        plan: String,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+
+        /// Permission code (/UC) for bases with denied sessions
+        #[arg(long, value_name = "CODE")]
+        unlock_code: Option<String>,
+
+        /// Run outside the window of the plan
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Copy the newest dump of a plan to its destinations again
+    Upload { plan: String },
+
+    /// Save the secret key of an S3 access key in the OS keyring
+    Login { access_key: String },
+
+    /// Send a test message to the [notify] webhook and mailbox
+    Notify,
+
+    /// Save the password of the [notify.email] SMTP user in the OS keyring
+    SmtpLogin { user: String },
+
+    /// Delete the dumps of a plan its retention settings no longer keep
+    Prune {
+        plan: String,
+
+        /// Only list the dumps that would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check a .dt dump against the checksum in its manifest
+    Verify { file: PathBuf },
 }
 
-fn launch_base(path: PathKind, designer: bool) -> Result<(), Box<dyn Error>> {
-    // TODO: add option to get 1cestart.exe path from cmd args or config file
-    let starter = Path::new(r#"c:\Program Files\1cv8\common\1cestart.exe"#);
+#[derive(Subcommand)]
+enum RepoAction {
+    /// Save the repository user and password in the OS keyring
+    Login { repository: String, user: String },
 
-    if !starter.exists() {
-        return Err(format!("Could not locate 1C starter app: '{}'", starter.display()).into());
-    }
+    /// Bind the configuration of a base to a repository
+    Bind {
+        path: String,
 
-    let launch_mode = if designer { "DESIGNER" } else { "ENTERPRISE" };
+        /// Repository address, defaults to the one saved for the base
+        #[arg(long)]
+        repository: Option<String>,
 
-    match path {
-        PathKind::Server { host, ref_name } => {
-            Command::new(starter)
-                .args([launch_mode, "/S", &format!("{host}\\{ref_name}")])
-                .spawn()?;
-        }
+        /// Replace the configuration of the base with the repository's (-forceReplaceCfg)
+        #[arg(long)]
+        replace: bool,
 
-        PathKind::File { path } => {
-            Command::new(starter)
-                .args([launch_mode, "/F", &path])
-                .spawn()?;
-        }
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
 
-        PathKind::Web { url } => {
-            Command::new(starter)
-                .args([launch_mode, "/WS", &url])
-                .spawn()?;
-        }
-    }
+    /// Update the configuration of a base from its repository
+    Update {
+        path: String,
 
-    Ok(())
+        /// Also update the database configuration (/UpdateDBCfg)
+        #[arg(long)]
+        apply: bool,
+
+        #[command(flatten)]
+        credentials: InfobaseUser,
+    },
 }
 
-#[derive(Debug, Default)]
-pub struct App {
-    designer: bool,
-    input: Input,
-    error: bool,
-    error_text: String,
-    history: Vec<String>,
-    history_state: ListState,
+#[derive(Subcommand)]
+enum StoreAction {
+    /// Merge another copy of the bases file (e.g. a sync conflict copy) into this one
+    Merge {
+        other: PathBuf,
+
+        /// Keep our version of every conflicting entry
+        #[arg(long, conflicts_with = "theirs")]
+        ours: bool,
+
+        /// Take their version of every conflicting entry
+        #[arg(long)]
+        theirs: bool,
+    },
+
+    /// Put a backup of the history or the bases file back, list the backups without --from
+    Restore {
+        /// File name of the backup, e.g. rbaserun_bases.toml.20261015-093000123
+        #[arg(long)]
+        from: Option<String>,
+    },
 }
 
-impl App {
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
-        self.load_history();
-        loop {
-            let event = event::read()?;
-            match event {
-                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
-                    match key_event.code {
-                        KeyCode::Esc => break,
-                        KeyCode::Char('d') if ctrl => self.designer = !self.designer,
-                        KeyCode::Enter => {
-                            if let Some(selected_index) = self.history_state.selected() {
-                                self.input = self.history[selected_index].clone().into();
-                                self.history_state.select(None);
-                            } else if !self.input.value().is_empty() {
-                                let result =
-                                    try_parse_and_launch(self.input.value().to_string(), self.designer);
-                                match result {
-                                    Ok(()) => {
-                                        self.add_to_history(self.input.value().to_string())?;
-                                        break;
-                                    }
-                                    Err(e) => {
-                                        self.error = true;
-                                        self.error_text = e.to_string();
-                                    }
-                                };
-                            }
-                        },
-                        KeyCode::Up => self.history_state.select_previous(),
-                        KeyCode::Down => self.history_state.select_next(),
-                        _ => {
-                            self.history_state.select(None);
-                        }
-                    };
-                    self.input.handle_event(&event);
-                }
-                _ => {}
-            }
-            terminal.draw(|frame| {
-                let [input_area, config_area, history_area] = Layout::vertical([
-                    Constraint::Length(3),
-                    Constraint::Length(2),
-                    Constraint::Min(1),
-                ])
-                .areas(frame.area());
+#[derive(Args)]
+struct InfobaseUser {
+    /// Infobase user
+    #[arg(long)]
+    user: Option<String>,
+
+    #[arg(long, env = "RBASERUN_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
 
-                self.render_input(frame, input_area);
-                self.render_config(frame, config_area);
-                self.render_history(frame, history_area);
-            })?;
+impl From<InfobaseUser> for Credentials {
+    fn from(user: InfobaseUser) -> Credentials {
+        Credentials {
+            user: user.user,
+            password: user.password,
         }
-        Ok(())
     }
+}
 
-    fn render_input(&self, frame: &mut Frame, area: Rect) {
-        let width = area.width.max(3) - 3;
-        let scroll = self.input.visual_scroll(width as usize);
-        let input_widget = Paragraph::new(self.input.value())
-            .scroll((0, scroll as u16))
-            .block(Block::bordered().title("Base path:"));
+#[derive(Subcommand)]
+enum IntegrateTarget {
+    /// PowerShell module with Get-RBase and Start-RBase cmdlets
+    Powershell,
 
-        frame.render_widget(input_widget, area);
+    /// Windows Terminal settings fragment with profiles for pinned bases
+    WindowsTerminal,
 
-        let x = self.input.visual_cursor().max(scroll) - scroll + 1;
-        frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    /// Shortcuts launching the pinned bases, .lnk files on Windows
+    Shortcuts {
+        /// Directory the shortcuts are written to, e.g. the desktop
+        dir: PathBuf,
+
+        /// Every listed base instead of the pinned ones
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Debug)]
+pub enum PathKind {
+    Server { host: String, ref_name: String },
+    File { path: String },
+    Web { url: String },
+}
+
+impl PathKind {
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            PathKind::Server { .. } => "server",
+            PathKind::File { .. } => "file",
+            PathKind::Web { .. } => "web",
+        }
     }
 
-    fn render_config(&self, frame: &mut Frame, area: Rect) {
-        let mut lines = Vec::new();
+    /// What the starter receives after `/S`, `/F` or `/WS`.
+    pub fn target(&self) -> String {
+        match self {
+            PathKind::Server { host, ref_name } => format!("{host}\\{ref_name}"),
+            PathKind::File { path } => path.clone(),
+            PathKind::Web { url } => url.clone(),
+        }
+    }
 
-        if self.error {
-            lines.push(self.error_text.to_string().red().into());
+    /// The base in the form of the 1C connection dialog, e.g.
+    /// `Srvr="host";Ref="ref";`.
+    pub fn connection_string(&self) -> String {
+        match self {
+            PathKind::Server { host, ref_name } => {
+                format!("Srvr={};Ref={};", quote(host), quote(ref_name))
+            }
+            PathKind::File { path } => format!("File={};", quote(path)),
+            PathKind::Web { url } => format!("ws={};", quote(url)),
         }
+    }
 
-        if self.designer {
-            lines.push("Ctrl+D: Designer (on)".green().into());
-        } else {
-            lines.push("Ctrl+D: Designer (off)".into());
+    pub fn name(&self) -> String {
+        let last_segment = |s: &str| {
+            s.split(['/', '\\'])
+                .rfind(|segment| !segment.is_empty())
+                .unwrap_or(s)
+                .to_string()
         };
+        match self {
+            PathKind::Server { ref_name, .. } => ref_name.clone(),
+            PathKind::File { path } => last_segment(path),
+            PathKind::Web { url } => last_segment(url),
+        }
+    }
+}
 
-        let config_widget = Paragraph::new(lines);
-        frame.render_widget(config_widget, area);
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LaunchMode {
+    #[default]
+    Enterprise,
+    Designer,
+    Thick,
+    WebBrowser,
+}
+
+impl LaunchMode {
+    pub const ALL: [LaunchMode; 4] = [
+        LaunchMode::Enterprise,
+        LaunchMode::Designer,
+        LaunchMode::Thick,
+        LaunchMode::WebBrowser,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LaunchMode::Enterprise => "Enterprise",
+            LaunchMode::Designer => "Designer",
+            LaunchMode::Thick => "Thick client",
+            LaunchMode::WebBrowser => "Web browser",
+        }
     }
 
-    fn render_history(&mut self, frame: &mut Frame, area: Rect) {
-        let list = List::new(self.history.clone())
-            .block(Block::bordered().title("History"))
-            .highlight_style(Style::new().reversed());
-            // .highlight_symbol(">>");
-        frame.render_stateful_widget(list, area, &mut self.history_state);
+    pub fn hotkey(&self) -> char {
+        match self {
+            LaunchMode::Enterprise => 'e',
+            LaunchMode::Designer => 'd',
+            LaunchMode::Thick => 't',
+            LaunchMode::WebBrowser => 'w',
+        }
     }
+}
 
-    fn add_to_history(&mut self, path: String) -> Result<(), std::io::Error> {
-        if !self.history.contains(&path) {
-            self.history.insert(0, path);
-        } else if let Some(index) = self.history.iter().position(|x| x.to_string() == path.to_string()) {
-            let removed_value = self.history.remove(index);
-            self.history.insert(0, removed_value);
+/// Client picked explicitly instead of leaving the choice to the starter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Client {
+    /// `1cv8c.exe` of the platform
+    Thin,
+    /// `1cv8.exe` of the platform
+    Thick,
+    /// The url of a web base in the default browser
+    Web,
+}
+
+impl Client {
+    pub fn mode(self) -> LaunchMode {
+        match self {
+            Client::Thin => LaunchMode::Enterprise,
+            Client::Thick => LaunchMode::Thick,
+            Client::Web => LaunchMode::WebBrowser,
         }
-        self.dump_history()
     }
 
-    fn load_history(&mut self) {
-        if let Ok(lines) = read_lines("./rbaserun_history.txt") {
-            for line in lines.map_while(Result::ok) {
-                self.history.push(line);
-            }
+    pub fn label(self) -> &'static str {
+        match self {
+            Client::Thin => "thin",
+            Client::Thick => "thick",
+            Client::Web => "web",
         }
     }
 
-    fn dump_history(&self) -> Result<(), std::io::Error> {
-        if let Ok(mut file) = File::create("./rbaserun_history.txt") {
-            for line in &self.history {
-                writeln!(file, "{}", line)?;
+    /// Thin and thick clients are started from an installed platform.
+    pub fn needs_platform(self) -> bool {
+        self != Client::Web
+    }
+}
+
+/// Cleans up strings copied from 1C dialogs, chats and documents: BOMs,
+/// zero-width and non-breaking spaces, line breaks, surrounding quotes and the
+/// doubled quotes of escaped strings. Quotes doubled inside a value of an
+/// unescaped string are left to `ConnectionString`.
+pub fn normalize_input(input: &str) -> String {
+    let cleaned: String = input
+        .chars()
+        .filter(|c| !matches!(c, '\u{feff}' | '\u{200b}' | '\u{200e}' | '\u{200f}'))
+        .map(|c| {
+            if matches!(c, '\u{a0}' | '\u{202f}' | '\r' | '\n' | '\t') {
+                ' '
+            } else {
+                c
             }
+        })
+        .collect();
+    let mut s = cleaned.trim();
+    let mut escaped = false;
+    for (open, close) in [('"', '"'), ('\'', '\''), ('«', '»'), ('“', '”')] {
+        if s.len() > 1 && s.starts_with(open) && s.ends_with(close) {
+            s = s[open.len_utf8()..s.len() - close.len_utf8()].trim();
+            escaped = open == '"';
+            break;
         }
-        Ok(())
+    }
+    // every quote doubled, e.g. a v8i line copied from a CSV cell
+    escaped |= s.contains("\"\"") && !s.replace("\"\"", "").contains('"');
+    if escaped {
+        s.replace("\"\"", "\"")
+    } else {
+        s.to_string()
     }
 }
 
-fn try_parse_and_launch(path: String, designer: bool) -> Result<(), Box<dyn Error>> {
-    let parsed_path = match parse_base_path(&path) {
-        Ok(path) => path,
-        Err(e) => return Err(format!("Parsing error: {}", e).into()),
-    };
+/// The base of a connection string (see `ConnectionString`), or of the
+/// `host;ref` shorthand. Values keep their case, file paths on case-sensitive
+/// shares included.
+pub fn parse_base_path(input_path: &str) -> Result<PathKind, Box<dyn Error>> {
+    let normalized = normalize_input(input_path);
+    let s = normalized.as_str();
 
-    match launch_base(parsed_path, designer) {
-        Ok(()) => {}
-        Err(e) => return Err(format!("Launcher error: {}", e).into()),
-    };
+    if s.contains('=') {
+        ConnectionString::parse(s)
+            .and_then(|connection| connection.path())
+            .map_err(|e| format!("Could not parse provided path: {e}").into())
+    } else if s.contains(";") {
+        parse_base_simple_form(s)
+    } else {
+        Err(format!("Could not parse provided path: {input_path}").into())
+    }
+}
 
-    Ok(())
+fn parse_base_simple_form(input: &str) -> Result<PathKind, Box<dyn Error>> {
+    let captures = Regex::new(r"(.+)[;/\\](.+)")
+        .unwrap()
+        .captures(input.trim_end_matches(';'))
+        .ok_or("expected pattern: host[;/\\]ref")?;
+    Ok(PathKind::Server {
+        host: captures[1].trim().to_string(),
+        ref_name: captures[2].trim().to_string(),
+    })
 }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+/// Switches of a launch picked with safe mode in the mode picker: the safe
+/// mode of the client and no splash screen or startup messages in the way.
+const SAFE_MODE_SWITCHES: [&str; 3] = ["/SAFEMODE", "/DisableSplash", "/DisableStartupMessages"];
+
+/// Switches of a launch with `Action::ClearCache`: the client drops its
+/// cache of the base, the usual first fix for forms that broke after an update.
+const CLEAR_CACHE_SWITCHES: [&str; 1] = ["/ClearCache"];
+
+/// How bases are started, shared by the picker, the command line and the server.
+#[derive(Debug, Clone, Default)]
+pub struct Launcher {
+    /// Starter (`1cestart`) receiving the connection
+    pub starter: PathBuf,
+    /// Platform whose client is started directly, bypassing the starter
+    pub platform: Option<Platform>,
+    pub metrics: Metrics,
+    /// Print the command of a launch instead of running it
+    pub dry_run: bool,
+    /// Keep the client of a launch to wait for its exit, see `watch::Watched`
+    pub wait: bool,
+    /// 1C switches given after `--`, appended to the ones saved with the base
+    pub switches: Vec<String>,
+    /// Settings of the reachability check run before each launch, if enabled
+    pub precheck: Option<CheckConfig>,
+    /// Seconds after a launch in which launching the base again is confirmed
+    /// first, 0 for never
+    pub duplicate_window: u64,
+    /// Values of the `{name}` placeholders of the connection string, see
+    /// `placeholders`
+    pub vars: BTreeMap<String, String>,
+    /// `[browser]` of the config, web bases are opened in
+    pub browser: BrowserConfig,
+    /// Browser given with `--browser`, over the ones of the config and the base
+    pub browser_program: Option<PathBuf>,
+    /// `[hooks]` of the config, run around every launch
+    pub hooks: Hooks,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::parse();
+impl Launcher {
+    pub fn new(config: &Config, platform: Option<Platform>) -> Launcher {
+        Launcher {
+            starter: config.starter.clone(),
+            platform,
+            metrics: Metrics::new(config.metrics),
+            dry_run: false,
+            wait: false,
+            switches: Vec::new(),
+            precheck: config.precheck.then(|| config.check.clone()),
+            duplicate_window: config.duplicate_window_secs,
+            vars: BTreeMap::new(),
+            browser: config.browser.clone(),
+            browser_program: None,
+            hooks: config.hooks.clone(),
+        }
+    }
 
-    if let Some(path) = cli.path {
-        try_parse_and_launch(path, cli.designer)
-    } else {
-        let mut terminal = ratatui::init();
-        let app_result = App::default().run(&mut terminal);
-        ratatui::restore();
-        app_result
+    /// Client started for `mode`: the one of the chosen platform, the starter otherwise.
+    pub fn binary(&self, mode: LaunchMode) -> PathBuf {
+        match &self.platform {
+            Some(platform) => platform.binary(mode),
+            None => self.starter.clone(),
+        }
     }
+
+    /// This launcher with `switches` added, for one launch in safe mode or
+    /// clearing the cache.
+    pub fn with_switches(&self, switches: &[&str]) -> Launcher {
+        let mut launcher = self.clone();
+        launcher
+            .switches
+            .extend(switches.iter().map(|switch| switch.to_string()));
+        launcher
+    }
+
+    /// Extra switches of a launch of `path`: the ones saved with the base,
+    /// then the ones of the command line, with their placeholders filled.
+    pub fn switches(&self, path: &str, bases: &Bases) -> Result<Vec<String>, Box<dyn Error>> {
+        let saved = bases.find(path).map_or(&[][..], |entry| &entry.switches);
+        saved
+            .iter()
+            .chain(&self.switches)
+            .map(|switch| placeholders::fill(switch, &self.vars))
+            .collect()
+    }
+
+    /// What a launch of `path` adds to the connection: the switches, the
+    /// browser and the environment of the client.
+    pub fn options(&self, path: &str, bases: &Bases) -> Result<LaunchOptions, Box<dyn Error>> {
+        Ok(LaunchOptions {
+            switches: self.switches(path, bases)?,
+            browser: self.browser(path, bases),
+            process: bases
+                .find(path)
+                .map(|entry| entry.process.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Browser a web launch of `path` opens in: `[browser]` of the config,
+    /// overridden by the one saved with the base, then by `--browser`.
+    pub fn browser(&self, path: &str, bases: &Bases) -> BrowserConfig {
+        let saved = bases.find(path).and_then(|entry| entry.browser.as_ref());
+        let browser = saved.map_or_else(|| self.browser.clone(), |saved| self.browser.merge(saved));
+        BrowserConfig {
+            program: self.browser_program.clone().or(browser.program),
+            ..browser
+        }
+    }
+}
+
+/// Settings of a launch besides the connection, see `Launcher::options`.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub switches: Vec<String>,
+    pub browser: BrowserConfig,
+    pub process: ProcessEnv,
+}
+
+/// Command line of the 1C client opening `path` in `mode`.
+fn client_args(path: PathKind, mode: LaunchMode, credentials: &Credentials) -> Vec<String> {
+    let launch_mode = if mode == LaunchMode::Designer {
+        "DESIGNER"
+    } else {
+        "ENTERPRISE"
+    };
+    let (key, target) = match path {
+        PathKind::Server { host, ref_name } => ("/S", format!("{host}\\{ref_name}")),
+        PathKind::File { path } => ("/F", path),
+        PathKind::Web { url } => ("/WS", url),
+    };
+    let mut args = vec![launch_mode.to_string(), key.to_string(), target];
+    if mode == LaunchMode::Thick {
+        args.push("/RunModeOrdinaryApplication".to_string());
+    }
+    if let Some(user) = &credentials.user {
+        args.extend(["/N".to_string(), user.clone()]);
+    }
+    if let Some(password) = &credentials.password {
+        args.extend(["/P".to_string(), password.clone()]);
+    }
+    args
+}
+
+fn launch_base(
+    path: PathKind,
+    mode: LaunchMode,
+    launcher: &Launcher,
+    credentials: &Credentials,
+    options: &LaunchOptions,
+    log: Option<&Path>,
+) -> Result<Child, Box<dyn Error>> {
+    if mode == LaunchMode::WebBrowser {
+        let PathKind::Web { url } = path else {
+            return Err("Web browser mode is only available for ws= bases".into());
+        };
+        return open_in_browser(&url, &options.browser, &options.process);
+    }
+
+    let binary = launcher.binary(mode);
+    if !binary.exists() {
+        return Err(match launcher.platform {
+            Some(_) => format!("Could not locate 1C client: '{}'", binary.display()),
+            None => format!(
+                "Could not locate 1C starter app: '{}', set `starter` in the config",
+                binary.display()
+            ),
+        }
+        .into());
+    }
+
+    let mut command = Command::new(binary);
+    command.args(client_args(path, mode, credentials));
+    command.args(&options.switches);
+    set_up_process(&mut command, &options.process)?;
+    // a waited for client reports what went wrong through /Out and stderr
+    if let Some(log) = log {
+        command.arg("/Out").arg(log).stderr(Stdio::piped());
+    }
+    Ok(command.spawn()?)
+}
+
+/// Applies the working directory and the variables saved with a base.
+fn set_up_process(command: &mut Command, process: &ProcessEnv) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = &process.working_dir {
+        if !dir.is_dir() {
+            return Err(format!("Working directory '{}' does not exist", dir.display()).into());
+        }
+        command.current_dir(dir);
+    }
+    command.envs(&process.env);
+    Ok(())
+}
+
+fn open_in_browser(
+    url: &str,
+    browser: &BrowserConfig,
+    process: &ProcessEnv,
+) -> Result<Child, Box<dyn Error>> {
+    let mut command = match browser.command_line(url) {
+        Some((program, args)) => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        None => {
+            let mut command = Command::new(platform::browser_opener());
+            command.arg(browser.url(url));
+            command
+        }
+    };
+    set_up_process(&mut command, process)?;
+    command
+        .spawn()
+        .map_err(|e| format!("Could not open browser: {e}").into())
+}
+
+/// What the picker is doing, each mode with its own keys and rendering: the
+/// picker itself, a prompt in the input or one of the popups.
+#[derive(Debug, Default)]
+enum Mode {
+    #[default]
+    Normal,
+    /// The input holds a prompt instead of the filter
+    Editing(Prompt),
+    Errors,
+    Editor(EntryEditor),
+    /// Editor closed with unsaved changes, waiting for them to be discarded or kept
+    DiscardEdits(EntryEditor),
+    Picker(ModePicker),
+    Confirm(Confirm),
+    Login(LoginPanel),
+    /// Values of the placeholders of a base, asked for before launching it
+    Variables(VariablesPanel),
+    Share(SharePanel),
+    /// Report of the launch just made, with `--report`
+    Report(ReportPanel),
+    /// Connection string assembled field by field
+    Builder(ConnectionBuilder),
+    Plugins(PluginsPanel),
+    Sets(SetsPanel),
+    Cluster(ClusterBrowser),
+    Usage(UsagePanel),
+    /// Locks of the selected server base
+    Access(Box<AccessPanel>),
+    /// End of the event log of the selected base
+    Log(LogPanel),
+    Activity(ActivityPanel),
+    /// Notes of the versions since the last run, once after an update
+    WhatsNew(WhatsNewPanel),
+    /// The pinned bases, launched with a single key
+    Favorites(FavoritesPanel),
+}
+
+/// What the input is typed for in `Mode::Editing`.
+#[derive(Debug)]
+enum Prompt {
+    /// History entry edited with `e`
+    Entry(usize),
+    /// History entry whose folder is typed after `f`
+    Folder(usize),
+    /// Recorded launches waiting for the name of the macro
+    MacroName(Vec<Step>),
+}
+
+/// A line of the history pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    /// Listed entry, see `filtered_history`
+    Entry(usize),
+    Folder {
+        name: String,
+        count: usize,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct App {
+    designer: bool,
+    input: Input,
+    errors: ErrorLog,
+    /// Connection strings of the history, shared with `launches`
+    history: Vec<Arc<str>>,
+    /// The history with its launch counts and times, for the detail pane
+    launches: Vec<HistoryEntry>,
+    /// Position in `launches` of each connection string, see `launch_of`
+    launch_index: HashMap<Arc<str>, usize>,
+    /// Launches of each base in the launch log, for `sort = "frequent"`
+    launch_stats: BTreeMap<String, journal::Stats>,
+    /// Bases of the 1C launcher that are not in the history, listed after it
+    registered: Vec<RegisteredBase>,
+    /// Bumped when `registered`, `launches` or `launch_stats` change, see `list_stamp`
+    list_revision: u64,
+    /// Index of the listed entries for `filtered_history`
+    search: RefCell<SearchIndex>,
+    /// All bases of `ibases.v8i`, `registered` leaves out those in the history
+    launcher_bases: Vec<RegisteredBase>,
+    /// The lists are still read in the background, see `start_loading`
+    loading: bool,
+    history_state: ListState,
+    config: Config,
+    /// The config file given with `--config`, read again when it changes
+    config_path: Option<PathBuf>,
+    bases: Bases,
+    launcher: Launcher,
+    /// Client toggled with Ctrl+O, `None` leaves the choice to the starter
+    client: Option<Client>,
+    /// Credentials entered with Ctrl+N and the path they were entered for
+    credentials: Option<(String, Credentials)>,
+    /// Installed platforms Ctrl+P cycles through, found on the first press
+    platforms: Vec<Platform>,
+    /// Result of the last Ctrl+T connection check and the path it was made for
+    check: Option<(String, Vec<Probe>)>,
+    /// Launches with a tunnel or a client watched with --wait, waited for on exit
+    waiting: Vec<Launched>,
+    mode: Mode,
+    detail_height: u16,
+    history_area: Rect,
+    detail_area: Rect,
+    /// Where the designer toggle of the config line was drawn, clicked to toggle it
+    designer_area: Rect,
+    resizing: bool,
+    /// Time and row of the last click in the history pane, for double-clicks
+    last_click: Option<(Instant, usize)>,
+    launched: Option<(String, LaunchMode)>,
+    /// Folders of the history pane collapsed with Left
+    collapsed: Vec<String>,
+    macros: Macros,
+    /// Launches of the macro being recorded with Ctrl+X
+    recording: Option<Vec<Step>>,
+    /// Macro picked with its function key, launched once the picker closes
+    replaying: Option<String>,
+    /// Plugin chosen with Ctrl+U, run with the selected base once the picker closed
+    plugin: Option<(Plugin, String)>,
+    print_selection: bool,
+    /// Show the report of each launch in a popup, `--report`
+    report: bool,
+    /// The connection string picked with --print-selection or the command
+    /// picked with --dry-run, printed once the picker closes
+    selection: Option<String>,
+    /// UI state last written to the session file
+    session: Session,
+    /// Entry whose last launch mode the toggles were last set from
+    recalled: Option<String>,
+    /// Only the entries whose last launch failed recently are listed, Ctrl+F
+    failed_only: bool,
+    /// Entry `r` asked to check again, launched once the check passes
+    retrying: Option<String>,
+    /// Base whose connection check runs in the background
+    checking: Option<String>,
+    /// Reachability of the bases in sight, shown next to them
+    availability: Availability,
+    /// 1C clients running on this machine, counted next to their bases
+    clients: LocalClients,
+    /// Configurations of the bases read over COM, shown in the detail pane
+    releases: Releases,
+    /// Output of the `[[columns]]` commands, shown next to the bases
+    columns: ColumnValues,
+    /// Locks of the server bases last seen or set with Alt+I, by path
+    access: HashMap<String, ras::Access>,
+    /// Script of `decorate` in the config, run when the list is built
+    decorator: Option<Decorator>,
+    /// What the script said about the listed bases, by path
+    decorations: HashMap<String, Decoration>,
+    /// Confirmation shown in the status line until the next key, e.g. after a copy
+    notice: Option<String>,
+    /// Switches of the launch under way only, `SAFE_MODE_SWITCHES` or
+    /// `CLEAR_CACHE_SWITCHES`
+    once: &'static [&'static str],
+    /// Actions used so far, the status line suggests the others
+    tips: Tips,
+    /// The platform was picked for a recalled thin or thick client, not with Ctrl+P
+    recalled_platform: bool,
+    /// Slot of the week the recalled toggles are the usual mode of, see `smart_mode`
+    guessed: Option<Slot>,
+    keymap: Keymap,
+    /// The terminal has no colors, see `tui::Capabilities`
+    monochrome: bool,
+    /// The terminal has no UTF-8, borders are drawn in ASCII
+    ascii: bool,
+    /// The terminal opens OSC 8 links, the URL of a web base is one
+    hyperlinks: bool,
+    /// Where background work posts its results while the picker runs
+    events: Option<Sender<AppEvent>>,
+    /// Layout of the last frame, following the width of the terminal
+    breakpoint: Breakpoint,
+}
+
+impl App {
+    pub fn run(&mut self, terminal: &mut Tui) -> Result<(), Box<dyn Error>> {
+        match Macros::load() {
+            Ok(macros) => self.macros = macros,
+            Err(e) => self.errors.push(e.to_string()),
+        }
+        let events = Events::start(self.watched_files());
+        self.events = Some(events.sender());
+        self.start_loading(events.sender());
+        loop {
+            self.recall_mode();
+            self.draw(terminal)?;
+            if self.update(events.next()?)? {
+                break;
+            }
+            self.save_session();
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, terminal: &mut Tui) -> io::Result<()> {
+        let running = self.running().count() as u16;
+        terminal.draw(|frame| {
+            self.breakpoint = Breakpoint::of(frame.area().width, &self.config.layout);
+            let favorites = match self.breakpoint {
+                Breakpoint::Wide => self.pinned(),
+                _ => Vec::new(),
+            };
+            let panes = layout::panes(
+                frame.area(),
+                self.breakpoint,
+                running,
+                self.detail_height,
+                !favorites.is_empty(),
+            );
+            self.history_area = panes.history;
+            self.detail_area = panes.details;
+
+            self.render_input(frame, panes.input);
+            self.render_config(frame, panes.config);
+            self.render_running(frame, panes.running);
+            favorites::render_pane(
+                frame,
+                panes.favorites,
+                &favorites,
+                &self.keymap.label(Action::Favorites),
+            );
+            self.render_history(frame, panes.history);
+            self.render_details(frame, panes.details);
+
+            match &mut self.mode {
+                Mode::Normal | Mode::Editing(_) => {}
+                Mode::Errors => self.errors.render(frame),
+                Mode::Editor(editor) => editor.render(frame),
+                Mode::DiscardEdits(editor) => {
+                    editor.render(frame);
+                    editor.render_discard_prompt(frame);
+                }
+                Mode::Picker(picker) => picker.render(frame),
+                Mode::Confirm(confirm) => confirm.render(frame),
+                Mode::Login(login) => login.render(frame),
+                Mode::Variables(variables) => variables.render(frame),
+                Mode::Share(share) => share.render(frame),
+                Mode::Report(report) => report.render(frame),
+                Mode::Builder(builder) => builder.render(frame),
+                Mode::Plugins(plugins) => plugins.render(frame),
+                Mode::Sets(sets) => sets.render(frame),
+                Mode::Cluster(browser) => browser.render(frame),
+                Mode::Usage(usage) => usage.render(frame),
+                Mode::Access(access) => access.render(frame),
+                Mode::Log(log) => log.render(frame),
+                Mode::Activity(activity) => activity.render(frame),
+                Mode::WhatsNew(whats_new) => whats_new.render(frame),
+                Mode::Favorites(favorites) => favorites.render(frame),
+            }
+            if self.monochrome {
+                tui::strip_colors(frame.buffer_mut());
+            }
+            if self.ascii {
+                tui::ascii_symbols(frame.buffer_mut());
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Applies one event to the state. Returns `true` when the picker should
+    /// close, after a launch or to quit.
+    fn update(&mut self, event: AppEvent) -> Result<bool, Box<dyn Error>> {
+        match event {
+            AppEvent::Key(event) => return self.handle_event(event),
+            AppEvent::Tick => {
+                self.poll_watched();
+                self.probe_visible();
+                self.read_release();
+                if let Some(sender) = self.events.as_ref().filter(|_| self.config.local_sessions) {
+                    self.clients.scan(sender);
+                }
+            }
+            AppEvent::CheckResult { path, probes } => return Ok(self.finish_check(path, probes)?),
+            AppEvent::Availability { path, status } => self.availability.finish(path, status),
+            AppEvent::LocalClients(clients) => self.clients.finish(clients),
+            AppEvent::Release { path, release } => self.releases.finish(path, release),
+            AppEvent::ColumnValues { path, values } => match self.columns.finish(path, values) {
+                Some(Ok(())) => self.notice = Some("Columns refreshed".to_string()),
+                Some(Err(e)) => self.errors.push(e),
+                None => {}
+            },
+            AppEvent::LaunchResult(result) => self.finish_launch(result),
+            AppEvent::FilesChanged => self.reload_files(),
+            AppEvent::Loaded(loaded) => self.finish_loading(loaded),
+        }
+        Ok(false)
+    }
+
+    /// Handles terminal input with the keys of the current mode.
+    fn handle_event(&mut self, event: Event) -> Result<bool, Box<dyn Error>> {
+        if matches!(event, Event::Key(_) | Event::Paste(_)) {
+            self.notice = None;
+        }
+        // safe mode lasts from the mode picker to the launch, not beyond a
+        // popup cancelled on the way
+        if matches!(self.mode, Mode::Normal) {
+            self.once = &[];
+        }
+        match event {
+            Event::Key(key_event) => match mem::take(&mut self.mode) {
+                Mode::Normal => return self.handle_normal_key(key_event, &event),
+                Mode::Editing(prompt) => self.mode = self.handle_prompt_key(prompt, key_event),
+                mode => {
+                    self.mode = mode;
+                    return Ok(self.handle_popup_event(&event)?);
+                }
+            },
+            Event::Mouse(mouse_event) => return self.handle_mouse_event(mouse_event),
+            Event::Paste(text) if matches!(self.mode, Mode::Normal | Mode::Editing(_)) => {
+                self.paste(&text)
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Keys of the picker itself: the keymap, the keys of the selected
+    /// history entry and typing the filter.
+    fn handle_normal_key(
+        &mut self,
+        key_event: KeyEvent,
+        event: &Event,
+    ) -> Result<bool, Box<dyn Error>> {
+        if key_event.kind != KeyEventKind::Press
+            || self.handle_history_key(key_event)
+            || self.handle_folder_key(key_event)
+        {
+            return Ok(false);
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let typed = !key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && matches!(key_event.code, KeyCode::Char(_));
+        let action = self
+            .keymap
+            .action(&key_event, self.history_state.selected().is_some());
+        match keys::binding(key_event.code) {
+            KeyCode::Char('q')
+                if !ctrl
+                    && action.is_none()
+                    && self.config.quit_on_q
+                    && self.history_state.selected().is_some() =>
+            {
+                return Ok(true);
+            }
+            KeyCode::F(n) if action.is_none() && self.recording.is_none() => {
+                if let Some(recorded) = self.macros.by_key(n) {
+                    self.replaying = Some(recorded.name.clone());
+                    return Ok(true);
+                }
+            }
+            _ => match action {
+                // only launching and the errors popup are left in a kiosk build
+                Some(action) if KIOSK && !action.in_kiosk() => {}
+                Some(action) => {
+                    if self.handle_action(action)? {
+                        return Ok(true);
+                    }
+                }
+                None if ctrl && KIOSK => {}
+                None => self.history_state.select(None),
+            },
+        };
+        // keys bound without Ctrl or Alt are not typed
+        if !action.is_some_and(|_| typed) {
+            self.input.handle_event(event);
+        }
+        Ok(false)
+    }
+
+    /// Keys of the input while it holds a prompt: Enter finishes it, Esc
+    /// drops it and the rest edit the text.
+    fn handle_prompt_key(&mut self, prompt: Prompt, key_event: KeyEvent) -> Mode {
+        if key_event.kind != KeyEventKind::Press {
+            return Mode::Editing(prompt);
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => {
+                self.input.reset();
+                Mode::Normal
+            }
+            KeyCode::Enter => {
+                let result = match &prompt {
+                    Prompt::Entry(index) => self.finish_edit(*index),
+                    Prompt::Folder(index) => self.finish_filing(*index),
+                    Prompt::MacroName(steps) => self.save_macro(steps),
+                };
+                match result {
+                    Ok(()) => {
+                        self.input.reset();
+                        Mode::Normal
+                    }
+                    Err(e) => {
+                        self.errors.push(e.to_string());
+                        Mode::Editing(prompt)
+                    }
+                }
+            }
+            _ if self.keymap.action(&key_event, false) == Some(Action::Paste) => {
+                self.paste_clipboard();
+                Mode::Editing(prompt)
+            }
+            _ => {
+                self.input.handle_event(&Event::Key(key_event));
+                Mode::Editing(prompt)
+            }
+        }
+    }
+
+    /// Runs a key binding of the picker. Returns `true` when the picker should
+    /// close, after a launch or to quit.
+    fn handle_action(&mut self, action: Action) -> Result<bool, Box<dyn Error>> {
+        if self.config.tips {
+            self.tips.record(action);
+        }
+        match action {
+            Action::Quit
+                if self.config.esc == EscAction::Clear && !self.input.value().is_empty() =>
+            {
+                self.input.reset();
+                self.history_state.select(None);
+            }
+            Action::Quit => return Ok(true),
+            Action::ToggleDesigner => match lockdown::check_mode(LaunchMode::Designer) {
+                Ok(()) => {
+                    self.designer = !self.designer;
+                    self.guessed = None;
+                }
+                Err(e) => self.errors.push(e.to_string()),
+            },
+            Action::Paste => self.paste_clipboard(),
+            Action::RecordMacro => self.toggle_recording(),
+            Action::Sets => {
+                let path = self.current_path();
+                let mode = self.default_mode(&path);
+                self.mode = Mode::Sets(SetsPanel::new(self.macros.clone(), path, mode));
+            }
+            Action::Activity => self.mode = Mode::Activity(ActivityPanel::new(journal::load())),
+            Action::FocusClient => {
+                let path = self.current_path();
+                match self.clients.of(&path).first() {
+                    Some(pid) => match platform::focus_window(*pid) {
+                        Ok(()) => return Ok(true),
+                        Err(e) => self.errors.push(e.to_string()),
+                    },
+                    None => self
+                        .errors
+                        .push(format!("{} is not open on this machine", path.trim())),
+                }
+            }
+            Action::Plugins => {
+                let plugins = plugins::discover();
+                if plugins.is_empty() {
+                    self.errors
+                        .push("No rbaserun-<name> plugins found on PATH".to_string());
+                } else {
+                    self.mode = Mode::Plugins(PluginsPanel::new(plugins));
+                }
+            }
+            Action::RefreshColumns => self.refresh_columns(),
+            Action::ClearCache => {
+                let path = self.current_path();
+                if path.trim().is_empty() {
+                    self.errors
+                        .push("Select a base to clear the cache of".to_string());
+                } else {
+                    self.once = &CLEAR_CACHE_SWITCHES;
+                    let mode = self.default_mode(&path);
+                    if self.start_launch(path, mode)? {
+                        return Ok(true);
+                    }
+                }
+            }
+            Action::ResetSettings => {
+                let path = self.current_path();
+                if path.trim().is_empty() {
+                    self.errors
+                        .push("Select a base to reset the settings of".to_string());
+                } else {
+                    self.confirm_reset_settings(path);
+                }
+            }
+            Action::BuildConnection => {
+                self.mode = Mode::Builder(ConnectionBuilder::new(&self.current_path()))
+            }
+            Action::BrowseCluster => {
+                let host = ras::browse_host(&self.current_path());
+                match ClusterBrowser::open(host.as_deref(), &self.config.ras) {
+                    Ok(browser) => self.mode = Mode::Cluster(browser),
+                    Err(e) => self.errors.push(e.to_string()),
+                }
+            }
+            Action::Licenses => match ras::browse_host(&self.current_path()) {
+                Some(host) => {
+                    match UsagePanel::new(&host, self.config.licenses.as_ref(), &self.config.ras) {
+                        Ok(usage) => self.mode = Mode::Usage(usage),
+                        Err(e) => self.errors.push(e.to_string()),
+                    }
+                }
+                None => self
+                    .errors
+                    .push("Type a host or select a server base to see its licenses".to_string()),
+            },
+            Action::InfobaseAccess => {
+                let path = self.current_path();
+                let credentials = self.credentials_for(&path);
+                match AccessPanel::open(&path, credentials, &self.config.ras) {
+                    Ok(panel) => {
+                        self.access.insert(panel.path.clone(), panel.access());
+                        self.mode = Mode::Access(Box::new(panel));
+                    }
+                    Err(e) => self.errors.push(e.to_string()),
+                }
+            }
+            Action::Favorites => match self.favorites() {
+                Some(favorites) => self.mode = Mode::Favorites(favorites),
+                None => self.errors.push(format!(
+                    "No pinned bases, {} pins the selected entry",
+                    self.keymap.label(Action::Pin)
+                )),
+            },
+            Action::EventLog => match LogPanel::open(&self.current_path(), &self.config.ras) {
+                Ok(log) => self.mode = Mode::Log(log),
+                Err(e) => self.errors.push(e.to_string()),
+            },
+            Action::NextPlatform => self.next_platform(),
+            Action::NextClient => self.next_client(),
+            Action::Credentials => {
+                let path = self.current_path();
+                if !path.trim().is_empty() {
+                    let credentials = self.credentials_for(&path);
+                    let saved = self
+                        .bases
+                        .find(&path)
+                        .is_some_and(|entry| entry.user.is_some());
+                    self.mode = Mode::Login(LoginPanel::new(path, credentials, saved));
+                }
+            }
+            Action::Share => {
+                let path = self.current_path();
+                match SharePanel::new(&path) {
+                    Ok(share) => self.mode = Mode::Share(share),
+                    Err(e) => self.errors.push(e.to_string()),
+                }
+            }
+            Action::Errors => {
+                self.errors.open();
+                self.mode = Mode::Errors;
+            }
+            Action::EditEntry => {
+                let path = self.current_path();
+                if self.bases.is_managed(&path) {
+                    self.errors
+                        .push(format!("{path} is managed by the machine configuration"));
+                } else if !path.trim().is_empty() {
+                    self.mode =
+                        Mode::Editor(EntryEditor::new(path.clone(), self.bases.find(&path)));
+                }
+            }
+            Action::Sources => {
+                let path = self.current_path();
+                if self.open_sources(&path) && self.start_launch(path, LaunchMode::Designer)? {
+                    return Ok(true);
+                }
+            }
+            Action::Check => self.start_check(self.current_path()),
+            Action::ShowFailed => {
+                self.failed_only = !self.failed_only;
+                self.history_state.select_first();
+            }
+            Action::Rdp => {
+                let path = self.current_path();
+                match rdp::connect(&path, &self.bases) {
+                    Ok(()) => return Ok(true),
+                    Err(e) => self.errors.push(e.to_string()),
+                }
+            }
+            Action::Launch => {
+                if let Some(selected) = self.selected_entry() {
+                    self.input = selected.into();
+                    self.history_state.select(None);
+                } else if !self.input.value().is_empty() {
+                    let path = self.bases.expand_alias(self.input.value());
+                    if self.print_selection {
+                        self.selection = Some(path);
+                        return Ok(true);
+                    } else if self.config.ask_mode && !KIOSK {
+                        let mode = self.default_mode(&path);
+                        self.mode = Mode::Picker(ModePicker::new(path, mode));
+                    } else if self.start_launch(path.clone(), self.default_mode(&path))? {
+                        return Ok(true);
+                    }
+                }
+            }
+            Action::NextMatch => self.cycle_selection(true),
+            Action::PreviousMatch => self.cycle_selection(false),
+            Action::Up => self.history_state.select_previous(),
+            Action::Down => self.history_state.select_next(),
+            // handled with the selected entry in handle_history_key
+            Action::Delete
+            | Action::EditPath
+            | Action::Pin
+            | Action::Copy
+            | Action::MoveToFolder
+            | Action::Retry
+            | Action::MoveUp
+            | Action::MoveDown => self.history_state.select(None),
+        }
+        Ok(false)
+    }
+
+    fn save_session(&mut self) {
+        if !self.config.restore_session {
+            return;
+        }
+        let session = Session {
+            input: self.input.value().to_string(),
+            selected: self.history_state.selected(),
+            offset: self.history_state.offset(),
+            designer: self.designer,
+            detail_height: self.detail_height,
+            collapsed: self.collapsed.clone(),
+        };
+        if session != self.session {
+            session.save();
+            self.session = session;
+        }
+    }
+
+    fn restore_session(&mut self, session: Session) {
+        self.input = session.input.as_str().into();
+        self.history_state = ListState::default()
+            .with_selected(session.selected)
+            .with_offset(session.offset);
+        self.designer = session.designer && !KIOSK;
+        self.detail_height = session.detail_height;
+        self.collapsed = session.collapsed.clone();
+        self.session = session;
+    }
+
+    /// Dragging the border of the detail pane in every mode; clicks, double
+    /// clicks and the wheel in the history pane and clicks on the designer
+    /// toggle while no popup is open. Returns `true` when a double click
+    /// launched and the picker should close.
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<bool, Box<dyn Error>> {
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let normal = matches!(self.mode, Mode::Normal);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                // only the detail pane below the history has a border to drag
+                self.resizing = self.breakpoint == Breakpoint::Normal
+                    && self.detail_area.height > 0
+                    && mouse_event.row == self.detail_area.y;
+                if self.resizing || !normal {
+                    return Ok(false);
+                }
+                if self.designer_area.contains(position) {
+                    self.notice = None;
+                    return self.handle_action(Action::ToggleDesigner);
+                }
+                if let Some(row) = self.history_row_at(position) {
+                    self.notice = None;
+                    return self.click_history(row);
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.resizing => {
+                let bottom = self.detail_area.bottom();
+                let max_height = bottom.saturating_sub(self.history_area.y + 3);
+                self.detail_height = bottom
+                    .saturating_sub(mouse_event.row)
+                    .clamp(3, max_height.max(3));
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.resizing = false,
+            MouseEventKind::ScrollDown if normal && self.history_area.contains(position) => {
+                self.history_state.scroll_down_by(SCROLL_LINES);
+            }
+            MouseEventKind::ScrollUp if normal && self.history_area.contains(position) => {
+                self.history_state.scroll_up_by(SCROLL_LINES);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Row of the history pane under the mouse, inside its border.
+    fn history_row_at(&self, position: Position) -> Option<usize> {
+        let inner = self.history_area.inner(Margin::new(1, 1));
+        if !inner.contains(position) {
+            return None;
+        }
+        let row = self.history_state.offset() + usize::from(position.y - inner.y);
+        (row < self.history_rows().len()).then_some(row)
+    }
+
+    /// Selects the clicked row; a second click on it soon after launches the
+    /// entry like Enter twice, or folds the folder like Enter.
+    fn click_history(&mut self, row: usize) -> Result<bool, Box<dyn Error>> {
+        let now = Instant::now();
+        let double = self.last_click.is_some_and(|(time, clicked)| {
+            clicked == row && now.duration_since(time) < DOUBLE_CLICK
+        });
+        self.last_click = (!double).then_some((now, row));
+        self.history_state.select(Some(row));
+        if !double {
+            return Ok(false);
+        }
+        if self.handle_folder_key(KeyEvent::from(KeyCode::Enter)) {
+            return Ok(false);
+        }
+        // the first Launch takes the entry into the input, the second launches it
+        Ok(self.handle_action(Action::Launch)? || self.handle_action(Action::Launch)?)
+    }
+
+    /// Passes a key to the open popup and moves on to the next modal state.
+    /// Returns `true` when a base was launched and the app should exit.
+    fn handle_popup_event(&mut self, event: &Event) -> Result<bool, io::Error> {
+        self.mode = match mem::take(&mut self.mode) {
+            mode @ (Mode::Normal | Mode::Editing(_)) => mode,
+            Mode::Errors if self.handle_errors_event(event) => Mode::Errors,
+            Mode::Errors => Mode::Normal,
+            Mode::Editor(mut editor) => match editor.handle_event(event) {
+                EditorAction::None => Mode::Editor(editor),
+                EditorAction::Cancel if editor.is_dirty() => Mode::DiscardEdits(editor),
+                EditorAction::Cancel => Mode::Normal,
+                EditorAction::Save => match self.save_entry(&editor) {
+                    Ok(()) => Mode::Normal,
+                    Err(e) => {
+                        editor.error = Some(e.to_string());
+                        Mode::Editor(editor)
+                    }
+                },
+            },
+            Mode::DiscardEdits(editor) => match editor.handle_discard_event(event) {
+                DiscardAction::None => Mode::DiscardEdits(editor),
+                DiscardAction::Discard => Mode::Normal,
+                DiscardAction::Keep => Mode::Editor(editor),
+            },
+            Mode::Picker(mut picker) => match picker.handle_event(event) {
+                PickerAction::None => Mode::Picker(picker),
+                PickerAction::Cancel => Mode::Normal,
+                PickerAction::Pick(mode) => {
+                    if picker.safe_mode {
+                        self.once = &SAFE_MODE_SWITCHES;
+                    }
+                    return self.start_launch(picker.path, mode);
+                }
+            },
+            Mode::Confirm(mut confirm) => match confirm.handle_event(event) {
+                ConfirmAction::None => Mode::Confirm(confirm),
+                ConfirmAction::Cancel => Mode::Normal,
+                ConfirmAction::Focus(pid) => match platform::focus_window(pid) {
+                    Ok(()) => return Ok(true),
+                    Err(e) => {
+                        self.errors.push(e.to_string());
+                        Mode::Normal
+                    }
+                },
+                ConfirmAction::Confirmed => match confirm.guard {
+                    Guard::Duplicate => return self.guard_production(confirm.path, confirm.mode),
+                    Guard::Production => return self.launch(confirm.path, confirm.mode),
+                    Guard::ResetSettings => {
+                        self.reset_settings(&confirm.path);
+                        Mode::Normal
+                    }
+                },
+            },
+            Mode::Variables(mut variables) => match variables.handle_event(event) {
+                VariablesAction::None => Mode::Variables(variables),
+                VariablesAction::Cancel => Mode::Normal,
+                VariablesAction::Apply => match variables.missing() {
+                    Some(name) => {
+                        self.errors.push(format!("Type a value for {{{name}}}"));
+                        Mode::Variables(variables)
+                    }
+                    None => {
+                        self.launcher.vars.extend(variables.values());
+                        return self.guard_launch(variables.path, variables.mode);
+                    }
+                },
+            },
+            Mode::Login(mut login) => match login.handle_event(event) {
+                LoginAction::None => Mode::Login(login),
+                LoginAction::Cancel => Mode::Normal,
+                LoginAction::Apply => {
+                    self.apply_login(&login);
+                    Mode::Normal
+                }
+            },
+            Mode::Share(mut share) => match share.handle_event(event) {
+                ShareAction::None => Mode::Share(share),
+                ShareAction::Close => Mode::Normal,
+            },
+            Mode::Report(mut report) => match report.handle_event(event) {
+                ReportAction::None => Mode::Report(report),
+                ReportAction::Close => Mode::Normal,
+            },
+            Mode::Plugins(mut plugins) => match plugins.handle_event(event) {
+                PluginsAction::None => Mode::Plugins(plugins),
+                PluginsAction::Close => Mode::Normal,
+                PluginsAction::Run(plugin) => {
+                    self.plugin = Some((plugin, self.current_path()));
+                    return Ok(true);
+                }
+            },
+            Mode::Builder(mut builder) => match builder.handle_event(event) {
+                BuilderAction::None => Mode::Builder(builder),
+                BuilderAction::Cancel => Mode::Normal,
+                BuilderAction::Done(built) => {
+                    self.use_built(built);
+                    Mode::Normal
+                }
+            },
+            Mode::Sets(mut sets) => match sets.handle_event(event) {
+                SetsAction::None => Mode::Sets(sets),
+                SetsAction::Close => {
+                    self.macros = sets.macros;
+                    Mode::Normal
+                }
+                SetsAction::Launch(name) => {
+                    self.replaying = Some(name);
+                    return Ok(true);
+                }
+            },
+            Mode::Activity(mut activity) => match activity.handle_event(event) {
+                ActivityAction::None => Mode::Activity(activity),
+                ActivityAction::Close => Mode::Normal,
+                ActivityAction::Select(path) => {
+                    self.input = path.into();
+                    self.history_state.select(None);
+                    Mode::Normal
+                }
+            },
+            Mode::Favorites(mut favorites) => match favorites.handle_event(event) {
+                FavoritesAction::None => Mode::Favorites(favorites),
+                FavoritesAction::Close => Mode::Normal,
+                FavoritesAction::Launch(path) => {
+                    let mode = self.default_mode(&path);
+                    return self.start_launch(path, mode);
+                }
+            },
+            Mode::WhatsNew(mut whats_new) => match whats_new.handle_event(event) {
+                WhatsNewAction::None => Mode::WhatsNew(whats_new),
+                WhatsNewAction::Close => Mode::Normal,
+            },
+            Mode::Usage(mut usage) => match usage.handle_event(event) {
+                UsageAction::None => Mode::Usage(usage),
+                UsageAction::Close => Mode::Normal,
+            },
+            Mode::Log(mut log) => match log.handle_event(event) {
+                LogAction::None => Mode::Log(log),
+                LogAction::Close => Mode::Normal,
+            },
+            Mode::Access(mut panel) => match panel.handle_event(event) {
+                AccessAction::None => Mode::Access(panel),
+                AccessAction::Close => Mode::Normal,
+                AccessAction::Saved(access) => {
+                    self.notice = Some(format!("Saved the locks of {}", panel.path));
+                    self.access.insert(panel.path, access);
+                    Mode::Normal
+                }
+            },
+            Mode::Cluster(mut browser) => match browser.handle_event(event) {
+                BrowserAction::None => Mode::Cluster(browser),
+                BrowserAction::Close => Mode::Normal,
+                BrowserAction::Select(path) => {
+                    self.input = path.into();
+                    self.history_state.select(None);
+                    Mode::Normal
+                }
+                BrowserAction::Import(listed) => {
+                    match ras::import(browser.ras(), &listed, &mut self.bases) {
+                        Ok(added) => {
+                            self.load_history();
+                            browser.notify(
+                                format!("Imported {} infobases, {added} new", listed.len()),
+                                false,
+                            );
+                        }
+                        Err(e) => browser.notify(e.to_string(), true),
+                    }
+                    Mode::Cluster(browser)
+                }
+            },
+        };
+        Ok(false)
+    }
+
+    /// Uses the credentials of the login panel for the next launches of its
+    /// base, saving or forgetting them as chosen.
+    fn apply_login(&mut self, login: &LoginPanel) {
+        let credentials = login.credentials();
+        let saved = self
+            .bases
+            .find(&login.path)
+            .is_some_and(|entry| entry.user.is_some());
+        let result = if login.remember {
+            credentials::remember(&login.path, &credentials, &mut self.bases)
+        } else if saved {
+            credentials::remember(&login.path, &Credentials::default(), &mut self.bases)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result {
+            self.errors.push(format!("Could not save credentials: {e}"));
+        }
+        self.credentials = Some((login.path.clone(), credentials));
+    }
+
+    /// Puts the connection of the builder in the input, saving the user and
+    /// switches typed for it to `bases.toml`.
+    fn use_built(&mut self, built: Built) {
+        if built.user.is_some() || !built.switches.is_empty() {
+            let entry = self.bases.entry_mut(&built.path);
+            if built.user.is_some() {
+                entry.user = built.user;
+            }
+            if !built.switches.is_empty() {
+                entry.switches = built.switches;
+            }
+            if let Err(e) = self.bases.save() {
+                self.errors
+                    .push(format!("Could not save {}: {e}", built.path));
+            }
+        }
+        self.input = built.path.into();
+        self.history_state.select(None);
+    }
+
+    /// Credentials entered with Ctrl+N for `path`, the saved ones otherwise.
+    fn credentials_for(&self, path: &str) -> Credentials {
+        match &self.credentials {
+            Some((entered, credentials)) if entered.trim() == path.trim() => credentials.clone(),
+            _ => credentials::stored(path, &self.bases),
+        }
+    }
+
+    /// Credentials shown in the command preview: the saved password is not
+    /// read from the keyring on every frame.
+    fn preview_credentials(&self, path: &str) -> Credentials {
+        match &self.credentials {
+            Some((entered, credentials)) if entered.trim() == path.trim() => credentials.clone(),
+            _ => Credentials {
+                user: self.bases.find(path).and_then(|entry| entry.user.clone()),
+                password: None,
+            },
+        }
+    }
+
+    /// Returns `false` once the errors popup should close.
+    fn handle_errors_event(&mut self, event: &Event) -> bool {
+        let Event::Key(key_event) = event else {
+            return true;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return true;
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => return false,
+            KeyCode::Char('l') if ctrl => return false,
+            KeyCode::Up => self.errors.scroll_up(1),
+            KeyCode::Down => self.errors.scroll_down(1),
+            KeyCode::PageUp => self.errors.scroll_up(10),
+            KeyCode::PageDown => self.errors.scroll_down(10),
+            KeyCode::Char('c') => {
+                let notice = match self.errors.latest() {
+                    Some(error) => match clipboard::copy(&error.text) {
+                        Ok(()) => "Copied latest error to clipboard".to_string(),
+                        Err(e) => e.to_string(),
+                    },
+                    None => "Nothing to copy".to_string(),
+                };
+                self.errors.set_notice(notice);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Switches to the next installed platform, newest first, and back to the starter.
+    fn next_platform(&mut self) {
+        self.recalled_platform = false;
+        if self.platforms.is_empty() {
+            self.platforms = platform::installed();
+        }
+        if self.platforms.is_empty() {
+            self.errors.push("No installed 1C platform found".into());
+            return;
+        }
+        let current = self
+            .launcher
+            .platform
+            .as_ref()
+            .and_then(|current| self.platforms.iter().position(|p| p == current));
+        self.launcher.platform = match current {
+            None => self.platforms.last().cloned(),
+            Some(0) => None,
+            Some(index) => Some(self.platforms[index - 1].clone()),
+        };
+    }
+
+    /// Switches between the starter's choice, thin, thick and web client. Thin
+    /// and thick clients start from the newest platform unless one is chosen.
+    fn next_client(&mut self) {
+        self.guessed = None;
+        let next = match self.client {
+            None => Some(Client::Thin),
+            Some(Client::Thin) => Some(Client::Thick),
+            Some(Client::Thick) => Some(Client::Web),
+            Some(Client::Web) => None,
+        };
+        self.set_client(next);
+    }
+
+    fn set_client(&mut self, next: Option<Client>) {
+        if next.is_some_and(Client::needs_platform) && self.launcher.platform.is_none() {
+            if self.platforms.is_empty() {
+                self.platforms = platform::installed();
+            }
+            match self.platforms.last() {
+                Some(newest) => self.launcher.platform = Some(newest.clone()),
+                None => {
+                    self.errors.push(
+                        "No installed 1C platform found for the thin and thick client".into(),
+                    );
+                    self.client = Some(Client::Web);
+                    return;
+                }
+            }
+        }
+        self.client = next;
+    }
+
+    /// Inserts text pasted from chats and documents into the input, cleaned up
+    /// like typed input, so that it becomes the base to launch.
+    fn paste(&mut self, text: &str) {
+        let text = normalize_input(text);
+        if self.history_state.selected().is_some() {
+            self.input.reset();
+            self.history_state.select(None);
+        }
+        for c in text.chars() {
+            self.input.handle(InputRequest::InsertChar(c));
+        }
+    }
+
+    fn paste_clipboard(&mut self) {
+        match clipboard::paste() {
+            Ok(text) if text.trim().is_empty() => {
+                self.errors.push("The clipboard holds no text".to_string());
+            }
+            Ok(text) => self.paste(&text),
+            Err(e) => self.errors.push(e.to_string()),
+        }
+    }
+
+    /// Sets the Ctrl+D and Ctrl+O toggles to the mode the highlighted entry was
+    /// last launched in, once each time another entry is highlighted. Entries
+    /// never launched keep the toggles as they are.
+    fn recall_mode(&mut self) {
+        let selected = self.selected_entry();
+        if selected == self.recalled {
+            return;
+        }
+        self.recalled = selected;
+        self.guessed = None;
+        let Some((designer, client, slot)) = self
+            .recalled
+            .as_deref()
+            .and_then(|path| self.recalled_mode(path))
+        else {
+            return;
+        };
+        self.designer = designer && !KIOSK && lockdown::check_mode(LaunchMode::Designer).is_ok();
+        if self.designer == designer {
+            self.guessed = slot;
+        }
+        if KIOSK {
+            return;
+        }
+        if self.recalled_platform {
+            self.launcher.platform = None;
+        }
+        let starter = self.launcher.platform.is_none();
+        self.set_client(client);
+        self.recalled_platform = starter && self.launcher.platform.is_some();
+    }
+
+    /// The Ctrl+D toggle and client recalled for `path`, with the slot of the
+    /// week when `smart_mode` took them from the usual mode of the slot.
+    fn recalled_mode(&self, path: &str) -> Option<(bool, Option<Client>, Option<Slot>)> {
+        let entry = self.launch_of(path)?;
+        if self.config.smart_mode
+            && let Some((designer, client, slot)) = entry.usual(&Local::now())
+        {
+            return Some((designer, client, Some(slot)));
+        }
+        let (designer, client) = entry.remembered()?;
+        Some((designer, client, None))
+    }
+
+    /// Designer when toggled, then the chosen client, otherwise the client the
+    /// base was last opened with.
+    fn default_mode(&self, path: &str) -> LaunchMode {
+        if self.designer {
+            LaunchMode::Designer
+        } else if let Some(client) = self.client {
+            client.mode()
+        } else if self
+            .bases
+            .find(path)
+            .is_some_and(|entry| entry.prefer_browser)
+        {
+            LaunchMode::WebBrowser
+        } else {
+            // designer as the default only turns the Ctrl+D toggle on at startup
+            match self.config.default_mode_for(path) {
+                LaunchMode::Designer => LaunchMode::Enterprise,
+                mode => mode,
+            }
+        }
+    }
+
+    /// Opens the sources repository of the base in the configured editor.
+    fn open_sources(&mut self, path: &str) -> bool {
+        let Some(sources) = self
+            .bases
+            .find(path)
+            .and_then(|entry| entry.sources.as_ref())
+        else {
+            self.errors
+                .push("No sources repository set for this base".into());
+            return false;
+        };
+        // the path goes in a variable, the shell never parses it
+        let line = format!(
+            "{} {}",
+            self.config.editor,
+            platform::shell_var("RBASERUN_SOURCES")
+        );
+        match platform::shell(&line)
+            .env("RBASERUN_SOURCES", sources)
+            .spawn()
+        {
+            Ok(_) => true,
+            Err(e) => {
+                self.errors.push(format!("Could not start editor: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Remembers whether a web base was opened in the thin client or the browser.
+    fn remember_web_client(&mut self, path: &str, mode: LaunchMode) {
+        let browser = match mode {
+            LaunchMode::WebBrowser => true,
+            LaunchMode::Enterprise | LaunchMode::Thick => false,
+            LaunchMode::Designer => return,
+        };
+        if !matches!(parse_base_path(path), Ok(PathKind::Web { .. })) {
+            return;
+        }
+        if self
+            .bases
+            .find(path)
+            .is_some_and(|entry| entry.prefer_browser)
+            == browser
+        {
+            return;
+        }
+        self.bases.entry_mut(path).prefer_browser = browser;
+        if let Err(e) = self.bases.save() {
+            self.errors.push(format!("Could not save bases: {e}"));
+        }
+    }
+
+    fn save_entry(&mut self, editor: &EntryEditor) -> Result<(), Box<dyn Error>> {
+        if let Some(alias) = editor.alias() {
+            check_alias(&self.bases, &editor.path, &alias)?;
+        }
+        let was_production = self.bases.is_production(&editor.path);
+        let before = self.bases.find(&editor.path).cloned();
+        editor.apply(self.bases.entry_mut(&editor.path))?;
+        if was_production
+            && !self.bases.is_production(&editor.path)
+            && let Err(e) = lockdown::check_removal(&editor.path)
+        {
+            if let Some(before) = before {
+                *self.bases.entry_mut(&editor.path) = before;
+            }
+            return Err(e);
+        }
+        self.bases.save()
+    }
+
+    /// Launches `path`, asking for the values of its placeholders first.
+    fn start_launch(&mut self, path: String, mode: LaunchMode) -> Result<bool, io::Error> {
+        let path = normalize_input(&path);
+        let mode = if KIOSK { LaunchMode::Enterprise } else { mode };
+        if let Err(e) = lockdown::check_mode(mode) {
+            self.errors.push(e.to_string());
+            return Ok(false);
+        }
+        let mut names = placeholders::names(&path);
+        let saved = self
+            .bases
+            .find(&path)
+            .map_or(&[][..], |entry| &entry.switches);
+        for switch in saved.iter().chain(&self.launcher.switches) {
+            for name in placeholders::names(switch) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        if !names.is_empty() {
+            let variables = VariablesPanel::new(path, mode, names, &self.launcher.vars);
+            self.mode = Mode::Variables(variables);
+            return Ok(false);
+        }
+        self.guard_launch(path, mode)
+    }
+
+    /// Launches `path` unless it was launched moments ago or is still
+    /// running, which is confirmed first.
+    fn guard_launch(&mut self, path: String, mode: LaunchMode) -> Result<bool, io::Error> {
+        if !self.launcher.dry_run && !self.print_selection {
+            let running = self.running().any(|watched| watched.path == path);
+            let open = self.clients.of(&path);
+            if self.bases.is_single_instance(&path) {
+                match open.first() {
+                    Some(pid) => match clients::focus_single(&path, *pid) {
+                        Ok(()) => return Ok(true),
+                        Err(e) => {
+                            self.errors.push(e);
+                            return Ok(false);
+                        }
+                    },
+                    None if running => {
+                        self.errors.push(format!(
+                            "{} allows a single session and is still running",
+                            path.trim()
+                        ));
+                        return Ok(false);
+                    }
+                    // a client started seconds ago may not be listed yet
+                    None => {}
+                }
+            }
+            let focus = open.first().copied();
+            let prompt = if running {
+                Some(format!("{} is still running.", path.trim()))
+            } else if !open.is_empty() {
+                Some(format!(
+                    "{} has {}.",
+                    path.trim(),
+                    clients::label(open.len())
+                ))
+            } else {
+                journal::launched_within(&path, self.launcher.duplicate_window)
+                    .map(|ago| format!("{} was launched {ago} s ago.", path.trim()))
+            };
+            if let Some(prompt) = prompt {
+                self.mode = Mode::Confirm(Confirm::duplicate(
+                    path,
+                    mode,
+                    format!("{prompt} Launch it again?"),
+                    focus,
+                ));
+                return Ok(false);
+            }
+        }
+        self.guard_production(path, mode)
+    }
+
+    /// Asks before removing the user settings of `path`, which no client of
+    /// this machine may have open.
+    fn confirm_reset_settings(&mut self, path: String) {
+        let registered = ibases::load(self.config.ibases_file.clone());
+        let dir = match settings::dir(&path, &self.bases, &registered) {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.errors.push(e.to_string());
+                return;
+            }
+        };
+        if !clients::running(&path).is_empty() {
+            self.errors.push(format!(
+                "{} is open on this machine, close it first",
+                path.trim()
+            ));
+            return;
+        }
+        let word = production_guard_word(&path);
+        self.mode = Mode::Confirm(Confirm::reset_settings(
+            path,
+            format!(
+                "The forms, windows and other user settings kept in {} will be reset.",
+                dir.display()
+            ),
+            word,
+        ));
+    }
+
+    fn reset_settings(&mut self, path: &str) {
+        let registered = ibases::load(self.config.ibases_file.clone());
+        match settings::reset(path, &self.bases, &registered) {
+            Ok(dir) => {
+                self.notice = Some(format!("Removed the user settings in {}", dir.display()))
+            }
+            Err(e) => self.errors.push(e.to_string()),
+        }
+    }
+
+    /// Launches `path` unless it is a production base opened in designer
+    /// mode, which is confirmed first.
+    fn guard_production(&mut self, path: String, mode: LaunchMode) -> Result<bool, io::Error> {
+        if mode == LaunchMode::Designer && self.bases.is_production(&path) && !self.launcher.dry_run
+        {
+            let word = production_guard_word(&path);
+            self.mode = Mode::Confirm(Confirm::new(
+                path,
+                "This is a production base, launching DESIGNER.".into(),
+                word,
+            ));
+            return Ok(false);
+        }
+        self.launch(path, mode)
+    }
+
+    /// Returns `true` when the base was launched and the picker should close.
+    fn launch(&mut self, path: String, mode: LaunchMode) -> Result<bool, io::Error> {
+        let once = mem::take(&mut self.once);
+        let extended = (!once.is_empty()).then(|| self.launcher.with_switches(once));
+        let launcher = extended.as_ref().unwrap_or(&self.launcher);
+        if launcher.dry_run {
+            let credentials = self.credentials_for(&path);
+            match script::plan(&path, mode, &self.bases, launcher, &credentials) {
+                Ok(plan) => self.selection = Some(plan.preview()),
+                Err(e) => self.errors.push(e.to_string()),
+            }
+            return Ok(self.selection.is_some());
+        }
+        match try_parse_and_launch(
+            path.clone(),
+            mode,
+            &self.bases,
+            launcher,
+            &self.credentials_for(&path),
+        ) {
+            Ok(mut launched) => {
+                let watched = launched.watched.is_some();
+                let report = launched.report.take().filter(|_| self.report);
+                if !launched.is_detached() {
+                    self.waiting.push(launched);
+                }
+                self.remember_web_client(&path, mode);
+                self.launched = Some((path.clone(), mode));
+                self.add_to_history(path.clone(), mode)?;
+                if let Some(report) = &report {
+                    self.mode = Mode::Report(ReportPanel::new(report));
+                }
+                // the picker stays open to report how a watched client exits
+                // and to show the report of the launch
+                if (watched || report.is_some()) && self.recording.is_none() {
+                    if watched {
+                        self.notice =
+                            Some(format!("Launched {}, waiting for it to exit", path.trim()));
+                    }
+                    self.input.reset();
+                    self.history_state.select(None);
+                    return Ok(false);
+                }
+                // the picker stays open while a macro is recorded
+                if let Some(steps) = &mut self.recording {
+                    steps.push(Step {
+                        path,
+                        mode,
+                        delay: None,
+                    });
+                    self.input.reset();
+                    self.history_state.select(None);
+                    return Ok(false);
+                }
+                Ok(true)
+            }
+            Err(e) => {
+                self.errors.push(e.to_string());
+                // shows the failure recorded in the history
+                self.set_launches(history::load_entries());
+                Ok(false)
+            }
+        }
+    }
+
+    /// Starts recording a macro, or stops and asks for its name.
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            None => self.recording = Some(Vec::new()),
+            Some(steps) if steps.is_empty() => {}
+            Some(steps) => {
+                self.mode = Mode::Editing(Prompt::MacroName(steps));
+                self.input.reset();
+                self.history_state.select(None);
+            }
+        }
+    }
+
+    fn save_macro(&mut self, steps: &[Step]) -> Result<(), Box<dyn Error>> {
+        let name = self.input.value().trim().to_string();
+        if name.is_empty() {
+            return Err("Type a name for the macro".into());
+        }
+        let mut macros = Macros::load()?;
+        macros.record(&name, steps.to_vec());
+        macros.save()?;
+        self.macros = macros;
+        Ok(())
+    }
+
+    fn current_path(&self) -> String {
+        self.selected_entry()
+            .unwrap_or_else(|| self.bases.expand_alias(self.input.value()))
+    }
+
+    fn selected_entry(&self) -> Option<String> {
+        match self.selected_row()? {
+            Row::Entry(index) => Some(self.listed_path(index).to_string()),
+            Row::Folder { .. } => None,
+        }
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        let selected = self.history_state.selected()?;
+        let rows = self.history_rows();
+        // the list clamps the selection only when it is drawn
+        rows.get(selected).or(rows.last()).cloned()
+    }
+
+    fn render_input(&self, frame: &mut Frame, area: Rect) {
+        let width = area.width.max(3) - 3;
+        let scroll = self.input.visual_scroll(width as usize);
+        let title = match &self.mode {
+            Mode::Editing(Prompt::Entry(_)) => "Edit history entry (Enter: save, Esc: cancel):",
+            Mode::Editing(Prompt::MacroName(_)) => "Macro name (Enter: save, Esc: discard):",
+            Mode::Editing(Prompt::Folder(_)) => {
+                "Folder (Enter: move, empty: no folder, Esc: cancel):"
+            }
+            _ => "Base path:",
+        };
+        let mut block = Block::bordered()
+            .title(title)
+            .border_style(theme::input_border());
+        if self.designer {
+            block = block.border_style(theme::warning()).title(
+                Line::from(Span::styled(" DESIGNER ", theme::designer().bold())).right_aligned(),
+            );
+        }
+        let input_widget = Paragraph::new(self.input.value())
+            .scroll((0, scroll as u16))
+            .block(block);
+
+        frame.render_widget(input_widget, area);
+
+        let x = self.input.visual_cursor().max(scroll) - scroll + 1;
+        frame.set_cursor_position((area.x + x as u16, area.y + 1));
+    }
+
+    /// The clients launched with --wait that are still running.
+    fn running(&self) -> impl Iterator<Item = &Watched> {
+        self.waiting
+            .iter()
+            .filter_map(|launched| launched.watched.as_ref())
+    }
+
+    /// Posts a `LaunchResult` for each watched client that exited since the
+    /// last tick, and closes the tunnels whose clients are gone.
+    fn poll_watched(&mut self) {
+        let Some(sender) = &self.events else {
+            return;
+        };
+        let mut index = 0;
+        while index < self.waiting.len() {
+            let result = match self.waiting[index].try_finish() {
+                Ok(None) => None,
+                Ok(Some(outcome)) => Some(Ok(outcome)),
+                Err(e) => Some(Err(e.to_string())),
+            };
+            if let Some(result) = result {
+                let _ = sender.send(AppEvent::LaunchResult(result));
+            }
+            if self.waiting[index].is_done() {
+                self.waiting.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Reports how a watched client exited.
+    fn finish_launch(&mut self, result: Result<Outcome, String>) {
+        match result.map(|outcome| (outcome.result(), outcome.path)) {
+            Ok((Ok(()), path)) => self.notice = Some(format!("{} exited normally", path.trim())),
+            Ok((Err(e), _)) => {
+                self.notice = None;
+                self.errors.push(e.to_string());
+            }
+            Err(e) => self.errors.push(e),
+        }
+        self.load_history();
+    }
+
+    /// The files behind the lists of the picker that other processes may
+    /// write: the history, the bases file unless it lives on a jump host, the
+    /// config files and the `ibases.v8i` of the 1C launcher.
+    fn watched_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![history::file().to_path_buf()];
+        if self.config.remote.is_none() {
+            files.push(bases::file());
+        }
+        files.extend(Config::files(self.config_path.as_deref()).unwrap_or_default());
+        if self.config.ibases {
+            files.extend(
+                self.config
+                    .ibases_file
+                    .clone()
+                    .or_else(ibases::default_file),
+            );
+        }
+        files
+    }
+
+    /// Reads the lists again after another process wrote one of the
+    /// `watched_files`, e.g. another rbaserun or 1CEStart. Of the config only
+    /// the settings of the lists are applied, the rest takes a restart.
+    fn reload_files(&mut self) {
+        match Config::load(self.config_path.as_deref()) {
+            Ok(config) => {
+                self.config.ibases = config.ibases;
+                self.config.ibases_file = config.ibases_file;
+                self.config.sort = config.sort;
+            }
+            Err(e) => self.errors.push(e.to_string()),
+        }
+        if self.config.remote.is_none() {
+            match Bases::load(None) {
+                Ok(bases) => self.bases = bases,
+                Err(e) => self.errors.push(e.to_string()),
+            }
+        }
+        self.load_history();
+    }
+
+    fn render_running(&self, frame: &mut Frame, area: Rect) {
+        if area.height == 0 {
+            return;
+        }
+        let lines: Vec<Line> = self
+            .running()
+            .map(|watched| {
+                let seconds = watched.started.elapsed().as_secs();
+                Line::from(vec![
+                    Span::raw(watched.path.trim().to_string()),
+                    Span::styled(
+                        format!(
+                            "  {}, {}:{:02}",
+                            watched.mode.label(),
+                            seconds / 60,
+                            seconds % 60
+                        ),
+                        theme::hint(),
+                    ),
+                ])
+            })
+            .collect();
+        let block = Block::bordered().title(format!("Running ({})", lines.len()));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_config(&mut self, frame: &mut Frame, area: Rect) {
+        let mut lines = Vec::new();
+
+        if let Some(notice) = &self.notice {
+            lines.push(Line::styled(notice.as_str(), theme::success()));
+        } else if let Some(error) = self.errors.latest() {
+            let hint = format!("  {}: details", self.keymap.label(Action::Errors));
+            let available = (area.width as usize).saturating_sub(hint.len());
+            let text = if error.text.chars().count() > available {
+                let truncated: String = error
+                    .text
+                    .chars()
+                    .take(available.saturating_sub(1))
+                    .collect();
+                format!("{truncated}…")
+            } else {
+                error.text.clone()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(text, theme::error()),
+                Span::styled(hint, theme::hint()),
+            ]));
+        } else if self.config.tips
+            && !KIOSK
+            && let Some(tip) = self.tips.current(&self.keymap, Local::now().timestamp())
+        {
+            lines.push(Line::styled(tip, theme::hint()));
+        }
+
+        self.designer_area = Rect::default();
+        if KIOSK {
+            lines.push(Line::styled(
+                format!(
+                    "{}: launch   {}: errors",
+                    self.keymap.label(Action::Launch),
+                    self.keymap.label(Action::Errors)
+                ),
+                theme::hint(),
+            ));
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        let designer = self.keymap.label(Action::ToggleDesigner);
+        let mut line = if lockdown::get().disable_designer {
+            Line::styled(format!("{designer}: Designer (disabled)"), theme::hint())
+        } else if self.designer {
+            Line::styled(
+                format!("{designer}: Designer (on)"),
+                theme::warning().bold(),
+            )
+        } else {
+            Line::from(format!("{designer}: Designer (off)"))
+        };
+        self.designer_area = Rect {
+            y: area.y + lines.len() as u16,
+            width: (line.width() as u16).min(area.width),
+            height: 1,
+            ..area
+        }
+        .intersection(area);
+        if let Some(slot) = self.guessed {
+            line.push_span(Span::styled(
+                format!(" usual on {}", slot.label()),
+                theme::hint(),
+            ));
+        }
+        line.push_span(format!("   {}: ", self.keymap.label(Action::NextPlatform)));
+        line.push_span(match &self.launcher.platform {
+            Some(platform) => format!("Platform {}", platform.version_string()).bold(),
+            None => "Starter".into(),
+        });
+        line.push_span(format!(
+            "   {}: Client ",
+            self.keymap.label(Action::NextClient)
+        ));
+        line.push_span(match self.client {
+            Some(client) => client.label().bold(),
+            None => "auto".into(),
+        });
+        if let Some(steps) = &self.recording {
+            line.push_span(Span::styled(
+                format!(
+                    "   {}: Recording ({} launched)",
+                    self.keymap.label(Action::RecordMacro),
+                    steps.len()
+                ),
+                theme::error().bold(),
+            ));
+        }
+        lines.push(line);
+
+        let config_widget = Paragraph::new(lines);
+        frame.render_widget(config_widget, area);
+    }
+
+    fn render_details(&self, frame: &mut Frame, area: Rect) {
+        if area.height == 0 {
+            return;
+        }
+
+        let path = self.current_path();
+        let mut lines: Vec<Line> = Vec::new();
+        if !path.trim().is_empty() && !self.prompts_name() {
+            match parse_base_path(&path) {
+                Ok(PathKind::Server { host, ref_name }) => {
+                    lines.push(format!("Server: {host}").into());
+                    lines.push(format!("Ref:    {ref_name}").into());
+                }
+                Ok(PathKind::File { path }) => lines.push(format!("File:   {path}").into()),
+                Ok(PathKind::Web { url }) => lines.push(format!("Web:    {url}").into()),
+                Err(e) => lines.push(Line::styled(e.to_string(), theme::hint())),
+            }
+            let mode = self.default_mode(&path);
+            if let Ok(plan) = script::plan(
+                &path,
+                mode,
+                &self.bases,
+                &self.launcher,
+                &self.preview_credentials(&path),
+            ) {
+                lines.push(format!("Run:    {}", plan.preview()).into());
+            }
+            if let Some(entry) = self.bases.find(&path) {
+                if let Some(name) = &entry.name {
+                    lines.push(format!("Name:   {name}").into());
+                }
+                if let Some(alias) = &entry.alias {
+                    lines.push(format!("Alias:  {alias}").into());
+                }
+                if !entry.tags.is_empty() {
+                    lines.push(format!("Tags:   {}", entry.tags.join(", ")).into());
+                }
+                if let Some(tunnel) = &entry.tunnel {
+                    lines.push(
+                        format!(
+                            "Tunnel: via {}, local port {}",
+                            tunnel.ssh, tunnel.local_port
+                        )
+                        .into(),
+                    );
+                }
+                if let Some(rdp) = &entry.rdp {
+                    lines.push(format!("RDP:    {rdp}").into());
+                }
+                if let Some(dir) = &entry.process.working_dir {
+                    lines.push(format!("Dir:    {}", dir.display()).into());
+                }
+                if !entry.process.env.is_empty() {
+                    let env: Vec<String> = entry
+                        .process
+                        .env
+                        .iter()
+                        .map(|(name, value)| format!("{name}={value}"))
+                        .collect();
+                    lines.push(format!("Env:    {}", env.join(" ")).into());
+                }
+                if let Some(user) = &entry.user {
+                    lines.push(format!("User:   {user}").into());
+                }
+                if let Some(window) = maintenance::current(&entry.maintenance) {
+                    lines.push(Line::styled(
+                        format!("Maint:  {}", window.label()),
+                        theme::warning(),
+                    ));
+                }
+                if let Some(sources) = &entry.sources {
+                    let branch =
+                        git::branch(sources).unwrap_or_else(|| "not a git repository".into());
+                    lines.push(format!("Git:    {} ({branch})", sources.display()).into());
+                }
+            }
+            if let Some(summary) = self.access.get(path.trim()).and_then(access_summary) {
+                lines.push(Line::styled(format!("Locks:  {summary}"), theme::warning()));
+            }
+            if let Some(release) = self.releases.get(&path) {
+                lines.push(Line::from(vec![
+                    format!("Config: {} {} ", release.name, release.version).into(),
+                    Span::styled(
+                        format!("({})", locale::ago(Local::now().timestamp(), release.at)),
+                        theme::hint(),
+                    ),
+                ]));
+            } else if self.releases.is_reading(&path) {
+                lines.push(Line::styled("Config: reading...", theme::hint()));
+            } else if let Some(reason) = self.releases.failure(&path) {
+                lines.push(Line::from(vec![
+                    "Config: ".into(),
+                    Span::styled(reason, theme::error()),
+                ]));
+            }
+            if let Some(reason) = crash::abnormal_end(
+                &path,
+                self.launch_of(&path),
+                // without the scan a lock file can't be told from an open session
+                !self.config.local_sessions || !self.clients.of(&path).is_empty(),
+            ) {
+                lines.push(Line::styled(
+                    format!("Crash:  last session ended abnormally, {reason}"),
+                    theme::warning(),
+                ));
+                lines.push(Line::styled(
+                    format!(
+                        "        {} clears the cache, `rbaserun repair` checks the base",
+                        self.keymap.label(Action::ClearCache)
+                    ),
+                    theme::hint(),
+                ));
+            }
+            if let Some(launch) = self
+                .launch_of(&path)
+                .filter(|launch| launch.launch_count > 0)
+            {
+                lines.push(format!("Used:   {}", launch_summary(launch)).into());
+                if let Some(failure) = &launch.failure {
+                    lines.push(Line::from(vec![
+                        "Exit:   ".into(),
+                        Span::styled(failure.as_str(), theme::error()),
+                    ]));
+                } else if let Some(code) = launch.exit_code {
+                    lines.push(format!("Exit:   {code}").into());
+                }
+            }
+            let now = Local::now().timestamp();
+            for column in &self.config.columns {
+                if let Some(value) = self.columns.get(&path, &column.name) {
+                    let label = format!("{}:", column.name);
+                    lines.push(Line::from(vec![
+                        format!("{label:<8}{} ", value.text).into(),
+                        Span::styled(format!("({})", locale::ago(now, value.at)), theme::hint()),
+                    ]));
+                }
+            }
+            let running = self.clients.of(&path);
+            if !running.is_empty() {
+                let pids: Vec<String> = running.iter().map(u32::to_string).collect();
+                lines.push(
+                    format!(
+                        "Open:   {} (pid {})",
+                        clients::label(running.len()),
+                        pids.join(", ")
+                    )
+                    .into(),
+                );
+            }
+            if let Some(Status::Down(reason)) = self.availability.status(&path) {
+                lines.push(Line::from(vec![
+                    "Status: ".into(),
+                    Span::styled(reason.as_str(), theme::error()),
+                ]));
+            }
+            if self.checking.as_ref() == Some(&path) {
+                lines.push(Line::styled("Check:  running...", theme::hint()));
+            } else if let Some((_, probes)) =
+                self.check.as_ref().filter(|(checked, _)| *checked == path)
+            {
+                for probe in probes {
+                    let status = if probe.ok {
+                        Span::styled("ok", theme::success())
+                    } else {
+                        Span::styled("FAIL", theme::error())
+                    };
+                    lines.push(Line::from(vec![
+                        "Check:  ".into(),
+                        status,
+                        format!(" {}: {}", probe.name, probe.detail).into(),
+                    ]));
+                }
+            }
+        }
+
+        let details = Paragraph::new(lines).block(Block::bordered().title("Details"));
+        frame.render_widget(details, area);
+        if self.hyperlinks
+            && !self.prompts_name()
+            && let Ok(PathKind::Web { url }) = parse_base_path(&path)
+        {
+            // the URL follows the label on the first line
+            let url_area = Rect {
+                x: area.x + 1 + "Web:    ".len() as u16,
+                y: area.y + 1,
+                width: Line::from(url.as_str()).width() as u16,
+                height: 1,
+            };
+            tui::hyperlink(
+                frame.buffer_mut(),
+                url_area.intersection(area.inner(Margin::new(1, 1))),
+                &url,
+            );
+        }
+    }
+
+    fn render_history(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.history_rows();
+        let grouped = self.filter().trim().is_empty();
+        let visible = self.visible_rows(rows.len(), area.height.saturating_sub(2) as usize);
+        let items = rows.iter().enumerate().map(|(position, row)| {
+            // rows out of sight only take up their line, the list scrolls by
+            // their count
+            if !visible.contains(&position) {
+                return ListItem::new("");
+            }
+            let index = match row {
+                Row::Entry(index) => *index,
+                Row::Folder { name, count } => {
+                    let marker = if self.collapsed.contains(name) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    return ListItem::new(Line::from(vec![
+                        format!("{marker} {name}").bold(),
+                        Span::styled(format!(" ({count})"), theme::hint()),
+                    ]));
+                }
+            };
+            let indent = if grouped && self.folder_of(index).is_some() {
+                "  "
+            } else {
+                ""
+            };
+            let matched = self.listed_match(index);
+            let positions = |field: usize| {
+                matched
+                    .as_ref()
+                    .filter(|(matched_field, _)| *matched_field == field)
+                    .map_or(&[][..], |(_, matched)| matched.positions.as_slice())
+            };
+            let dark = theme::hint();
+            if let Some(registered) = index
+                .checked_sub(self.history.len())
+                .map(|index| &self.registered[index])
+            {
+                let mut spans = vec![Span::raw(indent)];
+                spans.extend(self.availability_marker(&registered.connect));
+                spans.extend(fuzzy::highlight(
+                    &registered.connect,
+                    positions(0),
+                    Style::new(),
+                ));
+                spans.push(Span::styled("  ", dark));
+                spans.extend(fuzzy::highlight(&registered.title(), positions(2), dark));
+                let marker = if registered.managed {
+                    " [managed]"
+                } else {
+                    " [1C]"
+                };
+                spans.push(Span::styled(marker, dark));
+                return ListItem::new(Line::from(spans));
+            }
+            let path = &self.history[index];
+            let entry = self.bases.find(path);
+            let mut spans = vec![Span::raw(indent)];
+            spans.extend(self.availability_marker(path));
+            let badge = self
+                .decoration(index)
+                .and_then(|decoration| decoration.badge.as_deref())
+                .or(entry.and_then(|entry| entry.badge.as_deref()));
+            if let Some(badge) = badge {
+                spans.push(Span::raw(format!("{badge} ")));
+            }
+            if let Some(alias) = entry.and_then(|entry| entry.alias.as_deref()) {
+                spans.extend(fuzzy::highlight(alias, positions(1), theme::alias()));
+                spans.push(Span::styled(": ", theme::alias()));
+            }
+            spans.extend(fuzzy::highlight(path, positions(0), Style::new()));
+            if let Ok(PathKind::Web { .. }) = parse_base_path(path) {
+                let client = if entry.is_some_and(|entry| entry.prefer_browser) {
+                    " [browser]"
+                } else {
+                    " [thin]"
+                };
+                spans.push(Span::styled(client, dark));
+            } else if let Some((designer, client, _)) = self.recalled_mode(path) {
+                // the mode Enter recalls for the entry
+                if designer {
+                    spans.push(Span::styled(" [designer]", theme::warning()));
+                } else if let Some(client) = client {
+                    spans.push(Span::styled(format!(" [{}]", client.label()), dark));
+                }
+            }
+            if let Some(name) = self.display_name(path) {
+                spans.push(Span::styled("  ", dark));
+                spans.extend(fuzzy::highlight(name, positions(2), dark));
+            }
+            if entry.is_some_and(|entry| entry.pinned) {
+                spans.push(Span::styled(" [pinned]", dark));
+            }
+            for column in &self.config.columns {
+                if let Some(value) = self
+                    .columns
+                    .get(path, &column.name)
+                    .filter(|value| !value.text.is_empty())
+                {
+                    let text = self.breakpoint.shorten(&value.text);
+                    spans.push(Span::styled(format!("  {text}"), dark));
+                }
+            }
+            let running = self.clients.of(path).len();
+            if running > 0 {
+                spans.push(Span::styled(
+                    format!(" [{}]", clients::label(running)),
+                    theme::success(),
+                ));
+            }
+            if let Some(access) = self.access.get(path.trim()) {
+                for marker in access.markers() {
+                    spans.push(Span::styled(format!(" {marker}"), theme::warning()));
+                }
+            }
+            if self.failed_recently(index) {
+                spans.push(Span::styled(" [failed]", theme::error()));
+            }
+            let item = ListItem::new(Line::from(spans));
+            match entry.and_then(|entry| entry.color()) {
+                Some(color) => item.fg(color),
+                None => item,
+            }
+        });
+        let (block, highlight_style) = if self.designer {
+            (
+                Block::bordered().border_style(theme::warning()),
+                theme::designer(),
+            )
+        } else {
+            (Block::bordered(), theme::selected())
+        };
+        let keys: Vec<String> = self
+            .macros
+            .macros
+            .iter()
+            .filter_map(|recorded| Some(format!("{}: {}", recorded.key.as_ref()?, recorded.name)))
+            .collect();
+        let block = block.title(if self.failed_only {
+            format!(
+                "Failed recently ({}: all, {}: check and retry)",
+                self.keymap.label(Action::ShowFailed),
+                self.keymap.label(Action::Retry)
+            )
+        } else if self.loading {
+            "History (loading...)".to_string()
+        } else {
+            "History".to_string()
+        });
+        let block = if keys.is_empty() {
+            block
+        } else {
+            block.title(
+                Line::styled(format!(" {} ", keys.join("  ")), theme::hint()).right_aligned(),
+            )
+        };
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(highlight_style);
+        // .highlight_symbol(">>");
+        if self.loading {
+            // a list still empty or partial would clamp the selection restored
+            // from the session
+            let mut state = self.history_state.clone();
+            frame.render_stateful_widget(list, area, &mut state);
+        } else {
+            frame.render_stateful_widget(list, area, &mut self.history_state);
+        }
+    }
+
+    /// Status marker of a listed base: answered, unreachable or not probed
+    /// yet, different symbols so they tell apart without colors too.
+    fn availability_marker(&self, path: &str) -> Option<Span<'static>> {
+        if !self.config.availability.enabled {
+            return None;
+        }
+        Some(match self.availability.status(path) {
+            Some(Status::Up) => Span::styled("● ", theme::success()),
+            Some(Status::Down(_)) => Span::styled("× ", theme::error()),
+            None => Span::styled("○ ", theme::hint()),
+        })
+    }
+
+    /// Rows of the history pane that may be drawn this frame: a page around
+    /// both the selection and the scroll offset of the last frame, which
+    /// `List` moves the offset between.
+    fn visible_rows(&self, count: usize, height: usize) -> Range<usize> {
+        // the list clamps both to the rows when it is drawn
+        let last = count.saturating_sub(1);
+        let offset = self.history_state.offset().min(last);
+        let selected = self
+            .history_state
+            .selected()
+            .map_or(offset, |selected| selected.min(last));
+        let start = offset.min(selected).saturating_sub(height);
+        let end = offset.max(selected).saturating_add(height * 2);
+        start.min(count)..end.min(count)
+    }
+
+    /// Indices of the listed entries fuzzy matching the input, best first; all
+    /// of them in order when it is empty. Bases of the 1C launcher follow the
+    /// history, numbered after it.
+    fn filtered_history(&self) -> Vec<usize> {
+        let mut search = self.search.borrow_mut();
+        search.update(
+            self.list_stamp(),
+            self.history.len() + self.registered.len(),
+            |index| self.listed_texts(index),
+        );
+        search.filter(
+            self.filter(),
+            |index| {
+                if self.failed_only && !self.failed_recently(index)
+                    || self.bases.is_elsewhere(self.listed_path(index))
+                    || self
+                        .decoration(index)
+                        .is_some_and(|decoration| decoration.hidden)
+                {
+                    return None;
+                }
+                Some(self.listed_match(index)?.1.score)
+            },
+            |scored| {
+                scored.sort_by_cached_key(|&(score, index)| {
+                    let sort = self
+                        .decoration(index)
+                        .map_or(0, |decoration| decoration.sort);
+                    (Reverse(score), sort, self.frequency_rank(index))
+                })
+            },
+        )
+    }
+
+    /// Changes whenever the listed entries, their texts or their order may
+    /// have: the history is told apart by its shared paths.
+    fn list_stamp(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for path in &self.history {
+            Arc::as_ptr(path).hash(&mut hasher);
+        }
+        (
+            self.list_revision,
+            self.bases.revision(),
+            self.failed_only,
+            self.config.sort == SortOrder::Frequent,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Pinned entries, then the most launched ones with `sort = "frequent"`;
+    /// the same for every entry otherwise, keeping the order of the history.
+    fn frequency_rank(&self, index: usize) -> (bool, Reverse<usize>) {
+        if self.config.sort != SortOrder::Frequent {
+            return (false, Reverse(0));
+        }
+        let Some(path) = self.history.get(index) else {
+            return (true, Reverse(0));
+        };
+        let pinned = self.bases.find(path).is_some_and(|entry| entry.pinned);
+        let launches = self
+            .launch_stats
+            .get(path.trim())
+            .map_or(0, |stats| stats.launches);
+        (!pinned, Reverse(launches))
+    }
+
+    /// Lines of the history pane. With the filter empty, entries in folders
+    /// follow the others below the header of their folder, hidden while it is
+    /// collapsed; a filter lists the matches without folders.
+    fn history_rows(&self) -> Vec<Row> {
+        let filtered = self.filtered_history();
+        if !self.filter().trim().is_empty() || self.failed_only {
+            return filtered.into_iter().map(Row::Entry).collect();
+        }
+        let mut rows = Vec::new();
+        let mut folders: Vec<(&str, Vec<usize>)> = Vec::new();
+        for index in filtered {
+            match self.folder_of(index) {
+                None => rows.push(Row::Entry(index)),
+                Some(folder) => match folders.iter_mut().find(|(own, _)| *own == folder) {
+                    Some((_, entries)) => entries.push(index),
+                    None => folders.push((folder, vec![index])),
+                },
+            }
+        }
+        folders.sort_by_key(|(folder, _)| folder.to_lowercase());
+        for (folder, entries) in folders {
+            rows.push(Row::Folder {
+                name: folder.to_string(),
+                count: entries.len(),
+            });
+            if !self.collapsed.iter().any(|collapsed| collapsed == folder) {
+                rows.extend(entries.into_iter().map(Row::Entry));
+            }
+        }
+        rows
+    }
+
+    /// The last launch of a listed entry failed recently. Bases of the 1C
+    /// launcher have no launches of their own.
+    fn failed_recently(&self, index: usize) -> bool {
+        let now = Local::now().timestamp();
+        self.history.get(index).is_some_and(|path| {
+            self.launch_of(path)
+                .is_some_and(|launch| launch.failed_recently(now))
+        })
+    }
+
+    /// Checks `path` in the background like Ctrl+T, the probes arrive as a
+    /// `CheckResult`.
+    fn start_check(&mut self, path: String) {
+        let Some(sender) = self.events.clone() else {
+            return;
+        };
+        let bases = self.bases.clone();
+        let (config, ras) = (self.config.check.clone(), self.config.ras.clone());
+        self.checking = Some(path.clone());
+        thread::spawn(move || {
+            let probes = check::check_path(&path, &bases, &config, &ras);
+            let _ = sender.send(AppEvent::CheckResult { path, probes });
+        });
+    }
+
+    /// Probes the bases in sight in the history pane that are due, see
+    /// `Availability`.
+    /// Reads the configuration of the selected base over COM when `[release]`
+    /// is on and the detail pane shows.
+    fn read_release(&mut self) {
+        let Some(sender) = &self.events else {
+            return;
+        };
+        if !self.config.release.enabled || self.detail_area.height == 0 {
+            return;
+        }
+        let Some(path) = self.selected_entry() else {
+            return;
+        };
+        if !matches!(
+            parse_base_path(&path),
+            Ok(PathKind::Server { .. } | PathKind::File { .. })
+        ) {
+            return;
+        }
+        let bases = &self.bases;
+        self.releases.fetch(
+            &path,
+            || credentials::stored(&path, bases),
+            self.config.release.max_age(),
+            sender,
+        );
+    }
+
+    fn probe_visible(&mut self) {
+        let Some(sender) = &self.events else {
+            return;
+        };
+        if !self.config.availability.enabled || self.loading {
+            return;
+        }
+        let rows = self.history_rows();
+        let height = self.history_area.height.saturating_sub(2) as usize;
+        let start = self.history_state.offset().min(rows.len());
+        let paths = rows[start..]
+            .iter()
+            .take(height)
+            .filter_map(|row| match row {
+                Row::Entry(index) => Some(self.listed_path(*index).to_string()),
+                Row::Folder { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        self.availability.probe(
+            paths,
+            self.config.availability.interval(),
+            &self.bases,
+            &self.config.check,
+            sender,
+        );
+    }
+
+    /// Shows the probes of a finished check. A base `r` asked to retry is
+    /// launched in the mode Enter would use once every probe passed; returns
+    /// `true` when it was.
+    fn finish_check(
+        &mut self,
+        path: String,
+        probes: Result<Vec<Probe>, String>,
+    ) -> Result<bool, io::Error> {
+        if self.checking.as_ref() == Some(&path) {
+            self.checking = None;
+        }
+        let retry = self
+            .retrying
+            .take_if(|retrying| *retrying == path)
+            .is_some();
+        match probes {
+            Ok(probes) => {
+                let reachable = probes.iter().all(|probe| probe.ok);
+                self.check = Some((path.clone(), probes));
+                if !retry {
+                    return Ok(false);
+                }
+                if !reachable {
+                    self.errors.push(format!(
+                        "{} is still unreachable, see the detail pane",
+                        path.trim()
+                    ));
+                    return Ok(false);
+                }
+            }
+            Err(e) => {
+                self.errors.push(e);
+                return Ok(false);
+            }
+        }
+        if self.print_selection {
+            self.selection = Some(path);
+            return Ok(true);
+        }
+        let mode = self.default_mode(&path);
+        self.start_launch(path, mode)
+    }
+
+    /// Folder of a listed entry, `None` at the top level.
+    fn folder_of(&self, index: usize) -> Option<&str> {
+        let folder = match index.checked_sub(self.history.len()) {
+            Some(index) => self.registered[index].folder.as_str(),
+            None => self.bases.find(&self.history[index])?.folder.as_deref()?,
+        };
+        Some(folder.trim_matches('/')).filter(|folder| !folder.is_empty())
+    }
+
+    /// The input unless it holds a macro or folder name rather than a filter.
+    fn filter(&self) -> &str {
+        if self.prompts_name() {
+            ""
+        } else {
+            self.input.value()
+        }
+    }
+
+    /// The input asks for a macro or folder name, an edited entry still
+    /// filters the history.
+    fn prompts_name(&self) -> bool {
+        matches!(
+            self.mode,
+            Mode::Editing(Prompt::Folder(_) | Prompt::MacroName(_))
+        )
+    }
+
+    /// Texts a listed entry is filtered by: the connection string, alias, name
+    /// and tags.
+    fn listed_texts(&self, index: usize) -> [Option<Cow<'_, str>>; 4] {
+        if let Some(registered) = index
+            .checked_sub(self.history.len())
+            .map(|index| &self.registered[index])
+        {
+            return [
+                Some(registered.connect.as_str().into()),
+                None,
+                Some(registered.title().into()),
+                None,
+            ];
+        }
+        let path = &self.history[index];
+        let entry = self.bases.find(path);
+        [
+            Some(path.as_ref().into()),
+            entry.and_then(|entry| entry.alias.as_deref().map(Cow::from)),
+            self.display_name(path).map(Cow::from),
+            entry
+                .filter(|entry| !entry.tags.is_empty())
+                .map(|entry| entry.tags.join(" ").into()),
+        ]
+    }
+
+    /// Name from the bases file, otherwise the one the base was launched by.
+    fn display_name(&self, path: &str) -> Option<&str> {
+        self.bases
+            .find(path)
+            .and_then(|entry| entry.name.as_deref())
+            .or_else(|| self.launch_of(path)?.name.as_deref())
+    }
+
+    /// The pinned bases in the popup, `None` without any.
+    fn favorites(&self) -> Option<FavoritesPanel> {
+        let favorites = self.pinned();
+        (!favorites.is_empty()).then(|| FavoritesPanel::new(favorites))
+    }
+
+    /// The pinned bases in the order of the history.
+    fn pinned(&self) -> Vec<Favorite> {
+        self.history
+            .iter()
+            .filter(|path| self.bases.find(path).is_some_and(|entry| entry.pinned))
+            .map(|path| {
+                let alias = self
+                    .bases
+                    .find(path)
+                    .and_then(|entry| entry.alias.as_deref());
+                let target = parse_base_path(path).map(|parsed| parsed.target());
+                Favorite {
+                    label: self
+                        .display_name(path)
+                        .or(alias)
+                        .unwrap_or(path.trim())
+                        .to_string(),
+                    target: target.unwrap_or_default(),
+                    path: path.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// The history entry of `path` with its launches.
+    fn launch_of(&self, path: &str) -> Option<&HistoryEntry> {
+        self.launch_index
+            .get(path)
+            .map(|&index| &self.launches[index])
+    }
+
+    /// Best match of the input among the texts of a listed entry, with the
+    /// position of that text in `listed_texts`.
+    fn listed_match(&self, index: usize) -> Option<(usize, fuzzy::Match)> {
+        self.listed_texts(index)
+            .iter()
+            .enumerate()
+            .filter_map(|(field, text)| {
+                Some((field, fuzzy::find(self.filter(), text.as_deref()?)?))
+            })
+            .max_by_key(|(field, matched)| (matched.score, Reverse(*field)))
+    }
+
+    /// Moves the selection through the listed entries, wrapping around.
+    fn cycle_selection(&mut self, forward: bool) {
+        let count = self.history_rows().len();
+        if count == 0 {
+            return;
+        }
+        let next = match self
+            .history_state
+            .selected()
+            .map(|selected| selected.min(count - 1))
+        {
+            None if forward => 0,
+            None => count - 1,
+            Some(selected) if forward => (selected + 1) % count,
+            Some(selected) => (selected + count - 1) % count,
+        };
+        self.history_state.select(Some(next));
+    }
+
+    fn listed_path(&self, index: usize) -> &str {
+        match index.checked_sub(self.history.len()) {
+            Some(index) => &self.registered[index].connect,
+            None => &self.history[index],
+        }
+    }
+
+    fn add_to_history(&mut self, path: String, mode: LaunchMode) -> Result<(), std::io::Error> {
+        // a base of the 1C launcher keeps its title
+        let name = self
+            .registered
+            .iter()
+            .find(|base| base.connect == path && !base.managed)
+            .map(RegisteredBase::title);
+        let path = connection::without_password(&path);
+        history::record_launch(&path, mode, self.client, name)?;
+        history::touch(&mut self.history, path.into());
+        self.load_launch_stats();
+        self.sort_pinned();
+        history::save(&self.history)
+    }
+
+    /// Moves the pinned entries above the others, keeping their order.
+    fn sort_pinned(&mut self) {
+        let bases = &self.bases;
+        self.history
+            .sort_by_key(|path| !bases.find(path).is_some_and(|entry| entry.pinned));
+    }
+
+    /// Index in the history of the selected entry, `None` for a base of the
+    /// 1C launcher or the machine config.
+    fn selected_history_index(&self) -> Option<usize> {
+        match self.selected_row()? {
+            Row::Entry(index) if index < self.history.len() => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Selects history entry `index` wherever the filter lists it.
+    fn select_history(&mut self, index: usize) {
+        let position = self
+            .history_rows()
+            .iter()
+            .position(|row| *row == Row::Entry(index));
+        self.history_state.select(position);
+    }
+
+    /// Keys managing the selected history entry: Del, `e`, `p`, `c`, `f`, `r`
+    /// and Shift+Up/Down unless remapped in the keymap. Returns `false` for
+    /// other keys and when nothing in the history is selected.
+    fn handle_history_key(&mut self, key_event: KeyEvent) -> bool {
+        if KIOSK {
+            return false;
+        }
+        let Some(index) = self.selected_history_index() else {
+            return false;
+        };
+        let result = match self.keymap.action(&key_event, true) {
+            Some(Action::Delete) => self.delete_entry(index),
+            Some(Action::EditPath) => {
+                self.input = self.history[index].as_ref().into();
+                self.mode = Mode::Editing(Prompt::Entry(index));
+                self.history_state.select(None);
+                Ok(())
+            }
+            Some(Action::Pin) => self.toggle_pin(index),
+            Some(Action::Retry) => {
+                let path = self.history[index].to_string();
+                self.retrying = Some(path.clone());
+                self.start_check(path);
+                Ok(())
+            }
+            Some(Action::Copy) => clipboard::copy(&self.history[index]).map(|()| {
+                self.notice = Some(format!("Copied {}", self.history[index]));
+            }),
+            Some(Action::MoveToFolder) => {
+                let path = &self.history[index];
+                if self.bases.is_managed(path) {
+                    Err(format!("{path} is managed by the machine configuration").into())
+                } else {
+                    let folder = self.folder_of(index).unwrap_or_default();
+                    self.input = folder.into();
+                    self.mode = Mode::Editing(Prompt::Folder(index));
+                    self.history_state.select(None);
+                    Ok(())
+                }
+            }
+            Some(Action::MoveUp) => self.move_entry(index, false),
+            Some(Action::MoveDown) => self.move_entry(index, true),
+            _ => return false,
+        };
+        if let Err(e) = result {
+            self.errors.push(e.to_string());
+        }
+        true
+    }
+
+    /// Left, Right and Enter on the folders of the history pane: Left collapses
+    /// the selected folder or the one of the selected entry, Right expands it
+    /// and Enter toggles it. Returns `false` for other keys.
+    fn handle_folder_key(&mut self, key_event: KeyEvent) -> bool {
+        let folder = match self.selected_row() {
+            Some(Row::Folder { name, .. }) => name,
+            Some(Row::Entry(index)) if key_event.code == KeyCode::Left => {
+                match self.folder_of(index) {
+                    Some(folder) if self.filter().trim().is_empty() => folder.to_string(),
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        };
+        let collapsed = self.collapsed.contains(&folder);
+        let collapse = match key_event.code {
+            KeyCode::Left => true,
+            KeyCode::Right => false,
+            KeyCode::Enter => !collapsed,
+            _ => return false,
+        };
+        if collapse && !collapsed {
+            self.collapsed.push(folder.clone());
+        } else if !collapse {
+            self.collapsed.retain(|own| *own != folder);
+        }
+        let position = self
+            .history_rows()
+            .iter()
+            .position(|row| matches!(row, Row::Folder { name, .. } if *name == folder));
+        self.history_state.select(position);
+        true
+    }
+
+    /// Moves the history entry to the folder typed in the input, out of its
+    /// folder when the input is empty.
+    fn finish_filing(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let folder = self.input.value().trim().trim_matches('/').to_string();
+        let path = self.history[index].clone();
+        self.bases.entry_mut(&path).folder = (!folder.is_empty()).then_some(folder.clone());
+        self.bases.save()?;
+        self.collapsed.retain(|own| *own != folder);
+        self.select_history(index);
+        Ok(())
+    }
+
+    fn delete_entry(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let path = &self.history[index];
+        if self.bases.is_production(path) {
+            lockdown::check_removal(path)?;
+        }
+        self.history.remove(index);
+        history::save(&self.history)?;
+        if self.history.is_empty() && self.registered.is_empty() {
+            self.history_state.select(None);
+        }
+        Ok(())
+    }
+
+    fn toggle_pin(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let path = self.history[index].clone();
+        if self.bases.is_managed(&path) {
+            return Err(format!("{path} is managed by the machine configuration").into());
+        }
+        let entry = self.bases.entry_mut(&path);
+        entry.pinned = !entry.pinned;
+        if entry.pinned {
+            let path = self.history.remove(index);
+            self.history.insert(0, path);
+        }
+        self.sort_pinned();
+        self.bases.save()?;
+        history::save(&self.history)?;
+        if let Some(index) = self.history.iter().position(|listed| *listed == path) {
+            self.select_history(index);
+        }
+        Ok(())
+    }
+
+    /// Swaps the entry with its neighbour, within the pinned or the other entries.
+    fn move_entry(&mut self, index: usize, down: bool) -> Result<(), Box<dyn Error>> {
+        if !self.input.value().trim().is_empty() {
+            return Err("Clear the filter to reorder the history".into());
+        }
+        let Some(other) = (if down {
+            Some(index + 1).filter(|&other| other < self.history.len())
+        } else {
+            index.checked_sub(1)
+        }) else {
+            return Ok(());
+        };
+        let pinned = |path: &str| self.bases.find(path).is_some_and(|entry| entry.pinned);
+        if pinned(&self.history[index]) != pinned(&self.history[other]) {
+            return Ok(());
+        }
+        self.history.swap(index, other);
+        history::save(&self.history)?;
+        self.select_history(other);
+        Ok(())
+    }
+
+    /// Replaces the edited history entry with the input, carrying its
+    /// settings in the bases file over to the new connection string.
+    fn finish_edit(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let path = normalize_input(self.input.value());
+        parse_base_path(&path).map_err(|e| format!("Parsing error: {e}"))?;
+        let old = &self.history[index];
+        if self.bases.is_production(old) && !self.bases.is_production(&path) {
+            lockdown::check_removal(old)?;
+        }
+        let old = std::mem::replace(&mut self.history[index], path.as_str().into());
+        if *old == *path {
+            return Ok(());
+        }
+        if let Some(duplicate) =
+            (0..self.history.len()).find(|&other| other != index && *self.history[other] == *path)
+        {
+            self.history.remove(duplicate);
+        }
+        if self.bases.find(&old).is_some()
+            && self.bases.find(&path).is_none()
+            && !self.bases.is_managed(&old)
+        {
+            self.bases.entry_mut(&old).path = path;
+            self.bases.save()?;
+        }
+        self.sort_pinned();
+        history::save(&self.history)?;
+        Ok(())
+    }
+
+    fn load_launch_stats(&mut self) {
+        if self.config.sort == SortOrder::Frequent {
+            self.launch_stats = journal::stats(&journal::load());
+            self.list_revision += 1;
+        }
+    }
+
+    fn load_history(&mut self) {
+        self.loading = false;
+        self.set_history(history::load_entries());
+        self.load_launch_stats();
+        self.launcher_bases = if self.config.ibases {
+            ibases::load(self.config.ibases_file.clone())
+        } else {
+            Vec::new()
+        };
+        self.list_registered();
+    }
+
+    /// Reads the history, then the bases of the 1C launcher and the launch
+    /// statistics in the background, posting each as `AppEvent::Loaded`.
+    fn start_loading(&mut self, sender: Sender<AppEvent>) {
+        self.loading = true;
+        let ibases_file = self.config.ibases.then(|| self.config.ibases_file.clone());
+        let frequent = self.config.sort == SortOrder::Frequent;
+        thread::spawn(move || {
+            let history = Loaded::History(history::load_entries());
+            if sender.send(AppEvent::Loaded(history)).is_err() {
+                return;
+            }
+            let bases = ibases_file.map(ibases::load).unwrap_or_default();
+            let stats = if frequent {
+                journal::stats(&journal::load())
+            } else {
+                BTreeMap::new()
+            };
+            let _ = sender.send(AppEvent::Loaded(Loaded::Launcher { bases, stats }));
+        });
+    }
+
+    fn finish_loading(&mut self, loaded: Loaded) {
+        // a `load_history` since has read everything already
+        if !self.loading {
+            return;
+        }
+        match loaded {
+            Loaded::History(launches) => self.set_history(launches),
+            Loaded::Launcher { bases, stats } => {
+                self.launcher_bases = bases;
+                self.launch_stats = stats;
+                self.list_revision += 1;
+                self.loading = false;
+            }
+        }
+        self.list_registered();
+    }
+
+    fn set_history(&mut self, launches: Vec<HistoryEntry>) {
+        self.set_launches(launches);
+        self.history = self
+            .launches
+            .iter()
+            .map(|launch| launch.path.clone())
+            .collect();
+        self.sort_pinned();
+    }
+
+    fn set_launches(&mut self, launches: Vec<HistoryEntry>) {
+        self.launch_index = launches
+            .iter()
+            .enumerate()
+            .map(|(index, launch)| (launch.path.clone(), index))
+            .collect();
+        self.launches = launches;
+        self.list_revision += 1;
+    }
+
+    /// Lists the bases of the machine config and of the 1C launcher that are
+    /// not in the history.
+    fn list_registered(&mut self) {
+        // the platform matches hosts and refs regardless of case
+        let target = |path: &str| {
+            parse_base_path(path)
+                .ok()
+                .map(|path| path.target().to_lowercase())
+        };
+        let mut known: HashSet<_> = self.history.iter().map(|path| target(path)).collect();
+        self.registered.clear();
+        self.list_revision += 1;
+        for entry in self.bases.managed() {
+            if !known.insert(target(&entry.path)) {
+                continue;
+            }
+            self.registered.push(RegisteredBase {
+                name: entry.name.clone().unwrap_or_default(),
+                folder: entry.folder.clone().unwrap_or_else(|| "/".to_string()),
+                connect: entry.path.clone(),
+                id: entry.id.clone(),
+                managed: true,
+            });
+        }
+        self.registered.extend(
+            self.launcher_bases
+                .iter()
+                .filter(|base| !known.contains(&target(&base.connect)))
+                .cloned(),
+        );
+        self.decorate_list();
+    }
+
+    /// Runs the `[[columns]]` commands again for the bases of the history.
+    fn refresh_columns(&mut self) {
+        if self.config.columns.is_empty() {
+            self.errors
+                .push("No [[columns]] in the config to refresh".to_string());
+            return;
+        }
+        if self.columns.is_refreshing() {
+            self.notice = Some("Columns are still refreshing".to_string());
+            return;
+        }
+        let Some(sender) = &self.events else {
+            return;
+        };
+        let paths: Vec<String> = self.history.iter().map(|path| path.to_string()).collect();
+        self.notice = Some(format!(
+            "Refreshing the columns of {} bases...",
+            paths.len()
+        ));
+        self.columns.refresh(&self.config.columns, paths, sender);
+    }
+
+    /// Runs the `decorate` script for every listed base, reporting the first
+    /// failure only.
+    fn decorate_list(&mut self) {
+        let Some(decorator) = &self.decorator else {
+            return;
+        };
+        let mut decorations = HashMap::new();
+        let mut failure = None;
+        for index in 0..self.history.len() + self.registered.len() {
+            let path = self.listed_path(index);
+            let entry = self.bases.find(path);
+            let parsed = parse_base_path(path).ok();
+            let fields = Fields {
+                path,
+                kind: parsed.as_ref().map(PathKind::kind_name),
+                target: parsed.as_ref().map(PathKind::target),
+                name: self
+                    .display_name(path)
+                    .map(str::to_string)
+                    .or_else(|| parsed.as_ref().map(PathKind::name)),
+                alias: entry.and_then(|entry| entry.alias.as_deref()),
+                badge: entry.and_then(|entry| entry.badge.as_deref()),
+                folder: self.folder_of(index),
+                tags: entry.map_or(&[], |entry| entry.tags.as_slice()),
+                production: self.bases.is_production(path),
+                pinned: entry.is_some_and(|entry| entry.pinned),
+                launches: self
+                    .launch_stats
+                    .get(path.trim())
+                    .map_or(0, |stats| stats.launches),
+            };
+            match decorator.decorate(&fields) {
+                Ok(decoration) => {
+                    decorations.insert(path.trim().to_string(), decoration);
+                }
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        self.decorations = decorations;
+        if let Some(e) = failure {
+            self.errors.push(e);
+        }
+    }
+
+    fn decoration(&self, index: usize) -> Option<&Decoration> {
+        self.decorations.get(self.listed_path(index).trim())
+    }
+}
+
+/// Launch count, time and mode of the last launch, e.g.
+/// `3 launches, last 2026-03-01 09:15 in Designer`.
+fn launch_summary(launch: &HistoryEntry) -> String {
+    let count = match launch.launch_count {
+        1 => "1 launch".to_string(),
+        count => format!("{count} launches"),
+    };
+    let mut summary = count;
+    if let Some(time) = launch.last_launched {
+        summary.push_str(&format!(", last {}", locale::timestamp(time)));
+    }
+    if let Some(mode) = launch.mode {
+        summary.push_str(&format!(" in {}", mode.label()));
+        if let Some(client) = launch.client {
+            summary.push_str(&format!(" ({})", client.label()));
+        }
+    }
+    summary
+}
+
+/// The locks of a server base, e.g. `sessions denied from
+/// 2026-10-16T09:00:00: Update, scheduled jobs denied`; `None` without any.
+fn access_summary(access: &ras::Access) -> Option<String> {
+    let mut locks = Vec::new();
+    if access.sessions_denied {
+        let mut sessions = "sessions denied".to_string();
+        if !access.denied_from.is_empty() {
+            sessions.push_str(&format!(" from {}", access.denied_from));
+        }
+        if !access.denied_to.is_empty() {
+            sessions.push_str(&format!(" to {}", access.denied_to));
+        }
+        if !access.denied_message.is_empty() {
+            sessions.push_str(&format!(": {}", access.denied_message));
+        }
+        locks.push(sessions);
+    }
+    if access.jobs_denied {
+        locks.push("scheduled jobs denied".to_string());
+    }
+    (!locks.is_empty()).then(|| locks.join(", "))
+}
+
+fn try_parse_and_launch(
+    path: String,
+    mode: LaunchMode,
+    bases: &Bases,
+    launcher: &Launcher,
+    credentials: &Credentials,
+) -> Result<Launched, Box<dyn Error>> {
+    lockdown::check_mode(mode)?;
+    lockdown::audit(&path, mode, None)?;
+    parse_and_launch(path.clone(), mode, bases, launcher, credentials)
+        .inspect(|_| journal::record(&path, mode, None))
+        .inspect_err(|e| {
+            let _ = lockdown::audit(&path, mode, Some(e.to_string()));
+            let _ = history::record_failure(&path, &e.to_string());
+            journal::record(&path, mode, Some(e.to_string()));
+        })
+}
+
+fn parse_and_launch(
+    path: String,
+    mode: LaunchMode,
+    bases: &Bases,
+    launcher: &Launcher,
+    credentials: &Credentials,
+) -> Result<Launched, Box<dyn Error>> {
+    let metrics = launcher.metrics;
+    let started = Instant::now();
+    let filled = placeholders::fill(&path, &launcher.vars)?;
+    // `Usr` and `Pwd` of the connection string come before the saved user
+    let credentials = match ConnectionString::parse(&normalize_input(&filled))
+        .ok()
+        .and_then(|connection| connection.credentials())
+    {
+        Some(given) if credentials.user.is_none() => given,
+        _ => credentials::resolve(credentials, &path, bases),
+    };
+    let mut parsed_path = match parse_base_path(&filled) {
+        Ok(path) => path,
+        Err(e) => {
+            metrics.record_launch(&path, "", started.elapsed(), Some("parse"));
+            return Err(format!("Parsing error: {}", e).into());
+        }
+    };
+    let target = parsed_path.target();
+
+    let mut checks = Vec::new();
+    // hooks may set up what the pre-check needs, e.g. a VPN
+    let hooks = LaunchHooks::new(
+        &launcher.hooks,
+        bases.find(&path).and_then(|base| base.hooks.as_ref()),
+        &[
+            ("PATH", path.trim().to_string()),
+            ("KIND", parsed_path.kind_name().to_string()),
+            ("TARGET", target.clone()),
+            ("NAME", parsed_path.name()),
+            ("MODE", mode.label().to_string()),
+            ("USER", credentials.user.clone().unwrap_or_default()),
+        ],
+    );
+    // a kiosk build runs no hooks, they are commands of the config
+    let before = if KIOSK { Ok(0) } else { hooks.before() };
+    match before {
+        Ok(0) => {}
+        Ok(count) => checks.push(format!("{count} before hooks passed")),
+        Err(e) => {
+            metrics.record_launch(&path, &target, started.elapsed(), Some("hook"));
+            return Err(e.into());
+        }
+    }
+
+    if let Some(window) = bases
+        .find(&path)
+        .and_then(|base| maintenance::current(&base.maintenance))
+    {
+        checks.push(format!("launched during {}", window.label()));
+    }
+    if let Some(requires) = bases.find(&path).and_then(|base| base.requires.as_ref()) {
+        if let Err(e) = requires.check() {
+            metrics.record_launch(&path, &target, started.elapsed(), Some("requires"));
+            return Err(e.into());
+        }
+        checks.push("requirements met".to_string());
+    }
+
+    let tunneled = bases.find(&path).is_some_and(|base| base.tunnel.is_some());
+    // a tunneled base is only reachable once its tunnel is open
+    if let Some(config) = &launcher.precheck {
+        if tunneled {
+            checks.push("pre-check skipped behind the ssh tunnel".to_string());
+        } else if let Err(e) = check::precheck(&filled, bases, config) {
+            metrics.record_launch(&path, &target, started.elapsed(), Some("precheck"));
+            return Err(e.into());
+        } else {
+            checks.push("pre-check passed".to_string());
+        }
+    }
+
+    let mut tunnel = None;
+    if let Some(config) = bases.find(&path).and_then(|base| base.tunnel.as_ref()) {
+        if KIOSK {
+            return Err("ssh tunnels are not available in this kiosk build".into());
+        }
+        match Tunnel::open(config, parsed_path) {
+            Ok((opened, rewritten)) => {
+                checks.push(format!(
+                    "ssh tunnel via {} on local port {} open",
+                    config.ssh, config.local_port
+                ));
+                tunnel = Some(opened);
+                parsed_path = rewritten;
+            }
+            Err(e) => {
+                metrics.record_launch(&path, &target, started.elapsed(), Some("tunnel"));
+                return Err(format!("Tunnel error: {}", e).into());
+            }
+        }
+    }
+
+    let options = match launcher.options(&path, bases) {
+        Ok(options) => options,
+        Err(e) => {
+            metrics.record_launch(&path, &target, started.elapsed(), Some("parse"));
+            return Err(e);
+        }
+    };
+    // the browser opener exits right away, there is no client to wait for
+    let log = (launcher.wait && mode != LaunchMode::WebBrowser).then(|| Watched::log_file(&path));
+    let mut watched = None;
+    let pid = match launch_base(
+        parsed_path,
+        mode,
+        launcher,
+        &credentials,
+        &options,
+        log.as_deref(),
+    ) {
+        Ok(child) => {
+            let pid = child.id();
+            let through_starter = launcher.platform.is_none() && mode != LaunchMode::WebBrowser;
+            match log {
+                Some(log) => {
+                    // the watched starter exits long before the client it started
+                    if let Some(tunnel) = tunnel.as_mut() {
+                        tunnel.follow(through_starter);
+                    }
+                    watched = Some(Watched::new(&path, mode, child, log));
+                }
+                None => {
+                    if let Some(tunnel) = tunnel.as_mut() {
+                        tunnel.attach(child, through_starter);
+                    }
+                }
+            }
+            pid
+        }
+        Err(e) => {
+            metrics.record_launch(&path, &target, started.elapsed(), Some("launcher"));
+            return Err(format!("Launcher error: {}", e).into());
+        }
+    };
+
+    metrics.record_launch(&path, &target, started.elapsed(), None);
+    if !KIOSK {
+        checks.extend(hooks.after(pid));
+    }
+    let command = script::plan(&path, mode, bases, launcher, &credentials)
+        .map_or_else(|_| target, |plan| plan.preview());
+    let report = Report {
+        path,
+        mode,
+        command,
+        pid,
+        time: Local::now(),
+        checks,
+    };
+    Ok(Launched {
+        tunnel,
+        watched,
+        report: Some(report),
+    })
+}
+
+/// Aliases are given where the picker takes a connection string, so they must
+/// not shadow a subcommand either.
+fn check_alias(bases: &Bases, path: &str, alias: &str) -> Result<(), Box<dyn Error>> {
+    if Cli::command().find_subcommand(alias).is_some() {
+        return Err(format!("Alias '{alias}' is a rbaserun command").into());
+    }
+    if let Some(plugin) = plugins::find(alias) {
+        return Err(format!("Alias '{alias}' is the plugin {}", plugin.program.display()).into());
+    }
+    bases.check_alias(path, alias)
+}
+
+fn production_guard_word(path: &str) -> String {
+    parse_base_path(path)
+        .map(|path| path.name())
+        .unwrap_or_else(|_| path.trim().to_string())
+}
+
+fn confirm_production_on_stdin(path: &str) -> Result<(), Box<dyn Error>> {
+    let word = production_guard_word(path);
+    print!("This is a production base, launching DESIGNER. Type '{word}' to continue: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != word {
+        return Err("Launch cancelled".into());
+    }
+    Ok(())
+}
+
+/// Asks on a terminal before launching a base again seconds after it was,
+/// scripts launching it twice are left alone.
+fn confirm_duplicate_on_stdin(path: &str, ago: i64) -> Result<(), Box<dyn Error>> {
+    print!(
+        "{} was launched {ago} s ago. Launch it again? [y/N] ",
+        path.trim()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return Err("Launch cancelled".into());
+    }
+    Ok(())
+}
+
+/// Rejects everything beyond listing and launching in a kiosk build.
+fn check_kiosk(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    if !KIOSK {
+        return Ok(());
+    }
+    let allowed = match &cli.command {
+        None => !cli.designer && !cli.rdp && cli.switches.is_empty(),
+        Some(Commands::List { .. }) => true,
+        Some(Commands::Launch {
+            designer,
+            switches,
+            snapshot,
+            ..
+        }) => !designer && !snapshot && switches.is_empty(),
+        Some(_) => false,
+    };
+    let client = match &cli.command {
+        Some(Commands::Launch {
+            client, browser, ..
+        }) => client.or(browser.as_ref().map(|_| Client::Web)),
+        _ => cli.client.or(cli.browser.as_ref().map(|_| Client::Web)),
+    };
+    if !allowed || cli.config.is_some() || cli.platform.is_some() || client.is_some() {
+        return Err("Only listing and launching bases is available in this kiosk build".into());
+    }
+    Ok(())
+}
+
+fn launch_from_cli(
+    path: String,
+    mode: LaunchMode,
+    bases: &Bases,
+    launcher: &Launcher,
+    credentials: &Credentials,
+) -> Result<Launched, Box<dyn Error>> {
+    let mode = if KIOSK { LaunchMode::Enterprise } else { mode };
+    lockdown::check_mode(mode)?;
+    if launcher.dry_run {
+        let credentials = credentials::resolve(credentials, &path, bases);
+        println!(
+            "{}",
+            script::plan(&path, mode, bases, launcher, &credentials)?.preview()
+        );
+        return Ok(Launched::default());
+    }
+    if bases.is_single_instance(&path)
+        && let Some(pid) = clients::running(&path).first()
+    {
+        clients::focus_single(&path, *pid)?;
+        eprintln!("{} is open already, brought it to the front", path.trim());
+        return Ok(Launched::default());
+    }
+    if let Some(ago) = journal::launched_within(&path, launcher.duplicate_window)
+        && io::stdin().is_terminal()
+    {
+        confirm_duplicate_on_stdin(&path, ago)?;
+    }
+    if let Some(window) = bases
+        .find(&path)
+        .and_then(|base| maintenance::current(&base.maintenance))
+    {
+        eprintln!(
+            "Warning: {} is in maintenance, {}",
+            path.trim(),
+            window.label()
+        );
+    }
+    if mode == LaunchMode::Designer && bases.is_production(&path) {
+        confirm_production_on_stdin(&path)?;
+    }
+    try_parse_and_launch(path, mode, bases, launcher, credentials)
+}
+
+/// The subcommand path of the invocation, e.g. `repository update`, the
+/// name permission rules use.
+fn action(matches: &ArgMatches) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    (!names.is_empty()).then(|| names.join(" "))
+}
+
+/// Rejects permission rules naming no command or plugin, a typo must not
+/// leave the command it meant open.
+fn check_permission_rules(lockdown: &lockdown::Lockdown) -> Result<(), Box<dyn Error>> {
+    for rule in lockdown.permissions.keys() {
+        if rule == "designer" || (!rule.contains(' ') && plugins::find(rule).is_some()) {
+            continue;
+        }
+        let mut command = Cli::command();
+        for name in rule.split_whitespace() {
+            command = command
+                .find_subcommand(name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown action '{rule}' in [lockdown.permissions]"))?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    if !KIOSK && let Some((plugin, args)) = plugins::requested(&Cli::command()) {
+        // the arguments are the plugin's, so no --config: the permission
+        // rules come from the usual config locations
+        let config = Config::load(None)?;
+        check_permission_rules(&config.lockdown)?;
+        lockdown::set(config.lockdown);
+        lockdown::check_action(&plugin.name)?;
+        process::exit(plugins::run(&plugin, args, None)?);
+    }
+    let matches = if KIOSK {
+        Cli::command()
+    } else {
+        plugins::with_help(Cli::command())
+    }
+    .get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    check_kiosk(&cli)?;
+    let mut config = Config::load(cli.config.as_deref())?;
+    // a kiosk build runs no commands or scripts of the config in the picker
+    if KIOSK {
+        config.columns.clear();
+        config.decorate = None;
+    }
+    if config.history_file == history::default_file() && !config.history_file.exists() {
+        let legacy = config
+            .history_file
+            .with_file_name(history::LEGACY_FILE_NAME);
+        paths::migrate(history::LEGACY_FILE_NAME, &legacy);
+    }
+    if config.remote.is_none() {
+        paths::migrate(bases::FILE_NAME, &bases::file());
+    }
+    paths::migrate(metrics::FILE_NAME, &metrics::file());
+    history::set_file(config.history_file.clone());
+    store::set_keep_backups(config.store_backups);
+    history::import_legacy()?;
+    secrets::set_enabled(config.keyring);
+    locale::set(config.locale);
+    check_permission_rules(&config.lockdown)?;
+    lockdown::set(config.lockdown.clone());
+    theme::set(&config.theme, config.high_contrast, config.reduced_motion)?;
+    if let Some(action) = action(&matches) {
+        lockdown::check_action(&action)?;
+    }
+    let mut bases = Bases::load(config.remote.as_ref())?;
+    let platform = cli
+        .platform
+        .as_deref()
+        .or(config.platform.as_deref())
+        .map(platform::find)
+        .transpose()?;
+    let client = match &cli.command {
+        Some(Commands::Launch {
+            client, browser, ..
+        }) => client.or(browser.as_ref().map(|_| Client::Web)),
+        _ => cli.client.or(cli.browser.as_ref().map(|_| Client::Web)),
+    };
+    let platform = match platform {
+        None if client.is_some_and(Client::needs_platform) => Some(platform::newest()?),
+        platform => platform,
+    };
+    let mut launcher = Launcher::new(&config, platform);
+    (launcher.dry_run, launcher.switches) = match &cli.command {
+        None => (cli.dry_run, cli.switches.clone()),
+        Some(Commands::Launch {
+            dry_run, switches, ..
+        }) => (*dry_run, switches.clone()),
+        Some(Commands::Set {
+            action: SetAction::Launch { dry_run, .. },
+        }) => (*dry_run, Vec::new()),
+        Some(_) => (false, Vec::new()),
+    };
+    launcher.browser_program = match &cli.command {
+        None => cli.browser.clone(),
+        Some(Commands::Launch { browser, .. }) => browser.clone(),
+        Some(_) => None,
+    };
+    launcher.vars = match &cli.command {
+        None => cli.vars.clone(),
+        Some(Commands::Launch { vars, .. }) => vars.clone(),
+        Some(_) => Vec::new(),
+    }
+    .into_iter()
+    .collect();
+    launcher.wait = match &cli.command {
+        None => cli.wait,
+        Some(Commands::Launch { wait, .. }) => *wait,
+        Some(_) => false,
+    };
+
+    match cli.command {
+        Some(Commands::List {
+            launcher_feed: true,
+            ..
+        }) => return commands::launcher_feed(&history::load(), &bases),
+        Some(Commands::List { json, .. }) => {
+            return commands::list(&history::load(), &bases, json);
+        }
+        Some(Commands::Launch {
+            path,
+            designer,
+            quiet,
+            no_history,
+            toast,
+            snapshot,
+            credentials,
+            ..
+        }) => {
+            let credentials = credentials.into();
+            let result = suggest::resolve(&path, &bases, !quiet).and_then(|path| {
+                let mode = client.map_or_else(|| config.cli_mode(&path, designer), Client::mode);
+                let launched = if snapshot {
+                    let snapshot = Snapshot::take(&path)?;
+                    // the copy is launched as the user saved for the base
+                    let credentials = credentials::resolve(&credentials, &path, &bases);
+                    let launched = launch_from_cli(
+                        snapshot.path.clone(),
+                        mode,
+                        &bases,
+                        &launcher,
+                        &credentials,
+                    )?;
+                    (launched, Some(snapshot))
+                } else {
+                    let launched =
+                        launch_from_cli(path.clone(), mode, &bases, &launcher, &credentials)?;
+                    (launched, None)
+                };
+                Ok((path, mode, launched))
+            });
+            if toast {
+                match &result {
+                    Ok((path, ..)) => toast::show("rbaserun", &format!("Launched {path}")),
+                    Err(e) => toast::show("rbaserun failed", &e.to_string()),
+                }
+            }
+            let (path, mode, (launched, snapshot)) = match result {
+                Ok(launched) => launched,
+                Err(_) if quiet => process::exit(1),
+                Err(e) => return Err(e),
+            };
+            // the copy is gone afterwards, it has no place in the history
+            if !no_history && !launcher.dry_run && snapshot.is_none() {
+                history::record_launch(&path, mode, client, None)?;
+            }
+            if cli.report
+                && !quiet
+                && let Some(report) = &launched.report
+            {
+                println!("{}", report.text());
+            }
+            if let Some(message) = launched.waiting()
+                && !quiet
+            {
+                println!("{message}");
+            }
+            let watched = launched.watched.is_some();
+            if let Err(e) = launched.wait() {
+                if toast {
+                    toast::show("rbaserun failed", &e.to_string());
+                }
+                if quiet {
+                    drop(snapshot);
+                    process::exit(1);
+                }
+                return Err(e);
+            }
+            if let Some(snapshot) = snapshot
+                && !watched
+            {
+                if !quiet {
+                    println!("Waiting for the client of the snapshot to exit...");
+                }
+                snapshot.wait_for_clients();
+            }
+            return Ok(());
+        }
+        Some(Commands::Serve { http, token, .. }) => {
+            return match http {
+                Some(addr) => serve::http(bases, launcher, config.backups, addr, token),
+                None => serve::stdio(bases, launcher),
+            };
+        }
+        Some(Commands::Integrate { target }) => {
+            return match target {
+                IntegrateTarget::Powershell => integrate::powershell(),
+                IntegrateTarget::WindowsTerminal => integrate::windows_terminal(&bases),
+                IntegrateTarget::Shortcuts { dir, all } => {
+                    integrate::shortcuts(&dir, all, &history::load(), &bases)
+                }
+            };
+        }
+        Some(Commands::Check { paths }) => {
+            return check::run(paths, &bases, &config.check, &config.ras);
+        }
+        Some(Commands::Browse { host }) => return ras::run(&host, &bases, &config.ras),
+        Some(Commands::Licenses { host }) => {
+            return licenses::report(&host, &bases, config.licenses.as_ref(), &config.ras);
+        }
+        Some(Commands::Templates) => return templates::print(&config.templates),
+        Some(Commands::Create {
+            server,
+            file,
+            template,
+            name,
+            descr,
+            dbms,
+            db_server,
+            db_name,
+            db_user,
+            db_password,
+            locale,
+            security_level,
+            deny_scheduled_jobs,
+        }) => {
+            let template = match template {
+                Some(name) => Some(templates::find(&config.templates, &name)?),
+                None => templates::choose(&config.templates)?,
+            };
+            // broken bootstrap steps fail before the base exists
+            if let Some(template) = &template {
+                template.steps()?;
+            }
+            let (Some(server), Some(name), Some(dbms)) = (server, name, dbms) else {
+                let dir = file.ok_or("Give --server or --file")?;
+                return templates::create_file(&dir, template.as_ref(), descr, &mut bases);
+            };
+            let new = ras::NewInfobase {
+                name,
+                descr,
+                dbms,
+                db_server,
+                db_name,
+                db_user,
+                db_password,
+                locale,
+                security_level,
+                deny_scheduled_jobs,
+            };
+            return ras::create(&server, &new, template.as_ref(), &mut bases, &config.ras);
+        }
+        Some(Commands::Drop {
+            path,
+            drop_database,
+            credentials,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            let credentials = credentials::resolve(&credentials.into(), &path, &bases);
+            return ras::drop(&path, drop_database, &credentials, &mut bases, &config.ras);
+        }
+        Some(Commands::Clone { source, dest }) => return clone::run(&source, &dest, &mut bases),
+        Some(Commands::Refresh {
+            source,
+            target,
+            credentials,
+            unlock_code,
+            keep_dump,
+            yes,
+        }) => {
+            let options = RefreshOptions {
+                credentials: credentials.into(),
+                unlock_code,
+                keep_dump,
+                yes,
+            };
+            return refresh::run(&source, &target, &bases, options);
+        }
+        Some(Commands::DesignerDump {
+            path,
+            out,
+            restore,
+            load_cf,
+            update_db,
+            credentials,
+            unlock_code,
+            yes,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            let operation = match (out, restore, load_cf) {
+                (Some(out), _, _) => Operation::Dump(out),
+                (_, Some(dt), _) => Operation::Restore(dt),
+                (_, _, Some(file)) => Operation::LoadCf {
+                    file,
+                    update: update_db,
+                },
+                _ => unreachable!("clap requires one of the operations"),
+            };
+            let options = DumpOptions {
+                credentials: credentials::resolve(&credentials.into(), &path, &bases),
+                unlock_code,
+                yes,
+            };
+            return dump::run(&path, operation, &bases, options);
+        }
+        Some(Commands::EventLog {
+            path,
+            keep_days,
+            save_as,
+            credentials,
+            yes,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            let options = ReduceOptions {
+                credentials: credentials::resolve(&credentials.into(), &path, &bases),
+                keep_days,
+                save_as,
+                yes,
+            };
+            return eventlog::run(&path, options, &bases);
+        }
+        Some(Commands::Repair {
+            path,
+            test_only,
+            credentials,
+            yes,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            let options = RepairOptions {
+                credentials: credentials::resolve(&credentials.into(), &path, &bases),
+                test_only,
+                yes,
+            };
+            return crash::repair(&path, options, &bases);
+        }
+        Some(Commands::Agent {
+            path,
+            port,
+            listen,
+            base_dir,
+            connect,
+            credentials,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            let options = AgentOptions {
+                credentials: credentials::resolve(&credentials.into(), &path, &bases),
+                port,
+                listen,
+                base_dir,
+                connect,
+            };
+            return agent::run(&path, options, &bases);
+        }
+        Some(Commands::Compare {
+            path,
+            cf,
+            report,
+            full,
+            credentials,
+        }) => {
+            return compare::run(&path, &cf, report, full, &credentials.into());
+        }
+        Some(Commands::Backup { action }) => {
+            return match action {
+                BackupAction::Run {
+                    plan,
+                    credentials,
+                    unlock_code,
+                    force,
+                } => {
+                    let plan = backup::find(&config.backups, &plan)?;
+                    if !force {
+                        backup::check_window(plan)?;
+                    }
+                    let path = bases.expand_alias(&normalize_input(&plan.base));
+                    let options = DumpOptions {
+                        credentials: credentials::resolve(&credentials.into(), &path, &bases),
+                        unlock_code,
+                        yes: true,
+                    };
+                    let started = Instant::now();
+                    let result = backup::run(plan, &bases, options);
+                    Metrics::new(config.metrics).record_backup(
+                        &plan.name,
+                        started.elapsed(),
+                        result.as_ref().ok(),
+                    );
+                    notify::plan_finished(&config.notify, plan, &result, started.elapsed());
+                    result.map(|_| ())
+                }
+                BackupAction::Upload { plan } => {
+                    backup::upload_latest(backup::find(&config.backups, &plan)?)
+                }
+                BackupAction::Login { access_key } => upload::login(&access_key),
+                BackupAction::Notify => notify::test(&config.notify),
+                BackupAction::SmtpLogin { user } => notify::login(&user),
+                BackupAction::Prune { plan, dry_run } => {
+                    backup::prune(backup::find(&config.backups, &plan)?, dry_run)
+                }
+                BackupAction::Verify { file } => backup::verify(&file),
+            };
+        }
+        Some(Commands::Repo { action }) => {
+            return match action {
+                RepoAction::Login { repository, user } => repository::login(&repository, &user),
+                RepoAction::Bind {
+                    path,
+                    repository,
+                    replace,
+                    credentials,
+                } => repository::bind(&path, repository, replace, &mut bases, &credentials.into()),
+                RepoAction::Update {
+                    path,
+                    apply,
+                    credentials,
+                } => repository::update(&path, apply, &bases, &credentials.into()),
+            };
+        }
+        Some(Commands::Store {
+            action:
+                StoreAction::Merge {
+                    other,
+                    ours,
+                    theirs,
+                },
+        }) => {
+            let resolution = if ours {
+                Resolution::Ours
+            } else if theirs {
+                Resolution::Theirs
+            } else {
+                Resolution::Ask
+            };
+            return store::merge(&other, &mut bases, resolution);
+        }
+        Some(Commands::Store {
+            action: StoreAction::Restore { from },
+        }) => return store::restore(from.as_deref(), config.remote.as_ref()),
+        Some(Commands::Run {
+            path,
+            command,
+            credentials,
+        }) => return runner::run(&path, &command, &bases, &credentials.into()),
+        Some(Commands::Platforms) => return platform::list(),
+        Some(Commands::Share { path }) => return share::print(&bases.expand_alias(&path)),
+        Some(Commands::ExportScript {
+            path,
+            designer,
+            format,
+            output,
+            credentials,
+        }) => {
+            let path = bases.expand_alias(&normalize_input(&path));
+            return script::export(
+                &path,
+                config.cli_mode(&path, designer),
+                format,
+                output.as_deref(),
+                &bases,
+                &launcher,
+                &credentials.into(),
+            );
+        }
+        Some(Commands::Import { file, format, v8i }) => {
+            let format = if v8i { Some(ImportFormat::V8i) } else { format };
+            return import::run(&file, format, &mut bases);
+        }
+        Some(Commands::Export { v8i }) => return ibases::export(&v8i, &mut bases),
+        Some(Commands::Add { name, path }) => return commands::add(&name, &path, &mut bases),
+        Some(Commands::Rm { name }) => return commands::remove(&name, &mut bases),
+        Some(Commands::Organize { all }) => {
+            return organize::run(&history::load_entries(), &mut bases, all);
+        }
+        Some(Commands::Stats { internal: true }) => return metrics::report(config.metrics),
+        Some(Commands::Stats { internal: false }) => return journal::report(),
+        Some(Commands::Cluster { action }) => {
+            return match action {
+                ClusterAction::List => {
+                    ras::list_registered(&config.ras);
+                    Ok(())
+                }
+                ClusterAction::Login { name } => ras::login(&name, &config.ras),
+                ClusterAction::Import { name } => ras::import_all(&name, &mut bases, &config.ras),
+            };
+        }
+        Some(Commands::Set { action }) => {
+            return match action {
+                SetAction::List => macros::list(),
+                SetAction::Launch {
+                    name, credentials, ..
+                } => macros::run(&name, &bases, &launcher, &credentials.into(), &config),
+                SetAction::Add {
+                    name,
+                    path,
+                    mode,
+                    delay,
+                } => {
+                    let path = bases.expand_alias(&normalize_input(&path));
+                    parse_base_path(&path).map_err(|e| format!("Parsing error: {e}"))?;
+                    let mode = mode.unwrap_or_else(|| config.cli_mode(&path, false));
+                    let mut macros = Macros::load()?;
+                    macros.add_step(&name, Step { path, mode, delay });
+                    macros.save()
+                }
+                SetAction::Rm { name, path } => {
+                    let path = path.map(|path| bases.expand_alias(&normalize_input(&path)));
+                    let mut macros = Macros::load()?;
+                    macros.remove(&name, path.as_deref())?;
+                    macros.save()
+                }
+                SetAction::Stagger { name, seconds } => {
+                    let mut macros = Macros::load()?;
+                    macros.set_stagger(&name, seconds)?;
+                    macros.save()
+                }
+            };
+        }
+        None => {}
+    }
+
+    // a unique --auto match is launched like a path given on the command line
+    let mut auto_path = None;
+    if cli.auto
+        && let Some(filter) = &cli.filter
+    {
+        let history = history::load();
+        if let [index] = history::filter(&history, filter)[..] {
+            if cli.print_selection {
+                println!("{}", history[index]);
+                return Ok(());
+            }
+            auto_path = Some(history[index].clone());
+        }
+    }
+
+    if let Some(name) = &cli.macro_name {
+        return macros::run(name, &bases, &launcher, &cli.credentials.into(), &config);
+    }
+
+    let capabilities = Capabilities::detect(&config.terminal, cli.print_selection);
+    let waiting: Vec<Launched> = if let Some(path) = cli.path.or(auto_path.clone()) {
+        let path = bases.expand_alias(&path);
+        if cli.rdp {
+            return rdp::connect(&path, &bases);
+        }
+        let path = suggest::resolve(&path, &bases, true)?;
+        let mode = client.map_or_else(|| config.cli_mode(&path, cli.designer), Client::mode);
+        let launched = launch_from_cli(path, mode, &bases, &launcher, &cli.credentials.into())?;
+        if let Some(path) = auto_path
+            && !launcher.dry_run
+        {
+            history::record_launch(&path, mode, client, None)?;
+        }
+        vec![launched]
+    } else if cli.pick {
+        let options = LinearOptions {
+            config: &config,
+            launcher: &launcher,
+            client,
+            credentials: cli.credentials.into(),
+            print_selection: cli.print_selection,
+        };
+        vec![linear::pick(&bases, options, cli.designer)?]
+    } else if cli.linear || config.linear || !capabilities.cursor {
+        if !(capabilities.cursor || cli.linear || config.linear) {
+            eprintln!("This terminal can't show the picker, listing the bases line by line");
+        }
+        let options = LinearOptions {
+            config: &config,
+            launcher: &launcher,
+            client,
+            credentials: cli.credentials.into(),
+            print_selection: cli.print_selection,
+        };
+        vec![linear::run(&bases, options)?]
+    } else {
+        let decorator = config
+            .decorate
+            .as_deref()
+            .map(Decorator::load)
+            .transpose()?;
+        let mut app = App {
+            designer: config.default_mode == LaunchMode::Designer && !KIOSK,
+            errors: ErrorLog::new(config.error_history),
+            detail_height: config.layout.detail_height,
+            keymap: Keymap::new(&config.keymap)?,
+            tips: if config.tips && !KIOSK {
+                Tips::load()
+            } else {
+                Tips::default()
+            },
+            releases: if config.release.enabled {
+                Releases::load()
+            } else {
+                Releases::default()
+            },
+            columns: if config.columns.is_empty() {
+                ColumnValues::default()
+            } else {
+                ColumnValues::load()
+            },
+            config,
+            config_path: cli.config.clone(),
+            bases,
+            launcher,
+            decorator,
+            ..Default::default()
+        };
+        let inline = cli.inline || app.config.layout.inline || !capabilities.alternate_screen;
+        let options = TuiOptions {
+            inline: inline.then_some(app.config.layout.inline_height),
+            mouse: app.config.mouse && capabilities.mouse,
+            stderr: cli.print_selection,
+        };
+        app.monochrome = !capabilities.color;
+        app.ascii = !capabilities.unicode;
+        app.hyperlinks = capabilities.hyperlinks;
+        app.print_selection = cli.print_selection;
+        app.report = cli.report;
+        if let Some(filter) = cli.filter {
+            app.input = filter.into();
+            app.history_state.select_first();
+        } else if app.config.restore_session
+            && let Some(session) = Session::load()
+        {
+            app.restore_session(session);
+        }
+        if app.config.favorites_on_start
+            && let Some(favorites) = app.favorites()
+        {
+            app.mode = Mode::Favorites(favorites);
+        }
+        if app.config.whats_new
+            && !KIOSK
+            && let Some(notes) = changelog::since_last_run()
+        {
+            app.mode = Mode::WhatsNew(WhatsNewPanel::new(notes));
+        }
+        tui::run(&mut app, options)?;
+        Session::clear();
+
+        if let Some(selection) = &app.selection {
+            println!("{selection}");
+            return Ok(());
+        }
+        if let Some((plugin, path)) = &app.plugin {
+            lockdown::check_action(&plugin.name)?;
+            let input = plugins::entry_json(path, &app.bases)?;
+            let code = plugins::run(plugin, Vec::new(), Some(input))?;
+            if code != 0 {
+                process::exit(code);
+            }
+            return Ok(());
+        }
+        if let Some(name) = &app.replaying {
+            return macros::run(
+                name,
+                &app.bases,
+                &app.launcher,
+                &Credentials::default(),
+                &app.config,
+            );
+        }
+        if inline && let Some((path, mode)) = &app.launched {
+            println!("Launched {path} ({})", mode.label());
+        }
+        app.waiting
+    };
+
+    for launched in waiting {
+        if cli.report
+            && let Some(report) = &launched.report
+        {
+            println!("{}", report.text());
+        }
+        if let Some(message) = launched.waiting() {
+            println!("{message}");
+        }
+        launched.wait()?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// A daily stretch of time when a base is worked on, e.g. backed up or
+/// updated: `[[base.maintenance]]` warns on launches within it, `window` of
+/// a `[[backup]]` plan keeps the plan from running outside it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Window {
+    /// `02:00-03:00`, spanning midnight when it ends earlier, `22:00-02:00`
+    pub hours: Hours,
+    /// Time zone of the hours such as `Europe/Moscow`, this machine's when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<Tz>,
+    /// What happens meanwhile, e.g. `backup running`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Hours {
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+impl TryFrom<String> for Hours {
+    type Error = String;
+
+    fn try_from(hours: String) -> Result<Self, String> {
+        let time = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        hours
+            .split_once('-')
+            .and_then(|(from, to)| Some((time(from)?, time(to)?)))
+            .map(|(from, to)| Hours { from, to })
+            .ok_or_else(|| format!("expected hours like 02:00-03:00, got '{hours}'"))
+    }
+}
+
+impl From<Hours> for String {
+    fn from(hours: Hours) -> String {
+        format!(
+            "{}-{}",
+            hours.from.format("%H:%M"),
+            hours.to.format("%H:%M")
+        )
+    }
+}
+
+impl Window {
+    /// Whether `at` falls within the window, in its time zone.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let time = match self.time_zone {
+            Some(zone) => at.with_timezone(&zone).time(),
+            None => at.with_timezone(&Local).time(),
+        };
+        let Hours { from, to } = self.hours;
+        if from <= to {
+            from <= time && time < to
+        } else {
+            from <= time || time < to
+        }
+    }
+
+    /// The window as shown in warnings, e.g. `backup running 02:00–03:00 MSK`.
+    pub fn label(&self) -> String {
+        let now = Utc::now();
+        let zone = match self.time_zone {
+            Some(zone) => now.with_timezone(&zone).format("%Z").to_string(),
+            None => now.with_timezone(&Local).format("%Z").to_string(),
+        };
+        let hours = format!(
+            "{}–{} {zone}",
+            self.hours.from.format("%H:%M"),
+            self.hours.to.format("%H:%M")
+        );
+        match &self.reason {
+            Some(reason) => format!("{reason} {hours}"),
+            None => format!("maintenance {hours}"),
+        }
+    }
+}
+
+/// The first of `windows` open right now.
+pub fn current(windows: &[Window]) -> Option<&Window> {
+    let now = Utc::now();
+    windows.iter().find(|window| window.contains(now))
+}
@@ -0,0 +1,358 @@
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::backup::Plan;
+use crate::paths;
+
+pub const FILE_NAME: &str = "rbaserun_metrics.jsonl";
+
+/// Backup runs, kept apart from the launches of `FILE_NAME`.
+pub const BACKUPS_FILE_NAME: &str = "rbaserun_backups.jsonl";
+
+pub fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+fn backups_file() -> PathBuf {
+    paths::data_file(BACKUPS_FILE_NAME)
+}
+
+/// Launch outcomes recorded to a local file when `metrics = true`. Nothing is
+/// ever sent anywhere.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Metrics {
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LaunchEvent {
+    /// Unix timestamp, seconds
+    time: i64,
+    path: String,
+    /// Server host, file path or publication url, empty when parsing failed
+    target: String,
+    millis: u64,
+    /// `parse`, `tunnel` or `launcher` when the launch failed
+    failed: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupEvent {
+    /// Unix timestamp, seconds
+    time: i64,
+    plan: String,
+    seconds: u64,
+    /// Size of the dump, none when the run failed
+    size: Option<u64>,
+}
+
+impl Metrics {
+    pub fn new(enabled: bool) -> Metrics {
+        Metrics { enabled }
+    }
+
+    /// Records one launch attempt; `failed` is the stage that failed, if any.
+    pub fn record_launch(&self, path: &str, target: &str, took: Duration, failed: Option<&str>) {
+        if !self.enabled {
+            return;
+        }
+        let event = LaunchEvent {
+            time: Local::now().timestamp(),
+            path: path.to_string(),
+            target: target.to_string(),
+            millis: took.as_millis() as u64,
+            failed: failed.map(str::to_string),
+        };
+        // metrics must never break a launch
+        let _ = append(&file(), &event);
+    }
+
+    /// Records one run of backup plan `plan`; `dump` is the dump it made,
+    /// none when it failed.
+    pub fn record_backup(&self, plan: &str, took: Duration, dump: Option<&PathBuf>) {
+        if !self.enabled {
+            return;
+        }
+        let event = BackupEvent {
+            time: Local::now().timestamp(),
+            plan: plan.to_string(),
+            seconds: took.as_secs(),
+            size: dump.map(|dump| fs::metadata(dump).map_or(0, |meta| meta.len())),
+        };
+        let _ = append(&backups_file(), &event);
+    }
+}
+
+fn append(path: &Path, event: &impl Serialize) -> Result<(), Box<dyn Error>> {
+    paths::prepare(path)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, Box<dyn Error>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut events = Vec::new();
+    for line in BufReader::new(file).lines() {
+        // skip lines torn by concurrent writers instead of failing the report
+        if let Ok(event) = serde_json::from_str(&line?) {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// Prints launch latencies and failure rates collected in the metrics file.
+pub fn report(enabled: bool) -> Result<(), Box<dyn Error>> {
+    let events: Vec<LaunchEvent> = load(&file())?;
+    if !enabled {
+        println!("Metrics are off, set `metrics = true` in rbaserun.toml to collect them.");
+    }
+    if events.is_empty() {
+        println!("No launches recorded in {}", file().display());
+        return Ok(());
+    }
+
+    let total = events.len();
+    println!("Launches: {total}");
+    let mut stages: BTreeMap<&str, usize> = BTreeMap::new();
+    for event in &events {
+        if let Some(stage) = &event.failed {
+            *stages.entry(stage).or_default() += 1;
+        }
+    }
+    for (stage, count) in &stages {
+        println!(
+            "  {stage} failures: {count} ({:.1}%)",
+            *count as f64 * 100.0 / total as f64
+        );
+    }
+
+    let mut latencies: Vec<u64> = events
+        .iter()
+        .filter(|event| event.failed.is_none())
+        .map(|event| event.millis)
+        .collect();
+    latencies.sort_unstable();
+    if !latencies.is_empty() {
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+        println!(
+            "Launch latency: p50 {} ms, p95 {} ms, max {} ms",
+            percentile(50),
+            percentile(95),
+            latencies[latencies.len() - 1]
+        );
+    }
+
+    let mut targets: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for event in events.iter().filter(|event| !event.target.is_empty()) {
+        let (attempts, failures) = targets.entry(&event.target).or_default();
+        *attempts += 1;
+        if event.failed.is_some() {
+            *failures += 1;
+        }
+    }
+    let mut flaky: Vec<_> = targets
+        .into_iter()
+        .filter(|(_, (_, failures))| *failures > 0)
+        .collect();
+    flaky.sort_by_key(|(_, (_, failures))| Reverse(*failures));
+    if !flaky.is_empty() {
+        println!("Failing targets:");
+        for (target, (attempts, failures)) in flaky {
+            println!("  {target}: {failures} of {attempts} failed");
+        }
+    }
+    Ok(())
+}
+
+/// The recorded launches and backup runs in the Prometheus text format, for
+/// `GET /metrics` of `serve --http`. The dumps of `plans` are counted on
+/// every call, so their sizes show without `metrics = true`.
+pub fn prometheus(plans: &[Plan]) -> String {
+    let launches: Vec<LaunchEvent> = load(&file()).unwrap_or_default();
+    let backups: Vec<BackupEvent> = load(&backups_file()).unwrap_or_default();
+    let mut out = String::new();
+
+    let mut results: BTreeMap<&str, usize> = BTreeMap::new();
+    for event in &launches {
+        *results
+            .entry(event.failed.as_deref().unwrap_or("ok"))
+            .or_default() += 1;
+    }
+    header(
+        &mut out,
+        "rbaserun_launches_total",
+        "counter",
+        "Launch attempts by result, ok or the stage that failed",
+    );
+    for (result, count) in &results {
+        let _ = writeln!(
+            out,
+            "rbaserun_launches_total{{result=\"{result}\"}} {count}"
+        );
+    }
+    header(
+        &mut out,
+        "rbaserun_check_failures_total",
+        "counter",
+        "Launches stopped by the connection pre-check",
+    );
+    let _ = writeln!(
+        out,
+        "rbaserun_check_failures_total {}",
+        results.get("precheck").unwrap_or(&0)
+    );
+    let succeeded = launches.iter().filter(|event| event.failed.is_none());
+    header(
+        &mut out,
+        "rbaserun_launch_duration_seconds",
+        "summary",
+        "Time until the client was started",
+    );
+    let _ = writeln!(
+        out,
+        "rbaserun_launch_duration_seconds_sum {}",
+        succeeded.clone().map(|event| event.millis).sum::<u64>() as f64 / 1000.0
+    );
+    let _ = writeln!(
+        out,
+        "rbaserun_launch_duration_seconds_count {}",
+        succeeded.count()
+    );
+
+    let mut runs: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    let mut last: BTreeMap<&str, &BackupEvent> = BTreeMap::new();
+    let mut last_success: BTreeMap<&str, i64> = BTreeMap::new();
+    for event in &backups {
+        let result = if event.size.is_some() { "ok" } else { "failed" };
+        *runs.entry((&event.plan, result)).or_default() += 1;
+        last.insert(&event.plan, event);
+        if event.size.is_some() {
+            last_success.insert(&event.plan, event.time);
+        }
+    }
+    header(
+        &mut out,
+        "rbaserun_backup_runs_total",
+        "counter",
+        "Runs of backup plans by result",
+    );
+    for ((plan, result), count) in &runs {
+        let _ = writeln!(
+            out,
+            "rbaserun_backup_runs_total{{plan=\"{}\",result=\"{result}\"}} {count}",
+            label(plan)
+        );
+    }
+    header(
+        &mut out,
+        "rbaserun_backup_duration_seconds",
+        "gauge",
+        "Duration of the last run of each plan",
+    );
+    for (plan, event) in &last {
+        let _ = writeln!(
+            out,
+            "rbaserun_backup_duration_seconds{{plan=\"{}\"}} {}",
+            label(plan),
+            event.seconds
+        );
+    }
+    header(
+        &mut out,
+        "rbaserun_backup_last_success_timestamp_seconds",
+        "gauge",
+        "Unix time of the last successful run of each plan",
+    );
+    for (plan, time) in &last_success {
+        let _ = writeln!(
+            out,
+            "rbaserun_backup_last_success_timestamp_seconds{{plan=\"{}\"}} {time}",
+            label(plan)
+        );
+    }
+
+    header(
+        &mut out,
+        "rbaserun_backup_dumps",
+        "gauge",
+        "Dumps kept in the directory of each plan",
+    );
+    let dumps: Vec<(&Plan, Vec<u64>)> = plans
+        .iter()
+        .map(|plan| {
+            let sizes = plan
+                .dumps()
+                .unwrap_or_default()
+                .iter()
+                .map(|(path, _)| fs::metadata(path).map_or(0, |meta| meta.len()))
+                .collect();
+            (plan, sizes)
+        })
+        .collect();
+    for (plan, sizes) in &dumps {
+        let _ = writeln!(
+            out,
+            "rbaserun_backup_dumps{{plan=\"{}\"}} {}",
+            label(&plan.name),
+            sizes.len()
+        );
+    }
+    header(
+        &mut out,
+        "rbaserun_backup_dumps_bytes",
+        "gauge",
+        "Total size of the dumps of each plan",
+    );
+    for (plan, sizes) in &dumps {
+        let _ = writeln!(
+            out,
+            "rbaserun_backup_dumps_bytes{{plan=\"{}\"}} {}",
+            label(&plan.name),
+            sizes.iter().sum::<u64>()
+        );
+    }
+    header(
+        &mut out,
+        "rbaserun_backup_last_size_bytes",
+        "gauge",
+        "Size of the newest dump of each plan",
+    );
+    for (plan, sizes) in &dumps {
+        if let Some(size) = sizes.first() {
+            let _ = writeln!(
+                out,
+                "rbaserun_backup_last_size_bytes{{plan=\"{}\"}} {size}",
+                label(&plan.name)
+            );
+        }
+    }
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Escapes a label value of the text format.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
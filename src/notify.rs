@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::backup::Plan;
+use crate::{locale, platform, secrets};
+
+/// Environment variable with the SMTP password, read before the keyring.
+#[cfg(feature = "email")]
+const PASSWORD_VAR: &str = "RBASERUN_SMTP_PASSWORD";
+
+/// Where the results of `backup run` are reported, `[notify]`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Notify {
+    /// URL the summary is posted to as JSON, e.g. a Slack, Teams or Telegram
+    /// `sendMessage` webhook
+    pub webhook: Option<String>,
+    /// Chat the summary goes to with a Telegram webhook
+    pub chat_id: Option<String>,
+    /// Also report runs that succeeded, not only failures
+    pub on_success: bool,
+    pub email: Option<Email>,
+}
+
+/// SMTP server the summary is mailed through, `[notify.email]`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Email {
+    pub host: String,
+    /// 587 with STARTTLS, 465 with TLS and 25 without
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// Password in the OS keyring, see `backup smtp-login`
+    pub user: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    #[default]
+    Starttls,
+    Tls,
+    None,
+}
+
+/// What a webhook receives: `text` is shown by the chat services, the other
+/// fields are for scripts.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    pub plan: String,
+    pub base: String,
+    pub host: String,
+    pub ok: bool,
+    pub seconds: u64,
+    pub dump: Option<PathBuf>,
+    pub size: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "email")]
+impl Summary {
+    fn subject(&self) -> String {
+        let result = if self.ok { "succeeded" } else { "FAILED" };
+        format!("Backup {} {result} on {}", self.plan, self.host)
+    }
+}
+
+/// Reports the result of a run of `plan` that took `elapsed`, unless it
+/// succeeded and `on_success` is off. Failures to deliver are only printed,
+/// the exit code stays the one of the backup.
+pub fn plan_finished(
+    notify: &Notify,
+    plan: &Plan,
+    result: &Result<PathBuf, Box<dyn Error>>,
+    elapsed: Duration,
+) {
+    if result.is_ok() && !notify.on_success {
+        return;
+    }
+    let summary = summary(notify, plan, result, elapsed);
+    for error in send(notify, &summary) {
+        eprintln!("Could not send the backup notification: {error}");
+    }
+}
+
+/// Sends a sample summary to every configured target, `backup notify`.
+pub fn test(notify: &Notify) -> Result<(), Box<dyn Error>> {
+    if notify.webhook.is_none() && notify.email.is_none() {
+        return Err("No [notify] webhook or email is configured".into());
+    }
+    let mut summary = Summary {
+        text: String::new(),
+        chat_id: notify.chat_id.clone(),
+        plan: "test".to_string(),
+        base: String::new(),
+        host: platform::host_name(),
+        ok: true,
+        seconds: 0,
+        dump: None,
+        size: None,
+        error: None,
+    };
+    summary.text = format!("Test notification of rbaserun on {}", summary.host);
+    let errors = send(notify, &summary);
+    if let Some(error) = errors.first() {
+        return Err(error.clone().into());
+    }
+    println!("Sent a test notification");
+    Ok(())
+}
+
+fn summary(
+    notify: &Notify,
+    plan: &Plan,
+    result: &Result<PathBuf, Box<dyn Error>>,
+    elapsed: Duration,
+) -> Summary {
+    let host = platform::host_name();
+    let seconds = elapsed.as_secs();
+    let took = format!("{}m {}s", seconds / 60, seconds % 60);
+    let (text, dump, size, error) = match result {
+        Ok(dump) => {
+            let size = fs::metadata(dump).map(|meta| meta.len()).ok();
+            let text = format!(
+                "Backup {} on {host} succeeded in {took}: {} ({})",
+                plan.name,
+                dump.display(),
+                size.map(locale::size).unwrap_or_default()
+            );
+            (text, Some(dump.clone()), size, None)
+        }
+        Err(e) => (
+            format!("Backup {} on {host} FAILED after {took}: {e}", plan.name),
+            None,
+            None,
+            Some(e.to_string()),
+        ),
+    };
+    Summary {
+        text,
+        chat_id: notify.chat_id.clone(),
+        plan: plan.name.clone(),
+        base: plan.base.clone(),
+        host,
+        ok: result.is_ok(),
+        seconds,
+        dump,
+        size,
+        error,
+    }
+}
+
+/// Sends `summary` to the webhook and the mailbox, returning what failed.
+fn send(notify: &Notify, summary: &Summary) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Some(url) = &notify.webhook
+        && let Err(e) = post(url, summary)
+    {
+        errors.push(format!("webhook: {e}"));
+    }
+    if let Some(email) = &notify.email
+        && let Err(e) = mail(email, summary)
+    {
+        errors.push(format!("email: {e}"));
+    }
+    errors
+}
+
+#[cfg(feature = "http")]
+fn post(url: &str, summary: &Summary) -> Result<(), Box<dyn Error>> {
+    use ureq::Agent;
+
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let response = agent
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(serde_json::to_string(summary)?)?;
+    if !response.status().is_success() {
+        return Err(format!("the webhook answered {}", response.status()).into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+fn post(_url: &str, _summary: &Summary) -> Result<(), Box<dyn Error>> {
+    Err("rbaserun was built without the http feature".into())
+}
+
+#[cfg(feature = "email")]
+fn mail(email: &Email, summary: &Summary) -> Result<(), Box<dyn Error>> {
+    use lettre::message::header::ContentType;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{Message, SmtpTransport, Transport};
+
+    let mut message = Message::builder()
+        .from(email.from.parse()?)
+        .subject(summary.subject())
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        message = message.to(to.parse()?);
+    }
+    let message = message.body(summary.text.clone())?;
+
+    let mut transport = match email.tls {
+        SmtpTls::Starttls => SmtpTransport::starttls_relay(&email.host)?,
+        SmtpTls::Tls => SmtpTransport::relay(&email.host)?,
+        SmtpTls::None => SmtpTransport::builder_dangerous(&email.host),
+    };
+    if let Some(port) = email.port {
+        transport = transport.port(port);
+    }
+    if let Some(user) = &email.user {
+        transport = transport.credentials(Credentials::new(user.clone(), password(user)?));
+    }
+    transport.build().send(&message)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "email"))]
+fn mail(_email: &Email, _summary: &Summary) -> Result<(), Box<dyn Error>> {
+    Err("rbaserun was built without the email feature".into())
+}
+
+fn account(user: &str) -> String {
+    format!("smtp {user}")
+}
+
+#[cfg(feature = "email")]
+fn password(user: &str) -> Result<String, Box<dyn Error>> {
+    if let Ok(password) = std::env::var(PASSWORD_VAR) {
+        return Ok(password);
+    }
+    secrets::get(&account(user)).map_err(|e| {
+        format!(
+            "No SMTP password for {user}: {e}; save it with `rbaserun backup smtp-login {user}` or set {PASSWORD_VAR}"
+        )
+        .into()
+    })
+}
+
+/// Stores the SMTP password of `user` in the OS keyring, reading it from stdin.
+pub fn login(user: &str) -> Result<(), Box<dyn Error>> {
+    print!("SMTP password of {user}: ");
+    io::stdout().flush()?;
+    let mut password = String::new();
+    io::stdin().read_line(&mut password)?;
+    secrets::set(&account(user), password.trim_end_matches(['\r', '\n']))?;
+    println!("Saved the SMTP password of {user}");
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+use crate::bases::Bases;
+use crate::history::HistoryEntry;
+use crate::{PathKind, lockdown, normalize_input, parse_base_path};
+
+/// A base of the history to be named, with the name proposed for it.
+struct Unnamed {
+    path: String,
+    proposal: String,
+}
+
+/// Walks through the bases of the history without a name in the bases file,
+/// every one with `all`, asking for a name and tags for each on stdin and
+/// saving them as it goes, `rbaserun organize`.
+pub fn run(history: &[HistoryEntry], bases: &mut Bases, all: bool) -> Result<(), Box<dyn Error>> {
+    let unnamed = unnamed(history, bases, all);
+    if unnamed.is_empty() {
+        println!("Every base of the history has a name, --all goes through them anyway");
+        return Ok(());
+    }
+    println!(
+        "{} bases to organize. Enter accepts the proposal in brackets, typing replaces it, \
+         `-` skips the base and `q` stops. Tags are separated by commas, `-` removes them.",
+        unnamed.len()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut ask = |prompt: &str| -> Result<Option<String>, Box<dyn Error>> {
+        print!("{prompt}");
+        io::stdout().flush()?;
+        // the end of stdin stops like `q`
+        Ok(lines
+            .next()
+            .transpose()?
+            .map(|line| line.trim().to_string()))
+    };
+    let (mut named, mut tagged) = (0, 0);
+    for (number, base) in unnamed.iter().enumerate() {
+        println!("\n[{}/{}] {}", number + 1, unnamed.len(), base.path);
+        let Some(name) = ask(&format!("  Name [{}]: ", base.proposal))? else {
+            break;
+        };
+        match name.as_str() {
+            "q" => break,
+            "-" => continue,
+            "" => bases.entry_mut(&base.path).name = Some(base.proposal.clone()),
+            name => bases.entry_mut(&base.path).name = Some(name.to_string()),
+        }
+        named += 1;
+
+        let tags = bases.entry_mut(&base.path).tags.join(", ");
+        let Some(answer) = ask(&format!("  Tags [{tags}]: "))? else {
+            break;
+        };
+        match answer.as_str() {
+            "q" => break,
+            "" => {}
+            answer => {
+                let was_production = bases.is_production(&base.path);
+                let before = bases.entry_mut(&base.path).tags.clone();
+                bases.entry_mut(&base.path).tags = if answer == "-" {
+                    Vec::new()
+                } else {
+                    answer
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                };
+                if was_production
+                    && !bases.is_production(&base.path)
+                    && let Err(e) = lockdown::check_removal(&base.path)
+                {
+                    let prod = before
+                        .into_iter()
+                        .filter(|tag| tag.eq_ignore_ascii_case("prod"));
+                    bases.entry_mut(&base.path).tags.extend(prod);
+                    println!("  Kept the prod tag: {e}");
+                }
+                tagged += 1;
+            }
+        }
+        // saved after every base, stopping halfway keeps what was done
+        bases.save()?;
+    }
+    bases.save()?;
+    println!("\nNamed {named} bases and changed the tags of {tagged}");
+    Ok(())
+}
+
+/// The bases of the history to go through, in the order of the history,
+/// each with the name it was picked by, e.g. in the 1C launcher, or the
+/// one its connection string suggests.
+fn unnamed(history: &[HistoryEntry], bases: &Bases, all: bool) -> Vec<Unnamed> {
+    let parsed: Vec<(String, PathKind, Option<&str>)> = history
+        .iter()
+        .filter_map(|entry| {
+            let path = normalize_input(&entry.path);
+            let parsed = parse_base_path(&path).ok()?;
+            Some((path, parsed, entry.name.as_deref()))
+        })
+        .filter(|(path, ..)| !bases.is_managed(path))
+        .filter(|(path, ..)| all || bases.find(path).is_none_or(|entry| entry.name.is_none()))
+        .collect();
+    // an infobase name found on several servers gets the server added
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, parsed, _) in &parsed {
+        *counts.entry(parsed.name().to_lowercase()).or_default() += 1;
+    }
+    parsed
+        .into_iter()
+        .map(|(path, parsed, name)| {
+            let proposal = match (name, &parsed) {
+                (Some(name), _) => name.to_string(),
+                (None, PathKind::Server { host, ref_name })
+                    if counts[&ref_name.to_lowercase()] > 1 =>
+                {
+                    format!("{ref_name} ({host})")
+                }
+                (None, parsed) => parsed.name(),
+            };
+            let proposal = bases
+                .find(&path)
+                .and_then(|entry| entry.name.clone())
+                .unwrap_or(proposal);
+            Unnamed { path, proposal }
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Per-user config directory: `%APPDATA%\rbaserun`, `~/.config/rbaserun`
+/// elsewhere.
+pub fn config_dir() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    dir.map(|dir| dir.join("rbaserun"))
+}
+
+/// Directory of the machine-wide config and bases pushed by IT, read before
+/// the ones of the user. A fixed path, any user could point `%ProgramData%`
+/// at a directory of their own and drop the lockdown with it.
+pub fn machine_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        Some(PathBuf::from(r"C:\ProgramData\rbaserun"))
+    } else if cfg!(target_os = "macos") {
+        Some(PathBuf::from("/Library/Application Support/rbaserun"))
+    } else {
+        Some(PathBuf::from("/etc/rbaserun"))
+    }
+}
+
+/// Per-user directory of the history, bases and other state:
+/// `%LOCALAPPDATA%\rbaserun` on Windows, `~/Library/Application Support/rbaserun`
+/// on macOS and `~/.local/share/rbaserun` elsewhere. The working directory
+/// when none of them can be found.
+pub fn data_dir() -> PathBuf {
+    let dir = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    dir.map_or_else(|| PathBuf::from("."), |dir| dir.join("rbaserun"))
+}
+
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().join(name)
+}
+
+/// Creates the directory `file` goes to, so a first save does not fail.
+pub fn prepare(file: &Path) -> io::Result<()> {
+    match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
+
+/// Moves `name` from the working directory, where earlier versions kept it,
+/// to `target` unless a file is there already.
+pub fn migrate(name: &str, target: &Path) {
+    let legacy = Path::new(".").join(name);
+    if !legacy.is_file() || target.exists() {
+        return;
+    }
+    let moved = prepare(target).and_then(|()| {
+        fs::rename(&legacy, target).or_else(|_| {
+            // rename fails across file systems
+            fs::copy(&legacy, target)?;
+            fs::remove_file(&legacy)
+        })
+    });
+    match moved {
+        Ok(()) => eprintln!("Moved {} to {}", legacy.display(), target.display()),
+        Err(e) => eprintln!(
+            "Could not move {} to {}: {e}",
+            legacy.display(),
+            target.display()
+        ),
+    }
+}
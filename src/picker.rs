@@ -0,0 +1,91 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    text::Line,
+    widgets::{Block, Clear, List, ListState},
+};
+
+use crate::LaunchMode;
+use crate::keys;
+use crate::popup;
+use crate::theme;
+
+pub enum PickerAction {
+    None,
+    Pick(LaunchMode),
+    Cancel,
+}
+
+/// Per-launch mode selection shown on Enter when `ask_mode` is enabled.
+#[derive(Debug)]
+pub struct ModePicker {
+    pub path: String,
+    /// Start the client with `SAFE_MODE_SWITCHES`, toggled with `s`
+    pub safe_mode: bool,
+    state: ListState,
+}
+
+impl ModePicker {
+    pub fn new(path: String, initial: LaunchMode) -> ModePicker {
+        let index = LaunchMode::ALL.iter().position(|mode| *mode == initial);
+        ModePicker {
+            path,
+            safe_mode: false,
+            state: ListState::default().with_selected(index),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> PickerAction {
+        let Event::Key(key_event) = event else {
+            return PickerAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return PickerAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => PickerAction::Cancel,
+            KeyCode::Enter => match self.state.selected() {
+                Some(index) => PickerAction::Pick(LaunchMode::ALL[index]),
+                None => PickerAction::None,
+            },
+            KeyCode::Up => {
+                self.state.select_previous();
+                PickerAction::None
+            }
+            KeyCode::Down => {
+                self.state.select_next();
+                PickerAction::None
+            }
+            KeyCode::Char('s' | 'S') => {
+                self.safe_mode = !self.safe_mode;
+                PickerAction::None
+            }
+            KeyCode::Char(c) => LaunchMode::ALL
+                .iter()
+                .find(|mode| mode.hotkey() == c.to_ascii_lowercase())
+                .map_or(PickerAction::None, |mode| PickerAction::Pick(*mode)),
+            _ => PickerAction::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), LaunchMode::ALL.len() as u16 + 2);
+        let items = LaunchMode::ALL
+            .iter()
+            .map(|mode| format!("[{}] {}", mode.hotkey(), mode.label()));
+        let safe_mode = if self.safe_mode {
+            Line::styled("[s] Safe mode: on", theme::warning())
+        } else {
+            Line::styled("[s] Safe mode: off", theme::hint())
+        };
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title("Launch mode")
+                    .title_bottom(safe_mode),
+            )
+            .highlight_style(theme::selected());
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
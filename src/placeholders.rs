@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+
+/// A placeholder found in a connection string or switch.
+enum Placeholder<'a> {
+    /// `{name}` or `{prompt:Label}`, asked for before the launch
+    Value(&'a str),
+    /// `{env:NAME}`, taken from the environment at launch time
+    Env(&'a str),
+}
+
+/// Names of the placeholders in `text` asked for before a launch, in order
+/// of appearance and without repeats: the names of `{name}` and the labels
+/// of `{prompt:Label}`. Names are letters, digits and `_` starting with a
+/// letter, so GUIDs in braces are not taken for placeholders.
+pub fn names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for (_, _, placeholder) in placeholders(text) {
+        if let Placeholder::Value(name) = placeholder
+            && !names.iter().any(|own| own == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// `text` with its placeholders replaced by `values` and the environment,
+/// e.g. `Srvr="srv-{env}";Ref="hr_{env}";` with `env=test` becomes
+/// `Srvr="srv-test";Ref="hr_test";`.
+pub fn fill(text: &str, values: &BTreeMap<String, String>) -> Result<String, Box<dyn Error>> {
+    let mut filled = String::new();
+    let mut rest = 0;
+    for (start, end, placeholder) in placeholders(text) {
+        let value = match placeholder {
+            Placeholder::Value(name) => values.get(name).cloned().ok_or_else(|| {
+                let var = if is_name(name) {
+                    format!("{name}=VALUE")
+                } else {
+                    format!("'{name}=VALUE'")
+                };
+                format!(
+                    "{} needs a value for {{{}}}, pass it with --var {var}",
+                    text.trim(),
+                    &text[start + 1..end - 1]
+                )
+            })?,
+            Placeholder::Env(name) => env::var(name).map_err(|_| {
+                format!(
+                    "{} needs the environment variable {name}, which is not set",
+                    text.trim()
+                )
+            })?,
+        };
+        filled.push_str(&text[rest..start]);
+        filled.push_str(&value);
+        rest = end;
+    }
+    filled.push_str(&text[rest..]);
+    Ok(filled)
+}
+
+/// Parses a `--var` argument, `NAME=VALUE`, where the name may also be the
+/// label of a `{prompt:Label}`.
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{arg}'"))?;
+    if !is_name(name) && !is_label(name) {
+        return Err(format!("'{name}' is not a placeholder name"));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Byte offsets of the opening braces and past the closing braces of the
+/// placeholders of `text`.
+fn placeholders(text: &str) -> Vec<(usize, usize, Placeholder<'_>)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find('{').map(|open| from + open) {
+        let Some(close) = text[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        let inner = &text[open + 1..close];
+        let placeholder = if let Some(label) = inner.strip_prefix("prompt:") {
+            is_label(label).then_some(Placeholder::Value(label))
+        } else if let Some(name) = inner.strip_prefix("env:") {
+            (!name.is_empty() && !name.contains('{')).then_some(Placeholder::Env(name))
+        } else {
+            is_name(inner).then_some(Placeholder::Value(inner))
+        };
+        match placeholder {
+            Some(placeholder) => {
+                found.push((open, close + 1, placeholder));
+                from = close + 1;
+            }
+            None => from = open + 1,
+        }
+    }
+    found
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Labels of `{prompt:Label}` are any text without braces and `=`.
+fn is_label(label: &str) -> bool {
+    !label.trim().is_empty() && !label.contains(['{', '}', '='])
+}
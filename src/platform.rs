@@ -0,0 +1,854 @@
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::LaunchMode;
+
+/// An installed 1C platform version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub version: Vec<u32>,
+    /// Directory with the clients, `bin` of the version on Windows
+    pub bin: PathBuf,
+}
+
+impl Platform {
+    pub fn version_string(&self) -> String {
+        self.version
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// The thin client for Enterprise mode when installed, the thick one otherwise.
+    pub fn binary(&self, mode: LaunchMode) -> PathBuf {
+        let thin = os::client(&self.bin, "1cv8c");
+        if mode == LaunchMode::Enterprise && thin.exists() {
+            thin
+        } else {
+            os::client(&self.bin, "1cv8")
+        }
+    }
+}
+
+/// Platforms found in the install locations of this OS, oldest first.
+pub fn installed() -> Vec<Platform> {
+    let mut platforms: Vec<Platform> = os::version_dirs()
+        .iter()
+        .filter_map(|dir| platform_in(dir))
+        .collect();
+    platforms.sort_by(|a, b| a.version.cmp(&b.version));
+    platforms.dedup_by(|a, b| a.version == b.version);
+    platforms
+}
+
+/// The starter of this OS at its usual location, otherwise the one shipped
+/// with the newest platform. Falls back to the usual location when there is
+/// none, so the launch error names a sensible path.
+pub fn default_starter() -> PathBuf {
+    os::STARTERS
+        .iter()
+        .map(PathBuf::from)
+        .find(|starter| starter.exists())
+        .or_else(|| {
+            installed()
+                .iter()
+                .rev()
+                .map(|platform| os::client(&platform.bin, "1cestart"))
+                .find(|starter| starter.exists())
+        })
+        .unwrap_or_else(|| PathBuf::from(os::STARTERS[0]))
+}
+
+/// Location of `ibases.v8i`, the list of the 1C launcher of the current user.
+pub fn ibases_file() -> Option<PathBuf> {
+    os::ibases_file()
+}
+
+/// Directory where the 1C clients keep the settings of the current user, one
+/// subdirectory per base named by its launcher ID.
+pub fn user_settings_root() -> Option<PathBuf> {
+    os::user_settings_root()
+}
+
+/// The program opening urls and files with their default application.
+pub fn browser_opener() -> &'static str {
+    os::BROWSER_OPENER
+}
+
+/// Command running `line` in the shell of this OS.
+pub fn shell(line: &str) -> Command {
+    let [program, switch] = os::SHELL;
+    let mut command = Command::new(program);
+    command.arg(switch).arg(line);
+    command
+}
+
+/// The environment variable `name` quoted for a line run by `shell`, so its
+/// value is passed as one argument and never parsed by the shell.
+pub fn shell_var(name: &str) -> String {
+    let [before, after] = os::SHELL_VAR;
+    format!("{before}{name}{after}")
+}
+
+/// Runs `line` in the shell without a terminal and returns its stdout,
+/// killing it after `timeout`. Its stderr becomes the error when it fails.
+pub fn run_captured(
+    line: &str,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut command = shell(line);
+    command.envs(env.iter().cloned());
+    capture(command, line, timeout)
+}
+
+/// Runs `command` like `run_captured`, naming it `line` in the errors.
+fn capture(mut command: Command, line: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("`{line}` could not start: {e}"))?;
+    // read while it runs, a chatty command must not block on a full pipe
+    let read = |pipe: Option<Box<dyn Read + Send>>| {
+        pipe.map(|mut pipe| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let mut text = String::new();
+                let _ = pipe.read_to_string(&mut text);
+                let _ = sender.send(text);
+            });
+            receiver
+        })
+    };
+    let stdout = read(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+    let stderr = read(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("`{line}` timed out after {} s", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("`{line}`: {e}")),
+        }
+    };
+    // a process it left in the background, e.g. `ssh -fN` or a daemonized
+    // VPN client, may hold the pipes open: their output is given up on once
+    // the timeout is over
+    let joined = |reader: Option<Receiver<String>>| {
+        reader
+            .and_then(|reader| {
+                reader
+                    .recv_timeout(timeout.saturating_sub(started.elapsed()))
+                    .ok()
+            })
+            .unwrap_or_default()
+    };
+    if status.success() {
+        return Ok(joined(stdout));
+    }
+    let stderr = joined(stderr);
+    match stderr.lines().rfind(|line| !line.trim().is_empty()) {
+        Some(last) => Err(format!("`{line}` failed: {}", last.trim())),
+        None => Err(format!("`{line}` exited with {status}")),
+    }
+}
+
+/// Groups of the current OS user, empty when they can't be looked up.
+pub fn user_groups() -> Vec<String> {
+    os::groups()
+}
+
+/// Name of this machine, `localhost` when it can't be found out.
+pub fn host_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// Writes a shortcut named `name` into `dir` running `exe` with `args`, with
+/// `icon` instead of the icon of `exe` when given: a `.lnk` file on Windows,
+/// a `.desktop` entry elsewhere. Returns the file written.
+pub fn shortcut(
+    dir: &Path,
+    name: &str,
+    exe: &Path,
+    args: &[String],
+    icon: Option<&Path>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect();
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    os::shortcut(dir, name.trim(), exe, args, icon)
+}
+
+/// Command showing a desktop notification with `title` and `message`.
+pub fn notification(title: &str, message: &str) -> Command {
+    os::notification(title, message)
+}
+
+/// Names of the running processes of this machine, lowercase and without
+/// `.exe`, empty when the process list can't be read.
+pub fn process_names() -> Vec<String> {
+    os::process_names()
+        .into_iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            name.strip_suffix(".exe")
+                .map_or(name.clone(), str::to_string)
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Physical memory free for new processes in bytes, when it can be read.
+pub fn available_memory() -> Option<u64> {
+    os::available_memory()
+}
+
+/// Running 1C clients (`1cv8`, `1cv8c`) of this machine with their command
+/// lines, empty when the process list can't be read.
+pub fn client_processes() -> Vec<(u32, String)> {
+    os::client_processes()
+}
+
+/// Name and version of the configuration of the base at `connection`, a
+/// connection string with `Usr` and `Pwd`, read through the COM connector
+/// of 1C. Fails where there is no COM.
+pub fn configuration_info(connection: &str, timeout: Duration) -> Result<(String, String), String> {
+    let command = os::configuration_query(connection)
+        .ok_or("reading the configuration needs the COM connector of 1C on Windows")?;
+    let output = capture(command, "COM connector", timeout)?;
+    let (name, version) = output
+        .trim()
+        .split_once('\t')
+        .ok_or_else(|| format!("unexpected output of the COM connector: {}", output.trim()))?;
+    Ok((name.to_string(), version.to_string()))
+}
+
+/// Brings the window of the process `pid` to the front.
+pub fn focus_window(pid: u32) -> Result<(), Box<dyn Error>> {
+    let status = os::focus_window(pid)
+        .status()
+        .map_err(|e| format!("Could not focus the window: {e}"))?;
+    if !status.success() {
+        return Err(format!("Could not focus the window of process {pid}").into());
+    }
+    Ok(())
+}
+
+/// Newest installed platform whose version starts with `version`, e.g. `8.3.24`.
+pub fn find(version: &str) -> Result<Platform, Box<dyn Error>> {
+    let wanted = parse_version(version.trim())
+        .ok_or_else(|| format!("Invalid platform version '{version}'"))?;
+    let platforms = installed();
+    platforms
+        .iter()
+        .rev()
+        .find(|platform| platform.version.starts_with(&wanted))
+        .cloned()
+        .ok_or_else(|| {
+            let installed: Vec<String> = platforms.iter().map(Platform::version_string).collect();
+            if installed.is_empty() {
+                format!("Platform {version} is not installed, no 1C platforms found").into()
+            } else {
+                format!(
+                    "Platform {version} is not installed, installed: {}",
+                    installed.join(", ")
+                )
+                .into()
+            }
+        })
+}
+
+pub fn newest() -> Result<Platform, Box<dyn Error>> {
+    installed()
+        .pop()
+        .ok_or_else(|| "No installed 1C platform found".into())
+}
+
+/// Prints the installed platforms, newest first.
+pub fn list() -> Result<(), Box<dyn Error>> {
+    let platforms = installed();
+    if platforms.is_empty() {
+        return Err("No installed 1C platform found".into());
+    }
+    for platform in platforms.iter().rev() {
+        println!("{}\t{}", platform.version_string(), platform.bin.display());
+    }
+    Ok(())
+}
+
+fn platform_in(dir: &Path) -> Option<Platform> {
+    let dir = dir.to_str()?.trim_end_matches(['\\', '/']);
+    let dir = Path::new(dir);
+    let version = parse_version(dir.file_name()?.to_str()?)?;
+    let bin = os::bin_dir(dir);
+    os::client(&bin, "1cv8")
+        .exists()
+        .then_some(Platform { version, bin })
+}
+
+fn parse_version(text: &str) -> Option<Vec<u32>> {
+    text.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Entries of the directories in `roots`, the candidate version directories.
+fn subdirectories(roots: &[PathBuf]) -> Vec<PathBuf> {
+    roots
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()))
+        .collect()
+}
+
+/// What the unix systems share, the macOS and Linux `os` modules differ in
+/// the install locations of 1C only.
+#[cfg(unix)]
+mod unix {
+    use std::error::Error;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    pub const SHELL: [&str; 2] = ["sh", "-c"];
+    pub const SHELL_VAR: [&str; 2] = ["\"$", "\""];
+
+    pub fn groups() -> Vec<String> {
+        let Ok(output) = Command::new("id").arg("-Gn").output() else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn notification(title: &str, message: &str) -> Command {
+        let mut command = Command::new("notify-send");
+        command.args([title, message]);
+        command
+    }
+
+    /// No COM, see `configuration_info`.
+    pub fn configuration_query(_connection: &str) -> Option<Command> {
+        None
+    }
+
+    /// A `.desktop` entry, shown by the desktops following the freedesktop
+    /// specification when saved in `~/.local/share/applications`.
+    pub fn shortcut(
+        dir: &Path,
+        name: &str,
+        exe: &Path,
+        args: &[String],
+        icon: Option<&Path>,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let exec: Vec<String> = std::iter::once(exe.display().to_string())
+            .chain(args.iter().cloned())
+            .map(|arg| desktop_quote(&arg))
+            .collect();
+        let mut entry = format!(
+            "[Desktop Entry]\nType=Application\nName={name}\nExec={}\nTerminal=false\n",
+            exec.join(" ")
+        );
+        if let Some(icon) = icon {
+            entry.push_str(&format!("Icon={}\n", icon.display()));
+        }
+        let file = dir.join(format!("{name}.desktop"));
+        fs::write(&file, entry).map_err(|e| format!("Could not write {}: {e}", file.display()))?;
+        Ok(file)
+    }
+
+    /// An argument of the `Exec` key, quoted as the desktop entry
+    /// specification asks, with `%` doubled as it starts field codes.
+    fn desktop_quote(arg: &str) -> String {
+        let mut quoted = String::from('"');
+        for c in arg.chars() {
+            match c {
+                '"' | '`' | '$' | '\\' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                '%' => quoted.push_str("%%"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    pub fn process_names() -> Vec<String> {
+        let Ok(output) = Command::new("ps").args(["-eo", "comm="]).output() else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().rsplit('/').next().map(str::to_string))
+            .collect()
+    }
+
+    pub fn client_processes() -> Vec<(u32, String)> {
+        let Ok(output) = Command::new("ps").args(["-eo", "pid=,args="]).output() else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, args) = line.trim_start().split_once(' ')?;
+                let program = args.split_whitespace().next()?;
+                let name = program.rsplit('/').next()?;
+                if !matches!(name, "1cv8" | "1cv8c") {
+                    return None;
+                }
+                Some((pid.parse().ok()?, args.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::env;
+    use std::error::Error;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    pub const BROWSER_OPENER: &str = "explorer";
+    pub const SHELL: [&str; 2] = ["cmd", "/C"];
+    // a Windows path has no quotes, the expanded value can't leave the quoted
+    // argument
+    pub const SHELL_VAR: [&str; 2] = ["\"%", "%\""];
+
+    const POWERSHELL_TOAST: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $template.GetElementsByTagName('text')
+$text.Item(0).AppendChild($template.CreateTextNode($env:RBASERUN_TOAST_TITLE)) > $null
+$text.Item(1).AppendChild($template.CreateTextNode($env:RBASERUN_TOAST_MESSAGE)) > $null
+$appId = '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe'
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($appId).Show([Windows.UI.Notifications.ToastNotification]::new($template))
+"#;
+
+    const POWERSHELL_SHORTCUT: &str = r#"
+$link = (New-Object -ComObject WScript.Shell).CreateShortcut($env:RBASERUN_LNK)
+$link.TargetPath = $env:RBASERUN_LNK_TARGET
+$link.Arguments = $env:RBASERUN_LNK_ARGS
+if ($env:RBASERUN_LNK_ICON) { $link.IconLocation = "$env:RBASERUN_LNK_ICON,0" }
+$link.Save()
+"#;
+
+    const POWERSHELL_FOCUS: &str = r#"
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public static class RBaseRunWindows {
+    delegate bool EnumWindowsProc(IntPtr window, IntPtr param);
+    [DllImport("user32.dll")] static extern bool EnumWindows(EnumWindowsProc callback, IntPtr param);
+    [DllImport("user32.dll")] static extern uint GetWindowThreadProcessId(IntPtr window, out uint pid);
+    [DllImport("user32.dll")] static extern bool IsWindowVisible(IntPtr window);
+    [DllImport("user32.dll")] static extern bool IsIconic(IntPtr window);
+    [DllImport("user32.dll")] static extern bool ShowWindow(IntPtr window, int command);
+    [DllImport("user32.dll")] static extern bool SetForegroundWindow(IntPtr window);
+    public static bool Focus(uint pid) {
+        bool found = false;
+        EnumWindows((window, param) => {
+            uint owner;
+            GetWindowThreadProcessId(window, out owner);
+            if (owner == pid && IsWindowVisible(window)) {
+                if (IsIconic(window)) { ShowWindow(window, 9); }
+                SetForegroundWindow(window);
+                found = true;
+                return false;
+            }
+            return true;
+        }, IntPtr.Zero);
+        return found;
+    }
+}
+'@
+if (-not [RBaseRunWindows]::Focus([uint32]$env:RBASERUN_FOCUS_PID)) { exit 1 }
+"#;
+
+    /// Prints the name and version of the configuration, tab separated. The
+    /// connection string comes in a variable so the password stays out of
+    /// the command line.
+    const POWERSHELL_CONFIGURATION: &str = r#"
+$ErrorActionPreference = 'Stop'
+$connector = New-Object -ComObject V83.COMConnector
+$base = $connector.Connect($env:RBASERUN_COM_CONNECTION)
+"$($base.Metadata.Name)`t$($base.Metadata.Version)"
+"#;
+
+    pub const STARTERS: [&str; 2] = [
+        r"c:\Program Files\1cv8\common\1cestart.exe",
+        r"c:\Program Files (x86)\1cv8\common\1cestart.exe",
+    ];
+    const DEFAULT_ROOTS: [&str; 2] = [r"c:\Program Files\1cv8", r"c:\Program Files (x86)\1cv8"];
+    const UNINSTALL_KEYS: [&str; 2] = [
+        r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
+        r"HKLM\SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
+    ];
+
+    /// Versions in Program Files (both 64- and 32-bit) and the ones registered
+    /// with Windows.
+    pub fn version_dirs() -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+            .iter()
+            .filter_map(env::var_os)
+            .map(|dir| PathBuf::from(dir).join("1cv8"))
+            .collect();
+        roots.extend(DEFAULT_ROOTS.iter().map(PathBuf::from));
+        let mut dirs = super::subdirectories(&roots);
+        dirs.extend(registry_locations());
+        dirs
+    }
+
+    pub fn bin_dir(version_dir: &Path) -> PathBuf {
+        version_dir.join("bin")
+    }
+
+    pub fn client(bin: &Path, name: &str) -> PathBuf {
+        bin.join(format!("{name}.exe"))
+    }
+
+    pub fn ibases_file() -> Option<PathBuf> {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join(r"1C\1CEStart\ibases.v8i"))
+    }
+
+    pub fn user_settings_root() -> Option<PathBuf> {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join(r"1C\1cv8"))
+    }
+
+    /// Groups as `whoami` lists them, with their domain such as
+    /// `BUILTIN\Administrators`.
+    pub fn groups() -> Vec<String> {
+        let Ok(output) = Command::new("whoami")
+            .args(["/groups", "/fo", "csv", "/nh"])
+            .output()
+        else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split("\",\"").next())
+            .map(|name| name.trim_matches('"').to_string())
+            .collect()
+    }
+
+    /// A toast of the Windows notification center, shown through PowerShell.
+    pub fn notification(title: &str, message: &str) -> Command {
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                POWERSHELL_TOAST,
+            ])
+            .env("RBASERUN_TOAST_TITLE", title)
+            .env("RBASERUN_TOAST_MESSAGE", message);
+        command
+    }
+
+    pub fn process_names() -> Vec<String> {
+        let Ok(output) = Command::new("tasklist")
+            .args(["/FO", "CSV", "/NH"])
+            .output()
+        else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split("\",\"").next())
+            .map(|name| name.trim_matches('"').to_string())
+            .collect()
+    }
+
+    /// `FreePhysicalMemory` of `Win32_OperatingSystem`, in kB there.
+    pub fn available_memory() -> Option<u64> {
+        let output = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "(Get-CimInstance Win32_OperatingSystem).FreePhysicalMemory",
+            ])
+            .output()
+            .ok()?;
+        let kb: u64 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()?;
+        Some(kb * 1024)
+    }
+
+    pub fn client_processes() -> Vec<(u32, String)> {
+        let Ok(output) = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Get-CimInstance Win32_Process -Filter \"Name='1cv8.exe' or Name='1cv8c.exe'\" \
+                 | ForEach-Object { \"$($_.ProcessId)`t$($_.CommandLine)\" }",
+            ])
+            .output()
+        else {
+            return Vec::new();
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, args) = line.split_once('\t')?;
+                Some((pid.trim().parse().ok()?, args.to_string()))
+            })
+            .collect()
+    }
+
+    pub fn configuration_query(connection: &str) -> Option<Command> {
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                POWERSHELL_CONFIGURATION,
+            ])
+            .env("RBASERUN_COM_CONNECTION", connection);
+        Some(command)
+    }
+
+    /// A `.lnk` file written through `WScript.Shell`, the arguments quoted
+    /// the way `CommandLineToArgvW` expects.
+    pub fn shortcut(
+        dir: &Path,
+        name: &str,
+        exe: &Path,
+        args: &[String],
+        icon: Option<&Path>,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let file = dir.join(format!("{name}.lnk"));
+        let args: Vec<String> = args
+            .iter()
+            .map(|arg| format!("\"{}\"", arg.replace('"', "\\\"")))
+            .collect();
+        let status = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                POWERSHELL_SHORTCUT,
+            ])
+            .env("RBASERUN_LNK", &file)
+            .env("RBASERUN_LNK_TARGET", exe)
+            .env("RBASERUN_LNK_ARGS", args.join(" "))
+            .env("RBASERUN_LNK_ICON", icon.unwrap_or(Path::new("")))
+            .status()
+            .map_err(|e| format!("Could not run PowerShell: {e}"))?;
+        if !status.success() {
+            return Err(format!("Could not write {}", file.display()).into());
+        }
+        Ok(file)
+    }
+
+    /// Restores and activates the visible top-level windows of `pid`, found
+    /// with `EnumWindows`; exits with 1 when the process has none.
+    pub fn focus_window(pid: u32) -> Command {
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                POWERSHELL_FOCUS,
+            ])
+            .env("RBASERUN_FOCUS_PID", pid.to_string());
+        command
+    }
+
+    /// `InstallLocation` values of installed programs, 1C registers every
+    /// platform version there even when it lives outside Program Files.
+    fn registry_locations() -> Vec<PathBuf> {
+        UNINSTALL_KEYS
+            .iter()
+            .filter_map(|key| {
+                Command::new("reg")
+                    .args(["query", key, "/s", "/v", "InstallLocation"])
+                    .output()
+                    .ok()
+            })
+            .flat_map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.split_once("REG_SZ"))
+                    .map(|(_, location)| PathBuf::from(location.trim()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use std::env;
+    use std::path::{Path, PathBuf};
+
+    use std::process::Command;
+
+    pub use super::unix::{
+        SHELL, SHELL_VAR, client_processes, configuration_query, groups, notification,
+        process_names, shortcut,
+    };
+
+    pub const BROWSER_OPENER: &str = "open";
+
+    /// Free and inactive pages of `vm_stat`, which the system hands out
+    /// before swapping.
+    pub fn available_memory() -> Option<u64> {
+        let output = Command::new("vm_stat").output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let page_size: u64 = text
+            .lines()
+            .next()?
+            .split("page size of ")
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()?;
+        let pages = |name: &str| -> Option<u64> {
+            let line = text.lines().find(|line| line.starts_with(name))?;
+            line.split(':')
+                .nth(1)?
+                .trim()
+                .trim_end_matches('.')
+                .parse()
+                .ok()
+        };
+        Some((pages("Pages free")? + pages("Pages inactive")?) * page_size)
+    }
+
+    pub fn focus_window(pid: u32) -> Command {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "tell application \"System Events\" to set frontmost of \
+                 (first process whose unix id is {pid}) to true"
+            ),
+        ]);
+        command
+    }
+    pub const STARTERS: [&str; 1] =
+        ["/Applications/1cv8.localized/1cestart.app/Contents/MacOS/1cestart"];
+    const ROOTS: [&str; 1] = ["/Applications/1cv8.localized"];
+
+    pub fn version_dirs() -> Vec<PathBuf> {
+        super::subdirectories(&ROOTS.map(PathBuf::from))
+    }
+
+    pub fn bin_dir(version_dir: &Path) -> PathBuf {
+        version_dir.to_path_buf()
+    }
+
+    /// Every client is an application bundle of its own.
+    pub fn client(bin: &Path, name: &str) -> PathBuf {
+        bin.join(format!("{name}.app/Contents/MacOS/{name}"))
+    }
+
+    pub fn ibases_file() -> Option<PathBuf> {
+        env::var_os("HOME").map(|dir| {
+            PathBuf::from(dir).join("Library/Application Support/1C/1cestart/ibases.v8i")
+        })
+    }
+
+    pub fn user_settings_root() -> Option<PathBuf> {
+        env::var_os("HOME")
+            .map(|dir| PathBuf::from(dir).join("Library/Application Support/1C/1cv8"))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod os {
+    use std::env;
+    use std::path::{Path, PathBuf};
+
+    use std::process::Command;
+
+    pub use super::unix::{
+        SHELL, SHELL_VAR, client_processes, configuration_query, groups, notification,
+        process_names, shortcut,
+    };
+
+    pub const BROWSER_OPENER: &str = "xdg-open";
+
+    /// `MemAvailable` of `/proc/meminfo`, in kB there.
+    pub fn available_memory() -> Option<u64> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo
+            .lines()
+            .find(|line| line.starts_with("MemAvailable:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+
+    /// Needs `xdotool`, there is no common way across window managers.
+    pub fn focus_window(pid: u32) -> Command {
+        let mut command = Command::new("xdotool");
+        command.args(["search", "--pid", &pid.to_string(), "windowactivate"]);
+        command
+    }
+
+    /// The shared starter of current releases, then the single-version
+    /// layout of releases before 8.3.20.
+    pub const STARTERS: [&str; 3] = [
+        "/opt/1cv8/common/1cestart",
+        "/opt/1C/v8.3/x86_64/1cestart",
+        "/opt/1C/v8.3/i386/1cestart",
+    ];
+    const ROOTS: [&str; 3] = ["/opt/1cv8/x86_64", "/opt/1cv8/i386", "/opt/1cv8/aarch64"];
+
+    pub fn version_dirs() -> Vec<PathBuf> {
+        super::subdirectories(&ROOTS.map(PathBuf::from))
+    }
+
+    pub fn bin_dir(version_dir: &Path) -> PathBuf {
+        version_dir.to_path_buf()
+    }
+
+    pub fn client(bin: &Path, name: &str) -> PathBuf {
+        bin.join(name)
+    }
+
+    pub fn ibases_file() -> Option<PathBuf> {
+        env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".1C/1cestart/ibases.v8i"))
+    }
+
+    pub fn user_settings_root() -> Option<PathBuf> {
+        env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".1cv8/1C/1cv8"))
+    }
+}
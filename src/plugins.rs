@@ -0,0 +1,234 @@
+use clap::Command as CliCommand;
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    widgets::{Block, Clear, List, ListState},
+};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::bases::Bases;
+use crate::{commands, keys, popup, theme};
+
+/// Executables named `rbaserun-<name>` on PATH are run as `rbaserun <name>`.
+const PREFIX: &str = "rbaserun-";
+
+/// An executable on PATH extending rbaserun with a command of its own.
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    pub program: PathBuf,
+}
+
+/// The plugins on PATH by name, the first directory of PATH winning like in
+/// the shell.
+pub fn discover() -> Vec<Plugin> {
+    let mut found: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+    {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let program = entry.path();
+            if let Some(name) = plugin_name(&program) {
+                found.entry(name).or_insert(program);
+            }
+        }
+    }
+    found
+        .into_iter()
+        .map(|(name, program)| Plugin { name, program })
+        .collect()
+}
+
+/// The plugin `name`, looked up without listing every directory of PATH.
+pub fn find(name: &str) -> Option<Plugin> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .flat_map(|dir| candidates(&dir, name))
+        .find(|program| plugin_name(program).as_deref() == Some(name))
+        .map(|program| Plugin {
+            name: name.to_string(),
+            program,
+        })
+}
+
+/// Files in `dir` the plugin `name` may be, one per extension on Windows.
+fn candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    if cfg!(windows) {
+        runnable_extensions()
+            .iter()
+            .map(|ext| dir.join(format!("{PREFIX}{name}.{ext}")))
+            .collect()
+    } else {
+        vec![dir.join(format!("{PREFIX}{name}"))]
+    }
+}
+
+fn runnable_extensions() -> Vec<String> {
+    env::var("PATHEXT")
+        .unwrap_or_else(|_| ".EXE;.BAT;.CMD".to_string())
+        .split(';')
+        .map(|ext| ext.trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
+/// The plugin named by the first argument, with the others, when it is not
+/// a command of rbaserun itself. Looked up before the arguments are parsed,
+/// the ones of the plugin mean nothing to rbaserun.
+pub fn requested(cli: &CliCommand) -> Option<(Plugin, Vec<OsString>)> {
+    let mut args = env::args_os().skip(1);
+    let first = args.next()?;
+    let name = first.to_str()?;
+    // options, connection strings and file paths are never plugin names
+    if name.starts_with('-')
+        || name.contains(['=', ';', '/', '\\', '"'])
+        || cli.find_subcommand(name).is_some()
+    {
+        return None;
+    }
+    find(name).map(|plugin| (plugin, args.collect()))
+}
+
+/// Lists the plugins under the help of rbaserun, PATH is only searched when
+/// help was asked for.
+pub fn with_help(cli: CliCommand) -> CliCommand {
+    if !env::args()
+        .skip(1)
+        .any(|arg| arg == "-h" || arg == "--help")
+    {
+        return cli;
+    }
+    let plugins = discover();
+    if plugins.is_empty() {
+        return cli;
+    }
+    let names: Vec<String> = plugins
+        .iter()
+        .map(|plugin| format!("  {}", plugin.name))
+        .collect();
+    cli.after_help(format!("Plugins on PATH:\n{}", names.join("\n")))
+}
+
+/// Runs `plugin` with `args` and `RBASERUN` set to this executable, writing
+/// `input` (the selected base as JSON) to its stdin.
+pub fn run(
+    plugin: &Plugin,
+    args: Vec<OsString>,
+    input: Option<String>,
+) -> Result<i32, Box<dyn Error>> {
+    let mut command = Command::new(&plugin.program);
+    command.args(args);
+    if let Ok(exe) = env::current_exe() {
+        command.env("RBASERUN", exe);
+    }
+    if input.is_some() {
+        command.stdin(Stdio::piped());
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Could not start {}: {e}", plugin.program.display()))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        // a plugin not reading its stdin closes it, that is no error
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    Ok(child.wait()?.code().unwrap_or(1))
+}
+
+/// The base `path` as `rbaserun list --json` prints it.
+pub fn entry_json(path: &str, bases: &Bases) -> Result<String, Box<dyn Error>> {
+    let paths = [path.to_string()];
+    Ok(serde_json::to_string(&commands::listed(&paths, bases)[0])?)
+}
+
+/// Name of the plugin `program` is, without the extension Windows runs it by.
+fn plugin_name(program: &Path) -> Option<String> {
+    let file_name = program.file_name()?.to_str()?;
+    let name = file_name.strip_prefix(PREFIX)?;
+    let name = if cfg!(windows) {
+        let (name, extension) = name.rsplit_once('.')?;
+        let runnable = runnable_extensions()
+            .iter()
+            .any(|ext| ext.eq_ignore_ascii_case(extension));
+        (runnable && program.is_file()).then_some(name)?
+    } else {
+        is_executable(program).then_some(name)?
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(program: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(program).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(program: &Path) -> bool {
+    program.is_file()
+}
+
+pub enum PluginsAction {
+    None,
+    Close,
+    Run(Plugin),
+}
+
+/// Popup listing the plugins, the chosen one is run with the selected base
+/// once the picker closed.
+#[derive(Debug)]
+pub struct PluginsPanel {
+    plugins: Vec<Plugin>,
+    state: ListState,
+}
+
+impl PluginsPanel {
+    pub fn new(plugins: Vec<Plugin>) -> PluginsPanel {
+        PluginsPanel {
+            plugins,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> PluginsAction {
+        let Event::Key(key_event) = event else {
+            return PluginsAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return PluginsAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc => return PluginsAction::Close,
+            KeyCode::Enter => {
+                if let Some(plugin) = self.state.selected().and_then(|i| self.plugins.get(i)) {
+                    return PluginsAction::Run(plugin.clone());
+                }
+            }
+            KeyCode::Up => self.state.select_previous(),
+            KeyCode::Down => self.state.select_next(),
+            _ => {}
+        }
+        PluginsAction::None
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), self.plugins.len() as u16 + 2);
+        let items = self.plugins.iter().map(|plugin| plugin.name.as_str());
+        let list = List::new(items)
+            .block(Block::bordered().title("Run plugin with the selected base"))
+            .highlight_style(theme::selected());
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+}
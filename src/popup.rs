@@ -0,0 +1,11 @@
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+
+pub fn area(frame_area: Rect, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(frame_area);
+    let [area] = Layout::horizontal([Constraint::Percentage(80)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
@@ -0,0 +1,785 @@
+use clap::ValueEnum;
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::bases::Bases;
+use crate::config::{ClusterConfig, RasConfig};
+use crate::designer::Credentials;
+use crate::templates::Template;
+use crate::tunnel::split_host_port;
+use crate::{PathKind, history, lockdown, normalize_input, parse_base_path, secrets};
+
+/// Port clients connect to when the connection string has none.
+const MANAGER_PORT: u16 = 1541;
+
+/// A cluster administered by the ras of a host.
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    pub id: String,
+    pub host: String,
+    pub port: u16,
+    pub name: String,
+}
+
+/// Client license held by a session, from `session list --licenses`.
+#[derive(Debug, Clone)]
+pub struct SessionLicense {
+    /// Series of the license key, e.g. `8100123456`
+    pub series: String,
+    /// `soft` for a software key, `hasp` for a hardware one
+    pub kind: String,
+    /// Users the key licenses in total
+    pub max_users: u32,
+    /// Handed out by the cluster rather than found on the client computer
+    pub by_server: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Infobase {
+    pub id: String,
+    pub name: String,
+    pub descr: String,
+}
+
+impl Cluster {
+    /// Connection string of infobase `name` of the cluster, in the short
+    /// `host;ref` form typed into the picker.
+    pub fn base_path(&self, name: &str) -> String {
+        match self.port {
+            MANAGER_PORT => format!("{};{name}", self.host),
+            port => format!("{}:{port};{name}", self.host),
+        }
+    }
+}
+
+/// Host of the cluster a base lives on, `None` for file and web bases.
+pub fn cluster_host(path: &str) -> Option<String> {
+    let PathKind::Server { host, .. } = parse_base_path(path).ok()? else {
+        return None;
+    };
+    // `Srvr="srv1,srv2"` lists the servers of one cluster
+    let first = host.split(',').next()?.trim();
+    Some(split_host_port(first).0)
+}
+
+/// Host to browse for `input`: the cluster host of a server base, or the
+/// input itself when it is a bare host name.
+pub fn browse_host(input: &str) -> Option<String> {
+    let input = input.trim();
+    if let Some(host) = cluster_host(input) {
+        return Some(host);
+    }
+    let bare = !input.is_empty() && !input.contains([';', '=', '/', '\\', '"', ' ']);
+    bare.then(|| split_host_port(input).0)
+}
+
+/// Database of a server infobase, as registered in the cluster.
+#[derive(Debug, Clone)]
+pub struct Database {
+    /// `MSSQLServer`, `PostgreSQL`, `IBMDB2` or `OracleDatabase`
+    pub dbms: String,
+    /// Database server as 1C connects to it, e.g. `pg1 port=5433`
+    pub server: String,
+}
+
+/// Who may use a server infobase, the `infobase update` settings locking
+/// it for maintenance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    pub jobs_denied: bool,
+    pub sessions_denied: bool,
+    /// Shown to users refused while sessions are denied
+    pub denied_message: String,
+    /// Start of the lock as `2026-10-16T09:00:00`, empty for now
+    pub denied_from: String,
+    /// End of the lock, empty for until it is lifted
+    pub denied_to: String,
+    /// Code that still lets a user in while sessions are denied
+    pub permission_code: String,
+}
+
+impl Access {
+    /// Markers of the locks in the history, e.g. `[sessions locked]`.
+    pub fn markers(&self) -> Vec<&'static str> {
+        let mut markers = Vec::new();
+        if self.sessions_denied {
+            markers.push("[sessions locked]");
+        }
+        if self.jobs_denied {
+            markers.push("[jobs locked]");
+        }
+        markers
+    }
+}
+
+/// DBMS of a server infobase, as named by `rac infobase create --dbms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Dbms {
+    Mssql,
+    Postgresql,
+    Db2,
+    Oracle,
+}
+
+impl Dbms {
+    fn rac_name(self) -> &'static str {
+        match self {
+            Dbms::Mssql => "MSSQLServer",
+            Dbms::Postgresql => "PostgreSQL",
+            Dbms::Db2 => "IBMDB2",
+            Dbms::Oracle => "OracleDatabase",
+        }
+    }
+}
+
+/// Settings of an infobase to create on a cluster.
+#[derive(Debug)]
+pub struct NewInfobase {
+    pub name: String,
+    pub descr: Option<String>,
+    pub dbms: Dbms,
+    /// Database server, the cluster host when not given
+    pub db_server: Option<String>,
+    /// Database name, the infobase name when not given
+    pub db_name: Option<String>,
+    pub db_user: Option<String>,
+    pub db_password: Option<String>,
+    pub locale: String,
+    /// 0 to 3, how protected the connection to the cluster is
+    pub security_level: u8,
+    pub deny_scheduled_jobs: bool,
+}
+
+/// The administration server (ras) of a host, with the administrator of
+/// the registered cluster when it has one.
+#[derive(Debug, Clone)]
+pub struct Ras {
+    /// Name of the registered cluster, the host for others
+    pub name: String,
+    pub host: String,
+    port: u16,
+    rac: Option<PathBuf>,
+    /// Cluster administrator and password
+    admin: Option<(String, String)>,
+}
+
+/// An infobase listed by the ras, with the connection string selecting it.
+#[derive(Debug, Clone)]
+pub struct Listed {
+    pub path: String,
+    pub name: String,
+    pub descr: String,
+    /// Name of the cluster the infobase belongs to
+    pub cluster: String,
+}
+
+impl Ras {
+    /// The ras of `host`, as registered when a `[[ras.cluster]]` has this host.
+    pub fn new(host: &str, config: &RasConfig) -> Ras {
+        match config
+            .clusters
+            .iter()
+            .find(|cluster| cluster.host.eq_ignore_ascii_case(host))
+        {
+            Some(cluster) => Ras::registered(cluster, config),
+            None => Ras {
+                name: host.to_string(),
+                host: host.to_string(),
+                port: config.port,
+                rac: config.rac.clone(),
+                admin: None,
+            },
+        }
+    }
+
+    /// A registered cluster, its administrator password read from the keyring.
+    pub fn registered(cluster: &ClusterConfig, config: &RasConfig) -> Ras {
+        let admin = cluster.user.as_ref().map(|user| {
+            let password = secrets::get(&account(&cluster.name)).ok();
+            (user.clone(), password.unwrap_or_default())
+        });
+        Ras {
+            name: cluster.name.clone(),
+            host: cluster.host.clone(),
+            port: cluster.port.unwrap_or(config.port),
+            rac: config.rac.clone(),
+            admin,
+        }
+    }
+
+    /// The registered clusters, after the ras of `host` unless it is one of them.
+    pub fn all(host: Option<&str>, config: &RasConfig) -> Vec<Ras> {
+        let mut all: Vec<Ras> = config
+            .clusters
+            .iter()
+            .map(|cluster| Ras::registered(cluster, config))
+            .collect();
+        if let Some(host) = host
+            && !all.iter().any(|ras| ras.host.eq_ignore_ascii_case(host))
+        {
+            all.insert(0, Ras::new(host, config));
+        }
+        all
+    }
+
+    pub fn clusters(&self) -> Result<Vec<Cluster>, Box<dyn Error>> {
+        let output = self.rac(&["cluster", "list"])?;
+        Ok(records(&output)
+            .iter()
+            .filter_map(|record| {
+                Some(Cluster {
+                    id: value(record, "cluster")?.to_string(),
+                    host: value(record, "host").unwrap_or(&self.host).to_string(),
+                    port: value(record, "port")
+                        .and_then(|port| port.parse().ok())
+                        .unwrap_or(MANAGER_PORT),
+                    name: value(record, "name").unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    pub fn infobases(&self, cluster: &Cluster) -> Result<Vec<Infobase>, Box<dyn Error>> {
+        let output = self.rac_cluster(cluster, &["infobase", "summary", "list"])?;
+        Ok(records(&output)
+            .iter()
+            .filter_map(|record| {
+                Some(Infobase {
+                    id: value(record, "infobase")?.to_string(),
+                    name: value(record, "name")?.to_string(),
+                    descr: value(record, "descr").unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// The infobases of all clusters, sorted by name.
+    pub fn list(&self) -> Result<Vec<Listed>, Box<dyn Error>> {
+        let mut listed = Vec::new();
+        for cluster in self.clusters()? {
+            for infobase in self.infobases(&cluster)? {
+                listed.push(Listed {
+                    path: cluster.base_path(&infobase.name),
+                    name: infobase.name,
+                    descr: infobase.descr,
+                    cluster: cluster.name.clone(),
+                });
+            }
+        }
+        listed.sort_by_key(|listed| listed.name.to_lowercase());
+        Ok(listed)
+    }
+
+    /// Creates the infobase and its database, returns the id of the infobase.
+    pub fn create_infobase(
+        &self,
+        cluster: &Cluster,
+        new: &NewInfobase,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut args = vec![
+            "infobase".to_string(),
+            "create".to_string(),
+            "--create-database".to_string(),
+            format!("--name={}", new.name),
+            format!("--dbms={}", new.dbms.rac_name()),
+            format!(
+                "--db-server={}",
+                new.db_server.as_deref().unwrap_or(&cluster.host)
+            ),
+            format!("--db-name={}", new.db_name.as_deref().unwrap_or(&new.name)),
+            format!("--locale={}", new.locale),
+            format!("--security-level={}", new.security_level),
+            format!(
+                "--scheduled-jobs-deny={}",
+                if new.deny_scheduled_jobs { "on" } else { "off" }
+            ),
+        ];
+        if let Some(descr) = &new.descr {
+            args.push(format!("--descr={descr}"));
+        }
+        if let Some(user) = &new.db_user {
+            args.push(format!("--db-user={user}"));
+        }
+        if let Some(password) = &new.db_password {
+            args.push(format!("--db-pwd={password}"));
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.rac_cluster(cluster, &args)?;
+        records(&output)
+            .iter()
+            .find_map(|record| value(record, "infobase"))
+            .map(str::to_string)
+            .ok_or_else(|| format!("rac did not report the id of {}", new.name).into())
+    }
+
+    /// Deregisters the infobase from the cluster, dropping its database too
+    /// when `drop_database` is set. Infobases with users need `credentials`.
+    pub fn drop_infobase(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+        drop_database: bool,
+        credentials: &Credentials,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut args = vec![
+            "infobase".to_string(),
+            "drop".to_string(),
+            format!("--infobase={}", infobase.id),
+        ];
+        if drop_database {
+            args.push("--drop-database".to_string());
+        }
+        args.extend(infobase_login(credentials));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.rac_cluster(cluster, &args)?;
+        Ok(())
+    }
+
+    /// The database of the infobase, which ras only tells its administrators.
+    pub fn database(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+        credentials: &Credentials,
+    ) -> Result<Database, Box<dyn Error>> {
+        let output = self.infobase_info(cluster, infobase, credentials)?;
+        let records = records(&output);
+        let record = records
+            .first()
+            .ok_or_else(|| format!("rac reported nothing about {}", infobase.name))?;
+        Ok(Database {
+            dbms: value(record, "dbms").unwrap_or_default().to_string(),
+            server: value(record, "db-server")
+                .ok_or_else(|| format!("rac reported no database server of {}", infobase.name))?
+                .to_string(),
+        })
+    }
+
+    /// The locks of the infobase, see `Access`.
+    pub fn access(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+        credentials: &Credentials,
+    ) -> Result<Access, Box<dyn Error>> {
+        let output = self.infobase_info(cluster, infobase, credentials)?;
+        let records = records(&output);
+        let record = records
+            .first()
+            .ok_or_else(|| format!("rac reported nothing about {}", infobase.name))?;
+        let text = |key| value(record, key).unwrap_or_default().to_string();
+        Ok(Access {
+            jobs_denied: value(record, "scheduled-jobs-deny") == Some("on"),
+            sessions_denied: value(record, "sessions-deny") == Some("on"),
+            denied_message: text("denied-message"),
+            denied_from: text("denied-from"),
+            denied_to: text("denied-to"),
+            permission_code: text("permission-code"),
+        })
+    }
+
+    /// Sets the locks of the infobase, see `Access`.
+    pub fn set_access(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+        access: &Access,
+        credentials: &Credentials,
+    ) -> Result<(), Box<dyn Error>> {
+        let switch = |on: bool| if on { "on" } else { "off" };
+        let mut args = vec![
+            "infobase".to_string(),
+            "update".to_string(),
+            format!("--infobase={}", infobase.id),
+            format!("--scheduled-jobs-deny={}", switch(access.jobs_denied)),
+            format!("--sessions-deny={}", switch(access.sessions_denied)),
+            format!("--denied-message={}", access.denied_message),
+            format!("--denied-from={}", access.denied_from),
+            format!("--denied-to={}", access.denied_to),
+            format!("--permission-code={}", access.permission_code),
+        ];
+        args.extend(infobase_login(credentials));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.rac_cluster(cluster, &args)?;
+        Ok(())
+    }
+
+    /// Output of `infobase info`, the full one for the administrators of the
+    /// infobase.
+    fn infobase_info(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+        credentials: &Credentials,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut args = vec![
+            "infobase".to_string(),
+            "info".to_string(),
+            format!("--infobase={}", infobase.id),
+        ];
+        args.extend(infobase_login(credentials));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.rac_cluster(cluster, &args)
+    }
+
+    /// The cluster of the host that has the infobase `name`.
+    fn locate(&self, name: &str) -> Result<(Cluster, Infobase), Box<dyn Error>> {
+        let mut found = Vec::new();
+        for cluster in self.clusters()? {
+            if let Some(infobase) = self
+                .infobases(&cluster)?
+                .into_iter()
+                .find(|infobase| infobase.name.eq_ignore_ascii_case(name))
+            {
+                found.push((cluster, infobase));
+            }
+        }
+        match found.len() {
+            0 => Err(format!("No infobase {name} on {}", self.host).into()),
+            1 => Ok(found.remove(0)),
+            _ => Err(format!("{name} exists on several clusters of {}", self.host).into()),
+        }
+    }
+
+    /// Sessions connected to the infobase.
+    pub fn infobase_sessions(
+        &self,
+        cluster: &Cluster,
+        infobase: &Infobase,
+    ) -> Result<usize, Box<dyn Error>> {
+        let filter = format!("--infobase={}", infobase.id);
+        let output = self.rac_cluster(cluster, &["session", "list", &filter])?;
+        Ok(records(&output).len())
+    }
+
+    /// Application of every session of the cluster, e.g. `1CV8C` or `BackgroundJob`.
+    pub fn session_apps(&self, cluster: &Cluster) -> Result<Vec<String>, Box<dyn Error>> {
+        let output = self.rac_cluster(cluster, &["session", "list"])?;
+        Ok(records(&output)
+            .iter()
+            .filter_map(|record| value(record, "app-id").map(str::to_string))
+            .collect())
+    }
+
+    pub fn session_licenses(
+        &self,
+        cluster: &Cluster,
+    ) -> Result<Vec<SessionLicense>, Box<dyn Error>> {
+        let output = self.rac_cluster(cluster, &["session", "list", "--licenses"])?;
+        Ok(records(&output)
+            .iter()
+            .map(|record| SessionLicense {
+                series: value(record, "series").unwrap_or_default().to_string(),
+                kind: value(record, "license-type")
+                    .unwrap_or_default()
+                    .to_string(),
+                max_users: value(record, "max-users-all")
+                    .and_then(|users| users.parse().ok())
+                    .unwrap_or(0),
+                by_server: value(record, "issued-by-server") == Some("yes"),
+            })
+            .collect())
+    }
+
+    /// Runs a command of `cluster`, logged in as its administrator.
+    fn rac_cluster(&self, cluster: &Cluster, args: &[&str]) -> Result<String, Box<dyn Error>> {
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.push(format!("--cluster={}", cluster.id));
+        if let Some((user, password)) = &self.admin {
+            args.push(format!("--cluster-user={user}"));
+            if !password.is_empty() {
+                args.push(format!("--cluster-pwd={password}"));
+            }
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.rac(&args)
+    }
+
+    fn rac(&self, args: &[&str]) -> Result<String, Box<dyn Error>> {
+        if !cfg!(feature = "rac") {
+            return Err("rbaserun was built without the rac feature".into());
+        }
+        let program = self.rac.as_deref().unwrap_or(Path::new("rac"));
+        let server = format!("{}:{}", self.host, self.port);
+        let output = Command::new(program)
+            .arg(&server)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Could not run {}: {e}", program.display()))?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("rac {server} failed: {}", message.trim()).into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// The ras for `input`: a registered cluster by name, or the host of a
+/// server base, alias or bare host name.
+pub fn resolve(input: &str, bases: &Bases, config: &RasConfig) -> Result<Ras, Box<dyn Error>> {
+    if let Some(cluster) = config.clusters.iter().find(|cluster| cluster.name == input) {
+        return Ok(Ras::registered(cluster, config));
+    }
+    let host = browse_host(&bases.expand_alias(input)).ok_or_else(|| {
+        format!("{input} is neither a registered cluster, a host nor a server base")
+    })?;
+    Ok(Ras::new(&host, config))
+}
+
+/// Prints the connection string and description of every infobase on the
+/// clusters of `input`, see `resolve`.
+pub fn run(input: &str, bases: &Bases, config: &RasConfig) -> Result<(), Box<dyn Error>> {
+    for listed in resolve(input, bases, config)?.list()? {
+        println!("{}\t{}", listed.path, listed.descr);
+    }
+    Ok(())
+}
+
+/// Prints the registered clusters.
+pub fn list_registered(config: &RasConfig) {
+    for cluster in &config.clusters {
+        let port = cluster.port.unwrap_or(config.port);
+        let admin = match &cluster.user {
+            Some(user) => format!("\tadmin {user}"),
+            None => String::new(),
+        };
+        println!("{}\t{}:{port}{admin}", cluster.name, cluster.host);
+    }
+}
+
+/// Stores the administrator password of registered cluster `name` in the OS
+/// keyring, reading it from stdin.
+pub fn login(name: &str, config: &RasConfig) -> Result<(), Box<dyn Error>> {
+    let cluster = config
+        .clusters
+        .iter()
+        .find(|cluster| cluster.name == name)
+        .ok_or_else(|| format!("No cluster named '{name}' in the config"))?;
+    let user = cluster
+        .user
+        .as_deref()
+        .ok_or_else(|| format!("Set the user of cluster '{name}' in the config first"))?;
+    print!("Password of '{user}' for cluster {name}: ");
+    io::stdout().flush()?;
+    let mut password = String::new();
+    io::stdin().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+    secrets::set(&account(name), password)?;
+    println!("Saved the password of cluster {name}");
+    Ok(())
+}
+
+/// Adds the infobases to the history, named by their description and put
+/// into the folder named after `ras`. Returns how many were new.
+pub fn import(ras: &Ras, listed: &[Listed], bases: &mut Bases) -> Result<usize, Box<dyn Error>> {
+    let mut history = history::load();
+    let mut added = 0;
+    for listed in listed {
+        if !history.contains(&listed.path) {
+            history.push(listed.path.clone());
+            added += 1;
+        }
+        if bases.is_managed(&listed.path) {
+            continue;
+        }
+        let entry = bases.entry_mut(&listed.path);
+        if entry.name.is_none() && !listed.descr.is_empty() {
+            entry.name = Some(listed.descr.clone());
+        }
+        entry.folder = entry.folder.take().or_else(|| Some(ras.name.clone()));
+    }
+    history::save(&history)?;
+    bases.save()?;
+    Ok(added)
+}
+
+/// Creates an infobase on the cluster of `input`, see `resolve`, loads the
+/// template into it and adds it to the history. A host with several clusters
+/// gets it on the first one.
+pub fn create(
+    input: &str,
+    new: &NewInfobase,
+    template: Option<&Template>,
+    bases: &mut Bases,
+    config: &RasConfig,
+) -> Result<(), Box<dyn Error>> {
+    let ras = resolve(input, bases, config)?;
+    let clusters = ras.clusters()?;
+    let cluster = clusters
+        .first()
+        .ok_or_else(|| format!("No cluster found on {}", ras.host))?;
+    if ras
+        .infobases(cluster)?
+        .iter()
+        .any(|infobase| infobase.name.eq_ignore_ascii_case(&new.name))
+    {
+        return Err(format!("{} already exists on {}", new.name, ras.name).into());
+    }
+    ras.create_infobase(cluster, new)?;
+
+    let path = cluster.base_path(&new.name);
+    if let Some(template) = template {
+        let parsed = parse_base_path(&path)?;
+        template.apply(&parsed, &Credentials::default())?;
+        template.bootstrap(&parsed, &Credentials::default())?;
+    }
+    let entry = bases.entry_mut(&path);
+    if entry.name.is_none() {
+        entry.name = new.descr.clone();
+    }
+    if entry.folder.is_none() {
+        entry.folder = Some(ras.name.clone());
+    }
+    bases.save()?;
+    history::remember(path.clone())?;
+    match template {
+        Some(template) => println!(
+            "Created {} on {} from {}, registered {path}",
+            new.name, ras.name, template.name
+        ),
+        None => println!("Created {} on {}, registered {path}", new.name, ras.name),
+    }
+    Ok(())
+}
+
+/// The ras, cluster and infobase of server base `path`, looked up through
+/// the ras of its cluster host.
+pub fn server_infobase(
+    path: &str,
+    config: &RasConfig,
+) -> Result<(Ras, Cluster, Infobase), Box<dyn Error>> {
+    let Ok(PathKind::Server { ref_name, .. }) = parse_base_path(path) else {
+        return Err(format!("{path} is not a server base").into());
+    };
+    let host = cluster_host(path).ok_or_else(|| format!("{path} is not a server base"))?;
+    let ras = Ras::new(&host, config);
+    let (cluster, infobase) = ras.locate(&ref_name)?;
+    Ok((ras, cluster, infobase))
+}
+
+/// The database of server base `path`, see `server_infobase`.
+pub fn database(
+    path: &str,
+    credentials: &Credentials,
+    config: &RasConfig,
+) -> Result<Database, Box<dyn Error>> {
+    let (ras, cluster, infobase) = server_infobase(path, config)?;
+    ras.database(&cluster, &infobase, credentials)
+}
+
+/// Deregisters server base `input` from its cluster and removes it from the
+/// history, after the user typed its name. Refused while sessions are
+/// connected to it and, with `protect_prod`, for production bases.
+pub fn drop(
+    input: &str,
+    drop_database: bool,
+    credentials: &Credentials,
+    bases: &mut Bases,
+    config: &RasConfig,
+) -> Result<(), Box<dyn Error>> {
+    let path = bases.expand_alias(&normalize_input(input));
+    let Ok(PathKind::Server { ref_name, .. }) = parse_base_path(&path) else {
+        return Err(format!("{input} is not a server base").into());
+    };
+    let production = bases.is_production(&path);
+    if production {
+        lockdown::check_removal(&path)?;
+    }
+    let host = cluster_host(&path).ok_or_else(|| format!("{input} is not a server base"))?;
+    let ras = Ras::new(&host, config);
+    let (cluster, infobase) = ras.locate(&ref_name)?;
+    let sessions = ras.infobase_sessions(&cluster, &infobase)?;
+    if sessions > 0 {
+        return Err(format!(
+            "{sessions} sessions are connected to {ref_name}, end them before dropping it"
+        )
+        .into());
+    }
+
+    let what = if drop_database {
+        "deregistered from the cluster and its DATABASE DROPPED"
+    } else {
+        "deregistered from the cluster, its database is kept"
+    };
+    let warning = if production {
+        "This is a production base. "
+    } else {
+        ""
+    };
+    print!("{warning}'{path}' will be {what}. Type '{ref_name}' to continue: ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != ref_name {
+        return Err("Drop cancelled".into());
+    }
+
+    ras.drop_infobase(&cluster, &infobase, drop_database, credentials)?;
+    let mut history = history::load();
+    history.retain(|other| other.trim() != path);
+    history::save(&history)?;
+    if bases.remove(&path).is_some() {
+        bases.save()?;
+    }
+    println!("Dropped {ref_name} from {host}");
+    Ok(())
+}
+
+/// Imports every infobase of the clusters of `input`, see `resolve`.
+pub fn import_all(
+    input: &str,
+    bases: &mut Bases,
+    config: &RasConfig,
+) -> Result<(), Box<dyn Error>> {
+    let ras = resolve(input, bases, config)?;
+    let listed = ras.list()?;
+    let added = import(&ras, &listed, bases)?;
+    println!(
+        "Imported {} infobases from {}: {added} new",
+        listed.len(),
+        ras.name
+    );
+    Ok(())
+}
+
+/// `--infobase-user` and `--infobase-pwd` of the infobase administrator.
+fn infobase_login(credentials: &Credentials) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(user) = &credentials.user {
+        args.push(format!("--infobase-user={user}"));
+    }
+    if let Some(password) = &credentials.password {
+        args.push(format!("--infobase-pwd={password}"));
+    }
+    args
+}
+
+fn account(name: &str) -> String {
+    format!("cluster {name}")
+}
+
+/// Blocks of `key : value` lines separated by blank lines, as `rac` prints them.
+fn records(output: &str) -> Vec<Vec<(&str, &str)>> {
+    let mut records = vec![Vec::new()];
+    for line in output.lines() {
+        match line.split_once(':') {
+            Some((key, value)) => records
+                .last_mut()
+                .unwrap()
+                .push((key.trim(), value.trim().trim_matches('"'))),
+            None if line.trim().is_empty() => records.push(Vec::new()),
+            None => {}
+        }
+    }
+    records.retain(|record| !record.is_empty());
+    records
+}
+
+fn value<'a>(record: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    record
+        .iter()
+        .find_map(|&(own, value)| (own == key).then_some(value))
+}
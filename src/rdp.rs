@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::process::Command;
+
+use crate::bases::Bases;
+
+pub fn connect(path: &str, bases: &Bases) -> Result<(), Box<dyn Error>> {
+    let target = bases
+        .find(path)
+        .and_then(|base| base.rdp.as_deref())
+        .ok_or("No RDP target configured for this base")?;
+
+    Command::new("mstsc")
+        .arg(format!("/v:{target}"))
+        .spawn()
+        .map_err(|e| format!("Could not start mstsc: {e}"))?;
+
+    Ok(())
+}
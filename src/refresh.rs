@@ -0,0 +1,121 @@
+use chrono::Local;
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::backup;
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::space;
+use crate::{parse_base_path, production_guard_word};
+
+pub struct RefreshOptions {
+    pub credentials: Credentials,
+    /// Permission code (`/UC`) letting the designer in while sessions are denied
+    pub unlock_code: Option<String>,
+    /// Keep the dump at this path instead of deleting it afterwards
+    pub keep_dump: Option<PathBuf>,
+    pub yes: bool,
+}
+
+/// Dumps `source` to a .dt file and restores it into `target`.
+pub fn run(
+    source: &str,
+    target: &str,
+    bases: &Bases,
+    options: RefreshOptions,
+) -> Result<(), Box<dyn Error>> {
+    let source_path = parse_base_path(source).map_err(|e| format!("Source: {e}"))?;
+    let target_path = parse_base_path(target).map_err(|e| format!("Target: {e}"))?;
+    if source_path
+        .target()
+        .eq_ignore_ascii_case(&target_path.target())
+    {
+        return Err("Source and target are the same base".into());
+    }
+
+    // production targets always need the typed confirmation
+    if !options.yes || bases.is_production(target) {
+        confirm(source, target)?;
+    }
+
+    let dump = options.keep_dump.clone().unwrap_or_else(|| {
+        env::temp_dir().join(format!(
+            "rbaserun-{}-{}.dt",
+            source_path.name(),
+            Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+
+    space::check_dump(&source_path, &dump)?;
+    println!("Dumping {} to {}...", source_path.target(), dump.display());
+    let mut batch = Batch::new(&source_path, &options.credentials)?;
+    batch.arg("/DumpIB").arg(&dump);
+    if let Some(code) = &options.unlock_code {
+        batch.arg("/UC").arg(code);
+    }
+    batch.run().map_err(|e| format!("Dump failed: {e}"))?;
+    if options.keep_dump.is_some()
+        && let Err(e) = backup::record(&dump, source)
+    {
+        eprintln!("Warning: the dump has no manifest: {e}");
+    }
+
+    // the dump is there now, its own size tells what the restore takes
+    if let Err(e) = space::check_restore(&target_path, &dump) {
+        if options.keep_dump.is_none() {
+            let _ = fs::remove_file(&dump);
+        }
+        return Err(e);
+    }
+    println!("Restoring into {}...", target_path.target());
+    let mut batch = Batch::new(&target_path, &options.credentials)?;
+    batch.arg("/RestoreIB").arg(&dump);
+    if let Some(code) = &options.unlock_code {
+        batch.arg("/UC").arg(code);
+    }
+    let result = batch.run();
+    if options.keep_dump.is_none() {
+        let _ = fs::remove_file(&dump);
+    }
+    if let Err(e) = result {
+        let mut message = format!("Restore failed: {e}");
+        if mentions_sessions(&message) {
+            message.push_str(
+                "\nThe target has active sessions: deny new sessions with a permission code \
+                 in the cluster console and pass it with --unlock-code",
+            );
+        }
+        return Err(message.into());
+    }
+
+    println!(
+        "Refreshed {} from {}",
+        target_path.target(),
+        source_path.target()
+    );
+    Ok(())
+}
+
+fn confirm(source: &str, target: &str) -> Result<(), Box<dyn Error>> {
+    let word = production_guard_word(target);
+    print!("All data in '{target}' will be replaced with '{source}'. Type '{word}' to continue: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != word {
+        return Err("Refresh cancelled".into());
+    }
+    Ok(())
+}
+
+fn mentions_sessions(log: &str) -> bool {
+    let log = log.to_lowercase();
+    ["session", "сеанс", "монопольн", "exclusive"]
+        .iter()
+        .any(|marker| log.contains(marker))
+}
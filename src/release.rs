@@ -0,0 +1,147 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::designer::Credentials;
+use crate::events::AppEvent;
+use crate::{PathKind, connection, parse_base_path, paths, platform};
+
+const FILE_NAME: &str = "rbaserun_releases.json";
+
+/// Time the COM connector gets to open a base, which starts a session on it.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// Name and version of the configuration of a base.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Release {
+    pub name: String,
+    pub version: String,
+    /// Unix timestamp of the read, seconds
+    pub at: i64,
+}
+
+/// The releases of the bases, read in the background for the selected base
+/// and kept in rbaserun_releases.json, so that the right release can be
+/// confirmed before launching.
+#[derive(Debug, Default)]
+pub struct Releases {
+    read: BTreeMap<String, Release>,
+    /// Bases whose read runs
+    reading: Vec<String>,
+    /// Why the last read of a base failed, tried again after `max_age`
+    failed: HashMap<String, (Instant, String)>,
+}
+
+impl Releases {
+    pub fn load() -> Releases {
+        let read = fs::read_to_string(file())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Releases {
+            read,
+            ..Default::default()
+        }
+    }
+
+    fn save(&self) {
+        if let Ok(content) = serde_json::to_string_pretty(&self.read) {
+            let _ = paths::prepare(&file()).and_then(|()| fs::write(file(), content));
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Release> {
+        self.read.get(path.trim())
+    }
+
+    /// Why the release of `path` could not be read, until it is read again.
+    pub fn failure(&self, path: &str) -> Option<&str> {
+        self.failed
+            .get(path.trim())
+            .map(|(_, reason)| reason.as_str())
+    }
+
+    pub fn is_reading(&self, path: &str) -> bool {
+        self.reading.iter().any(|reading| reading == path.trim())
+    }
+
+    /// Reads the release of `path` in a thread posting `AppEvent::Release`
+    /// when it was never read, or read or failed more than `max_age` ago.
+    pub fn fetch(
+        &mut self,
+        path: &str,
+        credentials: impl FnOnce() -> Credentials,
+        max_age: Duration,
+        sender: &Sender<AppEvent>,
+    ) {
+        let path = path.trim().to_string();
+        if path.is_empty() || self.is_reading(&path) {
+            return;
+        }
+        let now = Local::now().timestamp();
+        let fresh = self
+            .read
+            .get(&path)
+            .is_some_and(|release| now - release.at < max_age.as_secs() as i64);
+        let failed = self
+            .failed
+            .get(&path)
+            .is_some_and(|(at, _)| at.elapsed() < max_age);
+        if fresh || failed {
+            return;
+        }
+        self.reading.push(path.clone());
+        // looked up only now, the keyring is slow to ask on every tick
+        let credentials = credentials();
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let release = read(&path, &credentials);
+            let _ = sender.send(AppEvent::Release { path, release });
+        });
+    }
+
+    pub fn finish(&mut self, path: String, release: Result<Release, String>) {
+        self.reading.retain(|reading| *reading != path);
+        match release {
+            Ok(release) => {
+                self.failed.remove(&path);
+                self.read.insert(path, release);
+                self.save();
+            }
+            Err(e) => {
+                self.failed.insert(path, (Instant::now(), e));
+            }
+        }
+    }
+}
+
+/// Connects to the base over COM with `credentials` and reads its metadata.
+fn read(path: &str, credentials: &Credentials) -> Result<Release, String> {
+    let parsed = parse_base_path(path).map_err(|e| e.to_string())?;
+    if let PathKind::Web { .. } = parsed {
+        return Err("web bases can't be opened over COM".to_string());
+    }
+    let mut connection = parsed.connection_string();
+    if let Some(user) = &credentials.user {
+        connection.push_str(&format!("Usr={};", connection::quote(user)));
+    }
+    if let Some(password) = &credentials.password {
+        connection.push_str(&format!("Pwd={};", connection::quote(password)));
+    }
+    let (name, version) = platform::configuration_info(&connection, TIMEOUT)?;
+    Ok(Release {
+        name,
+        version,
+        at: Local::now().timestamp(),
+    })
+}
@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::RemoteConfig;
+
+/// Reads the shared store from the jump host, an empty string when it does
+/// not exist yet.
+pub fn read(remote: &RemoteConfig) -> Result<String, Box<dyn Error>> {
+    let path = quote(&remote.path);
+    let output = ssh(remote)
+        .arg(format!("cat {path} 2>/dev/null || test ! -e {path}"))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Could not start ssh: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not read {}: {}",
+            remote.location(),
+            failure(&output.stderr)
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Replaces the shared store on the jump host, going through a temporary
+/// file so that readers never see it half written.
+pub fn write(remote: &RemoteConfig, content: &str) -> Result<(), Box<dyn Error>> {
+    let path = quote(&remote.path);
+    let temp = quote(&format!("{}.tmp", remote.path));
+    let mut child = ssh(remote)
+        .arg(format!("cat > {temp} && mv {temp} {path}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not start ssh: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not write {}: {}",
+            remote.location(),
+            failure(&output.stderr)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn ssh(remote: &RemoteConfig) -> Command {
+    let mut command = Command::new("ssh");
+    command
+        .args(["-o", "BatchMode=yes"])
+        .args(&remote.ssh_args)
+        .arg(&remote.host)
+        .arg("--");
+    command
+}
+
+/// Single-quotes `value` for the remote POSIX shell.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn failure(stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    match stderr.trim() {
+        "" => "ssh failed".to_string(),
+        message => message.to_string(),
+    }
+}
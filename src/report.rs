@@ -0,0 +1,100 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::{LaunchMode, clipboard, keys, locale, popup, theme};
+
+/// What a launch did, printed with `--report` for pasting into a ticket.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub path: String,
+    pub mode: LaunchMode,
+    /// The command as `--dry-run` prints it, with the password masked
+    pub command: String,
+    /// Process of the client, or of the browser opener for the web browser
+    pub pid: u32,
+    pub time: DateTime<Local>,
+    /// Checks passed or skipped before the launch, e.g. the pre-check
+    pub checks: Vec<String>,
+}
+
+impl Report {
+    /// The report as aligned `Label: value` lines.
+    pub fn text(&self) -> String {
+        let checks = if self.checks.is_empty() {
+            "none".to_string()
+        } else {
+            self.checks.join("; ")
+        };
+        [
+            format!("Base:    {}", self.path.trim()),
+            format!("Mode:    {}", self.mode.label()),
+            format!("Command: {}", self.command),
+            format!("PID:     {}", self.pid),
+            format!("Time:    {}", locale::date_time(&self.time)),
+            format!("Checks:  {checks}"),
+        ]
+        .join("\n")
+    }
+}
+
+pub enum ReportAction {
+    None,
+    Close,
+}
+
+/// Popup showing the report of the launch just made with `--report`.
+#[derive(Debug)]
+pub struct ReportPanel {
+    text: String,
+    notice: Option<String>,
+}
+
+impl ReportPanel {
+    pub fn new(report: &Report) -> ReportPanel {
+        ReportPanel {
+            text: report.text(),
+            notice: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ReportAction {
+        let Event::Key(key_event) = event else {
+            return ReportAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return ReportAction::None;
+        }
+        match keys::binding(key_event.code) {
+            KeyCode::Esc | KeyCode::Enter => return ReportAction::Close,
+            KeyCode::Char('c') => {
+                self.notice = Some(match clipboard::copy(&self.text) {
+                    Ok(()) => "Copied to clipboard".to_string(),
+                    Err(e) => e.to_string(),
+                });
+            }
+            _ => {}
+        }
+        ReportAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let mut lines: Vec<Line> = self.text.lines().map(Line::from).collect();
+        lines.push(Line::default());
+        lines.push(match &self.notice {
+            Some(notice) => Span::styled(notice.as_str(), theme::success()).into(),
+            None => Span::styled("c: copy, Esc: close", theme::hint()).into(),
+        });
+
+        let area = popup::area(frame.area(), lines.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Launched")),
+            area,
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::io::{self, Write};
+
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::{parse_base_path, production_guard_word, secrets};
+
+/// Stores the repository user and password in the OS keyring, reading the
+/// password from stdin.
+pub fn login(repository: &str, user: &str) -> Result<(), Box<dyn Error>> {
+    print!("Password of '{user}' for {repository}: ");
+    io::stdout().flush()?;
+    let mut password = String::new();
+    io::stdin().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+
+    secrets::set(&account(repository), &format!("{user}\n{password}"))?;
+    println!("Saved credentials for {repository}");
+    Ok(())
+}
+
+/// Binds the configuration of `path` to `repository` (or the one saved for the
+/// base) and remembers the repository for later updates. With `replace` the
+/// configuration of the base is replaced by the repository's, after a typed
+/// confirmation.
+pub fn bind(
+    path: &str,
+    repository: Option<String>,
+    replace: bool,
+    bases: &mut Bases,
+    credentials: &Credentials,
+) -> Result<(), Box<dyn Error>> {
+    let repository = match repository {
+        Some(repository) => repository,
+        None => saved_repository(path, bases)?,
+    };
+    if replace {
+        confirm(
+            path,
+            &format!(
+                "The configuration of '{}' will be replaced with the one of {repository}",
+                path.trim()
+            ),
+            "Binding cancelled",
+        )?;
+    }
+    let mut batch = repository_batch(path, &repository, credentials)?;
+    batch
+        .arg("/ConfigurationRepositoryBindCfg")
+        .arg("-forceBindAlreadyBindedUser");
+    if replace {
+        batch.arg("-forceReplaceCfg");
+    }
+    batch.run().map_err(|e| format!("Binding failed: {e}"))?;
+
+    bases.entry_mut(path).repository = Some(repository.clone());
+    bases.save()?;
+    println!("Bound {path} to {repository}");
+    Ok(())
+}
+
+/// Pulls the latest repository version into the configuration of `path`,
+/// optionally applying it to the database configuration, which a production
+/// base confirms first.
+pub fn update(
+    path: &str,
+    apply: bool,
+    bases: &Bases,
+    credentials: &Credentials,
+) -> Result<(), Box<dyn Error>> {
+    let repository = saved_repository(path, bases)?;
+    if apply && bases.is_production(path) {
+        confirm(
+            path,
+            &format!(
+                "The database configuration of '{}' will be updated from {repository}",
+                path.trim()
+            ),
+            "Update cancelled",
+        )?;
+    }
+    let mut batch = repository_batch(path, &repository, credentials)?;
+    batch.arg("/ConfigurationRepositoryUpdateCfg").arg("-force");
+    if apply {
+        batch.arg("/UpdateDBCfg");
+    }
+    batch.run().map_err(|e| format!("Update failed: {e}"))?;
+    println!("Updated {path} from {repository}");
+    Ok(())
+}
+
+/// Asks to type the name of the base after saying what is about to happen,
+/// failing with `cancelled` otherwise.
+fn confirm(path: &str, what: &str, cancelled: &str) -> Result<(), Box<dyn Error>> {
+    let word = production_guard_word(path);
+    print!("{what}. Type '{word}' to continue: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if answer.trim() != word {
+        return Err(cancelled.into());
+    }
+    Ok(())
+}
+
+fn saved_repository(path: &str, bases: &Bases) -> Result<String, Box<dyn Error>> {
+    bases
+        .find(path)
+        .and_then(|entry| entry.repository.clone())
+        .ok_or_else(|| {
+            format!("No repository saved for {path}, bind it with --repository first").into()
+        })
+}
+
+fn repository_batch(
+    path: &str,
+    repository: &str,
+    credentials: &Credentials,
+) -> Result<Batch, Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    let secret = secrets::get(&account(repository)).map_err(|e| {
+        format!("No credentials for {repository} ({e}), run `rbaserun repo login` first")
+    })?;
+    let (user, password) = secret.split_once('\n').unwrap_or((&secret, ""));
+
+    let mut batch = Batch::new(&parsed, credentials)?;
+    batch
+        .arg("/ConfigurationRepositoryF")
+        .arg(repository)
+        .arg("/ConfigurationRepositoryN")
+        .arg(user);
+    if !password.is_empty() {
+        batch.arg("/ConfigurationRepositoryP").arg(password);
+    }
+    Ok(batch)
+}
+
+fn account(repository: &str) -> String {
+    format!("repository {repository}")
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+use crate::{locale, platform};
+
+/// What has to be in place on this machine before a base is launched,
+/// `[base.requires]`, e.g. a mapped drive, a running VPN client or enough
+/// free memory. Checked after the `before` hooks, which may set it up.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Requirements {
+    /// Paths that must exist, e.g. the drive `Z:\` or a share
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    /// Processes that must be running, e.g. `openvpn-gui`, `.exe` optional
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<String>,
+    /// Megabytes of physical memory that must be free
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_memory_mb: Option<u64>,
+    /// Added to every failure, telling what to do about it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Requirements {
+    /// Fails naming every requirement that is not met.
+    pub fn check(&self) -> Result<(), String> {
+        let mut failures = Vec::new();
+        for path in &self.paths {
+            if !path.exists() {
+                failures.push(format!("{} is not available", path.display()));
+            }
+        }
+        if !self.processes.is_empty() {
+            let running = platform::process_names();
+            for process in &self.processes {
+                let name = process.trim().to_lowercase();
+                let name = name.strip_suffix(".exe").unwrap_or(&name);
+                if !running.iter().any(|running| running == name) {
+                    failures.push(format!("{process} is not running"));
+                }
+            }
+        }
+        if let Some(needed) = self.free_memory_mb
+            && let Some(free) = platform::available_memory()
+            && free < needed * 1024 * 1024
+        {
+            failures.push(format!(
+                "{} of memory free, the base needs {}",
+                locale::size(free),
+                locale::size(needed * 1024 * 1024)
+            ));
+        }
+        if failures.is_empty() {
+            return Ok(());
+        }
+        if let Some(hint) = &self.hint {
+            failures.push(hint.clone());
+        }
+        Err(format!("Requirements not met: {}", failures.join("; ")))
+    }
+}
@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::process::{self, Command};
+
+use crate::bases::Bases;
+use crate::designer::Credentials;
+use crate::{PathKind, parse_base_path, platform};
+
+/// Runs a saved action of the base, or `args` as a command, with the base
+/// exported as environment variables.
+pub fn run(
+    path: &str,
+    args: &[String],
+    bases: &Bases,
+    credentials: &Credentials,
+) -> Result<(), Box<dyn Error>> {
+    let parsed = parse_base_path(path)?;
+    let action = match args {
+        [name] => bases
+            .find(path)
+            .and_then(|entry| entry.actions.get(name.as_str())),
+        _ => None,
+    };
+    let mut command = match (action, args) {
+        (Some(action), _) => platform::shell(action),
+        (None, [program, rest @ ..]) => {
+            let mut command = Command::new(program);
+            command.args(rest);
+            command
+        }
+        (None, []) => {
+            let actions: Vec<&str> = bases
+                .find(path)
+                .map(|entry| entry.actions.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            return Err(if actions.is_empty() {
+                "Nothing to run, pass a command after --".into()
+            } else {
+                format!(
+                    "Nothing to run, actions of this base: {}",
+                    actions.join(", ")
+                )
+                .into()
+            });
+        }
+    };
+
+    command
+        .env("RBASERUN_PATH", path.trim())
+        .env("RBASERUN_KIND", parsed.kind_name())
+        .env("RBASERUN_TARGET", parsed.target())
+        .env("RBASERUN_NAME", parsed.name())
+        // picked up by vanessa-runner as --ibconnection, --db-user and --db-pwd
+        .env("RUNNER_IBCONNECTION", ib_connection(&parsed));
+    if let Some(user) = &credentials.user {
+        command.env("RUNNER_DBUSER", user);
+    }
+    if let Some(password) = &credentials.password {
+        command.env("RUNNER_DBPWD", password);
+    }
+
+    let status = command
+        .status()
+        .map_err(|e| format!("Could not start command: {e}"))?;
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Connection in the `/S`, `/F` or `/WS` form used by 1C command line tools.
+fn ib_connection(path: &PathKind) -> String {
+    match path {
+        PathKind::Server { .. } => format!("/S{}", path.target()),
+        PathKind::File { .. } => format!("/F{}", path.target()),
+        PathKind::Web { .. } => format!("/WS{}", path.target()),
+    }
+}
@@ -0,0 +1,284 @@
+use clap::ValueEnum;
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::bases::Bases;
+use crate::designer::Credentials;
+use crate::{LaunchMode, Launcher, PathKind, client_args, credentials, parse_base_path};
+use crate::{lockdown, placeholders, platform, tunnel};
+
+/// Seconds the scripts give the ssh tunnel to come up before starting the client.
+const TUNNEL_DELAY: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ScriptFormat {
+    /// PowerShell script
+    Ps1,
+    /// Batch file
+    Cmd,
+}
+
+/// A launch spelled out as commands: an optional ssh forward, then either the
+/// client with its arguments or a url opened in the browser.
+pub struct Plan {
+    tunnel: Option<(String, String)>,
+    program: String,
+    args: Vec<String>,
+    browser: bool,
+}
+
+impl Plan {
+    /// The commands as they would be typed in a shell, with the password masked.
+    pub fn preview(&self) -> String {
+        let mut words = Vec::new();
+        if let Some((forward, ssh)) = &self.tunnel {
+            words.extend(["ssh", "-N", "-L", forward, "--", ssh, "&"].map(shell_quote));
+        }
+        if self.browser {
+            words.push(platform::browser_opener().to_string());
+        }
+        words.push(shell_quote(&self.program));
+        let mut masked = false;
+        for arg in &self.args {
+            words.push(if masked {
+                "****".to_string()
+            } else {
+                shell_quote(arg)
+            });
+            masked = arg == "/P";
+        }
+        words.join(" ")
+    }
+}
+
+/// How `path` is launched in `mode` with `credentials`, without starting anything.
+pub fn plan(
+    path: &str,
+    mode: LaunchMode,
+    bases: &Bases,
+    launcher: &Launcher,
+    credentials: &Credentials,
+) -> Result<Plan, Box<dyn Error>> {
+    let filled = placeholders::fill(path, &launcher.vars)?;
+    let mut parsed = parse_base_path(&filled).map_err(|e| format!("Parsing error: {e}"))?;
+
+    let mut forward = None;
+    if let Some(config) = bases.find(path).and_then(|base| base.tunnel.as_ref()) {
+        let (spec, rewritten) = tunnel::forward(config, parsed)?;
+        forward = Some((spec, config.ssh.clone()));
+        parsed = rewritten;
+    }
+
+    if mode == LaunchMode::WebBrowser {
+        let PathKind::Web { url } = parsed else {
+            return Err("Web browser mode is only available for ws= bases".into());
+        };
+        let browser = launcher.browser(path, bases);
+        return Ok(match browser.command_line(&url) {
+            Some((program, args)) => Plan {
+                tunnel: forward,
+                program: program.display().to_string(),
+                args,
+                browser: false,
+            },
+            None => Plan {
+                tunnel: forward,
+                program: browser.url(&url),
+                args: Vec::new(),
+                browser: true,
+            },
+        });
+    }
+    let mut args = client_args(parsed, mode, credentials);
+    args.extend(launcher.switches(path, bases)?);
+    Ok(Plan {
+        tunnel: forward,
+        program: launcher.binary(mode).display().to_string(),
+        args,
+        browser: false,
+    })
+}
+
+/// Writes a script launching `path` the way rbaserun would, to `output` or
+/// stdout. Saved passwords stay in the keyring, only an explicit `--password`
+/// ends up in the script.
+pub fn export(
+    path: &str,
+    mode: LaunchMode,
+    format: ScriptFormat,
+    output: Option<&Path>,
+    bases: &Bases,
+    launcher: &Launcher,
+    explicit: &Credentials,
+) -> Result<(), Box<dyn Error>> {
+    lockdown::check_mode(mode)?;
+    let credentials = if explicit.user.is_some() {
+        explicit.clone()
+    } else {
+        Credentials {
+            user: credentials::stored(path, bases).user,
+            password: None,
+        }
+    };
+    let plan = plan(path, mode, bases, launcher, &credentials)?;
+
+    let script = match format {
+        ScriptFormat::Ps1 => powershell(path, &plan),
+        ScriptFormat::Cmd => batch(path, &plan),
+    };
+    match output {
+        Some(output) => {
+            fs::write(output, script)
+                .map_err(|e| format!("Could not write {}: {e}", output.display()))?;
+            eprintln!("Wrote {}", output.display());
+        }
+        None => print!("{script}"),
+    }
+    Ok(())
+}
+
+fn powershell(path: &str, plan: &Plan) -> String {
+    let mut lines = vec![
+        format!("# Launches {path}, generated by `rbaserun export-script`"),
+        "$ErrorActionPreference = 'Stop'".to_string(),
+    ];
+    let program = ps_quote(&plan.program);
+    let args: Vec<String> = plan.args.iter().map(|arg| ps_quote(arg)).collect();
+    let launch = if plan.browser {
+        format!("Start-Process {program}")
+    } else {
+        format!("& {program} {}", args.join(" "))
+    };
+    match &plan.tunnel {
+        Some((forward, ssh)) => {
+            let ssh_args = [
+                "-N",
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-L",
+                forward,
+                "--",
+                ssh,
+            ]
+            .map(|arg| ps_quote(&argv_quote(arg)))
+            .join(", ");
+            lines.push(format!(
+                "$tunnel = Start-Process ssh -ArgumentList {ssh_args} -WindowStyle Hidden -PassThru"
+            ));
+            lines.push(format!("Start-Sleep -Seconds {TUNNEL_DELAY}"));
+            lines.push("try {".to_string());
+            if plan.browser {
+                lines.push(format!("    {launch}"));
+                lines.push("    Read-Host 'Press Enter to close the tunnel'".to_string());
+            } else {
+                // Start-Process joins the list with spaces into one command
+                // line, so each argument is quoted for Windows first
+                let joined: Vec<String> = plan
+                    .args
+                    .iter()
+                    .map(|arg| ps_quote(&argv_quote(arg)))
+                    .collect();
+                lines.push(format!(
+                    "    Start-Process {program} -ArgumentList {} -Wait",
+                    joined.join(", ")
+                ));
+            }
+            lines.push("} finally {".to_string());
+            lines.push("    Stop-Process -Id $tunnel.Id -ErrorAction SilentlyContinue".to_string());
+            lines.push("}".to_string());
+        }
+        None => lines.push(launch),
+    }
+    lines.join("\r\n") + "\r\n"
+}
+
+fn batch(path: &str, plan: &Plan) -> String {
+    let mut lines = vec![
+        "@echo off".to_string(),
+        format!(
+            "rem Launches {}, generated by `rbaserun export-script`",
+            path.replace('%', "%%")
+        ),
+    ];
+    let launch = if plan.browser {
+        format!("start \"\" {}", cmd_quote(&plan.program))
+    } else {
+        let args: Vec<String> = plan.args.iter().map(|arg| cmd_quote(arg)).collect();
+        format!("{} {}", cmd_quote(&plan.program), args.join(" "))
+    };
+    match &plan.tunnel {
+        Some((forward, ssh)) => {
+            lines.push(format!(
+                "start \"rbaserun tunnel\" /min ssh -N -o BatchMode=yes -o ExitOnForwardFailure=yes -L {} -- {}",
+                cmd_quote(forward),
+                cmd_quote(ssh)
+            ));
+            lines.push(format!("timeout /t {TUNNEL_DELAY} /nobreak >nul"));
+            if plan.browser {
+                lines.push(launch);
+                lines.push("pause".to_string());
+            } else {
+                lines.push(format!("start \"\" /wait {launch}"));
+            }
+            lines.push("taskkill /fi \"WINDOWTITLE eq rbaserun tunnel*\" >nul 2>&1".to_string());
+        }
+        None => lines.push(launch),
+    }
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Quotes an argument with spaces or quotes for reading, not for a
+/// particular shell.
+pub fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+fn ps_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "''"))
+}
+
+/// Quotes an argument for a Windows command line the way the programs split
+/// it again: wrapped in quotes when it has spaces, quotes inside escaped with
+/// a backslash and the backslashes before them doubled.
+fn argv_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+        } else {
+            if c == '"' {
+                quoted.push_str(&"\\".repeat(backslashes + 1));
+            }
+            backslashes = 0;
+        }
+        quoted.push(c);
+    }
+    quoted.push_str(&"\\".repeat(backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes an argument for a batch file: `%` is doubled so it is not expanded
+/// and arguments with spaces or shell characters are wrapped in quotes.
+fn cmd_quote(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || "\"&|<>^(),;=".contains(c))
+    {
+        return arg;
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
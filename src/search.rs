@@ -0,0 +1,101 @@
+use crate::fuzzy;
+
+/// Characters of the listed entries and the matches of the last query, so
+/// that filtering tens of thousands of bases stays instant. An entry lacking
+/// a character of the query is skipped without looking at its texts, and a
+/// query typed further only goes through the matches of the one before.
+///
+/// Fuzzy matches don't have to be next to each other, so the index keeps the
+/// characters of an entry rather than longer n-grams.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Identifies the list the index was built for, see `App::list_stamp`
+    stamp: Option<u64>,
+    signatures: Vec<u64>,
+    last: Option<Filtered>,
+}
+
+#[derive(Debug)]
+struct Filtered {
+    query: String,
+    /// Indices of the matching entries, in order
+    matched: Vec<usize>,
+    /// The same best first
+    listed: Vec<usize>,
+}
+
+impl SearchIndex {
+    /// Indexes the `count` entries of the list identified by `stamp` with
+    /// their `texts`, unless the index is of that list already.
+    pub fn update<T: AsRef<str>, const N: usize>(
+        &mut self,
+        stamp: u64,
+        count: usize,
+        texts: impl Fn(usize) -> [Option<T>; N],
+    ) {
+        if self.stamp == Some(stamp) {
+            return;
+        }
+        self.stamp = Some(stamp);
+        self.last = None;
+        self.signatures = (0..count)
+            .map(|index| {
+                texts(index)
+                    .iter()
+                    .flatten()
+                    .fold(0, |signature, text| signature | signature_of(text.as_ref()))
+            })
+            .collect();
+    }
+
+    /// The entries matching `query`, best first: `score` tells whether one
+    /// matches and how well, `rank` puts the scored entries in order.
+    pub fn filter(
+        &mut self,
+        query: &str,
+        score: impl Fn(usize) -> Option<i64>,
+        rank: impl FnOnce(&mut Vec<(i64, usize)>),
+    ) -> Vec<usize> {
+        let query = query.trim();
+        if let Some(last) = self.last.as_ref().filter(|last| last.query == query) {
+            return last.listed.clone();
+        }
+        // every match of a query is a match of the ones it starts with
+        let narrowed = self
+            .last
+            .take()
+            .filter(|last| query.starts_with(&last.query));
+        let required = signature_of(query);
+        let candidates = match narrowed {
+            Some(last) => last.matched,
+            None => (0..self.signatures.len()).collect(),
+        };
+        let mut scored: Vec<(i64, usize)> = candidates
+            .into_iter()
+            .filter(|&index| self.signatures[index] & required == required)
+            .filter_map(|index| Some((score(index)?, index)))
+            .collect();
+        let matched = scored.iter().map(|&(_, index)| index).collect();
+        rank(&mut scored);
+        let listed: Vec<usize> = scored.into_iter().map(|(_, index)| index).collect();
+        self.last = Some(Filtered {
+            query: query.to_string(),
+            matched,
+            listed: listed.clone(),
+        });
+        listed
+    }
+}
+
+/// The lowercase characters of `text` as bits: one per ASCII letter and
+/// digit, the rest share the remaining bits.
+fn signature_of(text: &str) -> u64 {
+    text.chars().map(fuzzy::lower).fold(0, |signature, c| {
+        let bit = match c {
+            'a'..='z' => c as u32 - 'a' as u32,
+            '0'..='9' => 26 + c as u32 - '0' as u32,
+            c => 36 + c as u32 % 28,
+        };
+        signature | 1 << bit
+    })
+}
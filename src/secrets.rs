@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::sync::OnceLock;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Turns the OS credential store off for this run, `keyring = false` in the
+/// config. Called once at startup.
+pub fn set_enabled(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+fn check_enabled() -> Result<(), Box<dyn Error>> {
+    if !ENABLED.get().copied().unwrap_or(true) {
+        return Err("the OS keyring is turned off with `keyring = false` in the config".into());
+    }
+    Ok(())
+}
+
+/// The secret saved under `account`, e.g. `base Srvr="srv";Ref="erp";`, in the
+/// Windows Credential Manager, the Secret Service or the macOS Keychain.
+pub fn get(account: &str) -> Result<String, Box<dyn Error>> {
+    check_enabled()?;
+    store::get(account)
+}
+
+pub fn set(account: &str, secret: &str) -> Result<(), Box<dyn Error>> {
+    check_enabled()?;
+    store::set(account, secret)
+}
+
+/// Forgets the secret of `account`, if there is one.
+pub fn delete(account: &str) {
+    if check_enabled().is_ok() {
+        store::delete(account);
+    }
+}
+
+#[cfg(feature = "keyring")]
+mod store {
+    use keyring::Entry;
+
+    use std::error::Error;
+
+    /// Service the secrets are filed under.
+    const SERVICE: &str = "rbaserun";
+
+    pub fn get(account: &str) -> Result<String, Box<dyn Error>> {
+        Ok(Entry::new(SERVICE, account)?.get_password()?)
+    }
+
+    pub fn set(account: &str, secret: &str) -> Result<(), Box<dyn Error>> {
+        Ok(Entry::new(SERVICE, account)?.set_password(secret)?)
+    }
+
+    pub fn delete(account: &str) {
+        if let Ok(entry) = Entry::new(SERVICE, account) {
+            let _ = entry.delete_credential();
+        }
+    }
+}
+
+/// Nothing is saved and every secret is missing in builds without the
+/// `keyring` feature.
+#[cfg(not(feature = "keyring"))]
+mod store {
+    use std::error::Error;
+
+    pub fn get(_account: &str) -> Result<String, Box<dyn Error>> {
+        Err("rbaserun was built without the keyring feature".into())
+    }
+
+    pub fn set(_account: &str, _secret: &str) -> Result<(), Box<dyn Error>> {
+        Err("rbaserun was built without the keyring feature".into())
+    }
+
+    pub fn delete(_account: &str) {}
+}
@@ -0,0 +1,426 @@
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::backup::Plan;
+use crate::bases::Bases;
+use crate::commands;
+use crate::designer::Credentials;
+use crate::history;
+use crate::metrics;
+use crate::tunnel::Tunnel;
+use crate::{LaunchMode, Launcher, parse_base_path, production_guard_word, try_parse_and_launch};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const APP_ERROR: i64 = -32000;
+
+/// Largest request body read, a bigger `Content-Length` is refused with 413
+/// before anything is allocated for it.
+const MAX_BODY: usize = 64 * 1024;
+
+/// Largest request line and headers read together, a longer head is refused
+/// with 431.
+const MAX_HEAD: u64 = 8 * 1024;
+
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Operations shared by every automation transport.
+pub struct Server {
+    bases: Bases,
+    launcher: Launcher,
+    tunnels: Vec<Tunnel>,
+}
+
+impl Server {
+    pub fn new(bases: Bases, launcher: Launcher) -> Server {
+        Server {
+            bases,
+            launcher,
+            tunnels: Vec::new(),
+        }
+    }
+
+    pub fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "list" => {
+                let paths = commands::known_paths(&history::load(), &self.bases);
+                Ok(json!(commands::listed(&paths, &self.bases)))
+            }
+            "parse" => {
+                let path = string_param(params, "path")?;
+                let parsed =
+                    parse_base_path(&path).map_err(|e| RpcError::new(APP_ERROR, e.to_string()))?;
+                Ok(json!({
+                    "kind": parsed.kind_name(),
+                    "target": parsed.target(),
+                    "name": parsed.name(),
+                }))
+            }
+            "launch" => {
+                let path = string_param(params, "path")?;
+                let mode = match params.get("mode").and_then(Value::as_str) {
+                    Some(mode) => LaunchMode::from_str(mode, true).map_err(|_| {
+                        RpcError::new(INVALID_PARAMS, format!("unknown mode: {mode}"))
+                    })?,
+                    None => LaunchMode::Enterprise,
+                };
+                if mode == LaunchMode::Designer && self.bases.is_production(&path) {
+                    let word = production_guard_word(&path);
+                    if params.get("confirm").and_then(Value::as_str) != Some(word.as_str()) {
+                        return Err(RpcError::new(
+                            APP_ERROR,
+                            format!(
+                                "production base: pass \"confirm\": \"{word}\" to launch the designer"
+                            ),
+                        ));
+                    }
+                }
+                // tunnels of exited clients hold local ports a relaunch needs
+                self.tunnels.retain_mut(|tunnel| !tunnel.is_done());
+                let launched = try_parse_and_launch(
+                    path,
+                    mode,
+                    &self.bases,
+                    &self.launcher,
+                    &Credentials::default(),
+                )
+                .map_err(|e| RpcError::new(APP_ERROR, e.to_string()))?;
+                let tunneled = launched.tunnel.is_some();
+                self.tunnels.extend(launched.tunnel);
+                Ok(json!({ "launched": true, "tunnel": tunneled }))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {method}"),
+            )),
+        }
+    }
+
+    /// Handles one JSON-RPC 2.0 request, returning `None` for notifications.
+    fn handle_line(&mut self, line: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ));
+            }
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let error = RpcError::new(INVALID_REQUEST, "missing method");
+            return Some(error_response(id.unwrap_or(Value::Null), error));
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = self.call(method, &params);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+}
+
+fn string_param(params: &Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing string param: {name}")))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// Serves newline-delimited JSON-RPC requests from stdin until it is closed.
+pub fn stdio(bases: Bases, launcher: Launcher) -> Result<(), Box<dyn Error>> {
+    let mut server = Server::new(bases, launcher);
+    let mut stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Serves the read-only `GET /list`, `GET /status` and `GET /metrics`
+/// endpoints and a `POST /launch` endpoint on a loopback address; all but
+/// `/metrics` need the bearer token. `plans` are the backup plans reported by
+/// `/metrics`.
+pub fn http(
+    bases: Bases,
+    launcher: Launcher,
+    plans: Vec<Plan>,
+    addr: SocketAddr,
+    token: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    if !addr.ip().is_loopback() {
+        return Err(format!("Refusing to listen on non-local address {addr}").into());
+    }
+    let token = token.unwrap_or_else(random_token);
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Listening on http://{addr}");
+    eprintln!("Launch token: {token}");
+
+    let mut server = Server::new(bases, launcher);
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Connection error: {e}");
+                continue;
+            }
+        };
+        let response = match read_http_request(&mut stream) {
+            Ok(request) => server.handle_http(&request, &token, &plans),
+            Err(e) if e.is::<BodyTooLarge>() => HttpResponse::error(413, e.to_string()),
+            Err(e) if e.is::<HeadTooLarge>() => HttpResponse::error(431, e.to_string()),
+            Err(e) => HttpResponse::error(400, e.to_string()),
+        };
+        if let Err(e) = response.write_to(&mut stream) {
+            eprintln!("Connection error: {e}");
+        }
+    }
+    Ok(())
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+struct HttpResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl HttpResponse {
+    fn json(status: u16, body: Value) -> HttpResponse {
+        HttpResponse {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: String) -> HttpResponse {
+        HttpResponse::json(status, json!({ "error": message }))
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            413 => "Content Too Large",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {reason}\r\n\
+             Content-Type: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )?;
+        stream.flush()
+    }
+}
+
+impl Server {
+    fn handle_http(&mut self, request: &HttpRequest, token: &str, plans: &[Plan]) -> HttpResponse {
+        let route = (request.method.as_str(), request.path.as_str());
+        let authorized = request
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| same_token(given, token));
+        if matches!(route, ("GET", "/status" | "/list") | ("POST", "/launch")) && !authorized {
+            return HttpResponse::error(401, "missing or wrong bearer token".into());
+        }
+
+        let (method, params) = match route {
+            ("GET", "/metrics") => {
+                return HttpResponse {
+                    status: 200,
+                    content_type: "text/plain; version=0.0.4",
+                    body: metrics::prometheus(plans),
+                };
+            }
+            ("GET", "/status") => {
+                return HttpResponse::json(
+                    200,
+                    json!({ "version": env!("CARGO_PKG_VERSION"), "bases": self.bases.entries().len() }),
+                );
+            }
+            ("GET", "/list") => ("list", Value::Null),
+            ("POST", "/launch") => match serde_json::from_slice(&request.body) {
+                Ok(params) => ("launch", params),
+                Err(e) => return HttpResponse::error(400, e.to_string()),
+            },
+            _ => {
+                return HttpResponse::error(
+                    404,
+                    format!("no route for {} {}", request.method, request.path),
+                );
+            }
+        };
+
+        match self.call(method, &params) {
+            Ok(result) => HttpResponse::json(200, result),
+            Err(error) if error.code == INVALID_PARAMS => HttpResponse::error(400, error.message),
+            Err(error) => HttpResponse::error(500, error.message),
+        }
+    }
+}
+
+fn read_http_request(stream: &mut TcpStream) -> Result<HttpRequest, Box<dyn Error>> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream);
+    let mut head_left = MAX_HEAD;
+
+    let request_line = read_head_line(&mut reader, &mut head_left)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err("malformed request line".into());
+    };
+
+    let mut authorization = None;
+    let mut content_length = 0;
+    loop {
+        let header = read_head_line(&mut reader, &mut head_left)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse()?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Err(BodyTooLarge(content_length).into());
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        authorization,
+        body,
+    })
+}
+
+/// Reads a line of the request head, at most `left` bytes in total over all
+/// its lines.
+fn read_head_line(
+    reader: &mut BufReader<&mut TcpStream>,
+    left: &mut u64,
+) -> Result<String, Box<dyn Error>> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(*left).read_line(&mut line)?;
+    *left -= read as u64;
+    if !line.ends_with('\n') && *left == 0 {
+        return Err(HeadTooLarge.into());
+    }
+    Ok(line)
+}
+
+/// A request whose line and headers are over `MAX_HEAD`.
+#[derive(Debug)]
+struct HeadTooLarge;
+
+impl fmt::Display for HeadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "request headers are over the limit of {MAX_HEAD} bytes")
+    }
+}
+
+impl Error for HeadTooLarge {}
+
+/// A request whose `Content-Length` is over `MAX_BODY`.
+#[derive(Debug)]
+struct BodyTooLarge(usize);
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "request body of {} bytes is over the limit of {MAX_BODY}",
+            self.0
+        )
+    }
+}
+
+impl Error for BodyTooLarge {}
+
+/// Compares the tokens in a time that does not depend on where they differ.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn random_token() -> String {
+    let state = RandomState::new();
+    let mut token = String::new();
+    for salt in 0..2u64 {
+        let mut hasher = state.build_hasher();
+        hasher.write_u64(salt);
+        token.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    token
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths;
+
+const FILE_NAME: &str = "rbaserun_session.toml";
+
+fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+/// Transient UI state, saved while the picker runs so that a session killed
+/// with the terminal (e.g. a dropped SSH connection) can be picked up again.
+/// A clean exit removes it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Session {
+    pub input: String,
+    pub selected: Option<usize>,
+    pub offset: usize,
+    pub designer: bool,
+    pub detail_height: u16,
+    /// Folders collapsed in the history pane
+    #[serde(default)]
+    pub collapsed: Vec<String>,
+}
+
+impl Session {
+    pub fn load() -> Option<Session> {
+        let content = fs::read_to_string(file()).ok()?;
+        toml::from_str(&content).ok()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string(self) {
+            let _ = paths::prepare(&file()).and_then(|()| fs::write(file(), content));
+        }
+    }
+
+    pub fn clear() {
+        let _ = fs::remove_file(file());
+    }
+}
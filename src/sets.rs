@@ -0,0 +1,230 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+
+use std::error::Error;
+
+use crate::LaunchMode;
+use crate::keys;
+use crate::macros::{Macros, Step};
+use crate::popup;
+use crate::theme;
+
+/// Seconds `+` and `-` change a delay by.
+const DELAY_STEP: u64 = 5;
+
+pub enum SetsAction {
+    None,
+    Launch(String),
+    Close,
+}
+
+/// Launch sets shown with Ctrl+Y: the current base can be added to them and
+/// their bases removed or given other modes and delays.
+#[derive(Debug)]
+pub struct SetsPanel {
+    pub macros: Macros,
+    /// Base of the picker `a` adds and the mode it is added with
+    path: String,
+    mode: LaunchMode,
+    state: ListState,
+    error: Option<String>,
+}
+
+impl SetsPanel {
+    pub fn new(macros: Macros, path: String, mode: LaunchMode) -> SetsPanel {
+        SetsPanel {
+            macros,
+            path,
+            mode,
+            state: ListState::default().with_selected(Some(0)),
+            error: None,
+        }
+    }
+
+    /// The set and step of every line, `None` for the line naming the set.
+    fn rows(&self) -> Vec<(usize, Option<usize>)> {
+        let mut rows = Vec::new();
+        for (set, recorded) in self.macros.macros.iter().enumerate() {
+            rows.push((set, None));
+            rows.extend((0..recorded.steps.len()).map(|step| (set, Some(step))));
+        }
+        rows
+    }
+
+    fn selected(&self) -> Option<(usize, Option<usize>)> {
+        let rows = self.rows();
+        let index = self.state.selected()?.min(rows.len().checked_sub(1)?);
+        Some(rows[index])
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> SetsAction {
+        let Event::Key(key_event) = event else {
+            return SetsAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return SetsAction::None;
+        }
+        let result = match keys::binding(key_event.code) {
+            KeyCode::Esc => return SetsAction::Close,
+            KeyCode::Enter => match self.selected() {
+                Some((set, _)) => return SetsAction::Launch(self.macros.macros[set].name.clone()),
+                None => Ok(false),
+            },
+            KeyCode::Up => {
+                self.state.select_previous();
+                Ok(false)
+            }
+            KeyCode::Down => {
+                self.state.select_next();
+                Ok(false)
+            }
+            KeyCode::Char('a') => self.add_current(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char('m') => self.cycle_mode(),
+            KeyCode::Char('+') => self.change_delay(true),
+            KeyCode::Char('-') => self.change_delay(false),
+            _ => Ok(false),
+        };
+        self.error = None;
+        match result.and_then(|changed| if changed { self.macros.save() } else { Ok(()) }) {
+            Ok(()) => {}
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        SetsAction::None
+    }
+
+    fn add_current(&mut self) -> Result<bool, Box<dyn Error>> {
+        let Some((set, _)) = self.selected() else {
+            return Err("Record a set with Ctrl+X first".into());
+        };
+        if self.path.trim().is_empty() {
+            return Err("Type or select a base to add first".into());
+        }
+        self.macros.macros[set].steps.push(Step {
+            path: self.path.clone(),
+            mode: self.mode,
+            delay: None,
+        });
+        Ok(true)
+    }
+
+    fn delete(&mut self) -> Result<bool, Box<dyn Error>> {
+        match self.selected() {
+            Some((set, Some(step))) => {
+                self.macros.macros[set].steps.remove(step);
+            }
+            Some((set, None)) => {
+                self.macros.macros.remove(set);
+            }
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn cycle_mode(&mut self) -> Result<bool, Box<dyn Error>> {
+        let Some((set, Some(step))) = self.selected() else {
+            return Ok(false);
+        };
+        let step = &mut self.macros.macros[set].steps[step];
+        let index = LaunchMode::ALL
+            .iter()
+            .position(|mode| *mode == step.mode)
+            .unwrap_or(0);
+        step.mode = LaunchMode::ALL[(index + 1) % LaunchMode::ALL.len()];
+        Ok(true)
+    }
+
+    /// Changes the delay of the selected step, or the stagger of the set.
+    fn change_delay(&mut self, longer: bool) -> Result<bool, Box<dyn Error>> {
+        let change = |seconds: u64| {
+            if longer {
+                seconds + DELAY_STEP
+            } else {
+                seconds.saturating_sub(DELAY_STEP)
+            }
+        };
+        match self.selected() {
+            Some((set, Some(step))) => {
+                let delay = self.macros.macros[set].delay(step);
+                self.macros.macros[set].steps[step].delay = Some(change(delay));
+            }
+            Some((set, None)) => {
+                let recorded = &mut self.macros.macros[set];
+                recorded.stagger = change(recorded.stagger);
+            }
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let rows = self.rows();
+        let height = (rows.len() as u16).clamp(1, frame.area().height.saturating_sub(8)) + 4;
+        let area = popup::area(frame.area(), height);
+        let items: Vec<Line> = if rows.is_empty() {
+            vec![Line::styled(
+                "No launch sets, record one with Ctrl+X",
+                theme::hint(),
+            )]
+        } else {
+            rows.iter()
+                .map(|&(set, step)| {
+                    let recorded = &self.macros.macros[set];
+                    match step {
+                        None => {
+                            let mut line = Line::from(recorded.name.clone().bold());
+                            if let Some(key) = &recorded.key {
+                                line.push_span(format!(" ({key})"));
+                            }
+                            if recorded.stagger > 0 {
+                                line.push_span(Span::styled(
+                                    format!("  {} s apart", recorded.stagger),
+                                    theme::hint(),
+                                ));
+                            }
+                            line
+                        }
+                        Some(index) => {
+                            let step = &recorded.steps[index];
+                            let mut line = Line::from(format!(
+                                "  {}  {}",
+                                step.path.trim(),
+                                step.mode.label()
+                            ));
+                            if step.delay.is_some() {
+                                line.push_span(Span::styled(
+                                    format!("  after {} s", recorded.delay(index)),
+                                    theme::hint(),
+                                ));
+                            }
+                            line
+                        }
+                    }
+                })
+                .collect()
+        };
+
+        let block = Block::bordered().title("Launch sets");
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+        let list = List::new(items).highlight_style(theme::selected());
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        let hint = match &self.error {
+            Some(error) => Span::styled(error.clone(), theme::error()),
+            None => Span::styled(
+                "Enter: launch, a: add base, Del: remove, m: mode, +/-: delay, Esc: close",
+                theme::hint(),
+            ),
+        };
+        frame.render_widget(Paragraph::new(Line::from(hint)), hint_area);
+    }
+}
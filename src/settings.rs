@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::bases::Bases;
+use crate::ibases::RegisteredBase;
+use crate::{parse_base_path, platform};
+
+/// The directory the 1C clients keep the user settings of `path` in on this
+/// machine, found by the launcher `ID=` of the base: the one saved in the
+/// bases file or the one of the same base in `ibases.v8i`.
+pub fn dir(
+    path: &str,
+    bases: &Bases,
+    registered: &[RegisteredBase],
+) -> Result<PathBuf, Box<dyn Error>> {
+    let target = parse_base_path(path)?.target();
+    let id = bases
+        .find(path)
+        .and_then(|entry| entry.id.clone())
+        .or_else(|| {
+            registered
+                .iter()
+                .filter(|base| {
+                    parse_base_path(&base.connect)
+                        .is_ok_and(|parsed| parsed.target().eq_ignore_ascii_case(&target))
+                })
+                .find_map(|base| base.id.clone())
+        })
+        .ok_or_else(|| {
+            format!(
+                "{} is not registered in the 1C launcher, its settings can't be found",
+                path.trim()
+            )
+        })?;
+    // the ID ends up in a path that is deleted, only a GUID is let through
+    if !is_guid(&id) {
+        return Err(format!("{} has an invalid launcher ID '{id}'", path.trim()).into());
+    }
+    let root = platform::user_settings_root()
+        .ok_or("The settings directory of the 1C clients is unknown on this machine")?;
+    let dir = root.join(&id);
+    if !dir.is_dir() {
+        return Err(format!("No user settings of {} in {}", path.trim(), dir.display()).into());
+    }
+    check_inside(&dir, &root)?;
+    Ok(dir)
+}
+
+/// Whether `id` has the shape of a GUID, `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+fn is_guid(id: &str) -> bool {
+    id.len() == 36
+        && id.char_indices().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Refuses `dir` unless it resolves to a direct child of the settings root,
+/// e.g. when it is a link to somewhere else.
+fn check_inside(dir: &Path, root: &Path) -> Result<(), Box<dyn Error>> {
+    let resolved = fs::canonicalize(dir)?;
+    let root = fs::canonicalize(root)?;
+    if resolved.parent() != Some(root.as_path()) || fs::symlink_metadata(dir)?.is_symlink() {
+        return Err(format!(
+            "{} is not a directory of the 1C settings in {}, not removing it",
+            dir.display(),
+            root.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Removes the user settings of `path` kept on this machine, so the client
+/// starts over with the default forms and windows the next time.
+pub fn reset(
+    path: &str,
+    bases: &Bases,
+    registered: &[RegisteredBase],
+) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = dir(path, bases, registered)?;
+    fs::remove_dir_all(&dir).map_err(|e| format!("Could not remove {}: {e}", dir.display()))?;
+    Ok(dir)
+}
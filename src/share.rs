@@ -0,0 +1,114 @@
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::Alignment,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use std::error::Error;
+use std::io::{self, IsTerminal};
+
+use crate::{clipboard, keys, parse_base_path, popup, theme};
+
+pub enum ShareAction {
+    None,
+    Close,
+}
+
+/// Popup showing the canonical connection string of a base as a QR code, to
+/// be scanned from the screen with a phone.
+#[derive(Debug)]
+pub struct SharePanel {
+    pub text: String,
+    code: Vec<String>,
+    notice: Option<String>,
+}
+
+impl SharePanel {
+    pub fn new(path: &str) -> Result<SharePanel, Box<dyn Error>> {
+        let text = parse_base_path(path)?.connection_string();
+        Ok(SharePanel {
+            code: render(&text)?.lines().map(str::to_string).collect(),
+            text,
+            notice: None,
+        })
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> ShareAction {
+        let Event::Key(key_event) = event else {
+            return ShareAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return ShareAction::None;
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match keys::binding(key_event.code) {
+            KeyCode::Esc | KeyCode::Enter => return ShareAction::Close,
+            KeyCode::Char('s') if ctrl => return ShareAction::Close,
+            KeyCode::Char('c') => {
+                self.notice = Some(match clipboard::copy(&self.text) {
+                    Ok(()) => "Copied to clipboard".to_string(),
+                    Err(e) => e.to_string(),
+                });
+            }
+            _ => {}
+        }
+        ShareAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), self.code.len() as u16 + 5);
+
+        let mut lines: Vec<Line> = self
+            .code
+            .iter()
+            .map(|line| Line::from(line.as_str().black().on_white()))
+            .collect();
+        lines.push(Line::from(self.text.as_str()));
+        lines.push(Line::default());
+        lines.push(match &self.notice {
+            Some(notice) => Span::styled(notice.as_str(), theme::success()).into(),
+            None => Span::styled("c: copy, Esc: close", theme::hint()).into(),
+        });
+
+        let block = Block::bordered().title("Share");
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(block),
+            area,
+        );
+    }
+}
+
+/// The QR code of `text` in half blocks, two modules per character, with the
+/// quiet zone around it.
+fn render(text: &str) -> Result<String, Box<dyn Error>> {
+    let code =
+        QrCode::new(text.as_bytes()).map_err(|e| format!("Could not encode a QR code: {e}"))?;
+    Ok(code.render::<Dense1x2>().quiet_zone(true).build())
+}
+
+/// Prints the QR code of the canonical connection string of `path` followed
+/// by the string itself. On a terminal the code is drawn black on white, so
+/// it scans on dark color schemes too.
+pub fn print(path: &str) -> Result<(), Box<dyn Error>> {
+    use ratatui::crossterm::style::{Stylize as _, style};
+
+    let text = parse_base_path(path)?.connection_string();
+    let terminal = io::stdout().is_terminal();
+    for line in render(&text)?.lines() {
+        if terminal {
+            println!("{}", style(line).black().on_white());
+        } else {
+            println!("{line}");
+        }
+    }
+    println!("{text}");
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use chrono::Local;
+
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::clients;
+use crate::clone;
+use crate::space::{self, DATABASE_FILE, LOCK_FILE};
+
+/// How long the client of a snapshot launched through the starter may take
+/// to show up before the snapshot is deleted.
+const START_TIMEOUT: Duration = Duration::from_secs(60);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A copy of a file base in the temp directory, `launch --snapshot`, so a
+/// production base can be looked into without locking it or changing it.
+/// The copy is deleted when dropped.
+pub struct Snapshot {
+    dir: PathBuf,
+    /// Connection string of the copy
+    pub path: String,
+}
+
+impl Snapshot {
+    /// Copies the database file of the file base `source`.
+    pub fn take(source: &str) -> Result<Snapshot, Box<dyn Error>> {
+        let source_dir = clone::source_dir(source)?;
+        let source_file = source_dir.join(DATABASE_FILE);
+        // no lock is taken, the sessions of the base go on undisturbed
+        let mut input = File::open(&source_file)
+            .map_err(|e| format!("Could not open {}: {e}", source_file.display()))?;
+        if source_dir.join(LOCK_FILE).exists() {
+            eprintln!("Warning: {LOCK_FILE} exists, the snapshot may miss unfinished transactions");
+        }
+        let name = source_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let dir = env::temp_dir().join(format!(
+            "rbaserun-snapshot-{name}-{}",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let size = input.metadata()?.len();
+        space::check(&env::temp_dir(), size)?;
+        fs::create_dir_all(&dir)?;
+        let snapshot = Snapshot {
+            path: format!("File=\"{}\";", dir.display()),
+            dir,
+        };
+        clone::copy_with_progress(&mut input, &snapshot.dir.join(DATABASE_FILE), size)
+            .map_err(|e| format!("Copy failed: {e}"))?;
+        Ok(snapshot)
+    }
+
+    /// Waits for the clients of the copy started through the starter to
+    /// exit: first for one to show up, then for all of them to be gone.
+    pub fn wait_for_clients(&self) {
+        let started = Instant::now();
+        while clients::running(&self.path).is_empty() && started.elapsed() < START_TIMEOUT {
+            thread::sleep(POLL_INTERVAL);
+        }
+        while !clients::running(&self.path).is_empty() {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            eprintln!("Could not delete the snapshot {}: {e}", self.dir.display());
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::{PathKind, locale};
+
+/// Database file of a file base.
+pub const DATABASE_FILE: &str = "1Cv8.1CD";
+
+/// Lock file of a file base, there while sessions are open.
+pub const LOCK_FILE: &str = "1Cv8.1CL";
+
+/// A .dt dump is compressed and takes at most this part of the database file
+/// it is made of.
+const DUMP_SHARE: u64 = 2;
+
+/// A database file restored from a .dt takes up to this many times its size.
+const RESTORE_FACTOR: u64 = 5;
+
+/// Fails unless the volume of `dir`, or of its nearest existing parent when
+/// it is to be created, has `needed` bytes free.
+pub fn check(dir: &Path, needed: u64) -> Result<(), Box<dyn Error>> {
+    let existing = dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .unwrap_or(Path::new("."));
+    let available = fs4::available_space(existing)
+        .map_err(|e| format!("Could not read the free space in {}: {e}", dir.display()))?;
+    if available < needed {
+        return Err(format!(
+            "Not enough free space in {}: {} needed, {} available",
+            dir.display(),
+            locale::size(needed),
+            locale::size(available)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Checks the free space for dumping `path` to `out`. A dump of a file base
+/// is estimated from its database file, one replacing an older dump from the
+/// older one; the database of a server base dumped the first time is not
+/// known here and not checked.
+pub fn check_dump(path: &PathKind, out: &Path) -> Result<(), Box<dyn Error>> {
+    let database = match path {
+        PathKind::File { path } => file_size(&Path::new(path).join(DATABASE_FILE)) / DUMP_SHARE,
+        _ => 0,
+    };
+    let needed = database.max(file_size(out));
+    if needed == 0 {
+        return Ok(());
+    }
+    let dir = out
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    check(dir, needed)
+}
+
+/// Checks the free space for restoring `dt` into `path`: the database file
+/// of a file base grows to several times the dump, less what the file it
+/// replaces frees. Server bases are restored on the DBMS and not checked.
+pub fn check_restore(path: &PathKind, dt: &Path) -> Result<(), Box<dyn Error>> {
+    let PathKind::File { path } = path else {
+        return Ok(());
+    };
+    let dir = Path::new(path);
+    let needed =
+        (file_size(dt) * RESTORE_FACTOR).saturating_sub(file_size(&dir.join(DATABASE_FILE)));
+    if needed == 0 {
+        return Ok(());
+    }
+    check(dir, needed)
+}
+
+fn file_size(file: &Path) -> u64 {
+    fs::metadata(file).map_or(0, |meta| meta.len())
+}
@@ -0,0 +1,250 @@
+use chrono::Local;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::bases::{self, BaseEntry, Bases};
+use crate::config::RemoteConfig;
+use crate::{history, lockdown, paths};
+
+/// Directory of the backups of the history and bases file, in the data directory.
+const BACKUP_DIR: &str = "store-backups";
+
+static KEEP_BACKUPS: OnceLock<usize> = OnceLock::new();
+
+/// Files backed up in this run, each is backed up before its first write only.
+static BACKED_UP: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// How conflicting entries are resolved, `Ask` prompts for each of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ask,
+    Ours,
+    Theirs,
+}
+
+/// Merges another copy of the bases file into the current one, matching
+/// entries by connection string.
+pub fn merge(
+    other: &Path,
+    bases: &mut Bases,
+    resolution: Resolution,
+) -> Result<(), Box<dyn Error>> {
+    let theirs = Bases::read(other)?;
+
+    let mut patterns = 0;
+    for pattern in theirs.prod_patterns() {
+        if !bases.prod_patterns().contains(pattern) {
+            bases.add_prod_pattern(pattern.clone());
+            patterns += 1;
+        }
+    }
+
+    let (mut added, mut updated, mut kept) = (0, 0, 0);
+    for entry in theirs.entries() {
+        let Some(ours) = bases.find(&entry.path) else {
+            *bases.entry_mut(&entry.path) = entry.clone();
+            added += 1;
+            continue;
+        };
+        let (ours_text, theirs_text) = (describe(ours)?, describe(entry)?);
+        if ours_text == theirs_text {
+            continue;
+        }
+        let take_theirs = match resolution {
+            Resolution::Ours => false,
+            Resolution::Theirs => true,
+            Resolution::Ask if io::stdin().is_terminal() => {
+                ask(&entry.path, &ours_text, &theirs_text)?
+            }
+            Resolution::Ask => {
+                return Err(format!(
+                    "{} conflicts, resolve it interactively or pass --ours or --theirs",
+                    entry.path
+                )
+                .into());
+            }
+        };
+        if !take_theirs {
+            kept += 1;
+            continue;
+        }
+        // their version may drop the prod tag of a protected base
+        let was_production = bases.is_production(&entry.path);
+        let before = ours.clone();
+        *bases.entry_mut(&entry.path) = entry.clone();
+        if was_production
+            && !bases.is_production(&entry.path)
+            && let Err(e) = lockdown::check_removal(&entry.path)
+        {
+            *bases.entry_mut(&entry.path) = before;
+            eprintln!("Kept our version: {e}");
+            kept += 1;
+            continue;
+        }
+        updated += 1;
+    }
+
+    if added + updated + patterns > 0 {
+        bases.save()?;
+    }
+    println!(
+        "Merged {}: {added} added, {updated} updated, {kept} kept, {patterns} prod patterns added",
+        other.display()
+    );
+    Ok(())
+}
+
+fn describe(entry: &BaseEntry) -> Result<String, Box<dyn Error>> {
+    Ok(toml::to_string_pretty(entry)?)
+}
+
+/// Shows both versions of a conflicting entry, returns `true` to take theirs.
+fn ask(path: &str, ours: &str, theirs: &str) -> Result<bool, Box<dyn Error>> {
+    println!("\nConflict in {path}");
+    println!("--- ours");
+    print!("{ours}");
+    println!("--- theirs");
+    print!("{theirs}");
+    loop {
+        print!("Keep [o]urs or take [t]heirs? ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Err("Merge cancelled".into());
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "o" | "ours" => return Ok(false),
+            "t" | "theirs" => return Ok(true),
+            _ => {}
+        }
+    }
+}
+
+/// Keeps `keep` backups of each file, `store_backups` in the config, 0
+/// takes none. Called once at startup.
+pub fn set_keep_backups(keep: usize) {
+    let _ = KEEP_BACKUPS.set(keep);
+}
+
+fn backup_dir() -> PathBuf {
+    paths::data_file(BACKUP_DIR)
+}
+
+/// Copies `file` to the backups before it is written for the first time in
+/// this run, then removes its oldest backups beyond `store_backups`. A
+/// failed backup is reported but does not stop the write.
+pub fn backup(file: &Path) {
+    let keep = *KEEP_BACKUPS.get().unwrap_or(&10);
+    if keep == 0 || !file.is_file() {
+        return;
+    }
+    {
+        let mut backed_up = BACKED_UP.lock().unwrap_or_else(|e| e.into_inner());
+        if !backed_up
+            .get_or_insert_with(HashSet::new)
+            .insert(file.to_path_buf())
+        {
+            return;
+        }
+    }
+    if let Err(e) = rotate(file, keep) {
+        eprintln!("Warning: could not back up {}: {e}", file.display());
+    }
+}
+
+fn rotate(file: &Path, keep: usize) -> io::Result<()> {
+    let dir = backup_dir();
+    fs::create_dir_all(&dir)?;
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    fs::copy(
+        file,
+        dir.join(format!(
+            "{name}.{}",
+            Local::now().format("%Y%m%d-%H%M%S%3f")
+        )),
+    )?;
+    for old in backups_of(&name).into_iter().skip(keep) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// The backups of the file named `name`, latest first.
+fn backups_of(name: &str) -> Vec<PathBuf> {
+    let prefix = format!("{name}.");
+    let mut backups: Vec<PathBuf> = fs::read_dir(backup_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|file| file.to_string_lossy().starts_with(&prefix))
+        })
+        .collect();
+    // the timestamps sort by name
+    backups.sort_unstable_by(|a, b| b.cmp(a));
+    backups
+}
+
+/// Puts the backup `from`, a file name of the backups or any path, back in
+/// place of the history or bases file it was taken of, the current one
+/// being backed up first. Lists the backups without `from`. The bases file
+/// is not restored while it is read from `remote`.
+pub fn restore(from: Option<&str>, remote: Option<&RemoteConfig>) -> Result<(), Box<dyn Error>> {
+    let targets = [bases::file(), history::file().to_path_buf()];
+    let Some(from) = from else {
+        for target in &targets {
+            let name = target.file_name().unwrap_or_default().to_string_lossy();
+            for backup in backups_of(&name) {
+                println!(
+                    "{}",
+                    backup.file_name().unwrap_or_default().to_string_lossy()
+                );
+            }
+        }
+        return Ok(());
+    };
+    let source = match Path::new(from) {
+        path if path.is_file() => path.to_path_buf(),
+        _ => backup_dir().join(from),
+    };
+    if !source.is_file() {
+        return Err(format!("No backup {from}, `rbaserun store restore` lists them").into());
+    }
+    let source_name = source.file_name().unwrap_or_default().to_string_lossy();
+    let target = targets
+        .iter()
+        .find(|target| {
+            let name = target.file_name().unwrap_or_default().to_string_lossy();
+            source_name.starts_with(&format!("{name}."))
+        })
+        .ok_or_else(|| format!("{source_name} is not a backup of the history or the bases file"))?;
+    if *target == targets[0] {
+        if let Some(remote) = remote {
+            return Err(format!(
+                "The bases file is read from {}, a local backup would not be used",
+                remote.location()
+            )
+            .into());
+        }
+        Bases::read(&source)?;
+        let content = fs::read(&source)?;
+        backup(target);
+        paths::prepare(target)?;
+        fs::write(target, content)
+            .map_err(|e| format!("Could not restore {}: {e}", target.display()))?;
+    } else {
+        let content = fs::read_to_string(&source)
+            .map_err(|e| format!("Could not read {}: {e}", source.display()))?;
+        history::restore_from(&content, &source.display().to_string())
+            .map_err(|e| format!("Could not restore {}: {e}", target.display()))?;
+    }
+    println!("Restored {} from {source_name}", target.display());
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+
+use crate::bases::Bases;
+use crate::{PathKind, commands, history, normalize_input, parse_base_path};
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the base `input` is an alias of, or `input` when it parses as a
+/// connection string. Otherwise fails with the closest known bases as
+/// suggestions, letting the user pick one of them when `interactive` and stdin
+/// is a terminal.
+pub fn resolve(input: &str, bases: &Bases, interactive: bool) -> Result<String, Box<dyn Error>> {
+    if let Some(entry) = bases.find_alias(input) {
+        return Ok(entry.path.clone());
+    }
+    let parse_error = match parse_base_path(input) {
+        Ok(_) => return Ok(normalize_input(input)),
+        Err(e) => e,
+    };
+    let suggestions = closest(input, &commands::known_paths(&history::load(), bases));
+    if suggestions.is_empty() {
+        return Err(parse_error);
+    }
+
+    if interactive && io::stdin().is_terminal() {
+        eprintln!("'{input}' is not a connection string. Did you mean:");
+        for (number, path) in suggestions.iter().enumerate() {
+            eprintln!("  {}) {path}", number + 1);
+        }
+        eprint!("Launch [1-{}, Enter to cancel]: ", suggestions.len());
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        return match answer.trim().parse::<usize>() {
+            Ok(number) if (1..=suggestions.len()).contains(&number) => {
+                Ok(suggestions[number - 1].clone())
+            }
+            _ => Err("Launch cancelled".into()),
+        };
+    }
+    Err(format!("{parse_error}. Did you mean: {}?", suggestions.join(", ")).into())
+}
+
+/// Known paths whose full text, base name or server is close to `input`, best first.
+fn closest(input: &str, paths: &[String]) -> Vec<String> {
+    let input = input.trim().to_lowercase();
+    let threshold = (input.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &String)> = paths
+        .iter()
+        .filter_map(|path| {
+            let mut distance = levenshtein(&input, &path.to_lowercase());
+            if let Ok(parsed) = parse_base_path(path) {
+                distance = distance.min(levenshtein(&input, &parsed.name().to_lowercase()));
+                if let PathKind::Server { host, .. } = &parsed {
+                    distance = distance.min(levenshtein(&input, &host.to_lowercase()));
+                }
+            }
+            (distance <= threshold).then_some((distance, path))
+        })
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance);
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, path)| path.clone())
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,277 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::bases::Bases;
+use crate::designer::{Batch, Credentials};
+use crate::{PathKind, history, paths};
+
+pub const DIR_NAME: &str = "templates";
+
+pub fn default_dir() -> PathBuf {
+    paths::data_file(DIR_NAME)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    /// A configuration, loaded into an empty base
+    Cf,
+    /// An infobase dump with data, restored into the base
+    Dt,
+}
+
+/// A `.cf` or `.dt` file new bases can be created from, e.g. a demo
+/// configuration or an empty framework.
+#[derive(Debug, Clone)]
+pub struct Template {
+    /// File name without the extension
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: TemplateKind,
+}
+
+/// A step run on every base created from a template, listed in a `.toml`
+/// file named like the template as `[[bootstrap]]` tables with one key.
+/// Relative paths start at the directory of the template.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Loads a configuration and updates the database configuration
+    Cf(PathBuf),
+    /// Loads a `.cfe` as the extension named after the file
+    Extension(PathBuf),
+    /// Runs an external data processor in Enterprise mode, which has to
+    /// close the session itself
+    Execute(PathBuf),
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Metadata {
+    #[serde(default)]
+    bootstrap: Vec<Step>,
+}
+
+impl Template {
+    fn read(path: &Path) -> Option<Template> {
+        let kind = match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "cf" => TemplateKind::Cf,
+            "dt" => TemplateKind::Dt,
+            _ => return None,
+        };
+        Some(Template {
+            name: path.file_stem()?.to_string_lossy().into_owned(),
+            path: path.to_path_buf(),
+            kind,
+        })
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            TemplateKind::Cf => "cf",
+            TemplateKind::Dt => "dt",
+        }
+    }
+
+    /// The bootstrap steps of the template, none without a metadata file.
+    pub fn steps(&self) -> Result<Vec<Step>, Box<dyn Error>> {
+        let path = self.path.with_extension("toml");
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Could not read {}: {e}", path.display()).into()),
+        };
+        let metadata: Metadata = toml::from_str(&text)
+            .map_err(|e| format!("Could not parse {}: {e}", path.display()))?;
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        Ok(metadata
+            .bootstrap
+            .into_iter()
+            .map(|step| match step {
+                Step::Cf(file) => Step::Cf(dir.join(file)),
+                Step::Extension(file) => Step::Extension(dir.join(file)),
+                Step::Execute(file) => Step::Execute(dir.join(file)),
+            })
+            .collect())
+    }
+
+    /// Runs the bootstrap steps of the template on the new base at `path`.
+    pub fn bootstrap(
+        &self,
+        path: &PathKind,
+        credentials: &Credentials,
+    ) -> Result<(), Box<dyn Error>> {
+        for step in self.steps()? {
+            let (mut batch, file) = match &step {
+                Step::Cf(file) | Step::Extension(file) => (Batch::new(path, credentials)?, file),
+                Step::Execute(file) => (Batch::enterprise(path, credentials)?, file),
+            };
+            println!("Bootstrap: {}", file.display());
+            match &step {
+                Step::Cf(file) => batch.arg("/LoadCfg").arg(file).arg("/UpdateDBCfg"),
+                Step::Extension(file) => {
+                    let name = file.file_stem().unwrap_or_default();
+                    batch
+                        .arg("/LoadCfg")
+                        .arg(file)
+                        .arg("-Extension")
+                        .arg(name)
+                        .arg("/UpdateDBCfg")
+                        .arg("-Extension")
+                        .arg(name)
+                }
+                Step::Execute(file) => batch
+                    .arg("/Execute")
+                    .arg(file)
+                    .arg("/DisableStartupMessages"),
+            };
+            batch
+                .run()
+                .map_err(|e| format!("Bootstrap step {} failed: {e}", file.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Loads the template into the empty base at `path`.
+    pub fn apply(&self, path: &PathKind, credentials: &Credentials) -> Result<(), Box<dyn Error>> {
+        let mut batch = Batch::new(path, credentials)?;
+        match self.kind {
+            TemplateKind::Cf => batch.arg("/LoadCfg").arg(&self.path).arg("/UpdateDBCfg"),
+            TemplateKind::Dt => batch.arg("/RestoreIB").arg(&self.path),
+        };
+        batch
+            .run()
+            .map_err(|e| format!("Loading template {} failed: {e}", self.name))?;
+        Ok(())
+    }
+}
+
+/// The templates in `dir` sorted by name, none when it does not exist.
+pub fn list(dir: &Path) -> Result<Vec<Template>, Box<dyn Error>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Could not read {}: {e}", dir.display()).into()),
+    };
+    let mut templates: Vec<Template> = entries
+        .filter_map(|entry| Template::read(&entry.ok()?.path()))
+        .collect();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+    Ok(templates)
+}
+
+/// Prints the templates of `dir` for `rbaserun templates`.
+pub fn print(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let templates = list(dir)?;
+    if templates.is_empty() {
+        println!("No .cf or .dt templates in {}", dir.display());
+    }
+    for template in templates {
+        println!(
+            "{}\t{}\t{}",
+            template.name,
+            template.kind_name(),
+            template.path.display()
+        );
+    }
+    Ok(())
+}
+
+/// The template named `name` in `dir`, or the `.cf`/`.dt` file at `name`.
+pub fn find(dir: &Path, name: &str) -> Result<Template, Box<dyn Error>> {
+    if let Some(template) = list(dir)?
+        .into_iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(template);
+    }
+    let path = Path::new(name);
+    match Template::read(path) {
+        Some(template) if path.is_file() => Ok(template),
+        _ => Err(format!(
+            "No template {name} in {}, see `rbaserun templates`",
+            dir.display()
+        )
+        .into()),
+    }
+}
+
+/// Asks which template of `dir` a new base starts from, `None` for an empty
+/// base. Does not ask without templates or when stdin is not a terminal.
+pub fn choose(dir: &Path) -> Result<Option<Template>, Box<dyn Error>> {
+    let mut templates = list(dir)?;
+    if templates.is_empty() || !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    eprintln!("Templates in {}:", dir.display());
+    for (number, template) in templates.iter().enumerate() {
+        eprintln!(
+            "  {}) {} ({})",
+            number + 1,
+            template.name,
+            template.kind_name()
+        );
+    }
+    eprint!(
+        "Create from [1-{}, Enter for an empty base]: ",
+        templates.len()
+    );
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Ok(None);
+    }
+    match answer.parse::<usize>() {
+        Ok(number) if (1..=templates.len()).contains(&number) => {
+            Ok(Some(templates.swap_remove(number - 1)))
+        }
+        _ => Err("Creation cancelled".into()),
+    }
+}
+
+/// Creates a file base in `dir`, from a `.cf` template directly, from a `.dt`
+/// one by restoring it into the new base, and adds it to the history.
+pub fn create_file(
+    dir: &Path,
+    template: Option<&Template>,
+    descr: Option<String>,
+    bases: &mut Bases,
+) -> Result<(), Box<dyn Error>> {
+    if dir.join("1Cv8.1CD").exists() {
+        return Err(format!("{} already holds a file base", dir.display()).into());
+    }
+    fs::create_dir_all(dir)?;
+    let dir = fs::canonicalize(dir)?;
+    let path = format!("File=\"{}\";", dir.display());
+    let mut batch = Batch::create(&path)?;
+    if let Some(template) = template.filter(|template| template.kind == TemplateKind::Cf) {
+        batch.arg("/UseTemplate").arg(&template.path);
+    }
+    batch
+        .run()
+        .map_err(|e| format!("Creating {path} failed: {e}"))?;
+    if let Some(template) = template {
+        let parsed = PathKind::File {
+            path: dir.display().to_string(),
+        };
+        if template.kind == TemplateKind::Dt {
+            template.apply(&parsed, &Credentials::default())?;
+        }
+        template.bootstrap(&parsed, &Credentials::default())?;
+    }
+
+    if descr.is_some() {
+        bases.entry_mut(&path).name = descr;
+        bases.save()?;
+    }
+    history::remember(path.clone())?;
+    match template {
+        Some(template) => println!("Created {path} from {}", template.name),
+        None => println!("Created {path}"),
+    }
+    Ok(())
+}
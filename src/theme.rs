@@ -0,0 +1,295 @@
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use serde::Deserialize;
+
+use std::error::Error;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::config::ThemeConfig;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static HIGH_CONTRAST: OnceLock<bool> = OnceLock::new();
+static REDUCED_MOTION: OnceLock<bool> = OnceLock::new();
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct Settings {
+    high_contrast: Option<bool>,
+    reduced_motion: Option<bool>,
+    preset: Option<Preset>,
+    styles: Vec<(String, Style)>,
+}
+
+/// Built-in color schemes, `[theme] preset` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// Styles of the parts of the picker, a preset with the styles of the config
+/// over it.
+#[derive(Debug, Clone)]
+struct Theme {
+    input_border: Style,
+    hint: Style,
+    error: Style,
+    success: Style,
+    warning: Style,
+    matched: Style,
+    alias: Style,
+    selected: Style,
+    designer: Style,
+}
+
+impl Theme {
+    fn preset(preset: Preset) -> Theme {
+        match preset {
+            Preset::Dark => Theme {
+                input_border: Style::new(),
+                hint: Style::new().dark_gray(),
+                error: Style::new().red(),
+                success: Style::new().green(),
+                warning: Style::new().yellow(),
+                matched: Style::new().yellow().bold(),
+                alias: Style::new().cyan(),
+                selected: Style::new().reversed(),
+                designer: Style::new().black().on_yellow(),
+            },
+            // yellow and cyan fade into a light background
+            Preset::Light => Theme {
+                input_border: Style::new(),
+                hint: Style::new().dark_gray(),
+                error: Style::new().red(),
+                success: Style::new().green(),
+                warning: Style::new().magenta(),
+                matched: Style::new().blue().bold(),
+                alias: Style::new().blue(),
+                selected: Style::new().reversed(),
+                designer: Style::new().white().on_magenta(),
+            },
+            Preset::HighContrast => Theme {
+                input_border: Style::new(),
+                hint: Style::new(),
+                error: Style::new().light_red().bold(),
+                success: Style::new().light_green().bold(),
+                warning: Style::new().light_yellow().bold(),
+                matched: Style::new().light_yellow().bold().underlined(),
+                alias: Style::new().light_cyan(),
+                selected: Style::new().reversed(),
+                designer: Style::new().black().on_light_yellow(),
+            },
+        }
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "input_border" => &mut self.input_border,
+            "hint" => &mut self.hint,
+            "error" => &mut self.error,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "matched" => &mut self.matched,
+            "alias" => &mut self.alias,
+            "selected" => &mut self.selected,
+            "designer" => &mut self.designer,
+            _ => return None,
+        })
+    }
+}
+
+/// Uses the `[theme]`, `high_contrast` and `reduced_motion` settings of the
+/// config, called once at startup. Unset ones follow the system when first
+/// needed.
+pub fn set(
+    config: &ThemeConfig,
+    high_contrast: Option<bool>,
+    reduced_motion: Option<bool>,
+) -> Result<(), Box<dyn Error>> {
+    let mut styles = Vec::new();
+    for (name, spec) in &config.styles {
+        if Theme::preset(Preset::Dark).style_mut(name).is_none() {
+            return Err(format!("Unknown [theme] style '{name}'").into());
+        }
+        let style = parse_style(spec).map_err(|e| format!("[theme] {name}: {e}"))?;
+        styles.push((name.clone(), style));
+    }
+    let _ = SETTINGS.set(Settings {
+        high_contrast,
+        reduced_motion,
+        preset: config.preset,
+        styles,
+    });
+    Ok(())
+}
+
+/// Reads a style such as `bold red on yellow`: a foreground color, `on` and a
+/// background color, and any of `bold`, `dim`, `italic`, `underlined` and
+/// `reversed`. Colors are the 16 terminal colors, `#rrggbb` or a 256-color index.
+fn parse_style(spec: &str) -> Result<Style, String> {
+    let color = |name: &str| Color::from_str(name).map_err(|_| format!("unknown color '{name}'"));
+    let mut style = Style::new();
+    let mut words = spec.split_whitespace();
+    while let Some(word) = words.next() {
+        style = match word.to_lowercase().as_str() {
+            "on" => match words.next() {
+                Some(background) => style.bg(color(background)?),
+                None => return Err(format!("no color after 'on' in '{spec}'")),
+            },
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            _ => style.fg(color(word)?),
+        };
+    }
+    Ok(style)
+}
+
+fn high_contrast() -> bool {
+    *HIGH_CONTRAST.get_or_init(|| {
+        let configured = SETTINGS.get().and_then(|settings| settings.high_contrast);
+        configured.unwrap_or_else(|| os::high_contrast().unwrap_or(false))
+    })
+}
+
+pub fn reduced_motion() -> bool {
+    *REDUCED_MOTION.get_or_init(|| {
+        let configured = SETTINGS.get().and_then(|settings| settings.reduced_motion);
+        configured.unwrap_or_else(|| os::reduced_motion().unwrap_or(false))
+    })
+}
+
+/// The preset of the config, or the one `high_contrast` asks for, with the
+/// styles of the config over it.
+fn theme() -> &'static Theme {
+    THEME.get_or_init(|| {
+        let settings = SETTINGS.get_or_init(Settings::default);
+        let preset = settings.preset.unwrap_or(if high_contrast() {
+            Preset::HighContrast
+        } else {
+            Preset::Dark
+        });
+        let mut theme = Theme::preset(preset);
+        for (name, style) in &settings.styles {
+            if let Some(own) = theme.style_mut(name) {
+                *own = *style;
+            }
+        }
+        theme
+    })
+}
+
+/// Border of the input box while Designer mode is off.
+pub fn input_border() -> Style {
+    theme().input_border
+}
+
+/// Key hints and secondary text.
+pub fn hint() -> Style {
+    theme().hint
+}
+
+pub fn error() -> Style {
+    theme().error
+}
+
+pub fn success() -> Style {
+    theme().success
+}
+
+/// Designer mode and other warnings.
+pub fn warning() -> Style {
+    theme().warning
+}
+
+/// Characters matched by the filter, on top of the style of their text.
+pub fn matched(style: Style) -> Style {
+    style.patch(theme().matched)
+}
+
+pub fn alias() -> Style {
+    theme().alias
+}
+
+/// The selected row of a list.
+pub fn selected() -> Style {
+    theme().selected
+}
+
+/// The DESIGNER badge and the selected history row while Designer mode is on.
+pub fn designer() -> Style {
+    theme().designer
+}
+
+/// Output of a settings query, `None` when the tool is missing or fails.
+fn query(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(windows)]
+mod os {
+    use super::query;
+
+    /// Bit 1 of `Flags` is set while a high contrast theme is on.
+    pub fn high_contrast() -> Option<bool> {
+        let flags = reg_value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")?;
+        Some(flags.parse::<u32>().ok()? & 1 == 1)
+    }
+
+    /// "Show animations in Windows" off clears `MinAnimate`.
+    pub fn reduced_motion() -> Option<bool> {
+        let animate = reg_value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate")?;
+        Some(animate == "0")
+    }
+
+    fn reg_value(key: &str, name: &str) -> Option<String> {
+        let output = query("reg", &["query", key, "/v", name])?;
+        let (_, value) = output.lines().find_map(|line| line.split_once("REG_SZ"))?;
+        Some(value.trim().to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use super::query;
+
+    pub fn high_contrast() -> Option<bool> {
+        universal_access("increaseContrast")
+    }
+
+    pub fn reduced_motion() -> Option<bool> {
+        universal_access("reduceMotion")
+    }
+
+    fn universal_access(key: &str) -> Option<bool> {
+        Some(query("defaults", &["read", "com.apple.universalaccess", key])? == "1")
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod os {
+    use super::query;
+
+    /// The GNOME accessibility settings, also honored by most other desktops
+    /// shipping gsettings.
+    pub fn high_contrast() -> Option<bool> {
+        Some(gsettings("org.gnome.desktop.a11y.interface", "high-contrast")? == "true")
+    }
+
+    pub fn reduced_motion() -> Option<bool> {
+        Some(gsettings("org.gnome.desktop.interface", "enable-animations")? == "false")
+    }
+
+    fn gsettings(schema: &str, key: &str) -> Option<String> {
+        query("gsettings", &["get", schema, key])
+    }
+}
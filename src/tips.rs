@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::keys::{Action, Keymap};
+use crate::paths;
+
+const FILE_NAME: &str = "rbaserun_used.toml";
+
+/// Seconds each tip stays in the status line.
+const ROTATE_SECS: i64 = 20;
+
+/// Lesser-known actions of the picker and what they do, in the order the
+/// tips rotate through them.
+const TIPS: [(Action, &str); 25] = [
+    (
+        Action::Pin,
+        "pins the selected entry to the top of the history",
+    ),
+    (
+        Action::Copy,
+        "copies the connection string of the selected entry",
+    ),
+    (Action::MoveToFolder, "moves the selected entry to a folder"),
+    (
+        Action::EditEntry,
+        "edits the alias, badge and switches of a base",
+    ),
+    (
+        Action::Credentials,
+        "saves the infobase user and password of a base",
+    ),
+    (
+        Action::NextClient,
+        "switches between the thin, thick and web client",
+    ),
+    (Action::NextPlatform, "picks an installed platform version"),
+    (Action::Check, "checks whether the base is reachable"),
+    (
+        Action::ShowFailed,
+        "lists the bases whose last launch failed",
+    ),
+    (
+        Action::Retry,
+        "checks the selected entry again and launches it once it is up",
+    ),
+    (Action::RecordMacro, "records the next launches as a macro"),
+    (Action::Sets, "launches several bases at once as a set"),
+    (Action::Activity, "shows how often each base was launched"),
+    (
+        Action::BuildConnection,
+        "builds a connection string field by field",
+    ),
+    (
+        Action::FocusClient,
+        "brings an open client of the base to the front",
+    ),
+    (Action::Share, "shows the base as a QR code"),
+    (Action::BrowseCluster, "browses the infobases of a cluster"),
+    (Action::Licenses, "shows the licenses used on the cluster"),
+    (Action::Plugins, "runs a plugin with the selected base"),
+    (
+        Action::ClearCache,
+        "launches the base with its client cache cleared",
+    ),
+    (
+        Action::ResetSettings,
+        "resets the user settings of the base on this machine",
+    ),
+    (
+        Action::InfobaseAccess,
+        "locks sessions or scheduled jobs of a server base",
+    ),
+    (
+        Action::EventLog,
+        "shows the end of the event log of the base",
+    ),
+    (
+        Action::Paste,
+        "pastes a connection string from the clipboard",
+    ),
+    (
+        Action::Favorites,
+        "launches a pinned base with a single key",
+    ),
+];
+
+/// The actions used at least once, kept in the data directory so that the
+/// tips of the status line only suggest the ones never tried. Nothing else
+/// is recorded and nothing leaves the machine.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Tips {
+    #[serde(default)]
+    used: BTreeSet<Action>,
+}
+
+fn file() -> PathBuf {
+    paths::data_file(FILE_NAME)
+}
+
+impl Tips {
+    pub fn load() -> Tips {
+        fs::read_to_string(file())
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Notes that `action` was used, saving only the first time.
+    pub fn record(&mut self, action: Action) {
+        if !self.used.insert(action) {
+            return;
+        }
+        if let Ok(content) = toml::to_string(self) {
+            let _ = paths::prepare(&file()).and_then(|()| fs::write(file(), content));
+        }
+    }
+
+    /// The tip to show at `now` (a unix timestamp), `None` once every action
+    /// with a key was used.
+    pub fn current(&self, keymap: &Keymap, now: i64) -> Option<String> {
+        let mut unused: Vec<String> = TIPS
+            .iter()
+            .filter(|(action, _)| !self.used.contains(action))
+            .map(|(action, text)| (keymap.label(*action), text))
+            .filter(|(key, _)| key != "-")
+            .map(|(key, text)| format!("Tip: {key} {text}"))
+            .collect();
+        if unused.is_empty() {
+            return None;
+        }
+        let index = (now / ROTATE_SECS).rem_euclid(unused.len() as i64) as usize;
+        Some(unused.swap_remove(index))
+    }
+}
@@ -0,0 +1,17 @@
+use std::process::Stdio;
+
+use crate::platform;
+
+/// Shows a desktop notification without waiting for it; failures are ignored
+/// since the notification is only a courtesy on top of the exit code. Builds
+/// without the `notifications` feature stay silent.
+pub fn show(title: &str, message: &str) {
+    if !cfg!(feature = "notifications") {
+        return;
+    }
+    let _ = platform::notification(title, message)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
@@ -0,0 +1,206 @@
+use ratatui::{
+    Terminal, TerminalOptions, Viewport,
+    backend::CrosstermBackend,
+    buffer::Buffer,
+    crossterm::{
+        cursor::Show,
+        event::{
+            DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        },
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+    layout::Rect,
+    style::Color,
+};
+
+use std::env;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+
+use crate::App;
+use crate::config::TerminalConfig;
+
+/// Terminals without colors, e.g. serial consoles.
+const MONOCHROME_TERMS: [&str; 4] = ["vt52", "vt100", "vt102", "vt220"];
+/// Terminals that ignore the alternate screen and xterm mouse reporting, e.g.
+/// the Linux console, where the picker would be left on the screen.
+const BASIC_TERMS: [&str; 7] = ["vt52", "vt100", "vt102", "vt220", "linux", "ansi", "cons25"];
+
+/// What the terminal can do: going by `TERM`, `NO_COLOR`, the locale and
+/// whether stdin and the output are terminals, with `[terminal]` of the
+/// config over that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The picker can run at all, `--linear` is used otherwise
+    pub cursor: bool,
+    pub color: bool,
+    pub alternate_screen: bool,
+    pub mouse: bool,
+    pub unicode: bool,
+    pub hyperlinks: bool,
+}
+
+impl Capabilities {
+    /// The capabilities of the terminal the picker draws on, stderr with
+    /// `stderr` like `TuiOptions`.
+    pub fn detect(config: &TerminalConfig, stderr: bool) -> Capabilities {
+        let output = if stderr {
+            io::stderr().is_terminal()
+        } else {
+            io::stdout().is_terminal()
+        };
+        // the Windows console needs no TERM
+        let term = env::var("TERM").unwrap_or_default().to_lowercase();
+        let cursor = io::stdin().is_terminal()
+            && output
+            && (cfg!(windows) || !(term.is_empty() || term == "dumb"));
+        let basic = BASIC_TERMS.contains(&term.as_str());
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let monochrome = MONOCHROME_TERMS.contains(&term.as_str())
+            || term.ends_with("-mono")
+            || term.ends_with("-m");
+        // the first locale variable set decides, like for the C library
+        let utf8 = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map(|locale| {
+                let locale = locale.to_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+        Capabilities {
+            cursor,
+            color: config.color.unwrap_or(!no_color && !monochrome),
+            alternate_screen: config.alternate_screen.unwrap_or(!basic),
+            mouse: !basic,
+            hyperlinks: config.hyperlinks.unwrap_or(!basic),
+            unicode: config
+                .unicode
+                .unwrap_or(cfg!(windows) || utf8.unwrap_or(!monochrome)),
+        }
+    }
+}
+
+pub type Tui = Terminal<CrosstermBackend<Box<dyn Write>>>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TuiOptions {
+    /// Inline viewport height, `None` for the alternate screen
+    pub inline: Option<u16>,
+    pub mouse: bool,
+    /// Draw on stderr so stdout stays free for `--print-selection`
+    pub stderr: bool,
+}
+
+pub fn run(app: &mut App, options: TuiOptions) -> Result<(), Box<dyn Error>> {
+    let mut terminal = init(options)?;
+    let result = app.run(&mut terminal);
+    if options.inline.is_some() {
+        // keep the last frame in the scrollback and continue below it
+        let bottom = terminal.get_frame().area().bottom();
+        terminal.set_cursor_position((0, bottom.saturating_sub(1)))?;
+        writeln!(terminal.backend_mut())?;
+    }
+    restore(options)?;
+    result
+}
+
+/// Drops the colors of everything drawn, keeping bold, reversed and the
+/// other modifiers a monochrome terminal shows.
+pub fn strip_colors(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        cell.set_fg(Color::Reset).set_bg(Color::Reset);
+    }
+}
+
+/// Replaces the borders and markers with ASCII for terminals without UTF-8,
+/// where they would come out garbled.
+pub fn ascii_symbols(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        let ascii = match cell.symbol().chars().next() {
+            Some('─' | '━' | '═') => "-",
+            Some('│' | '┃' | '║') => "|",
+            Some('\u{2500}'..='\u{257f}') => "+",
+            Some('▸' | '▶' | '►') => ">",
+            Some('▾' | '▼') => "v",
+            Some('█' | '▓' | '▒' | '░') => "#",
+            Some('●') => "*",
+            Some('○') => "o",
+            Some('×') => "x",
+            Some('…') => ".",
+            Some('«' | '»' | '“' | '”') => "\"",
+            _ => continue,
+        };
+        cell.set_symbol(ascii);
+    }
+}
+
+/// Turns the text drawn on the first line of `area` into an OSC 8 link to
+/// `url`, which terminals like WezTerm and Windows Terminal open on
+/// Ctrl+click. Works around ratatui taking the escape sequence for the width
+/// of the text (ratatui#902) by linking two cells at a time, like its
+/// hyperlink example.
+pub fn hyperlink(buffer: &mut Buffer, area: Rect, url: &str) {
+    let area = area.intersection(buffer.area);
+    if area.is_empty() {
+        return;
+    }
+    // an odd last cell stays unlinked, the cell after it would not be drawn
+    for x in (area.left()..area.right().saturating_sub(1)).step_by(2) {
+        let text = format!(
+            "{}{}",
+            buffer[(x, area.y)].symbol(),
+            buffer[(x + 1, area.y)].symbol()
+        );
+        let link = format!("\x1B]8;;{url}\x07{text}\x1B]8;;\x07");
+        buffer[(x, area.y)].set_symbol(&link);
+    }
+}
+
+fn writer(options: TuiOptions) -> Box<dyn Write> {
+    if options.stderr {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+fn init(options: TuiOptions) -> io::Result<Tui> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore(options);
+        hook(info);
+    }));
+
+    enable_raw_mode()?;
+    let mut writer = writer(options);
+    if options.inline.is_none() {
+        execute!(writer, EnterAlternateScreen)?;
+    }
+    if options.mouse {
+        execute!(writer, EnableMouseCapture)?;
+    }
+    // pasted text arrives as one event instead of keys that could trigger shortcuts
+    execute!(writer, EnableBracketedPaste)?;
+
+    let viewport = match options.inline {
+        Some(height) => Viewport::Inline(height),
+        None => Viewport::Fullscreen,
+    };
+    Terminal::with_options(CrosstermBackend::new(writer), TerminalOptions { viewport })
+}
+
+fn restore(options: TuiOptions) -> io::Result<()> {
+    let mut writer = writer(options);
+    execute!(writer, DisableBracketedPaste)?;
+    if options.mouse {
+        execute!(writer, DisableMouseCapture)?;
+    }
+    if options.inline.is_none() {
+        execute!(writer, LeaveAlternateScreen)?;
+    }
+    execute!(writer, Show)?;
+    disable_raw_mode()
+}
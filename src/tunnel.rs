@@ -0,0 +1,206 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{PathKind, clients};
+
+const DEFAULT_SERVER_PORT: u16 = 1541;
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the client started by the starter may take to show up.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TunnelConfig {
+    /// ssh destination of the jump host, e.g. `admin@jump.example.com`
+    pub ssh: String,
+    pub local_port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_port: Option<u16>,
+}
+
+#[derive(Debug)]
+pub struct Tunnel {
+    ssh: Child,
+    client: Option<Child>,
+    /// Connection string pointing at the local end, as the client opens it
+    path: String,
+    /// The attached process is the starter rather than the client itself
+    through_starter: bool,
+    /// When the attached process exited
+    exited: Option<Instant>,
+    /// A client of the base has been seen running after the starter
+    client_seen: bool,
+}
+
+impl Tunnel {
+    /// Starts the ssh forward for `path` and returns the tunnel together with
+    /// the path rewritten to point at the local end of it.
+    pub fn open(
+        config: &TunnelConfig,
+        path: PathKind,
+    ) -> Result<(Tunnel, PathKind), Box<dyn Error>> {
+        let (forward, rewritten) = forward(config, path)?;
+        let ssh = Command::new("ssh")
+            .args([
+                "-N",
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-L",
+                &forward,
+                "--",
+                &config.ssh,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not start ssh: {e}"))?;
+
+        let mut tunnel = Tunnel {
+            ssh,
+            client: None,
+            path: rewritten.connection_string(),
+            through_starter: false,
+            exited: None,
+            client_seen: false,
+        };
+        tunnel.wait_ready(config.local_port)?;
+        Ok((tunnel, rewritten))
+    }
+
+    /// Keeps the tunnel up for `client`, the 1C starter when
+    /// `through_starter` is set.
+    pub fn attach(&mut self, client: Child, through_starter: bool) {
+        self.client = Some(client);
+        self.through_starter = through_starter;
+    }
+
+    /// Keeps the tunnel up for the clients of the base once the process the
+    /// caller waits for itself, e.g. a client launched with `--wait`, is done.
+    pub fn follow(&mut self, through_starter: bool) {
+        self.through_starter = through_starter;
+    }
+
+    /// Blocks until the launched client exits, then tears the tunnel down.
+    /// The starter exits as soon as it has started the client, so after it
+    /// the clients that opened the base are waited for: first for one to
+    /// show up, then for all of them to be gone.
+    pub fn wait(mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(mut client) = self.client.take() {
+            client.wait()?;
+        }
+        self.exited = Some(Instant::now());
+        while !self.is_done() {
+            thread::sleep(POLL_INTERVAL);
+        }
+        Ok(())
+    }
+
+    /// Whether the launched client and, through the starter, the clients of
+    /// the base are gone, without blocking.
+    pub fn is_done(&mut self) -> bool {
+        if let Some(client) = &mut self.client {
+            if let Ok(None) = client.try_wait() {
+                return false;
+            }
+            self.client = None;
+            self.exited = Some(Instant::now());
+        }
+        if !self.through_starter {
+            return true;
+        }
+        if !clients::running(&self.path).is_empty() {
+            self.client_seen = true;
+            return false;
+        }
+        self.client_seen
+            || self
+                .exited
+                .is_none_or(|exited| exited.elapsed() >= CLIENT_TIMEOUT)
+    }
+
+    fn wait_ready(&mut self, local_port: u16) -> Result<(), Box<dyn Error>> {
+        let started = Instant::now();
+        loop {
+            if let Some(status) = self.ssh.try_wait()? {
+                return Err(format!("ssh tunnel exited early ({status})").into());
+            }
+            if TcpStream::connect(("127.0.0.1", local_port)).is_ok() {
+                return Ok(());
+            }
+            if started.elapsed() > READY_TIMEOUT {
+                return Err(
+                    format!("ssh tunnel did not open local port {local_port} in time").into(),
+                );
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let _ = self.ssh.kill();
+        let _ = self.ssh.wait();
+    }
+}
+
+/// The `-L` forward spec for `path` and the path rewritten to point at the
+/// local end of it.
+pub fn forward(
+    config: &TunnelConfig,
+    path: PathKind,
+) -> Result<(String, PathKind), Box<dyn Error>> {
+    // ssh would take a destination like `-oProxyCommand=...` for an option
+    if config.ssh.trim().is_empty() || config.ssh.starts_with('-') {
+        return Err(format!("Invalid ssh destination of the tunnel: '{}'", config.ssh).into());
+    }
+    let local = format!("localhost:{}", config.local_port);
+
+    let (remote_host, remote_port, rewritten) = match path {
+        PathKind::Server { host, ref_name } => {
+            let (host, port) = split_host_port(&host);
+            let port = config.remote_port.or(port).unwrap_or(DEFAULT_SERVER_PORT);
+            (
+                host,
+                port,
+                PathKind::Server {
+                    host: local,
+                    ref_name,
+                },
+            )
+        }
+        PathKind::Web { url } => {
+            let captures = Regex::new(r"^(\w+)://([^/]+)(.*)$")
+                .unwrap()
+                .captures(&url)
+                .ok_or(format!("Could not determine host of url: {url}"))?;
+            let scheme = captures[1].to_lowercase();
+            let (host, port) = split_host_port(&captures[2]);
+            let default_port = if scheme == "https" { 443 } else { 80 };
+            let port = config.remote_port.or(port).unwrap_or(default_port);
+            let url = format!("{scheme}://{local}{}", &captures[3]);
+            (host, port, PathKind::Web { url })
+        }
+        PathKind::File { .. } => {
+            return Err("ssh tunnel is not supported for file bases".into());
+        }
+    };
+    let forward = format!("{}:{remote_host}:{remote_port}", config.local_port);
+    Ok((forward, rewritten))
+}
+
+pub fn split_host_port(host: &str) -> (String, Option<u16>) {
+    match host.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host.to_string(), port.parse().ok()),
+        _ => (host.to_string(), None),
+    }
+}
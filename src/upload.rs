@@ -0,0 +1,492 @@
+use serde::Deserialize;
+
+use std::env;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::{locale, secrets, theme};
+
+/// Attempts at a copy or an S3 part before the upload fails.
+const ATTEMPTS: u32 = 5;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Environment variable with the secret key of S3 destinations, read before
+/// the keyring.
+const SECRET_VAR: &str = "RBASERUN_S3_SECRET";
+
+/// Where the dumps of a backup plan are copied, `[[backup.upload]]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Destination {
+    /// A directory, local or an SMB share such as `\\nas\backups\erp`
+    Dir { dir: PathBuf },
+    /// A bucket of an S3-compatible storage, addressed path-style
+    S3 {
+        /// e.g. `https://s3.eu-central-1.amazonaws.com` or a MinIO server
+        endpoint: String,
+        bucket: String,
+        /// Added before the file names, e.g. `erp/`
+        #[serde(default)]
+        prefix: String,
+        #[serde(default = "default_region")]
+        region: String,
+        /// The secret key is kept in the OS keyring, see `login`
+        access_key: String,
+    },
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl Destination {
+    fn label(&self) -> String {
+        match self {
+            Destination::Dir { dir } => dir.display().to_string(),
+            Destination::S3 {
+                endpoint, bucket, ..
+            } => format!("{}/{bucket}", endpoint.trim_end_matches('/')),
+        }
+    }
+}
+
+/// Copies `file` to `destination`. Transient failures are retried, and a
+/// copy to a directory picks up where the last attempt stopped.
+pub fn upload(file: &Path, destination: &Destination) -> Result<(), Box<dyn Error>> {
+    let result = match destination {
+        Destination::Dir { dir } => copy_resumable(file, dir),
+        Destination::S3 {
+            endpoint,
+            bucket,
+            prefix,
+            region,
+            access_key,
+        } => {
+            let name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bucket = s3::Bucket {
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                name: bucket.clone(),
+                region: region.clone(),
+                access_key: access_key.clone(),
+                secret_key: secret_key(access_key)?,
+            };
+            bucket.upload(file, &format!("{prefix}{name}"))
+        }
+    };
+    result.map_err(|e| format!("Upload to {} failed: {e}", destination.label()).into())
+}
+
+fn account(access_key: &str) -> String {
+    format!("s3 {access_key}")
+}
+
+fn secret_key(access_key: &str) -> Result<String, Box<dyn Error>> {
+    if let Ok(secret) = env::var(SECRET_VAR) {
+        return Ok(secret);
+    }
+    secrets::get(&account(access_key)).map_err(|e| {
+        format!(
+            "No secret key for {access_key}: {e}; save it with `rbaserun backup login {access_key}` or set {SECRET_VAR}"
+        )
+        .into()
+    })
+}
+
+/// Stores the secret key of S3 access key `access_key` in the OS keyring,
+/// reading it from stdin.
+pub fn login(access_key: &str) -> Result<(), Box<dyn Error>> {
+    print!("Secret key of {access_key}: ");
+    io::stdout().flush()?;
+    let mut secret = String::new();
+    io::stdin().read_line(&mut secret)?;
+    secrets::set(&account(access_key), secret.trim_end_matches(['\r', '\n']))?;
+    println!("Saved the secret key of {access_key}");
+    Ok(())
+}
+
+/// Copies `file` into `dir` through a `.part` file, renamed once complete.
+/// A failed attempt is resumed from the end of the `.part` file.
+fn copy_resumable(file: &Path, dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    let name = file.file_name().ok_or("The dump has no file name")?;
+    let dest = dir.join(name);
+    let mut part = dest.clone().into_os_string();
+    part.push(".part");
+    let part = PathBuf::from(part);
+    let size = fs::metadata(file)?.len();
+    let label = format!("Copying to {}", dir.display());
+
+    let mut attempt = 1;
+    loop {
+        match append_from(file, &part, size, &label) {
+            Ok(()) => break,
+            Err(e) if attempt < ATTEMPTS => {
+                eprintln!("{e}, retrying...");
+                thread::sleep(backoff(attempt));
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    fs::rename(&part, &dest).map_err(|e| format!("Could not rename {}: {e}", part.display()))?;
+    Ok(())
+}
+
+/// Appends to `part` what it still lacks of `file`.
+fn append_from(file: &Path, part: &Path, size: u64, label: &str) -> io::Result<()> {
+    let mut output = OpenOptions::new().create(true).append(true).open(part)?;
+    let mut copied = output.metadata()?.len();
+    if copied > size {
+        // left over from another file of the same name
+        output.set_len(0)?;
+        copied = 0;
+    }
+    let mut input = File::open(file)?;
+    input.seek(SeekFrom::Start(copied))?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buffer[..read])?;
+        copied += read as u64;
+        progress(label, copied, size);
+    }
+    output.sync_all()?;
+    progress_done(label, size);
+    Ok(())
+}
+
+/// Wait before attempt `attempt + 1`: 2, 4, 8... seconds.
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.min(6))
+}
+
+/// Redraws the progress line on stderr, unless motion is reduced.
+fn progress(label: &str, done: u64, size: u64) {
+    if theme::reduced_motion() {
+        return;
+    }
+    let percent = (done * 100).checked_div(size).unwrap_or(100);
+    eprint!(
+        "\r{label} {percent:>3}% ({} of {})",
+        locale::size(done),
+        locale::size(size)
+    );
+    let _ = io::stderr().flush();
+}
+
+fn progress_done(label: &str, size: u64) {
+    if theme::reduced_motion() {
+        eprintln!("{label}: {} done", locale::size(size));
+    } else {
+        eprintln!();
+    }
+}
+
+#[cfg(feature = "http")]
+mod s3 {
+    use chrono::Utc;
+    use sha2::{Digest, Sha256};
+    use ureq::Agent;
+
+    use std::error::Error;
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::Path;
+    use std::thread;
+
+    use super::{ATTEMPTS, backoff, progress, progress_done};
+    use crate::backup::hex;
+
+    /// Size of the parts of a multipart upload, S3 takes at most 10000.
+    const PART_SIZE: usize = 16 * 1024 * 1024;
+
+    pub struct Bucket {
+        pub endpoint: String,
+        pub name: String,
+        pub region: String,
+        pub access_key: String,
+        pub secret_key: String,
+    }
+
+    impl Bucket {
+        /// Uploads `file` as `key` in parts, each retried on its own, and
+        /// aborts the upload when a part keeps failing.
+        pub fn upload(&self, file: &Path, key: &str) -> Result<(), Box<dyn Error>> {
+            let agent: Agent = Agent::config_builder()
+                .http_status_as_error(false)
+                .build()
+                .into();
+            let size = file.metadata()?.len();
+            let label = format!("Uploading to {}/{}", self.endpoint, self.name);
+            let created = self.request(&agent, "POST", key, &[("uploads", "")], b"")?;
+            let upload_id = between(&created, "<UploadId>", "</UploadId>")
+                .ok_or("S3 did not return an upload id")?
+                .to_string();
+            let id = [("uploadId", upload_id.as_str())];
+
+            let result = (|| -> Result<(), Box<dyn Error>> {
+                let mut input = File::open(file)?;
+                let mut buffer = vec![0; PART_SIZE];
+                let mut parts = String::new();
+                let mut sent = 0;
+                for number in 1.. {
+                    let read = read_full(&mut input, &mut buffer)?;
+                    if read == 0 && number > 1 {
+                        break;
+                    }
+                    let number = number.to_string();
+                    let query = [("partNumber", number.as_str()), id[0]];
+                    let etag =
+                        self.with_retries(|| self.put_part(&agent, key, &query, &buffer[..read]))?;
+                    parts.push_str(&format!(
+                        "<Part><PartNumber>{number}</PartNumber><ETag>{etag}</ETag></Part>"
+                    ));
+                    sent += read as u64;
+                    progress(&label, sent, size);
+                    if read < PART_SIZE {
+                        break;
+                    }
+                }
+                let body = format!("<CompleteMultipartUpload>{parts}</CompleteMultipartUpload>");
+                let completed =
+                    self.with_retries(|| self.request(&agent, "POST", key, &id, body.as_bytes()))?;
+                // a failure after the parts arrived comes with status 200
+                if completed.contains("<Error>") {
+                    return Err(between(&completed, "<Message>", "</Message>")
+                        .unwrap_or("S3 did not complete the upload")
+                        .into());
+                }
+                progress_done(&label, size);
+                Ok(())
+            })();
+            if result.is_err() {
+                let _ = self.request(&agent, "DELETE", key, &id, b"");
+            }
+            result
+        }
+
+        fn with_retries<T>(
+            &self,
+            mut call: impl FnMut() -> Result<T, Box<dyn Error>>,
+        ) -> Result<T, Box<dyn Error>> {
+            let mut attempt = 1;
+            loop {
+                match call() {
+                    Ok(value) => return Ok(value),
+                    Err(e) if attempt < ATTEMPTS => {
+                        eprintln!("\n{e}, retrying...");
+                        thread::sleep(backoff(attempt));
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        fn put_part(
+            &self,
+            agent: &Agent,
+            key: &str,
+            query: &[(&str, &str)],
+            body: &[u8],
+        ) -> Result<String, Box<dyn Error>> {
+            let (url, headers) = self.sign("PUT", key, query, body);
+            let mut request = agent.put(&url);
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+            let response = request.send(body)?;
+            if !response.status().is_success() {
+                return Err(
+                    format!("S3 answered {} to part {}", response.status(), query[0].1).into(),
+                );
+            }
+            Ok(response
+                .headers()
+                .get("ETag")
+                .and_then(|etag| etag.to_str().ok())
+                .ok_or("S3 returned no ETag for a part")?
+                .to_string())
+        }
+
+        /// Sends a request without a large body and returns the response body.
+        fn request(
+            &self,
+            agent: &Agent,
+            method: &str,
+            key: &str,
+            query: &[(&str, &str)],
+            body: &[u8],
+        ) -> Result<String, Box<dyn Error>> {
+            let (url, headers) = self.sign(method, key, query, body);
+            let mut response = match method {
+                "POST" => {
+                    let mut request = agent.post(&url);
+                    for (name, value) in &headers {
+                        request = request.header(name, value);
+                    }
+                    request.send(body)?
+                }
+                _ => {
+                    let mut request = agent.delete(&url);
+                    for (name, value) in &headers {
+                        request = request.header(name, value);
+                    }
+                    request.call()?
+                }
+            };
+            let status = response.status();
+            let text = response.body_mut().read_to_string().unwrap_or_default();
+            if !status.is_success() {
+                let message = between(&text, "<Message>", "</Message>").unwrap_or_default();
+                return Err(format!("S3 answered {status} {message}").trim_end().into());
+            }
+            Ok(text)
+        }
+
+        /// URL and headers of a request signed with AWS Signature Version 4.
+        fn sign(
+            &self,
+            method: &str,
+            key: &str,
+            query: &[(&str, &str)],
+            body: &[u8],
+        ) -> (String, Vec<(String, String)>) {
+            let now = Utc::now();
+            let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+            let date = now.format("%Y%m%d").to_string();
+            let host = self
+                .endpoint
+                .split_once("://")
+                .map_or(self.endpoint.as_str(), |(_, host)| host)
+                .to_string();
+            let path = format!("/{}/{}", encode(&self.name, false), encode(key, true));
+            let mut query: Vec<(String, String)> = query
+                .iter()
+                .map(|(name, value)| (encode(name, false), encode(value, false)))
+                .collect();
+            query.sort();
+            let query = query
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join("&");
+            let payload = hex(&Sha256::digest(body));
+
+            let canonical = format!(
+                "{method}\n{path}\n{query}\nhost:{host}\nx-amz-content-sha256:{payload}\nx-amz-date:{stamp}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload}"
+            );
+            let scope = format!("{date}/{}/s3/aws4_request", self.region);
+            let to_sign = format!(
+                "AWS4-HMAC-SHA256\n{stamp}\n{scope}\n{}",
+                hex(&Sha256::digest(canonical.as_bytes()))
+            );
+            let mut signing = hmac(
+                format!("AWS4{}", self.secret_key).as_bytes(),
+                date.as_bytes(),
+            );
+            for part in [self.region.as_str(), "s3", "aws4_request"] {
+                signing = hmac(&signing, part.as_bytes());
+            }
+            let signature = hex(&hmac(&signing, to_sign.as_bytes()));
+
+            let url = format!("{}{path}?{query}", self.endpoint);
+            let headers = vec![
+                ("x-amz-date".to_string(), stamp),
+                ("x-amz-content-sha256".to_string(), payload),
+                (
+                    "Authorization".to_string(),
+                    format!(
+                        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+                        self.access_key
+                    ),
+                ),
+            ];
+            (url, headers)
+        }
+    }
+
+    fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+        const BLOCK: usize = 64;
+        let mut block = [0u8; BLOCK];
+        if key.len() > BLOCK {
+            block[..32].copy_from_slice(&Sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+        let inner = Sha256::new()
+            .chain_update(pad(0x36))
+            .chain_update(message)
+            .finalize();
+        Sha256::new()
+            .chain_update(pad(0x5c))
+            .chain_update(inner)
+            .finalize()
+            .to_vec()
+    }
+
+    /// URI encoding of S3, keeping `/` in keys.
+    fn encode(text: &str, keep_slash: bool) -> String {
+        text.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (byte as char).to_string()
+                }
+                b'/' if keep_slash => "/".to_string(),
+                _ => format!("%{byte:02X}"),
+            })
+            .collect()
+    }
+
+    fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+        let from = text.find(start)? + start.len();
+        let to = text[from..].find(end)? + from;
+        Some(&text[from..to])
+    }
+
+    /// Fills `buffer` unless the file ends first, returns the bytes read.
+    fn read_full(input: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match input.read(&mut buffer[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        Ok(filled)
+    }
+}
+
+/// Without the `http` feature dumps can only be copied to directories.
+#[cfg(not(feature = "http"))]
+mod s3 {
+    use std::error::Error;
+    use std::path::Path;
+
+    #[allow(dead_code)]
+    pub struct Bucket {
+        pub endpoint: String,
+        pub name: String,
+        pub region: String,
+        pub access_key: String,
+        pub secret_key: String,
+    }
+
+    impl Bucket {
+        pub fn upload(&self, _file: &Path, _key: &str) -> Result<(), Box<dyn Error>> {
+            Err("rbaserun was built without the http feature".into())
+        }
+    }
+}
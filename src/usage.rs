@@ -0,0 +1,90 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use std::error::Error;
+
+use crate::config::{LicenseConfig, RasConfig};
+use crate::licenses::{self, Usage};
+use crate::{keys, popup, theme};
+
+pub enum UsageAction {
+    None,
+    Close,
+}
+
+/// License usage on the cluster of the current base, shown with Ctrl+Q to
+/// answer "are there free licenses?" before launching.
+#[derive(Debug)]
+pub struct UsagePanel {
+    usage: Usage,
+    licenses: Option<LicenseConfig>,
+    ras: RasConfig,
+    error: Option<String>,
+}
+
+impl UsagePanel {
+    pub fn new(
+        host: &str,
+        licenses: Option<&LicenseConfig>,
+        ras: &RasConfig,
+    ) -> Result<UsagePanel, Box<dyn Error>> {
+        Ok(UsagePanel {
+            usage: licenses::usage(host, licenses, ras)?,
+            licenses: licenses.cloned(),
+            ras: ras.clone(),
+            error: None,
+        })
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> UsageAction {
+        let Event::Key(key_event) = event else {
+            return UsageAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return UsageAction::None;
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match keys::binding(key_event.code) {
+            KeyCode::Esc | KeyCode::Enter => return UsageAction::Close,
+            KeyCode::Char('q') if ctrl => return UsageAction::Close,
+            KeyCode::Char('r') => {
+                match licenses::usage(&self.usage.host, self.licenses.as_ref(), &self.ras) {
+                    Ok(usage) => {
+                        self.usage = usage;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+            _ => {}
+        }
+        UsageAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let summary = self.usage.lines();
+        let area = popup::area(frame.area(), summary.len() as u16 + 4);
+        let mut lines: Vec<Line> = summary
+            .into_iter()
+            .map(|(line, reached)| {
+                if reached {
+                    Line::styled(line, theme::error())
+                } else {
+                    Line::from(line)
+                }
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(match &self.error {
+            Some(error) => Span::styled(error.as_str(), theme::error()).into(),
+            None => Span::styled("r: refresh, Esc: close", theme::hint()).into(),
+        });
+        let block = Block::bordered().title(format!("Licenses on {}", self.usage.host));
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
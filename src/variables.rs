@@ -0,0 +1,126 @@
+use ratatui::{
+    Frame,
+    crossterm::event::{Event, KeyCode, KeyEventKind},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+use std::collections::BTreeMap;
+
+use crate::{LaunchMode, popup, theme};
+
+pub enum VariablesAction {
+    None,
+    Apply,
+    Cancel,
+}
+
+/// Popup asking for the values of the `{name}` placeholders of a base before
+/// it is launched, see `placeholders`.
+#[derive(Debug)]
+pub struct VariablesPanel {
+    pub path: String,
+    pub mode: LaunchMode,
+    fields: Vec<(String, Input)>,
+    focus: usize,
+}
+
+impl VariablesPanel {
+    /// Fields for `names`, filled with the values of the previous launch.
+    pub fn new(
+        path: String,
+        mode: LaunchMode,
+        names: Vec<String>,
+        values: &BTreeMap<String, String>,
+    ) -> VariablesPanel {
+        let fields = names
+            .into_iter()
+            .map(|name| {
+                let value = values.get(&name).cloned().unwrap_or_default();
+                (name, value.into())
+            })
+            .collect();
+        VariablesPanel {
+            path,
+            mode,
+            fields,
+            focus: 0,
+        }
+    }
+
+    /// The first placeholder left empty.
+    pub fn missing(&self) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(_, input)| input.value().trim().is_empty())
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn values(&self) -> BTreeMap<String, String> {
+        self.fields
+            .iter()
+            .map(|(name, input)| (name.clone(), input.value().trim().to_string()))
+            .collect()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> VariablesAction {
+        let Event::Key(key_event) = event else {
+            return VariablesAction::None;
+        };
+        if key_event.kind != KeyEventKind::Press {
+            return VariablesAction::None;
+        }
+        let count = self.fields.len();
+        match key_event.code {
+            KeyCode::Esc => return VariablesAction::Cancel,
+            KeyCode::Enter => return VariablesAction::Apply,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % count,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + count - 1) % count,
+            _ => {
+                self.fields[self.focus].1.handle_event(event);
+            }
+        }
+        VariablesAction::None
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let area = popup::area(frame.area(), self.fields.len() as u16 + 4);
+        let width = self
+            .fields
+            .iter()
+            .map(|(name, _)| name.chars().count() + 2)
+            .max()
+            .unwrap_or_default();
+        let mut lines: Vec<Line> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, (name, input))| {
+                let line = Line::from(format!("{:<width$}{}", format!("{name}:"), input.value()));
+                if index == self.focus {
+                    line.bold()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(
+            Span::styled("Enter: launch, Esc: cancel, Tab: next field", theme::hint()).into(),
+        );
+
+        let block = Block::bordered().title(format!("Values: {}", self.path.trim()));
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+
+        let cursor = self.fields[self.focus].1.visual_cursor();
+        frame.set_cursor_position((
+            inner.x + (width + cursor) as u16,
+            inner.y + self.focus as u16,
+        ));
+    }
+}
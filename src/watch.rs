@@ -0,0 +1,206 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use chrono::Local;
+
+use crate::report::Report;
+use crate::tunnel::Tunnel;
+use crate::{LaunchMode, history};
+
+/// What a launch leaves to wait for: the ssh tunnel of the base, and the
+/// client itself when launched with `--wait`.
+#[derive(Debug, Default)]
+pub struct Launched {
+    pub tunnel: Option<Tunnel>,
+    pub watched: Option<Watched>,
+    /// What was started, `None` when nothing was, e.g. with `--dry-run`
+    pub report: Option<Report>,
+}
+
+impl Launched {
+    /// Nothing to wait for, the client runs on its own.
+    pub fn is_detached(&self) -> bool {
+        self.tunnel.is_none() && self.watched.is_none()
+    }
+
+    /// What the caller is about to wait for, printed before `wait`.
+    pub fn waiting(&self) -> Option<&'static str> {
+        if self.tunnel.is_some() {
+            Some("Tunnel is open, waiting for the client to exit...")
+        } else if self.watched.is_some() {
+            Some("Waiting for the client to exit...")
+        } else {
+            None
+        }
+    }
+
+    /// The outcome of the watched client once it has exited, without blocking.
+    pub fn try_finish(&mut self) -> Result<Option<Outcome>, Box<dyn Error>> {
+        let Some(watched) = &mut self.watched else {
+            return Ok(None);
+        };
+        let result = watched.try_finish();
+        if !matches!(result, Ok(None)) {
+            self.watched = None;
+        }
+        result
+    }
+
+    /// Whether the watched client has been finished and the tunnel is no
+    /// longer needed, without blocking.
+    pub fn is_done(&mut self) -> bool {
+        self.watched.is_none() && self.tunnel.as_mut().is_none_or(Tunnel::is_done)
+    }
+
+    /// Blocks until the client exits, then closes the tunnel once the
+    /// clients of the base are gone too. A watched client that failed becomes
+    /// the error.
+    pub fn wait(self) -> Result<(), Box<dyn Error>> {
+        if let Some(watched) = self.watched {
+            watched.wait()?.result()?;
+        }
+        if let Some(tunnel) = self.tunnel {
+            tunnel.wait()?;
+        }
+        Ok(())
+    }
+}
+
+/// A client launched with `--wait`, its stderr and `/Out` log collected
+/// until it exits.
+#[derive(Debug)]
+pub struct Watched {
+    pub path: String,
+    pub mode: LaunchMode,
+    pub started: Instant,
+    child: Child,
+    stderr: Option<JoinHandle<String>>,
+    log: PathBuf,
+}
+
+impl Watched {
+    /// Log file passed to the client with `/Out` for a launch of `path`.
+    pub fn log_file(path: &str) -> PathBuf {
+        let name: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(40)
+            .collect();
+        env::temp_dir().join(format!(
+            "rbaserun-wait-{name}-{}.log",
+            Local::now().format("%Y%m%d-%H%M%S%3f")
+        ))
+    }
+
+    /// Starts collecting the stderr of `child`, spawned with it piped.
+    pub fn new(path: &str, mode: LaunchMode, mut child: Child, log: PathBuf) -> Watched {
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut bytes = Vec::new();
+                let _ = stderr.read_to_end(&mut bytes);
+                String::from_utf8_lossy(&bytes).trim().to_string()
+            })
+        });
+        Watched {
+            path: path.to_string(),
+            mode,
+            started: Instant::now(),
+            child,
+            stderr,
+            log,
+        }
+    }
+
+    /// The outcome once the client has exited, without blocking.
+    pub fn try_finish(&mut self) -> Result<Option<Outcome>, Box<dyn Error>> {
+        match self.child.try_wait()? {
+            Some(status) => Ok(Some(self.finish(status))),
+            None => Ok(None),
+        }
+    }
+
+    /// Blocks until the client exits.
+    pub fn wait(mut self) -> Result<Outcome, Box<dyn Error>> {
+        let status = self.child.wait()?;
+        Ok(self.finish(status))
+    }
+
+    /// Collects the output of the exited client and records the outcome in
+    /// the history.
+    fn finish(&mut self, status: ExitStatus) -> Outcome {
+        let stderr = self
+            .stderr
+            .take()
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        let log = fs::read(&self.log)
+            .map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .trim_start_matches('\u{feff}')
+                    .trim()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let _ = fs::remove_file(&self.log);
+        // one line, for the status line of the picker and the history
+        let output = [log, stderr]
+            .iter()
+            .flat_map(|text| text.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let outcome = Outcome {
+            path: self.path.clone(),
+            code: status.code(),
+            output: (!output.is_empty()).then_some(output),
+        };
+        let _ = history::record_outcome(&self.path, outcome.code, outcome.failure());
+        outcome
+    }
+}
+
+/// How a watched client exited.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub path: String,
+    /// `None` when the client was killed by a signal
+    pub code: Option<i32>,
+    /// The `/Out` log and stderr of the client, joined into one line
+    pub output: Option<String>,
+}
+
+impl Outcome {
+    pub fn succeeded(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// What went wrong, `None` for a clean exit.
+    pub fn failure(&self) -> Option<String> {
+        if self.succeeded() {
+            return None;
+        }
+        let status = match self.code {
+            Some(code) => format!("exited with {code}"),
+            None => "was killed".to_string(),
+        };
+        Some(match &self.output {
+            Some(output) => format!("{status}: {output}"),
+            None => status,
+        })
+    }
+
+    /// Fails with the output of the client unless it exited cleanly.
+    pub fn result(&self) -> Result<(), Box<dyn Error>> {
+        match self.failure() {
+            Some(failure) => Err(format!("{} {failure}", self.path.trim()).into()),
+            None => Ok(()),
+        }
+    }
+}
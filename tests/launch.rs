@@ -0,0 +1,714 @@
+//! End-to-end tests of the command lines `rbaserun launch` passes to the
+//! starter, recorded by `examples/fake_starter`.
+
+// kiosk builds launch everything in Enterprise mode and reject most flags
+#![cfg(not(feature = "kiosk"))]
+
+mod support;
+
+use support::{Fake1C, fake_starter};
+
+#[test]
+fn server_base() {
+    let env = Fake1C::new("server_base", "");
+    assert_eq!(
+        env.launch(&["launch", r#"Srvr="srv";Ref="erp";"#]),
+        ["ENTERPRISE", "/S", r"srv\erp"]
+    );
+}
+
+#[test]
+fn server_base_with_port() {
+    let env = Fake1C::new("server_base_with_port", "");
+    assert_eq!(
+        env.launch(&["launch", r#"Srvr="srv:2541";Ref="erp";"#]),
+        ["ENTERPRISE", "/S", r"srv:2541\erp"]
+    );
+}
+
+#[test]
+fn file_base() {
+    let env = Fake1C::new("file_base", "");
+    assert_eq!(
+        env.launch(&["launch", r#"File="/srv/bases/demo";"#]),
+        ["ENTERPRISE", "/F", "/srv/bases/demo"]
+    );
+}
+
+#[test]
+fn web_base() {
+    let env = Fake1C::new("web_base", "");
+    assert_eq!(
+        env.launch(&["launch", r#"ws="http://web/demo";"#]),
+        ["ENTERPRISE", "/WS", "http://web/demo"]
+    );
+}
+
+#[test]
+fn web_base_in_browser_profile() {
+    let env = Fake1C::new(
+        "web_base_in_browser_profile",
+        "[browser]\nprofile = 'Work'\nurl_path = 'ru_RU/'",
+    );
+    let browser = fake_starter();
+    assert_eq!(
+        env.launch(&[
+            "launch",
+            "--browser",
+            browser.to_str().unwrap(),
+            r#"ws="http://web/demo";"#
+        ]),
+        ["--profile-directory=Work", "http://web/demo/ru_RU/"]
+    );
+}
+
+#[test]
+fn designer() {
+    let env = Fake1C::new("designer", "");
+    assert_eq!(
+        env.launch(&["launch", "--designer", r#"Srvr="srv";Ref="erp";"#]),
+        ["DESIGNER", "/S", r"srv\erp"]
+    );
+}
+
+#[test]
+fn thick_client_by_default() {
+    let env = Fake1C::new("thick_client_by_default", r#"default_mode = "thick""#);
+    assert_eq!(
+        env.launch(&["launch", r#"File="/srv/bases/demo";"#]),
+        [
+            "ENTERPRISE",
+            "/F",
+            "/srv/bases/demo",
+            "/RunModeOrdinaryApplication"
+        ]
+    );
+}
+
+#[test]
+fn credentials() {
+    let env = Fake1C::new("credentials", "");
+    assert_eq!(
+        env.launch(&[
+            "launch",
+            "--user",
+            "Admin",
+            "--password",
+            "secret",
+            r#"Srvr="srv";Ref="erp";"#
+        ]),
+        [
+            "ENTERPRISE",
+            "/S",
+            r"srv\erp",
+            "/N",
+            "Admin",
+            "/P",
+            "secret"
+        ]
+    );
+}
+
+#[test]
+fn extra_switches() {
+    let env = Fake1C::new("extra_switches", "");
+    assert_eq!(
+        env.launch(&["launch", r#"Srvr="srv";Ref="erp";"#, "--", "/L", "en"]),
+        ["ENTERPRISE", "/S", r"srv\erp", "/L", "en"]
+    );
+}
+
+#[test]
+fn alias() {
+    let env = Fake1C::new("alias", "");
+    let added = env.run(&["add", "erp", r#"Srvr="srv";Ref="erp";"#]);
+    assert!(added.status.success());
+    assert_eq!(
+        env.launch(&["launch", "erp"]),
+        ["ENTERPRISE", "/S", r"srv\erp"]
+    );
+}
+
+#[test]
+fn dry_run_launches_nothing() {
+    let env = Fake1C::new("dry_run_launches_nothing", "");
+    let output = env.run(&["launch", "--dry-run", r#"Srvr="srv";Ref="erp";"#]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(r"/S srv\erp"));
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn report_masks_the_password() {
+    let env = Fake1C::new("report_masks_the_password", "");
+    let output = env.run(&[
+        "launch",
+        "--report",
+        "--user",
+        "Admin",
+        "--password",
+        "secret",
+        r#"Srvr="srv";Ref="erp";"#,
+    ]);
+    assert!(output.status.success());
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(report.contains("Mode:    Enterprise"));
+    assert!(report.contains(r"/S srv\erp /N Admin /P ****"));
+    assert!(report.contains("PID:"));
+    assert!(!report.contains("secret"));
+    env.launches(1);
+}
+
+#[test]
+fn failed_client_with_wait() {
+    let env = Fake1C::new("failed_client_with_wait", "");
+    let output = env
+        .rbaserun(&["launch", "--wait", r#"Srvr="srv";Ref="erp";"#])
+        .env("FAKE_STARTER_EXIT", "3")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exited with 3"));
+    let args = env.launches(1).pop().unwrap();
+    assert_eq!(args[..4], ["ENTERPRISE", "/S", r"srv\erp", "/Out"]);
+}
+
+#[test]
+fn snapshot_launches_a_copy_and_deletes_it() {
+    let env = Fake1C::new("snapshot_launches_a_copy_and_deletes_it", "");
+    let base = env.dir().join("prod");
+    let tmp = env.dir().join("tmp");
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::create_dir_all(&tmp).unwrap();
+    std::fs::write(base.join("1Cv8.1CD"), "database").unwrap();
+    let path = format!("File=\"{}\";", base.display());
+
+    let output = env
+        .rbaserun(&["launch", "--snapshot", "--wait", &path])
+        .env("TMPDIR", &tmp)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let args = env.launches(1).pop().unwrap();
+    assert_eq!(args[..2], ["ENTERPRISE", "/F"]);
+    assert!(args[2].contains("rbaserun-snapshot-prod-"), "{args:?}");
+    assert_eq!(std::fs::read_dir(&tmp).unwrap().count(), 0);
+    assert!(base.join("1Cv8.1CD").exists());
+}
+
+#[test]
+fn repair_takes_only_file_bases() {
+    let env = Fake1C::new("repair_takes_only_file_bases", "");
+    let output = env.run(&["repair", "--test-only", r#"Srvr="srv";Ref="erp";"#]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Only file bases can be checked"), "{stderr}");
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn organize_names_the_bases_of_the_history() {
+    use std::io::Write;
+
+    let env = Fake1C::new("organize_names_the_bases_of_the_history", "");
+    env.launch(&["launch", r#"Srvr="srv1";Ref="erp";"#]);
+    env.launch(&["launch", r#"Srvr="srv2";Ref="erp";"#]);
+    let mut organize = env
+        .rbaserun(&["organize"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // accepts the first proposal with tags, renames the second
+    organize
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"\nprod, finance\nerp-test\n\n")
+        .unwrap();
+    let output = organize.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Name [erp (srv"), "{stdout}");
+
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    let bases = std::fs::read_to_string(&file).unwrap();
+    assert!(bases.contains("name = \"erp (srv"), "{bases}");
+    assert!(bases.contains("name = \"erp-test\""), "{bases}");
+    assert!(bases.contains("\"prod\""), "{bases}");
+    assert!(bases.contains("\"finance\""), "{bases}");
+    assert!(env.run(&["organize"]).stdout.starts_with(b"Every base"));
+}
+
+#[test]
+fn organize_keeps_a_protected_prod_tag() {
+    use std::io::Write;
+
+    let env = Fake1C::new("organize_keeps_a_protected_prod_tag", "");
+    env.launch(&["launch", r#"Srvr="srv";Ref="erp";"#]);
+    let organize = |args: &[&str], input: &[u8], protect: &str| {
+        let mut organize = env
+            .rbaserun(args)
+            .env("RBASERUN_PROTECT_PROD", protect)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        organize.stdin.take().unwrap().write_all(input).unwrap();
+        assert!(organize.wait_with_output().unwrap().status.success());
+    };
+    organize(&["organize"], b"\nprod\n", "0");
+    // removing every tag keeps the prod one
+    organize(&["organize", "--all"], b"\n-\n", "1");
+
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    let bases = std::fs::read_to_string(&file).unwrap();
+    assert!(bases.contains("\"prod\""), "{bases}");
+}
+
+#[test]
+fn store_restore_brings_back_a_removed_base() {
+    let env = Fake1C::new("store_restore_brings_back_a_removed_base", "");
+    assert!(env.run(&["add", "erp", "srv;erp"]).status.success());
+    assert!(env.run(&["rm", "erp"]).status.success());
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    assert!(!std::fs::read_to_string(&file).unwrap().contains("erp"));
+
+    let listed = env.run(&["store", "restore"]);
+    let listed = String::from_utf8_lossy(&listed.stdout);
+    let backup = listed
+        .lines()
+        .find(|line| line.starts_with("rbaserun_bases.toml."))
+        .expect("the bases file is backed up");
+    let output = env.run(&["store", "restore", "--from", backup]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(
+        std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("alias = \"erp\"")
+    );
+}
+
+#[test]
+fn invalid_path_launches_nothing() {
+    let env = Fake1C::new("invalid_path_launches_nothing", "");
+    let output = env.run(&["launch", "--quiet", r#"Srvr="srv";"#]);
+    assert!(!output.status.success());
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn concurrent_launches_keep_history() {
+    let env = Fake1C::new("concurrent_launches_keep_history", "");
+    let paths: Vec<String> = (0..8)
+        .map(|index| format!(r#"Srvr="srv";Ref="base{index}";"#))
+        .collect();
+    let children: Vec<_> = paths
+        .iter()
+        .map(|path| env.rbaserun(&["launch", path]).spawn().unwrap())
+        .collect();
+    for child in children {
+        assert!(child.wait_with_output().unwrap().status.success());
+    }
+    let listed = String::from_utf8_lossy(&env.run(&["list"]).stdout).into_owned();
+    for path in &paths {
+        assert!(
+            listed.contains(path.as_str()),
+            "{path} is missing from {listed}"
+        );
+    }
+}
+
+#[test]
+fn placeholders() {
+    let env = Fake1C::new("placeholders", "");
+    let template = r#"Srvr="srv-{env}";Ref="hr_{env}";"#;
+    assert_eq!(
+        env.launch(&["launch", "--var", "env=test", template]),
+        ["ENTERPRISE", "/S", r"srv-test\hr_test"]
+    );
+    let listed = env.run(&["list"]);
+    assert!(String::from_utf8_lossy(&listed.stdout).contains(template));
+}
+
+#[test]
+fn prompt_and_env_placeholders() {
+    let env = Fake1C::new("prompt_and_env_placeholders", "");
+    let output = env
+        .rbaserun(&[
+            "launch",
+            "--var",
+            "Client code=c42",
+            r#"Srvr="srv";Ref="{prompt:Client code}_{env:RBASERUN_TEST_DEPT}";"#,
+        ])
+        .env("RBASERUN_TEST_DEPT", "sales")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        env.launches(1).pop().unwrap(),
+        ["ENTERPRISE", "/S", r"srv\c42_sales"]
+    );
+}
+
+#[test]
+fn placeholder_without_value_launches_nothing() {
+    let env = Fake1C::new("placeholder_without_value_launches_nothing", "");
+    let output = env.run(&["launch", r#"Srvr="srv-{env}";Ref="hr";"#]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--var env=VALUE"));
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn failed_before_hook_launches_nothing() {
+    let env = Fake1C::new(
+        "failed_before_hook_launches_nothing",
+        "[hooks]\nbefore = ['echo no vpn>&2 && exit 1']",
+    );
+    let output = env.run(&["launch", r#"Srvr="srv";Ref="erp";"#]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed: no vpn"));
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn unmet_requirements_launch_nothing() {
+    let env = Fake1C::new("unmet_requirements_launch_nothing", "");
+    assert!(env.run(&["add", "erp", "srv;erp"]).status.success());
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    let bases = std::fs::read_to_string(&file).unwrap().replace(
+        "alias = \"erp\"",
+        "alias = \"erp\"\nrequires = { paths = ['/no/such/share'], hint = 'Mount the share first' }",
+    );
+    std::fs::write(&file, bases).unwrap();
+
+    let output = env.run(&["launch", "erp"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("/no/such/share is not available"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Mount the share first"), "{stderr}");
+    assert!(env.launches(0).is_empty());
+}
+
+#[test]
+fn plugin_on_path_runs_as_a_command() {
+    let env = Fake1C::new("plugin_on_path_runs_as_a_command", "");
+    let bin = env.dir().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let plugin = bin.join(format!("rbaserun-deploy{}", std::env::consts::EXE_SUFFIX));
+    std::fs::copy(fake_starter(), &plugin).unwrap();
+    let output = env
+        .rbaserun(&["deploy", "--to", "prod"])
+        .env("PATH", &bin)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(env.launches(1), [["--to", "prod"]]);
+}
+
+#[test]
+fn backup_verify_detects_a_changed_dump() {
+    let env = Fake1C::new("backup_verify_detects_a_changed_dump", "");
+    let dump = env.dir().join("erp.dt");
+    std::fs::write(&dump, "abc").unwrap();
+    std::fs::write(
+        env.dir().join("erp.dt.manifest.toml"),
+        r#"base = 'Srvr="srv";Ref="erp";'
+created = "2026-10-16T09:00:00+03:00"
+platform = "8.3.24.1467"
+size = 3
+sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+"#,
+    )
+    .unwrap();
+    assert!(env.run(&["backup", "verify", "erp.dt"]).status.success());
+
+    std::fs::write(&dump, "abd").unwrap();
+    assert!(!env.run(&["backup", "verify", "erp.dt"]).status.success());
+}
+
+#[test]
+fn backup_run_waits_for_the_window_of_the_plan() {
+    let hour = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / 3600;
+    let env = Fake1C::new(
+        "backup_run_waits_for_the_window_of_the_plan",
+        &format!(
+            "[[backup]]\nname = 'erp'\nbase = 'srv;erp'\ndir = 'dumps'\n\
+             window = {{ hours = '{:02}:00-{:02}:00', time_zone = 'UTC', reason = 'backup' }}",
+            (hour + 2) % 24,
+            (hour + 3) % 24
+        ),
+    );
+    let output = env.run(&["backup", "run", "erp"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("only runs within backup"), "{stderr}");
+    assert!(stderr.contains("UTC"), "{stderr}");
+    assert!(!env.dir().join("dumps").exists());
+}
+
+#[test]
+fn backup_prune_keeps_the_newest_dumps() {
+    let env = Fake1C::new(
+        "backup_prune_keeps_the_newest_dumps",
+        "[[backup]]\nname = 'erp'\nbase = 'srv;erp'\ndir = 'dumps'\nkeep_last = 2",
+    );
+    let dumps = env.dir().join("dumps");
+    std::fs::create_dir_all(&dumps).unwrap();
+    let names = [
+        "erp-20261001-090000.dt",
+        "erp-20261008-090000.dt",
+        "erp-20261015-090000.dt",
+        "other-20261001-090000.dt",
+    ];
+    for name in names {
+        std::fs::write(dumps.join(name), "dt").unwrap();
+    }
+
+    let output = env.run(&["backup", "prune", "erp", "--dry-run"]);
+    assert!(output.status.success());
+    let listed = String::from_utf8_lossy(&output.stdout);
+    assert!(listed.contains("erp-20261001-090000.dt"));
+    assert!(!listed.contains("erp-20261008-090000.dt"));
+    assert!(dumps.join(names[0]).exists());
+
+    assert!(env.run(&["backup", "prune", "erp"]).status.success());
+    let left: Vec<bool> = names.iter().map(|name| dumps.join(name).exists()).collect();
+    assert_eq!(left, [false, true, true, true]);
+}
+
+#[test]
+fn backup_upload_resumes_a_partial_copy() {
+    let env = Fake1C::new(
+        "backup_upload_resumes_a_partial_copy",
+        "[[backup]]\nname = 'erp'\nbase = 'srv;erp'\ndir = 'dumps'\n\n[[backup.upload]]\ndir = 'share'",
+    );
+    let dumps = env.dir().join("dumps");
+    let share = env.dir().join("share");
+    std::fs::create_dir_all(&dumps).unwrap();
+    std::fs::create_dir_all(&share).unwrap();
+    std::fs::write(dumps.join("erp-20261001-090000.dt"), "old").unwrap();
+    std::fs::write(dumps.join("erp-20261015-090000.dt"), "full dump").unwrap();
+    std::fs::write(share.join("erp-20261015-090000.dt.part"), "full").unwrap();
+
+    let output = env.run(&["backup", "upload", "erp"]);
+    assert!(output.status.success(), "{output:?}");
+    let copied = std::fs::read_to_string(share.join("erp-20261015-090000.dt")).unwrap();
+    assert_eq!(copied, "full dump");
+    assert!(!share.join("erp-20261015-090000.dt.part").exists());
+    assert!(!share.join("erp-20261001-090000.dt").exists());
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn backup_notify_posts_to_the_webhook() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        String::from_utf8(body).unwrap()
+    });
+
+    let env = Fake1C::new(
+        "backup_notify_posts_to_the_webhook",
+        &format!("[notify]\nwebhook = 'http://127.0.0.1:{port}/hook'\nchat_id = '42'"),
+    );
+    let output = env.run(&["backup", "notify"]);
+    assert!(output.status.success(), "{output:?}");
+    let body = server.join().unwrap();
+    assert!(
+        body.contains(r#""text":"Test notification of rbaserun"#),
+        "{body}"
+    );
+    assert!(body.contains(r#""chat_id":"42""#), "{body}");
+}
+
+#[test]
+fn serve_reports_backup_metrics() {
+    use std::io::{Read, Write};
+
+    let env = Fake1C::new(
+        "serve_reports_backup_metrics",
+        "[[backup]]\nname = 'erp'\nbase = 'srv;erp'\ndir = 'dumps'",
+    );
+    let dumps = env.dir().join("dumps");
+    std::fs::create_dir_all(&dumps).unwrap();
+    std::fs::write(dumps.join("erp-20261014-090000.dt"), "older").unwrap();
+    std::fs::write(dumps.join("erp-20261015-090000.dt"), "dump").unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{port}");
+    let mut server = env
+        .rbaserun(&["serve", "--http", &addr, "--token", "t"])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = std::time::Instant::now();
+    let mut stream = loop {
+        match std::net::TcpStream::connect(&addr) {
+            Ok(stream) => break stream,
+            Err(_) if started.elapsed().as_secs() < 10 => {
+                std::thread::sleep(std::time::Duration::from_millis(50))
+            }
+            Err(e) => panic!("serve did not listen: {e}"),
+        }
+    };
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(response.contains("text/plain"), "{response}");
+    assert!(
+        response.contains("rbaserun_backup_dumps{plan=\"erp\"} 2"),
+        "{response}"
+    );
+    assert!(
+        response.contains("rbaserun_backup_dumps_bytes{plan=\"erp\"} 9"),
+        "{response}"
+    );
+    assert!(
+        response.contains("rbaserun_backup_last_size_bytes{plan=\"erp\"} 4"),
+        "{response}"
+    );
+}
+
+#[test]
+fn serve_needs_the_token_for_status() {
+    use std::io::{Read, Write};
+
+    let env = Fake1C::new("serve_needs_the_token_for_status", "");
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{port}");
+    let mut server = env
+        .rbaserun(&["serve", "--http", &addr, "--token", "t"])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let request = |head: &str| {
+        let started = std::time::Instant::now();
+        let mut stream = loop {
+            match std::net::TcpStream::connect(&addr) {
+                Ok(stream) => break stream,
+                Err(_) if started.elapsed().as_secs() < 10 => {
+                    std::thread::sleep(std::time::Duration::from_millis(50))
+                }
+                Err(e) => panic!("serve did not listen: {e}"),
+            }
+        };
+        stream.write_all(head.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    let anonymous = request("GET /status HTTP/1.1\r\n\r\n");
+    let wrong = request("GET /status HTTP/1.1\r\nAuthorization: Bearer x\r\n\r\n");
+    let authorized = request("GET /status HTTP/1.1\r\nAuthorization: Bearer t\r\n\r\n");
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(anonymous.starts_with("HTTP/1.1 401"), "{anonymous}");
+    assert!(wrong.starts_with("HTTP/1.1 401"), "{wrong}");
+    assert!(authorized.starts_with("HTTP/1.1 200"), "{authorized}");
+    assert!(!authorized.contains("Access-Control"), "{authorized}");
+}
+
+#[test]
+fn when_leaves_out_bases_of_other_machines() {
+    let env = Fake1C::new("when_leaves_out_bases_of_other_machines", "");
+    for (alias, path) in [("here", "srv;here"), ("away", "srv;away")] {
+        assert!(env.run(&["add", alias, path]).status.success());
+    }
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    let bases = std::fs::read_to_string(&file).unwrap();
+    let bases = bases
+        .replace(
+            "alias = \"here\"",
+            "alias = \"here\"\nwhen = { hosts = [\"*\"], networks = [\"127.0.0.0/8\"] }",
+        )
+        .replace(
+            "alias = \"away\"",
+            "alias = \"away\"\nwhen = { hosts = [\"no-such-host-*\"] }",
+        );
+    std::fs::write(&file, bases).unwrap();
+
+    let listed = env.run(&["list"]);
+    let listed = String::from_utf8_lossy(&listed.stdout);
+    assert!(listed.contains("srv;here"), "{listed}");
+    assert!(!listed.contains("srv;away"), "{listed}");
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn shortcuts_take_the_icon_of_the_base() {
+    let env = Fake1C::new("shortcuts_take_the_icon_of_the_base", "");
+    assert!(env.run(&["add", "prod", "srv;erp"]).status.success());
+    let file = find_file(env.dir(), "rbaserun_bases.toml").expect("bases file is written");
+    let bases = std::fs::read_to_string(&file).unwrap().replace(
+        "alias = \"prod\"",
+        "alias = \"prod\"\nicon = '/icons/prod.ico'",
+    );
+    std::fs::write(&file, bases).unwrap();
+
+    let output = env.run(&["integrate", "shortcuts", "links", "--all"]);
+    assert!(output.status.success(), "{output:?}");
+    let entry = std::fs::read_to_string(env.dir().join("links/erp.desktop")).unwrap();
+    assert!(entry.contains("Icon=/icons/prod.ico"), "{entry}");
+    assert!(
+        entry.contains("\"launch\" \"srv;erp\" \"--quiet\""),
+        "{entry}"
+    );
+}
+
+fn find_file(dir: &std::path::Path, name: &str) -> Option<std::path::PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name) {
+                return Some(found);
+            }
+        } else if path.file_name().is_some_and(|file| file == name) {
+            return Some(path);
+        }
+    }
+    None
+}
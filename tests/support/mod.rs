@@ -0,0 +1,127 @@
+//! A 1C environment for the end-to-end tests: a scratch directory with a
+//! config whose `starter` is `examples/fake_starter`, and the command lines
+//! the fake starter recorded.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a detached launch may take to reach the fake starter.
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Fake1C {
+    dir: PathBuf,
+    log: PathBuf,
+}
+
+impl Fake1C {
+    /// An environment named after the test, with `config` appended to the
+    /// generated `rbaserun.toml`.
+    pub fn new(name: &str, config: &str) -> Fake1C {
+        let dir = env::temp_dir().join(format!("rbaserun-e2e-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("scratch directory is created");
+        let starter = fake_starter();
+        assert!(
+            starter.is_file(),
+            "{} is missing, run the tests with `cargo test`",
+            starter.display()
+        );
+        fs::write(
+            dir.join("rbaserun.toml"),
+            format!(
+                "starter = '{}'\nibases = false\n{config}\n",
+                starter.display()
+            ),
+        )
+        .expect("config is written");
+        Fake1C {
+            log: dir.join("starter.jsonl"),
+            dir,
+        }
+    }
+
+    /// `rbaserun` with `args`, run in the scratch directory with its data and
+    /// config directories inside it.
+    pub fn rbaserun(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rbaserun"));
+        command
+            .args(args)
+            .current_dir(&self.dir)
+            .env("FAKE_STARTER_LOG", &self.log)
+            .env_remove("FAKE_STARTER_EXIT")
+            .env_remove("RBASERUN_PASSWORD");
+        for var in [
+            "HOME",
+            "XDG_DATA_HOME",
+            "XDG_CONFIG_HOME",
+            "APPDATA",
+            "LOCALAPPDATA",
+        ] {
+            command.env(var, self.dir.join("home"));
+        }
+        command
+    }
+
+    /// The scratch directory, removed when the environment is dropped.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.rbaserun(args).output().expect("rbaserun runs")
+    }
+
+    /// Runs `args` expecting success, then waits for the command line of the
+    /// launch to reach the fake starter.
+    pub fn launch(&self, args: &[&str]) -> Vec<String> {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "rbaserun {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        self.launches(1).pop().expect("one launch")
+    }
+
+    /// The first `count` command lines the fake starter received, failing
+    /// when they don't arrive in time.
+    pub fn launches(&self, count: usize) -> Vec<Vec<String>> {
+        let started = Instant::now();
+        loop {
+            let recorded: Vec<Vec<String>> = fs::read_to_string(&self.log)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect();
+            if recorded.len() >= count {
+                return recorded;
+            }
+            assert!(
+                started.elapsed() < LAUNCH_TIMEOUT,
+                "expected {count} launches, the fake starter got {recorded:?}"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for Fake1C {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The fake starter `cargo test` builds next to the test binaries.
+pub fn fake_starter() -> PathBuf {
+    let exe = env::current_exe().expect("test binary has a path");
+    // target/<profile>/deps/<test> -> target/<profile>/examples/fake_starter
+    exe.parent()
+        .and_then(|deps| deps.parent())
+        .expect("test binary is in target/<profile>/deps")
+        .join("examples")
+        .join(format!("fake_starter{}", env::consts::EXE_SUFFIX))
+}